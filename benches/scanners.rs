@@ -0,0 +1,112 @@
+//! Benchmarks for size calculation, duplicate hashing, and full scanner runs.
+//!
+//! Each benchmark runs against a synthetic tree generated in a temp directory.
+//! Tree shape can be tuned with `DUSTER_BENCH_BREADTH` (subdirectories per
+//! level, default 3) and `DUSTER_BENCH_DEPTH` (levels, default 2).
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use duster::config::Config;
+use duster::scanner::{
+    build_artifacts::BuildArtifactsScanner, calculate_dir_size, duplicates::DuplicatesScanner,
+    large_files::LargeFilesScanner, Scanner,
+};
+use std::fs;
+use std::path::Path;
+
+/// Files written into every generated directory
+const FILES_PER_DIR: usize = 4;
+
+/// Size of each generated file (large enough to pass the duplicate scanner's 1MB floor)
+const FILE_SIZE: usize = 1024 * 1024 + 1;
+
+fn env_usize(name: &str, default: usize) -> usize {
+    std::env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Build a tree of `breadth^depth` leaf directories. Every other directory is
+/// a small npm project with a `node_modules` folder, and file contents repeat
+/// across directories so the duplicate scanner has work to do.
+fn generate_tree(root: &Path, breadth: usize, depth: usize) {
+    fn fill(dir: &Path, breadth: usize, depth: usize, index: &mut usize) {
+        fs::create_dir_all(dir).unwrap();
+
+        for i in 0..FILES_PER_DIR {
+            let byte = (i % 4) as u8;
+            fs::write(dir.join(format!("file_{}.bin", i)), vec![byte; FILE_SIZE]).unwrap();
+        }
+
+        *index += 1;
+        if index.is_multiple_of(2) {
+            fs::write(dir.join("package.json"), "{}").unwrap();
+            let modules = dir.join("node_modules").join("dep");
+            fs::create_dir_all(&modules).unwrap();
+            fs::write(modules.join("index.js"), vec![b'x'; FILE_SIZE]).unwrap();
+        }
+
+        if depth == 0 {
+            return;
+        }
+
+        for b in 0..breadth {
+            fill(&dir.join(format!("dir_{}", b)), breadth, depth - 1, index);
+        }
+    }
+
+    let mut index = 0;
+    fill(root, breadth, depth, &mut index);
+}
+
+fn bench_config(root: &Path) -> Config {
+    Config {
+        base_path: Some(root.to_path_buf()),
+        // Treat every generated project as stale so the build scanner reports it
+        project_recent_days: 0,
+        min_large_size_mb: 1,
+        // Measure hashing, not hash index lookups
+        rehash: true,
+        ..Config::default()
+    }
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let breadth = env_usize("DUSTER_BENCH_BREADTH", 3);
+    let depth = env_usize("DUSTER_BENCH_DEPTH", 2);
+
+    // Default temp dir names start with '.', which scanners treat as hidden
+    let tree = tempfile::Builder::new()
+        .prefix("duster-bench")
+        .tempdir()
+        .unwrap();
+    generate_tree(tree.path(), breadth, depth);
+    let config = bench_config(tree.path());
+
+    let mut group = c.benchmark_group(format!("tree_{}x{}", breadth, depth));
+    group.sample_size(10);
+
+    group.bench_function("calculate_dir_size", |b| {
+        b.iter(|| calculate_dir_size(tree.path()))
+    });
+
+    group.bench_function("duplicates_scan", |b| {
+        let scanner = DuplicatesScanner::new();
+        b.iter(|| scanner.scan(&config).unwrap())
+    });
+
+    group.bench_function("build_artifacts_scan", |b| {
+        let scanner = BuildArtifactsScanner::new();
+        b.iter(|| scanner.scan(&config).unwrap())
+    });
+
+    group.bench_function("large_files_scan", |b| {
+        let scanner = LargeFilesScanner::new();
+        b.iter(|| scanner.scan(&config).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, benchmarks);
+criterion_main!(benches);
@@ -1,79 +1,251 @@
 //! Disk usage analysis and reporting
 
-use crate::cli::{ScanCategory, ScanOptions};
+use crate::cli::ScanOptions;
 use crate::config::Config;
+use crate::passes::{self, Analyzer};
 use crate::scanner::{
-    build_artifacts::{BuildArtifactsScanner, GlobalCacheScanner},
-    cache::{CacheScanner, KnownCacheScanner},
-    downloads::DownloadsScanner,
-    duplicates::DuplicatesScanner,
-    large_files::LargeFilesScanner,
-    old_files::OldFilesScanner,
-    temp::TempScanner,
-    trash::TrashScanner,
-    Category, CleanableFile, ScanResult, Scanner,
+    extra, registry::ScannerRegistry, Cancelled, Category, CleanableFile, CleanupAction,
+    ScanResult, ScanVisitor, Scanner,
 };
+use crate::format;
+use crate::owner;
+use crate::tags::Tags;
+use crate::scanner;
+#[cfg(feature = "cli")]
 use crate::ui;
-use anyhow::Result;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "cli")]
 use colored::*;
 use rayon::prelude::*;
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
-/// Run all enabled scanners and aggregate results
-pub fn run_scan(options: &ScanOptions, config: &Config) -> Result<ScanResult> {
-    let mut result = ScanResult::new();
-    let mut scanners: Vec<Box<dyn Scanner>> = Vec::new();
+/// Build the list of built-in scanners enabled by the given options and
+/// the config's scanner toggles; fails on unknown scanner ids
+pub fn build_scanners(options: &ScanOptions, config: &Config) -> Result<Vec<Box<dyn Scanner>>> {
+    ScannerRegistry::with_builtins().into_selected(options, config)
+}
 
-    // Build list of scanners based on options
-    if options.should_scan(ScanCategory::Cache) {
-        scanners.push(Box::new(CacheScanner::new()));
-        scanners.push(Box::new(KnownCacheScanner::new()));
-    }
+/// Keeps the findings that pass the config's filters and aren't tagged keep,
+/// and the errors scanners reported along the way
+struct FilteringVisitor<'a> {
+    config: &'a Config,
+    tags: &'a Tags,
+    owner_uid: Option<u32>,
+    files: Vec<CleanableFile>,
+    errors: Vec<anyhow::Error>,
+}
+
+impl FilteringVisitor<'_> {
+    fn keep(&self, file: &CleanableFile) -> bool {
+        // Files owned by other users usually can't be deleted. Command actions
+        // (e.g. `nix-collect-garbage`) go through a tool that has its own access rules.
+        if let Some(uid) = self.owner_uid {
+            if !matches!(file.action, CleanupAction::Command { .. })
+                && !owner::is_owned_by(&file.path, uid)
+            {
+                return false;
+            }
+        }
+
+        // Scanners prune synced folders through `is_excluded`; this also
+        // covers findings from scanners that don't consult it
+        if self.config.is_cloud_locked(&file.path) {
+            return false;
+        }
 
-    if options.should_scan(ScanCategory::Trash) {
-        scanners.push(Box::new(TrashScanner::new()));
+        // Clean refuses to delete anything inside a bundle, so it isn't
+        // reported either (known caches and build dirs inside `.app` trees)
+        if matches!(file.action, CleanupAction::Delete)
+            && scanner::bundle_root(&file.path).is_some()
+        {
+            return false;
+        }
+
+        if self.config.is_disabled_on_volume(&file.path, file.category) {
+            return false;
+        }
+
+        if let Some(min_file_count) = self.config.min_file_count {
+            if file.file_count < min_file_count {
+                return false;
+            }
+        }
+
+        self.config
+            .where_extra
+            .iter()
+            .all(|(key, value)| file.extra_matches(key, value))
     }
+}
 
-    if options.should_scan(ScanCategory::Temp) {
-        scanners.push(Box::new(TempScanner::new()));
+impl ScanVisitor for FilteringVisitor<'_> {
+    fn progress(&mut self, _path: &Path) {
+        EXAMINED.fetch_add(1, Ordering::Relaxed);
     }
 
-    if options.should_scan(ScanCategory::Downloads) {
-        scanners.push(Box::new(DownloadsScanner::new()));
+    fn finding(&mut self, file: CleanableFile) -> Result<(), Cancelled> {
+        let Some(file) = self.tags.apply_one(file) else {
+            return Ok(());
+        };
+        if self.keep(&file) {
+            self.files.push(file.with_identity());
+        }
+        Ok(())
     }
 
-    if options.should_scan(ScanCategory::Build) {
-        scanners.push(Box::new(BuildArtifactsScanner::new()));
-        scanners.push(Box::new(GlobalCacheScanner::new()));
+    fn error(&mut self, error: anyhow::Error) {
+        self.errors.push(error);
     }
+}
 
-    if options.should_scan(ScanCategory::Large) {
-        scanners.push(Box::new(LargeFilesScanner::new()));
+/// Run `work` on a pool of `threads` threads, which caps the scanners
+/// running at once and the threads they use for hashing; on the global
+/// pool (one thread per core) when unset
+pub fn in_scan_pool<T: Send>(config: &Config, work: impl FnOnce() -> T + Send) -> Result<T> {
+    match config.threads {
+        Some(threads) => Ok(rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .context("Failed to start scan threads")?
+            .install(work)),
+        None => Ok(work()),
     }
+}
+
+/// Directories and projects scanners have moved through in this process
+static EXAMINED: AtomicU64 = AtomicU64::new(0);
+
+/// How many directories and projects scanners have moved through so far
+pub fn examined() -> u64 {
+    EXAMINED.load(Ordering::Relaxed)
+}
+
+/// Run all enabled scanners and aggregate results
+pub fn run_scan(options: &ScanOptions, config: &Config) -> Result<ScanResult> {
+    run_scan_streaming(options, config, &|_, _| {})
+}
+
+/// Run the scanners of `registry` enabled by the given options, e.g. the
+/// built-ins plus scanners of an embedding application
+pub fn run_scan_with_registry(
+    registry: ScannerRegistry,
+    options: &ScanOptions,
+    config: &Config,
+) -> Result<ScanResult> {
+    run_scan_with_analyzers(registry, &passes::builtin(), options, config)
+}
+
+/// Run the scanners of `registry` enabled by the given options, then
+/// `analyzers` over their findings in order, e.g. the built-in passes
+/// plus ones of an embedding application
+pub fn run_scan_with_analyzers(
+    registry: ScannerRegistry,
+    analyzers: &[Box<dyn Analyzer>],
+    options: &ScanOptions,
+    config: &Config,
+) -> Result<ScanResult> {
+    let scanners = registry.into_selected(options, config)?;
+    scan_with_scanners(scanners, analyzers, options, config, &|_, _| {})
+}
+
+/// Run all enabled scanners and aggregate results, handing each scanner's
+/// findings to `on_findings` as soon as that scanner finishes
+pub fn run_scan_streaming(
+    options: &ScanOptions,
+    config: &Config,
+    on_findings: &(dyn Fn(&str, &[CleanableFile]) + Sync),
+) -> Result<ScanResult> {
+    scan_with_scanners(
+        build_scanners(options, config)?,
+        &passes::builtin(),
+        options,
+        config,
+        on_findings,
+    )
+}
+
+fn scan_with_scanners(
+    scanners: Vec<Box<dyn Scanner>>,
+    analyzers: &[Box<dyn Analyzer>],
+    options: &ScanOptions,
+    config: &Config,
+    on_findings: &(dyn Fn(&str, &[CleanableFile]) + Sync),
+) -> Result<ScanResult> {
+    let mut result = ScanResult::new();
+    let owner_uid = owner::resolve_filter(config.owner.as_deref())?;
+    let tags = Tags::load();
 
-    if options.should_scan(ScanCategory::Duplicates) {
-        scanners.push(Box::new(DuplicatesScanner::new()));
+    if let Some(root) =
+        scanner::cloud::root_for(&config.get_base_path()).filter(|_| !config.include_cloud)
+    {
+        result.add_note(format!(
+            "{} is synced by {}; pass --include-cloud to scan it.",
+            format::format_path(&root.path),
+            root.provider
+        ));
     }
 
-    if options.should_scan(ScanCategory::Old) {
-        scanners.push(Box::new(OldFilesScanner::new()));
+    // Only the Cache Scanner reads `cache_paths`
+    let cache_scanner = scanner::cache::CacheScanner::new().name();
+    if scanners.iter().any(|s| s.name() == cache_scanner) {
+        for path in config.cache_paths_outside_path() {
+            result.add_note(if config.strict_path {
+                format!(
+                    "Skipping cache path {}: outside --path.",
+                    format::format_path(&path)
+                )
+            } else {
+                format!(
+                    "Cache Scanner also scans {}, outside --path; pass --strict-path to skip it.",
+                    format::format_path(&path)
+                )
+            });
+        }
     }
 
     // Show progress
+    #[cfg(feature = "cli")]
     let spinner = ui::create_spinner("Scanning for cleanable files...");
 
     // Run scanners in parallel
-    let scan_results: Vec<(String, Result<Vec<CleanableFile>>)> = scanners
-        .par_iter()
-        .map(|scanner| {
-            let name = scanner.name().to_string();
-            let files = scanner.scan(config);
-            (name, files)
-        })
-        .collect();
+    let scan = || -> Vec<(String, Result<Vec<CleanableFile>>, Vec<anyhow::Error>)> {
+        scanners
+            .par_iter()
+            .map(|scanner| {
+                let name = scanner.name().to_string();
+                let mut visitor = FilteringVisitor {
+                    config,
+                    tags: &tags,
+                    owner_uid,
+                    files: Vec::new(),
+                    errors: Vec::new(),
+                };
+                let files = scanner.scan_with(config, &mut visitor).map(|()| {
+                    let mut files = visitor.files;
+                    files.sort_by_key(|f| std::cmp::Reverse(f.size));
+
+                    if !files.is_empty() {
+                        #[cfg(feature = "cli")]
+                        spinner.suspend(|| on_findings(&name, &files));
+                        #[cfg(not(feature = "cli"))]
+                        on_findings(&name, &files);
+                    }
+                    files
+                });
+                (name, files, visitor.errors)
+            })
+            .collect()
+    };
+
+    let scan_results = in_scan_pool(config, scan)?;
 
     // Aggregate results
-    for (name, files_result) in scan_results {
+    for (name, files_result, errors) in scan_results {
         match files_result {
             Ok(files) => {
                 result.add_files(files);
@@ -82,19 +254,37 @@ pub fn run_scan(options: &ScanOptions, config: &Config) -> Result<ScanResult> {
                 result.add_error(format!("{}: {}", name, e));
             }
         }
+        for e in errors {
+            result.add_error(format!("{}: {}", name, e));
+        }
     }
 
+    #[cfg(feature = "cli")]
     spinner.finish_and_clear();
 
-    // Deduplicate results (same path shouldn't appear twice)
-    let mut seen_paths = std::collections::HashSet::new();
-    result.files.retain(|f| seen_paths.insert(f.path.clone()));
+    for pass in analyzers {
+        pass.analyze(&mut result, config);
+    }
+
+    if !options.no_sort {
+        result.sort_stable();
+    }
 
     Ok(result)
 }
 
+/// Print the notes on a scan as a whole
+#[cfg(feature = "cli")]
+pub fn print_notes(result: &ScanResult) {
+    for note in &result.notes {
+        ui::print_info(note);
+    }
+}
+
 /// Print a summary report of scan results
+#[cfg(feature = "cli")]
 pub fn print_report(result: &ScanResult) {
+    print_notes(result);
     let by_category = result.by_category();
 
     // Calculate category totals
@@ -108,7 +298,7 @@ pub fn print_report(result: &ScanResult) {
         .collect();
 
     // Sort by size descending
-    category_stats.sort_by(|a, b| b.2.cmp(&a.2));
+    category_stats.sort_by_key(|s| std::cmp::Reverse(s.2));
 
     // Print header
     ui::print_header("Scan Results");
@@ -141,6 +331,9 @@ pub fn print_report(result: &ScanResult) {
         ui::format_size(result.total_size()).yellow().bold()
     );
 
+    print_inode_heavy(result);
+    print_baseline_outliers(result);
+
     // Print any errors
     if !result.errors.is_empty() {
         println!();
@@ -151,8 +344,40 @@ pub fn print_report(result: &ScanResult) {
     }
 }
 
+/// One status line with each category's size, largest first, e.g.
+/// `build 12.3G · cache 8.1G · total 20.4G`
+pub fn oneline_summary(result: &ScanResult) -> String {
+    let mut sizes: Vec<(Category, u64)> = result
+        .by_category()
+        .iter()
+        .map(|(cat, files)| (*cat, files.iter().map(|f| f.size).sum()))
+        .collect();
+    sizes.sort_by_key(|(cat, size)| (std::cmp::Reverse(*size), *cat));
+
+    let mut parts: Vec<String> = sizes
+        .iter()
+        .map(|(cat, size)| format!("{} {}", cat.short_name(), format::format_size_compact(*size)))
+        .collect();
+    parts.push(format!(
+        "total {}",
+        format::format_size_compact(result.total_size())
+    ));
+    parts.join(" · ")
+}
+
+/// Print one scanner's findings as they arrive (`scan --stream`)
+#[cfg(feature = "cli")]
+pub fn print_streamed_findings(scanner: &str, files: &[CleanableFile]) {
+    let total_size: u64 = files.iter().map(|f| f.size).sum();
+    ui::print_category_header(scanner, total_size, files.len());
+    for file in files {
+        ui::print_file_entry(&file.path, file.size, 1, None);
+    }
+}
+
 /// Print detailed breakdown of scan results
-pub fn print_detailed_report(result: &ScanResult) {
+#[cfg(feature = "cli")]
+pub fn print_detailed_report(result: &ScanResult, metered: bool) {
     let by_category = result.by_category();
 
     // Sort categories by total size
@@ -165,6 +390,9 @@ pub fn print_detailed_report(result: &ScanResult) {
 
     ui::print_header("Detailed Analysis");
 
+    // Resolving user names spawns a process, so remember them
+    let mut owner_names: HashMap<u32, String> = HashMap::new();
+
     for (category, files) in categories {
         if files.is_empty() {
             continue;
@@ -175,10 +403,20 @@ pub fn print_detailed_report(result: &ScanResult) {
 
         // Show top 5 largest items
         let mut sorted_files: Vec<_> = files.iter().collect();
-        sorted_files.sort_by(|a, b| b.size.cmp(&a.size));
+        sorted_files.sort_by_key(|f| std::cmp::Reverse(f.size));
 
         for file in sorted_files.iter().take(5) {
-            ui::print_file_entry(&file.path, file.size, 1);
+            let owner = owner::owner_uid(&file.path).map(|uid| {
+                owner_names
+                    .entry(uid)
+                    .or_insert_with(|| owner::user_name(uid))
+                    .clone()
+            });
+            ui::print_file_entry(&file.path, file.size, 1, owner.as_deref());
+            if let Some(rebuild) = file.rebuild {
+                println!("      {}", rebuild.label().dimmed());
+            }
+            print_largest_files(file);
         }
 
         if files.len() > 5 {
@@ -188,44 +426,503 @@ pub fn print_detailed_report(result: &ScanResult) {
                 files.len() - 5
             );
         }
+
+        print_age_histogram(files);
+
+        let redownload: u64 = files.iter().filter_map(|f| f.redownload_bytes()).sum();
+        if redownload > 0 {
+            println!("  {}", redownload_note(redownload, metered).dimmed());
+        }
+
+        match category {
+            Category::Temp => print_breakdown("By application:", files, |f| {
+                format!("{} temp", scanner::temp::owning_app(&f.path).unwrap_or("Other"))
+            }),
+            Category::CiCache => print_breakdown("By runner:", files, |f| {
+                scanner::ci_runner::runner_kind(&f.path)
+                    .unwrap_or("Other")
+                    .to_string()
+            }),
+            Category::MetadataJunk => print_breakdown("By type:", files, |f| {
+                scanner::metadata_junk::metadata_kind(&f.path)
+                    .unwrap_or("Other")
+                    .to_string()
+            }),
+            _ => {}
+        }
     }
 
+    print_inode_heavy(result);
+
     ui::print_summary(result.total_count(), result.total_size());
 }
 
+/// Print the largest files a directory finding records, under its entry
+#[cfg(feature = "cli")]
+fn print_largest_files(file: &CleanableFile) {
+    let Some(serde_json::Value::Array(largest)) = file.extra.get(extra::LARGEST_FILES) else {
+        return;
+    };
+    for entry in largest {
+        let path = entry["path"].as_str().unwrap_or_default();
+        let size = entry["size"].as_u64().unwrap_or_default();
+        println!(
+            "      {}  {}",
+            path.dimmed(),
+            ui::format_size(size).dimmed()
+        );
+    }
+}
+
+/// Print a one-line age histogram for a category
+#[cfg(feature = "cli")]
+fn print_age_histogram(files: &[&CleanableFile]) {
+    let buckets: Vec<String> = age_histogram(files)
+        .iter()
+        .filter(|b| b.count > 0)
+        .map(|b| format!("{}: {}", b.label, ui::format_size(b.size)))
+        .collect();
+
+    println!("  {} {}", "By age:".dimmed(), buckets.join(", ").dimmed());
+}
+
+/// Flag findings with extreme entry counts, which strain backups and filesystems
+#[cfg(feature = "cli")]
+fn print_inode_heavy(result: &ScanResult) {
+    let heavy: Vec<_> = result.files.iter().filter(|f| f.is_inode_heavy()).collect();
+    if heavy.is_empty() {
+        return;
+    }
+
+    println!();
+    ui::print_warning(&format!(
+        "{} finding(s) contain over {} files:",
+        heavy.len(),
+        ui::format_number(scanner::INODE_HEAVY_THRESHOLD)
+    ));
+    for file in heavy {
+        println!(
+            "  {}  {}",
+            ui::format_path(&file.path),
+            format!("({} files)", ui::format_number(file.file_count)).dimmed()
+        );
+    }
+}
+
+#[cfg(feature = "cli")]
+fn print_baseline_outliers(result: &ScanResult) {
+    let mut notes: Vec<&str> = result
+        .files
+        .iter()
+        .filter_map(|f| f.extra.get(scanner::extra::BASELINE)?.as_str())
+        .collect();
+    notes.sort_unstable();
+    notes.dedup();
+    if notes.is_empty() {
+        return;
+    }
+
+    println!();
+    ui::print_warning("Larger than on a typical machine:");
+    for note in notes {
+        println!("  {}", note);
+    }
+}
+
+/// Print a category's files grouped by a derived label (owning app, file kind, ...)
+#[cfg(feature = "cli")]
+fn print_breakdown<F>(title: &str, files: &[&CleanableFile], label: F)
+where
+    F: Fn(&CleanableFile) -> String,
+{
+    let mut groups: HashMap<String, (usize, u64)> = HashMap::new();
+    for file in files {
+        let entry = groups.entry(label(file)).or_default();
+        entry.0 += 1;
+        entry.1 += file.size;
+    }
+
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(_, (_, size))| std::cmp::Reverse(*size));
+
+    println!("  {}", title.dimmed());
+    for (name, (count, size)) in groups {
+        println!(
+            "    {:<20} {:>10}  {}",
+            name,
+            ui::format_size(size),
+            format!("({} items)", ui::format_number(count as u64)).dimmed()
+        );
+    }
+}
+
+/// Upper bounds (exclusive, in days since last access) of the age histogram buckets
+const AGE_BUCKETS: &[(&str, i64)] = &[
+    ("<30d", 30),
+    ("30-90d", 90),
+    ("90d-1y", 365),
+    (">1y", i64::MAX),
+];
+
+/// Files and bytes whose last access falls in one age range
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AgeBucket {
+    pub label: &'static str,
+    pub count: usize,
+    pub size: u64,
+}
+
+/// Bucket files by time since last access, to help pick a sensible `--min-age`
+pub fn age_histogram(files: &[&CleanableFile]) -> Vec<AgeBucket> {
+    let now = Utc::now();
+    let mut buckets: Vec<AgeBucket> = AGE_BUCKETS
+        .iter()
+        .map(|(label, _)| AgeBucket {
+            label,
+            count: 0,
+            size: 0,
+        })
+        .collect();
+
+    for file in files {
+        let age_days = (now - file.last_accessed).num_days();
+        let index = AGE_BUCKETS
+            .iter()
+            .position(|(_, max)| age_days < *max)
+            .unwrap_or(AGE_BUCKETS.len() - 1);
+        buckets[index].count += 1;
+        buckets[index].size += file.size;
+    }
+
+    buckets
+}
+
+/// One level of a treemap: a directory or finding and the bytes below it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TreemapNode {
+    pub path: PathBuf,
+    pub size: u64,
+    /// Largest first; empty for findings and nodes at the depth limit
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TreemapNode>,
+}
+
+/// A treemap node while it is being filled
+#[derive(Default)]
+struct TreemapBuilder {
+    size: u64,
+    children: BTreeMap<OsString, TreemapBuilder>,
+}
+
+impl TreemapBuilder {
+    fn add(&mut self, components: &[&OsStr], size: u64) {
+        self.size += size;
+        if let Some((first, rest)) = components.split_first() {
+            self.children
+                .entry(first.to_os_string())
+                .or_default()
+                .add(rest, size);
+        }
+    }
+
+    fn build(self, path: PathBuf) -> TreemapNode {
+        let mut children: Vec<TreemapNode> = self
+            .children
+            .into_iter()
+            .map(|(name, child)| child.build(path.join(name)))
+            .collect();
+        children.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+        TreemapNode {
+            path,
+            size: self.size,
+            children,
+        }
+    }
+}
+
+/// Findings arranged by path, from the directory they all share down to at
+/// most `depth` levels; deeper findings count toward their ancestor at the
+/// limit. Findings inside a directory finding are left out, as its size
+/// already includes them.
+pub fn treemap(result: &ScanResult, depth: usize) -> TreemapNode {
+    let dirs: HashSet<&Path> = result
+        .files
+        .iter()
+        .filter(|f| f.is_directory)
+        .map(|f| f.path.as_path())
+        .collect();
+    let files: Vec<&CleanableFile> = result
+        .files
+        .iter()
+        .filter(|f| !f.path.ancestors().skip(1).any(|a| dirs.contains(a)))
+        .collect();
+
+    // The deepest directory holding every finding
+    let mut root: Option<PathBuf> = None;
+    for file in &files {
+        let parent = file.path.parent().unwrap_or(&file.path);
+        root = Some(match root {
+            None => parent.to_path_buf(),
+            Some(root) => root
+                .components()
+                .zip(parent.components())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect(),
+        });
+    }
+    let root = root.unwrap_or_default();
+
+    let mut tree = TreemapBuilder::default();
+    for file in files {
+        let relative = file.path.strip_prefix(&root).unwrap_or(&file.path);
+        let components: Vec<&OsStr> = relative.iter().take(depth).collect();
+        tree.add(&components, file.size);
+    }
+    tree.build(root)
+}
+
 /// Print JSON output of scan results
 pub fn print_json_report(result: &ScanResult) -> Result<()> {
-    let output = serde_json::json!({
+    println!("{}", serde_json::to_string_pretty(&json_report(result))?);
+
+    Ok(())
+}
+
+/// Build the JSON representation of scan results
+pub fn json_report(result: &ScanResult) -> serde_json::Value {
+    let totals = result.reconcile();
+    serde_json::json!({
         "summary": {
             "total_files": result.total_count(),
-            "total_size": result.total_size(),
-            "total_size_formatted": ui::format_size(result.total_size()),
+            "total_size": totals.total_size,
+            "total_size_formatted": format::format_size(totals.total_size),
+            "uncategorized": totals.uncategorized_size,
+            "overlap_adjustment": totals.overlap_adjustment,
+            "reclaimable_size": totals.reclaimable_size,
+            "reclaimable_size_formatted": format::format_size(totals.reclaimable_size),
         },
         "by_category": result.by_category().iter().map(|(cat, files)| {
             let size: u64 = files.iter().map(|f| f.size).sum();
             serde_json::json!({
                 "category": cat.display_name(),
                 "count": files.len(),
+                "file_count": files.iter().map(|f| f.file_count).sum::<u64>(),
                 "size": size,
-                "size_formatted": ui::format_size(size),
+                "size_formatted": format::format_size(size),
+                "age_buckets": age_histogram(files),
             })
         }).collect::<Vec<_>>(),
         "files": result.files.iter().map(|f| {
             serde_json::json!({
                 "path": f.path.display().to_string(),
                 "size": f.size,
-                "size_formatted": ui::format_size(f.size),
+                "size_formatted": format::format_size(f.size),
                 "category": f.category.display_name(),
                 "reason": f.reason,
                 "is_directory": f.is_directory,
+                "file_count": f.file_count,
+                "inode_heavy": f.is_inode_heavy(),
+                "action": f.action,
+                "recoverable": f.recoverable,
+                "redownload_bytes": f.redownload_bytes(),
+                "rebuild": f.rebuild,
+                "extra": f.extra,
             })
         }).collect::<Vec<_>>(),
         "errors": result.errors,
+        "notes": result.notes,
+    })
+}
+
+/// A ranked cleanup suggestion: one or more findings cleaned together
+#[derive(Debug, Clone)]
+pub struct Suggestion {
+    /// Short action label, e.g. "Empty trash"
+    pub title: String,
+    /// Bytes freed
+    pub size: u64,
+    /// size × safety × recoverability; higher is a better win
+    pub score: f64,
+    pub files: Vec<CleanableFile>,
+}
+
+/// How confident we are that deleting a category won't be regretted (0..=1)
+fn category_safety(category: Category) -> f64 {
+    match category {
+        Category::Cache | Category::Junk | Category::MetadataJunk => 1.0,
+        Category::BuildArtifact | Category::CiCache => 0.9,
+        Category::Trash => 0.8,
+        Category::Temp | Category::Logs => 0.7,
+        Category::GitObjects | Category::Docker => 0.6,
+        Category::Duplicate => 0.5,
+        Category::Downloads => 0.4,
+        Category::Desktop | Category::RenamedDuplicate => 0.3,
+        Category::LargeFile | Category::OldFile | Category::Explicit => 0.2,
+    }
+}
+
+/// Score weight of re-downloadable findings on a metered connection
+const METERED_WEIGHT: f64 = 0.25;
+
+/// Score a finding for suggestions. Report-only findings can't be applied.
+/// On a metered connection, findings that cost bandwidth to restore rank lower.
+pub fn suggestion_score(file: &CleanableFile, metered: bool) -> f64 {
+    if matches!(file.action, CleanupAction::ReportOnly { .. }) {
+        return 0.0;
+    }
+    let recoverability = if file.recoverable { 1.0 } else { 0.5 };
+    let bandwidth = if metered && file.redownload_bytes().is_some() {
+        METERED_WEIGHT
+    } else {
+        1.0
+    };
+    file.size as f64 * category_safety(file.category) * recoverability * bandwidth
+}
+
+/// Describe what restoring `bytes` of re-downloadable findings would cost
+#[cfg(feature = "cli")]
+pub fn redownload_note(bytes: u64, metered: bool) -> String {
+    let note = format!("Re-downloading costs ~{} if needed again", ui::format_size(bytes));
+    if metered {
+        format!("{} (metered connection)", note)
+    } else {
+        note
+    }
+}
+
+/// Everything known about one finding, for an inspector view
+#[derive(Debug, Clone, Serialize)]
+pub struct FileDetails {
+    pub finding: CleanableFile,
+    /// Owning user, where the platform tracks it
+    pub owner: Option<String>,
+    pub created: Option<DateTime<Utc>>,
+    pub modified: Option<DateTime<Utc>>,
+    pub accessed: Option<DateTime<Utc>>,
+    /// Every copy in the finding's set of duplicates, the kept one first
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub duplicate_set: Vec<PathBuf>,
+    pub project_root: Option<PathBuf>,
+    /// How safe deleting its category is (0..=1)
+    pub safety: f64,
+    /// Its rank among suggestions
+    pub score: f64,
+    /// Why it was flagged and what cleaning it does, in order
+    pub decisions: Vec<String>,
+}
+
+/// Details of the finding at `index` in `result`, or `None` if there isn't one.
+/// It is scored like suggestions, which rank lower on a metered connection.
+pub fn file_details(result: &ScanResult, index: usize, metered: bool) -> Option<FileDetails> {
+    let file = result.files.get(index)?;
+    let metadata = std::fs::symlink_metadata(&file.path).ok();
+    let time = |t: std::io::Result<std::time::SystemTime>| t.ok().map(DateTime::<Utc>::from);
+
+    let set = file
+        .extra
+        .get(extra::DUPLICATE_SET_ID)
+        .and_then(|v| v.as_str());
+    let mut duplicate_set = Vec::new();
+    if let Some(set) = set {
+        let kept = file.extra.get(extra::DUPLICATE_OF).and_then(|v| v.as_str());
+        duplicate_set.extend(kept.map(PathBuf::from));
+        duplicate_set.extend(
+            result
+                .files
+                .iter()
+                .filter(|f| f.extra_matches(extra::DUPLICATE_SET_ID, set))
+                .map(|f| f.path.clone()),
+        );
+    }
+    let extra_str = |key: &str| file.extra.get(key).and_then(|v| v.as_str());
+
+    let mut decisions = vec![
+        format!(
+            "{}: {}",
+            file.category.display_name(),
+            file.category.description()
+        ),
+        file.reason.clone(),
+    ];
+    if let Some(copy) = extra_str(extra::IDENTICAL_COPY) {
+        decisions.push(format!("An identical copy exists at {}", copy));
+    }
+    if let Some(tag) = extra_str(extra::TAG) {
+        decisions.push(format!("Tagged {}", tag));
+    }
+    decisions.push(match file.action.describe() {
+        Some(action) => format!("Cleaning {}", action),
+        None => "Cleaning deletes it".to_string(),
+    });
+    decisions.push(if file.recoverable {
+        "Comes back on its own if needed again".to_string()
+    } else {
+        "Irreplaceable; `clean --yes` skips it without --include-irreplaceable".to_string()
     });
+    if let Some(risk) = extra_str(extra::RISK) {
+        decisions.push(format!("Risk: {}", risk));
+    }
 
-    println!("{}", serde_json::to_string_pretty(&output)?);
+    Some(FileDetails {
+        owner: owner::owner_uid(&file.path).map(owner::user_name),
+        created: metadata.as_ref().and_then(|m| time(m.created())),
+        modified: metadata.as_ref().and_then(|m| time(m.modified())),
+        accessed: metadata.as_ref().and_then(|m| time(m.accessed())),
+        duplicate_set,
+        project_root: extra_str(extra::PROJECT_ROOT).map(PathBuf::from),
+        safety: category_safety(file.category),
+        score: suggestion_score(file, metered),
+        decisions,
+        finding: file.clone(),
+    })
+}
 
-    Ok(())
+/// Rank the highest-value, highest-safety cleanups. Trash, temp and junk
+/// findings are grouped into one suggestion each; everything else stands alone.
+pub fn suggestions(result: &ScanResult, limit: usize, metered: bool) -> Vec<Suggestion> {
+    let mut grouped: HashMap<Category, Suggestion> = HashMap::new();
+    let mut single = Vec::new();
+
+    for file in &result.files {
+        let score = suggestion_score(file, metered);
+        if score <= 0.0 {
+            continue;
+        }
+
+        let group_title = match file.category {
+            Category::Trash => Some("Empty trash"),
+            Category::Temp => Some("Clear old temp files"),
+            Category::Junk => Some("Remove empty directories and broken links"),
+            Category::MetadataJunk => Some("Remove OS metadata files"),
+            _ => None,
+        };
+
+        match group_title {
+            Some(title) => {
+                let group = grouped.entry(file.category).or_insert_with(|| Suggestion {
+                    title: title.to_string(),
+                    size: 0,
+                    score: 0.0,
+                    files: Vec::new(),
+                });
+                group.size += file.size;
+                group.score += score;
+                group.files.push(file.clone());
+            }
+            None => single.push(Suggestion {
+                title: file.reason.clone(),
+                size: file.size,
+                score,
+                files: vec![file.clone()],
+            }),
+        }
+    }
+
+    let mut ranked: Vec<Suggestion> = grouped.into_values().chain(single).collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.title.cmp(&b.title)));
+    ranked.truncate(limit);
+    ranked
 }
 
 /// Group files by category for interactive selection
@@ -238,3 +935,242 @@ pub fn group_by_category(files: &[CleanableFile]) -> HashMap<Category, Vec<&Clea
 
     groups
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::testing::{file_aged, finding};
+    use crate::scanner::CleanupAction;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_oneline_summary_orders_by_size() {
+        let mut cache = file_aged(1, 2048);
+        cache.category = Category::Cache;
+        let mut build = file_aged(1, 5 * 1024 * 1024);
+        build.category = Category::BuildArtifact;
+
+        let mut result = ScanResult::new();
+        result.add_files(vec![cache, build]);
+
+        assert_eq!(
+            oneline_summary(&result),
+            "build 5.0M · cache 2.0K · total 5.0M"
+        );
+    }
+
+    #[test]
+    fn test_suggestions_rank_safe_recoverable_first() {
+        let mut cache = file_aged(1, 1000);
+        cache.category = Category::Cache;
+        cache.recoverable = true;
+        cache.reason = "npm cache".to_string();
+
+        // Bigger, but old user files are far less safe to delete
+        let old = file_aged(400, 3000);
+
+        let mut trash_a = file_aged(1, 400);
+        trash_a.category = Category::Trash;
+        let mut trash_b = trash_a.clone();
+        trash_b.size = 500;
+
+        let mut history = file_aged(1, 1_000_000);
+        history.category = Category::GitObjects;
+        history.action = CleanupAction::ReportOnly {
+            hint: String::new(),
+        };
+
+        let result = ScanResult {
+            files: vec![old, trash_a, cache, history, trash_b],
+            errors: Vec::new(),
+            notes: Vec::new(),
+        };
+
+        let titles: Vec<_> = suggestions(&result, 5, false)
+            .into_iter()
+            .map(|s| (s.title, s.size))
+            .collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("npm cache".to_string(), 1000),
+                ("Empty trash".to_string(), 900),
+                (String::new(), 3000),
+            ]
+        );
+
+        // Package caches cost bandwidth to restore
+        let metered: Vec<_> = suggestions(&result, 1, true)
+            .into_iter()
+            .map(|s| s.title)
+            .collect();
+        assert_eq!(metered, vec!["Empty trash".to_string()]);
+    }
+
+    #[test]
+    fn test_age_histogram() {
+        let files = [
+            file_aged(1, 1),
+            file_aged(45, 2),
+            file_aged(89, 4),
+            file_aged(200, 8),
+            file_aged(1000, 16),
+        ];
+        let refs: Vec<_> = files.iter().collect();
+
+        let sizes: Vec<u64> = age_histogram(&refs).iter().map(|b| b.size).collect();
+        assert_eq!(sizes, vec![1, 6, 8, 16]);
+    }
+
+    #[test]
+    fn test_file_details_of_a_duplicate() {
+        let copy = |path: &str| {
+            CleanableFile {
+                path: PathBuf::from(path),
+                category: Category::Duplicate,
+                reason: "Duplicate of: a.jpg".to_string(),
+                ..file_aged(1, 100)
+            }
+            .with_extra(extra::DUPLICATE_SET_ID, "abc")
+            .with_extra(extra::DUPLICATE_OF, "/photos/a.jpg")
+        };
+        let result = ScanResult {
+            files: vec![copy("/backup/a.jpg"), copy("/old/a.jpg"), file_aged(1, 100)],
+            errors: Vec::new(),
+            notes: Vec::new(),
+        };
+
+        let details = file_details(&result, 1, false).unwrap();
+        assert_eq!(details.finding.path, PathBuf::from("/old/a.jpg"));
+        assert_eq!(
+            details.duplicate_set,
+            vec![
+                PathBuf::from("/photos/a.jpg"),
+                PathBuf::from("/backup/a.jpg"),
+                PathBuf::from("/old/a.jpg"),
+            ]
+        );
+        assert_eq!(details.safety, 0.5);
+        assert_eq!(
+            details.decisions,
+            vec![
+                "Exact Duplicates: Identical files with the same name",
+                "Duplicate of: a.jpg",
+                "Cleaning deletes it",
+                "Irreplaceable; `clean --yes` skips it without --include-irreplaceable",
+            ]
+        );
+        assert!(file_details(&result, 3, false).is_none());
+    }
+
+    #[test]
+    fn test_treemap() {
+        let at = |path: &str, size: u64, is_directory: bool| CleanableFile {
+            path: PathBuf::from(path),
+            is_directory,
+            ..file_aged(1, size)
+        };
+        let result = ScanResult {
+            files: vec![
+                at("/home/me/project/target", 500, true),
+                // Counted in target already
+                at("/home/me/project/target/debug/app", 200, false),
+                at("/home/me/Downloads/a/b/setup.dmg", 300, false),
+                at("/home/me/Downloads/notes.pdf", 50, false),
+            ],
+            errors: Vec::new(),
+            notes: Vec::new(),
+        };
+
+        let tree = treemap(&result, 2);
+        let summary = |node: &TreemapNode| -> Vec<(PathBuf, u64)> {
+            node.children
+                .iter()
+                .map(|c| (c.path.clone(), c.size))
+                .collect()
+        };
+        assert_eq!(tree.path, PathBuf::from("/home/me"));
+        assert_eq!(tree.size, 850);
+        assert_eq!(
+            summary(&tree),
+            vec![
+                (PathBuf::from("/home/me/project"), 500),
+                (PathBuf::from("/home/me/Downloads"), 350),
+            ]
+        );
+        // The dmg counts toward "a", two levels down
+        assert_eq!(
+            summary(&tree.children[1]),
+            vec![
+                (PathBuf::from("/home/me/Downloads/a"), 300),
+                (PathBuf::from("/home/me/Downloads/notes.pdf"), 50),
+            ]
+        );
+        assert!(tree.children[1].children[0].children.is_empty());
+    }
+
+    #[test]
+    fn test_cache_path_note_only_when_the_cache_scanner_runs() {
+        use clap::Parser;
+
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        std::fs::create_dir(&project).unwrap();
+        let shared_cache = dir.path().join("shared-cache");
+        let notes = |args: &[&str]| {
+            let options = ScanOptions::try_parse_from(args).unwrap();
+            let mut config = Config {
+                cache_paths: vec![shared_cache.display().to_string()],
+                ..Config::default()
+            };
+            config.apply_cli_options(&options);
+            run_scan(&options, &config).unwrap().notes
+        };
+
+        let path = project.to_str().unwrap();
+        assert!(notes(&["duster", "--path", path, "--temp"]).is_empty());
+        let cache = notes(&["duster", "--path", path, "--scanner", "cache-generic"]);
+        assert_eq!(cache.len(), 1);
+        assert!(cache[0].contains("outside --path"));
+    }
+
+    #[test]
+    fn test_findings_inside_bundles_are_not_reported() {
+        let config = Config::default();
+        let tags = Tags::default();
+        let mut visitor = FilteringVisitor {
+            config: &config,
+            tags: &tags,
+            owner_uid: None,
+            files: Vec::new(),
+            errors: Vec::new(),
+        };
+        let inside = "/Applications/Tool.app/Contents/Resources/app/node_modules";
+        let review = CleanableFile {
+            action: CleanupAction::ReportOnly {
+                hint: String::new(),
+            },
+            ..finding(inside, 1, Category::BuildArtifact)
+        };
+        for file in [
+            finding(inside, 1, Category::BuildArtifact),
+            finding("/Applications/Tool.app", 1, Category::LargeFile),
+            review,
+        ] {
+            visitor.finding(file).unwrap();
+        }
+
+        let kept: Vec<(&Path, bool)> = visitor
+            .files
+            .iter()
+            .map(|f| (f.path.as_path(), f.action == CleanupAction::Delete))
+            .collect();
+        assert_eq!(
+            kept,
+            vec![
+                (Path::new("/Applications/Tool.app"), true),
+                (Path::new(inside), false)
+            ]
+        );
+    }
+}
@@ -0,0 +1,166 @@
+//! Whole-project archiving for stale repositories: clean build artifacts,
+//! compress what's left into a dated tarball, then remove the working copy
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+use crate::cleaner;
+use crate::cli::ArchiveOptions;
+use crate::config::Config;
+use crate::scanner::{build_artifacts::BuildArtifactsScanner, Scanner};
+use crate::ui;
+
+/// Run the archive-project command
+pub fn run(options: &ArchiveOptions, config: &Config) -> Result<()> {
+    let project = options
+        .path
+        .canonicalize()
+        .with_context(|| format!("Path does not exist: {}", options.path.display()))?;
+    if !project.is_dir() {
+        bail!("Not a directory: {}", project.display());
+    }
+    // Projects often sit directly in home, so this is looser than the
+    // cleaner's check, but the archive is verified before anything is removed
+    let inside_home =
+        dirs::home_dir().is_some_and(|home| project.starts_with(&home) && project != home);
+    if !options.keep && !inside_home {
+        bail!(
+            "Refusing to archive {}: only project directories inside the home directory can be removed",
+            project.display()
+        );
+    }
+
+    let dest_dir = options
+        .dest
+        .clone()
+        .unwrap_or_else(|| config.get_archive_dir());
+    std::fs::create_dir_all(&dest_dir)
+        .with_context(|| format!("Failed to create archive directory: {}", dest_dir.display()))?;
+    if dest_dir.canonicalize()?.starts_with(&project) {
+        bail!("Archive directory must be outside the project");
+    }
+
+    // 1. Clean build artifacts; the project is being archived, so it
+    //    counts as inactive no matter when it was last touched
+    let mut artifact_config = config.clone();
+    artifact_config.base_path = Some(project.clone());
+    artifact_config.project_recent_days = 0;
+    let artifacts = BuildArtifactsScanner::new().scan(&artifact_config)?;
+    if !artifacts.is_empty() {
+        let cleaned = cleaner::delete_files(&artifacts, None)?;
+        if !cleaned.errors.is_empty() {
+            bail!(
+                "Could not remove build artifacts: {}",
+                cleaned.errors.join("; ")
+            );
+        }
+        ui::print_success(&format!(
+            "Removed {} build artifact directories ({})",
+            cleaned.deleted_count,
+            ui::format_size(cleaned.freed_bytes)
+        ));
+    }
+
+    // 2. Compress what's left
+    let archive = archive_path(&dest_dir, &project)?;
+    let parent = project
+        .parent()
+        .context("Cannot archive the filesystem root")?;
+    let name = project
+        .file_name()
+        .context("Cannot archive the filesystem root")?;
+
+    let spinner = ui::create_spinner("Compressing project...");
+    let status = Command::new("tar")
+        .arg("-czf")
+        .arg(&archive)
+        .arg("-C")
+        .arg(parent)
+        .arg(name)
+        .status()
+        .context("Failed to run tar")?;
+    spinner.finish_and_clear();
+    if !status.success() {
+        let _ = std::fs::remove_file(&archive);
+        bail!("tar failed to create {}", archive.display());
+    }
+
+    // 3. Verify the archive lists every entry before touching the working copy
+    verify_archive(&archive, &project)?;
+
+    let archive_size = std::fs::metadata(&archive).map(|m| m.len()).unwrap_or(0);
+    ui::print_success(&format!(
+        "Archived to {} ({})",
+        archive.display(),
+        ui::format_size(archive_size)
+    ));
+
+    // 4. Remove the working copy
+    if options.keep {
+        return Ok(());
+    }
+
+    let confirmed =
+        options.yes || ui::confirm(&format!("Remove working copy {}?", project.display()));
+    if !confirmed {
+        ui::print_info("Working copy kept.");
+        return Ok(());
+    }
+
+    std::fs::remove_dir_all(&project)
+        .with_context(|| format!("Failed to remove working copy: {}", project.display()))?;
+    ui::print_success(&format!("Removed {}", project.display()));
+
+    Ok(())
+}
+
+/// `<dest>/<project>-<YYYY-MM-DD>.tar.gz`, numbered if that name is taken
+fn archive_path(dest_dir: &Path, project: &Path) -> Result<PathBuf> {
+    let name = project
+        .file_name()
+        .context("Cannot archive the filesystem root")?
+        .to_string_lossy()
+        .to_string();
+    let stem = format!("{}-{}", name, chrono::Local::now().format("%Y-%m-%d"));
+
+    let mut path = dest_dir.join(format!("{}.tar.gz", stem));
+    let mut n = 2;
+    while path.exists() {
+        path = dest_dir.join(format!("{}-{}.tar.gz", stem, n));
+        n += 1;
+    }
+
+    Ok(path)
+}
+
+/// Check that the archive reads back cleanly and holds as many entries as the project
+fn verify_archive(archive: &Path, project: &Path) -> Result<()> {
+    let output = Command::new("tar")
+        .arg("-tzf")
+        .arg(archive)
+        .output()
+        .context("Failed to run tar")?;
+    if !output.status.success() {
+        bail!("Archive {} could not be read back", archive.display());
+    }
+
+    let archived = String::from_utf8_lossy(&output.stdout).lines().count();
+    let on_disk = WalkDir::new(project)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .count();
+
+    if archived != on_disk {
+        bail!(
+            "Archive {} has {} entries but the project has {}; keeping the working copy",
+            archive.display(),
+            archived,
+            on_disk
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,81 @@
+//! Async wrappers around the scanning API (enabled with the `async` feature).
+//!
+//! Scanners do blocking filesystem I/O, so these run them on tokio's blocking
+//! thread pool and hand results back to the async caller.
+
+use crate::analyzer;
+use crate::cli::ScanOptions;
+use crate::config::Config;
+use crate::scanner::{Cancelled, CleanableFile, ScanResult, ScanVisitor};
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tokio_stream::Stream;
+
+/// Number of findings buffered before scanners wait for the consumer
+const STREAM_BUFFER: usize = 256;
+
+/// Run all enabled scanners without blocking the async runtime
+pub async fn run_scan_async(options: ScanOptions, config: Config) -> Result<ScanResult> {
+    tokio::task::spawn_blocking(move || analyzer::run_scan(&options, &config))
+        .await
+        .context("Scan task panicked")?
+}
+
+/// Sends new findings to the stream's receiver, cancelling the scan once the
+/// stream has been dropped
+struct StreamVisitor<'a> {
+    sender: &'a mpsc::Sender<CleanableFile>,
+    seen_paths: &'a Mutex<HashSet<PathBuf>>,
+}
+
+impl ScanVisitor for StreamVisitor<'_> {
+    fn finding(&mut self, file: CleanableFile) -> Result<(), Cancelled> {
+        let is_new = self
+            .seen_paths
+            .lock()
+            .map(|mut seen| seen.insert(file.path.clone()))
+            .unwrap_or(false);
+
+        if is_new {
+            self.sender
+                .blocking_send(file.with_identity())
+                .map_err(|_| Cancelled)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stream findings as scanners find them.
+///
+/// Paths reported by more than one scanner are yielded once. Scanner errors,
+/// and unknown scanner ids in `options`, are not part of the stream; use
+/// `run_scan_async` when they matter.
+/// Must be called from within a tokio runtime.
+pub fn scan_stream(options: ScanOptions, config: Config) -> impl Stream<Item = CleanableFile> {
+    let (sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+    tokio::task::spawn_blocking(move || {
+        let Ok(scanners) = analyzer::build_scanners(&options, &config) else {
+            return;
+        };
+        let seen_paths = Mutex::new(HashSet::new());
+
+        let _ = analyzer::in_scan_pool(&config, || {
+            scanners.par_iter().for_each(|scanner| {
+                let mut visitor = StreamVisitor {
+                    sender: &sender,
+                    seen_paths: &seen_paths,
+                };
+                // Scanners stop early once the consumer has dropped the stream
+                let _ = scanner.scan_with(&config, &mut visitor);
+            });
+        });
+    });
+
+    ReceiverStream::new(receiver)
+}
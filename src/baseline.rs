@@ -0,0 +1,127 @@
+//! Typical finding sizes shipped with duster (`baseline.toml`), for pointing
+//! out caches that grew far past what other machines carry. Purely offline:
+//! nothing about the scan leaves the machine.
+
+use crate::format;
+use crate::scanner::{extra, CleanableFile};
+use serde::Deserialize;
+use std::path::{Component, Path};
+
+const BASELINE: &str = include_str!("baseline.toml");
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+#[derive(Debug, Deserialize)]
+struct Baseline {
+    #[serde(default)]
+    tool: Vec<ToolRange>,
+    #[serde(default)]
+    category: Vec<CategoryRange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ToolRange {
+    name: String,
+    paths: Vec<String>,
+    typical_gb: [f64; 2],
+}
+
+#[derive(Debug, Deserialize)]
+struct CategoryRange {
+    name: String,
+    category: String,
+    typical_gb: [f64; 2],
+}
+
+/// Whether `path` contains the components of `pattern` in a row
+fn contains_components(path: &Path, pattern: &str) -> bool {
+    let path: Vec<Component> = path.components().collect();
+    let pattern: Vec<Component> = Path::new(pattern).components().collect();
+    !pattern.is_empty() && path.windows(pattern.len()).any(|w| w == pattern.as_slice())
+}
+
+/// "Gradle caches: 22.0 GB — typically 2–6 GB"
+fn message(name: &str, size: u64, typical_gb: [f64; 2]) -> String {
+    format!(
+        "{}: {} — typically {}–{} GB",
+        name,
+        format::format_size(size),
+        typical_gb[0],
+        typical_gb[1]
+    )
+}
+
+/// Note on each finding of a tool or category whose total is above its
+/// typical range how it compares (`extra::BASELINE`)
+pub fn annotate(files: &mut [CleanableFile]) {
+    let baseline: Baseline = match toml::from_str(BASELINE) {
+        Ok(b) => b,
+        Err(_) => return,
+    };
+
+    // Each finding counts toward the first tool it matches
+    let mut groups: Vec<(String, [f64; 2], Vec<usize>)> = Vec::new();
+    let mut claimed = vec![false; files.len()];
+    for tool in &baseline.tool {
+        let members: Vec<usize> = (0..files.len())
+            .filter(|&i| !claimed[i])
+            .filter(|&i| {
+                tool.paths
+                    .iter()
+                    .any(|p| contains_components(&files[i].path, p))
+            })
+            .collect();
+        for &i in &members {
+            claimed[i] = true;
+        }
+        groups.push((tool.name.clone(), tool.typical_gb, members));
+    }
+    for range in &baseline.category {
+        let members = (0..files.len())
+            .filter(|&i| files[i].category.flag_name() == range.category)
+            .collect();
+        groups.push((range.name.clone(), range.typical_gb, members));
+    }
+
+    for (name, typical_gb, members) in groups {
+        let size: u64 = members.iter().map(|&i| files[i].size).sum();
+        if members.is_empty() || (size as f64) <= typical_gb[1] * GB {
+            continue;
+        }
+        let note = message(&name, size, typical_gb);
+        for i in members {
+            files[i]
+                .extra
+                .insert(extra::BASELINE.to_string(), note.clone().into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::testing::finding;
+    use crate::scanner::Category;
+
+    #[test]
+    fn test_baseline_flags_outliers() {
+        assert!(toml::from_str::<Baseline>(BASELINE).is_ok());
+
+        let mut files = vec![
+            finding("/home/me/.gradle/caches/modules-2", 15 << 30, Category::Cache),
+            finding("/home/me/.gradle/caches/transforms-3", 7 << 30, Category::Cache),
+            finding("/home/me/.npm/_cacache", 1 << 30, Category::Cache),
+            finding("/home/me/.local/share/Trash/files", 1 << 30, Category::Trash),
+        ];
+        annotate(&mut files);
+
+        assert!(
+            files[0].extra_matches(extra::BASELINE, "Gradle caches: 22.0 GB — typically 2–6 GB")
+        );
+        assert!(
+            files[1].extra_matches(extra::BASELINE, "Gradle caches: 22.0 GB — typically 2–6 GB")
+        );
+        assert!(!files[2].extra.contains_key(extra::BASELINE));
+        assert!(!files[3].extra.contains_key(extra::BASELINE));
+    }
+}
@@ -1,22 +1,129 @@
 //! Deletion logic with confirmation and progress
 
-use crate::scanner::{Category, CleanableFile};
+use crate::config::Config;
+use crate::quarantine;
+use crate::scanner::{
+    calculate_dir_stats, ci_runner, extra, get_last_accessed, get_last_modified, Category,
+    CleanableFile, CleanupAction, ScanResult,
+};
+use crate::tags::{Tag, Tags};
+#[cfg(feature = "cli")]
 use crate::ui;
 use anyhow::{Context, Result};
+#[cfg(feature = "cli")]
 use colored::*;
 use std::collections::HashMap;
+use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Extra attempts for items that failed because something had them open
+const RETRY_ATTEMPTS: u32 = 2;
+
+/// Wait before the first retry; doubles for each further attempt
+const RETRY_BACKOFF: Duration = Duration::from_millis(250);
+
+/// What happened to one finding during cleanup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum CleanupOutcome {
+    /// Deleted, or its cleanup command succeeded
+    Cleaned,
+    /// Report-only finding left in place
+    Skipped,
+    /// Inside a directory that was cleaned before it
+    Covered { by: PathBuf },
+    Failed { kind: FailureKind, error: String },
+}
+
+/// Why a finding could not be cleaned, for grouping errors with a fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureKind {
+    PermissionDenied,
+    InUse,
+    ReadOnly,
+    NotFound,
+    Other,
+}
+
+impl FailureKind {
+    /// Classify an error by the I/O error underneath it, if any
+    pub fn classify(error: &anyhow::Error) -> Self {
+        let io_error = match error.chain().find_map(|e| e.downcast_ref::<std::io::Error>()) {
+            Some(e) => e,
+            None => return FailureKind::Other,
+        };
+
+        // ERROR_SHARING_VIOLATION / ERROR_LOCK_VIOLATION
+        if cfg!(windows) && matches!(io_error.raw_os_error(), Some(32) | Some(33)) {
+            return FailureKind::InUse;
+        }
+
+        match io_error.kind() {
+            std::io::ErrorKind::PermissionDenied => FailureKind::PermissionDenied,
+            std::io::ErrorKind::ResourceBusy | std::io::ErrorKind::ExecutableFileBusy => {
+                FailureKind::InUse
+            }
+            std::io::ErrorKind::ReadOnlyFilesystem => FailureKind::ReadOnly,
+            std::io::ErrorKind::NotFound => FailureKind::NotFound,
+            _ => FailureKind::Other,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureKind::PermissionDenied => "Permission denied",
+            FailureKind::InUse => "In use",
+            FailureKind::ReadOnly => "Read-only filesystem",
+            FailureKind::NotFound => "Already gone",
+            FailureKind::Other => "Other errors",
+        }
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> &'static str {
+        match self {
+            FailureKind::PermissionDenied => {
+                if cfg!(target_os = "macos") {
+                    "Grant your terminal Full Disk Access (System Settings > Privacy & Security), or check the files' owner"
+                } else {
+                    "Check the files' owner and permissions; files owned by root need sudo"
+                }
+            }
+            FailureKind::InUse => "Quit the program or job using them and run clean again",
+            FailureKind::ReadOnly => "Remount the volume read-write, or skip it with --exclude",
+            FailureKind::NotFound => "Deleted since the scan; nothing to do",
+            FailureKind::Other => "See the messages below",
+        }
+    }
+}
+
+/// Per-finding cleanup result
+#[derive(Debug, Clone, Serialize)]
+pub struct CleanupItem {
+    pub path: PathBuf,
+    pub size: u64,
+    #[serde(flatten)]
+    pub outcome: CleanupOutcome,
+}
 
 /// Result of a cleanup operation
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct CleanupResult {
     /// Number of files/directories successfully deleted
     pub deleted_count: usize,
     /// Total bytes freed
     pub freed_bytes: u64,
+    /// Number of report-only findings left untouched
+    pub skipped_count: usize,
     /// Errors encountered during deletion
     pub errors: Vec<String>,
+    /// Outcome of each finding, in the order they were processed
+    pub items: Vec<CleanupItem>,
+    /// Whether items were moved into quarantine rather than deleted
+    pub quarantined: bool,
 }
 
 impl CleanupResult {
@@ -24,7 +131,10 @@ impl CleanupResult {
         Self {
             deleted_count: 0,
             freed_bytes: 0,
+            skipped_count: 0,
             errors: Vec::new(),
+            items: Vec::new(),
+            quarantined: false,
         }
     }
 }
@@ -35,8 +145,69 @@ impl Default for CleanupResult {
     }
 }
 
+/// A finding's place in the cleanup order
+#[derive(Debug, Clone, Serialize)]
+pub struct PlannedItem {
+    pub path: PathBuf,
+    pub category: Category,
+    pub size: u64,
+    /// Directory cleaned earlier that contains this finding, which then
+    /// needs no work of its own
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub covered_by: Option<PathBuf>,
+}
+
+/// Whether cleaning a finding removes everything below its path
+fn removes_tree(file: &CleanableFile) -> bool {
+    file.is_directory && matches!(file.action, CleanupAction::Delete)
+}
+
+/// Cleanup order of `files` as indices, each with the index of the finding
+/// whose directory contains it. Categories go by `cleanup_priority`; a
+/// nested finding follows the outermost directory containing it, so that
+/// directory is handled first whatever the nested finding's category.
+fn plan_order(files: &[&CleanableFile]) -> Vec<(usize, Option<usize>)> {
+    let mut trees: Vec<usize> = (0..files.len()).filter(|&i| removes_tree(files[i])).collect();
+    trees.sort_by_key(|&i| files[i].path.components().count());
+
+    let covered_by: Vec<Option<usize>> = (0..files.len())
+        .map(|i| {
+            trees.iter().copied().find(|&t| {
+                files[i].path != files[t].path && files[i].path.starts_with(&files[t].path)
+            })
+        })
+        .collect();
+
+    let mut order: Vec<usize> = (0..files.len()).collect();
+    order.sort_by(|&a, &b| {
+        let key = |i: usize| {
+            let root = covered_by[i].unwrap_or(i);
+            (files[root].category.cleanup_priority(), &files[i].path)
+        };
+        key(a).cmp(&key(b))
+    });
+
+    order.into_iter().map(|i| (i, covered_by[i])).collect()
+}
+
+/// The order `delete_files` would clean `files` in, for review before
+/// anything is touched
+pub fn cleanup_plan(files: &[CleanableFile]) -> Vec<PlannedItem> {
+    let files: Vec<&CleanableFile> = files.iter().collect();
+    plan_order(&files)
+        .into_iter()
+        .map(|(i, covered_by)| PlannedItem {
+            path: files[i].path.clone(),
+            category: files[i].category,
+            size: files[i].size,
+            covered_by: covered_by.map(|t| files[t].path.clone()),
+        })
+        .collect()
+}
+
 /// Preview what will be deleted
-pub fn preview_deletion(files: &[CleanableFile]) {
+#[cfg(feature = "cli")]
+pub fn preview_deletion(files: &[CleanableFile], metered: bool) {
     let mut by_category: HashMap<Category, Vec<&CleanableFile>> = HashMap::new();
 
     for file in files {
@@ -66,27 +237,203 @@ pub fn preview_deletion(files: &[CleanableFile]) {
 
         // Show top items
         let mut sorted: Vec<_> = cat_files.iter().collect();
-        sorted.sort_by(|a, b| b.size.cmp(&a.size));
+        sorted.sort_by_key(|f| std::cmp::Reverse(f.size));
 
         for file in sorted.iter().take(3) {
-            println!(
-                "  {} ({})",
-                ui::format_path(&file.path),
-                ui::format_size(file.size).dimmed()
-            );
+            let size = match file.rebuild {
+                Some(rebuild) => format!("{}, {}", ui::format_size(file.size), rebuild.label()),
+                None => ui::format_size(file.size),
+            };
+            println!("  {} ({})", ui::format_path(&file.path), size.dimmed());
+            if let Some(action) = file.action.describe() {
+                println!("    {}", action.dimmed());
+            }
+            let risk = file.extra.get(crate::scanner::extra::RISK);
+            if let Some(risk) = risk.and_then(|r| r.as_str()) {
+                println!("    {}", format!("risk: {}", risk).yellow());
+            }
         }
 
         if cat_files.len() > 3 {
             println!("  {} and {} more", "...".dimmed(), cat_files.len() - 3);
         }
+
+        let redownload: u64 = cat_files.iter().filter_map(|f| f.redownload_bytes()).sum();
+        if redownload > 0 {
+            println!(
+                "  {}",
+                crate::analyzer::redownload_note(redownload, metered).dimmed()
+            );
+        }
     }
 
+    print_cleanup_order(files);
+
     let total_size: u64 = files.iter().map(|f| f.size).sum();
     ui::print_summary(files.len(), total_size);
     ui::print_deletion_warning();
 }
 
+/// Print the order categories will be cleaned in, and how many findings are
+/// covered by a directory cleaned before them
+#[cfg(feature = "cli")]
+fn print_cleanup_order(files: &[CleanableFile]) {
+    let plan = cleanup_plan(files);
+
+    let mut categories: Vec<Category> = Vec::new();
+    for item in plan.iter().filter(|item| item.covered_by.is_none()) {
+        if !categories.contains(&item.category) {
+            categories.push(item.category);
+        }
+    }
+    let names: Vec<&str> = categories.iter().map(|c| c.display_name()).collect();
+
+    println!();
+    println!("{} {}", "Cleanup order:".bold(), names.join(" → "));
+
+    let covered = plan.iter().filter(|item| item.covered_by.is_some()).count();
+    if covered > 0 {
+        println!(
+            "  {}",
+            format!(
+                "{} item(s) inside directories cleaned earlier need no separate step",
+                covered
+            )
+            .dimmed()
+        );
+    }
+}
+
+/// Which paths a cleanup may remove
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Scan findings and `clean path`: what [`is_safe_to_delete`] allows
+    Findings,
+    /// Duplicates found in a root given with `--duplicates-path`: also
+    /// what lies on a drive mounted under `/Volumes`
+    DuplicateRoot,
+    /// `duster rm`: whatever the user names, except the filesystem root and
+    /// the directories right below it, the home itself, system directories
+    /// and the inside of bundles
+    Rm,
+}
+
+impl Policy {
+    /// The policy `file` is cleaned under
+    fn for_file(self, file: &CleanableFile) -> Self {
+        let duplicate = matches!(
+            file.category,
+            Category::Duplicate | Category::RenamedDuplicate
+        );
+        match self {
+            Policy::Findings if duplicate && file.extra.contains_key(extra::DUPLICATE_ROOT) => {
+                Policy::DuplicateRoot
+            }
+            policy => policy,
+        }
+    }
+
+    /// Why `path` may not be removed, or `None` if it may
+    fn refusal(self, path: &Path, verb: &str) -> Option<anyhow::Error> {
+        match self {
+            Policy::Findings => (!is_safe_to_delete(path)).then(|| refusal(path, verb)),
+            Policy::DuplicateRoot => {
+                let on_volume =
+                    is_on_mounted_volume(path) && crate::scanner::bundle_root(path).is_none();
+                (!is_safe_to_delete(path) && !on_volume).then(|| refusal(path, verb))
+            }
+            Policy::Rm => rm_refusal(path, verb),
+        }
+    }
+}
+
+/// Whether cleaning may delete `file` where it is
+pub(crate) fn may_delete(file: &CleanableFile) -> bool {
+    Policy::Findings
+        .for_file(file)
+        .refusal(&file.path, "delete")
+        .is_none()
+}
+
+/// Findings for the paths given to `clean path` or `duster rm`. Every path
+/// is checked before any is cleaned: one that is missing, tagged keep,
+/// excluded in the config or refused by `policy` fails the whole list.
+pub fn path_findings(
+    paths: &[PathBuf],
+    config: &Config,
+    tags: &Tags,
+    policy: Policy,
+) -> Result<Vec<CleanableFile>> {
+    let mut findings = Vec::new();
+    for given in paths {
+        let path = resolve(given)?;
+        let metadata = fs::symlink_metadata(&path)
+            .with_context(|| format!("Cannot clean {}", given.display()))?;
+        if let Some(refusal) = policy.refusal(&path, "delete") {
+            return Err(refusal).with_context(|| format!("Cannot clean {}", given.display()));
+        }
+        if tags.get(&path) == Some(Tag::Keep) {
+            anyhow::bail!(
+                "{} is tagged keep; clear the tag with `duster tag {} clear` first",
+                path.display(),
+                given.display()
+            );
+        }
+        if let Some(kept) = tags.kept_below(&path).filter(|_| metadata.is_dir()) {
+            anyhow::bail!(
+                "{} holds {}, which is tagged keep",
+                path.display(),
+                kept.display()
+            );
+        }
+        if config.is_excluded(&path) {
+            anyhow::bail!("{} is excluded in the config", path.display());
+        }
+
+        // A symlink is removed itself, never what it points to
+        let is_directory = metadata.is_dir();
+        let (size, file_count) = if is_directory {
+            let stats = calculate_dir_stats(&path);
+            (stats.size, stats.file_count)
+        } else {
+            (metadata.len(), 1)
+        };
+        let last_accessed = get_last_accessed(&path)
+            .or_else(|| get_last_modified(&path))
+            .unwrap_or_else(chrono::Utc::now);
+
+        findings.push(
+            CleanableFile {
+                last_accessed,
+                is_directory,
+                file_count,
+                ..CleanableFile::new(path, size, Category::Explicit, "Named on the command line")
+            }
+            .with_identity(),
+        );
+    }
+    Ok(findings)
+}
+
+/// `path` made absolute with `..` and symlinks in its parent resolved, so
+/// the safety checks see where it really is; the last component is kept,
+/// as a symlink given by name is what gets removed
+fn resolve(path: &Path) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("Cannot clean {}: name a file or directory", path.display()))?;
+    let parent = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let parent = parent
+        .canonicalize()
+        .with_context(|| format!("Cannot clean {}", path.display()))?;
+    Ok(parent.join(name))
+}
+
 /// Interactively select which categories to clean
+#[cfg(feature = "cli")]
 pub fn select_categories(files: &[CleanableFile]) -> Vec<Category> {
     let mut by_category: HashMap<Category, Vec<&CleanableFile>> = HashMap::new();
 
@@ -126,8 +473,32 @@ pub fn select_categories(files: &[CleanableFile]) -> Vec<Category> {
 pub fn delete_files(
     files: &[CleanableFile],
     categories: Option<&[Category]>,
+) -> Result<CleanupResult> {
+    clean_files(files, categories, false, Policy::Findings)
+}
+
+/// Move files in the specified categories into quarantine instead of
+/// deleting them; cleanup commands and report-only findings behave as usual
+pub fn quarantine_files(
+    files: &[CleanableFile],
+    categories: Option<&[Category]>,
+) -> Result<CleanupResult> {
+    clean_files(files, categories, true, Policy::Findings)
+}
+
+/// Move what `duster rm` was given into quarantine, under its own policy
+pub fn quarantine_removals(files: &[CleanableFile]) -> Result<CleanupResult> {
+    clean_files(files, None, true, Policy::Rm)
+}
+
+fn clean_files(
+    files: &[CleanableFile],
+    categories: Option<&[Category]>,
+    quarantine: bool,
+    policy: Policy,
 ) -> Result<CleanupResult> {
     let mut result = CleanupResult::new();
+    result.quarantined = quarantine;
 
     // Filter files by category if specified
     let files_to_delete: Vec<&CleanableFile> = if let Some(cats) = categories {
@@ -140,58 +511,305 @@ pub fn delete_files(
         return Ok(result);
     }
 
-    let progress = ui::create_progress_bar(files_to_delete.len() as u64, "Deleting files...");
+    #[cfg(feature = "cli")]
+    let progress = ui::create_progress_bar(
+        files_to_delete.len() as u64,
+        if quarantine { "Quarantining files..." } else { "Deleting files..." },
+    );
 
-    for file in files_to_delete {
-        let delete_result = if file.is_directory {
-            delete_directory(&file.path)
-        } else {
-            delete_file(&file.path)
-        };
+    // Findings in cleanup order; a nested finding whose directory was
+    // cleaned is gone with it
+    let order = plan_order(&files_to_delete);
+    let files_to_delete: Vec<&CleanableFile> =
+        order.iter().map(|&(i, _)| files_to_delete[i]).collect();
+    let position: HashMap<usize, usize> =
+        order.iter().enumerate().map(|(pos, &(i, _))| (i, pos)).collect();
+    let covered_by: Vec<Option<usize>> = order
+        .iter()
+        .map(|&(_, tree)| tree.map(|t| position[&t]))
+        .collect();
+
+    let mut results: Vec<Option<Result<()>>> = Vec::with_capacity(files_to_delete.len());
+    let mut covered: Vec<Option<usize>> = Vec::with_capacity(files_to_delete.len());
+    for (pos, file) in files_to_delete.iter().enumerate() {
+        let tree = covered_by[pos].filter(|&t| matches!(results[t], Some(Ok(()))));
+        results.push(match tree {
+            Some(_) => None,
+            None => clean_one(file, quarantine, policy),
+        });
+        covered.push(tree);
+
+        #[cfg(feature = "cli")]
+        progress.inc(1);
+    }
+
+    #[cfg(feature = "cli")]
+    progress.finish_and_clear();
 
-        match delete_result {
-            Ok(_) => {
+    // Files that were busy (open elsewhere, Windows sharing violations) often
+    // free up within moments
+    for attempt in 0..RETRY_ATTEMPTS {
+        let busy: Vec<usize> = (0..results.len())
+            .filter(|&i| {
+                matches!(&results[i], Some(Err(e)) if FailureKind::classify(e) == FailureKind::InUse)
+            })
+            .collect();
+        if busy.is_empty() {
+            break;
+        }
+
+        std::thread::sleep(RETRY_BACKOFF * 2u32.pow(attempt));
+        for i in busy {
+            results[i] = clean_one(files_to_delete[i], quarantine, policy);
+        }
+    }
+
+    for ((file, delete_result), tree) in files_to_delete.iter().zip(results).zip(covered) {
+        let outcome = match (delete_result, tree) {
+            (None, Some(t)) => CleanupOutcome::Covered {
+                by: files_to_delete[t].path.clone(),
+            },
+            (None, None) => {
+                result.skipped_count += 1;
+                CleanupOutcome::Skipped
+            }
+            (Some(Ok(_)), _) => {
                 result.deleted_count += 1;
                 result.freed_bytes += file.size;
+                CleanupOutcome::Cleaned
             }
-            Err(e) => {
+            (Some(Err(e)), _) => {
                 result.errors.push(format!("{}: {}", file.path.display(), e));
+                CleanupOutcome::Failed {
+                    kind: FailureKind::classify(&e),
+                    error: e.to_string(),
+                }
             }
+        };
+        result.items.push(CleanupItem {
+            path: file.path.clone(),
+            size: file.size,
+            outcome,
+        });
+    }
+
+    Ok(result)
+}
+
+/// Whether cleaning would act on `file` at all: report-only findings are
+/// left alone, and deletions `may_delete` rejects are refused
+#[cfg(feature = "cli")]
+pub(crate) fn would_clean(file: &CleanableFile) -> bool {
+    match file.action {
+        CleanupAction::ReportOnly { .. } => false,
+        CleanupAction::Command { .. } => true,
+        CleanupAction::Delete => may_delete(file),
+    }
+}
+
+/// Apply one finding's cleanup action; `None` for report-only findings
+fn clean_one(file: &CleanableFile, quarantine: bool, policy: Policy) -> Option<Result<()>> {
+    let policy = policy.for_file(file);
+    match &file.action {
+        CleanupAction::ReportOnly { .. } => None,
+        CleanupAction::Command { program, args, cwd } => {
+            Some(run_command(program, args, cwd.as_deref()))
+        }
+        CleanupAction::Delete if file.was_replaced() => Some(Err(anyhow::anyhow!(
+            "Replaced since the scan by a different file; scan again to review it"
+        ))),
+        CleanupAction::Delete
+            if file.category == Category::CiCache && ci_runner::is_in_use(&file.path) =>
+        {
+            Some(Err(std::io::Error::new(
+                std::io::ErrorKind::ResourceBusy,
+                "a CI job is using this directory",
+            )
+            .into()))
         }
+        CleanupAction::Delete if file.is_directory && file.category == Category::Junk => {
+            Some(delete_empty_tree(&file.path, policy))
+        }
+        CleanupAction::Delete if quarantine => Some(quarantine_item(file, policy)),
+        CleanupAction::Delete if file.is_directory => Some(delete_directory(&file.path, policy)),
+        CleanupAction::Delete => Some(delete_file(&file.path, policy)),
+    }
+}
 
-        progress.inc(1);
+/// Run a finding's cleanup command (e.g. `git lfs prune`)
+fn run_command(program: &str, args: &[String], cwd: Option<&Path>) -> Result<()> {
+    let mut cmd = std::process::Command::new(program);
+    cmd.args(args)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::piped());
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
     }
 
-    progress.finish_and_clear();
+    let output = cmd
+        .output()
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`{} {}` failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
 
-    Ok(result)
+    Ok(())
 }
 
 /// Delete a single file
-fn delete_file(path: &Path) -> Result<()> {
-    // Safety check: don't delete outside home directory
-    if !is_safe_to_delete(path) {
-        anyhow::bail!("Refusing to delete path outside home directory");
+fn delete_file(path: &Path, policy: Policy) -> Result<()> {
+    // Safety check: only what the policy allows
+    if let Some(refusal) = policy.refusal(path, "delete") {
+        return Err(refusal);
     }
 
     fs::remove_file(path).with_context(|| format!("Failed to delete file: {}", path.display()))
 }
 
 /// Delete a directory recursively
-fn delete_directory(path: &Path) -> Result<()> {
-    // Safety check: don't delete outside home directory
-    if !is_safe_to_delete(path) {
-        anyhow::bail!("Refusing to delete path outside home directory");
+fn delete_directory(path: &Path, policy: Policy) -> Result<()> {
+    // Safety check: only what the policy allows
+    if let Some(refusal) = policy.refusal(path, "delete") {
+        return Err(refusal);
     }
 
     fs::remove_dir_all(path)
         .with_context(|| format!("Failed to delete directory: {}", path.display()))
 }
 
+/// Move a finding into quarantine, with the same safety check as deletion
+fn quarantine_item(file: &CleanableFile, policy: Policy) -> Result<()> {
+    if let Some(refusal) = policy.refusal(&file.path, "quarantine") {
+        return Err(refusal);
+    }
+
+    quarantine::quarantine(file)
+}
+
+/// Delete a tree of empty directories bottom-up, failing if anything
+/// other than an empty directory turns up (e.g. a file created since the scan)
+fn delete_empty_tree(path: &Path, policy: Policy) -> Result<()> {
+    if let Some(refusal) = policy.refusal(path, "delete") {
+        return Err(refusal);
+    }
+
+    for entry in walkdir::WalkDir::new(path)
+        .follow_links(false)
+        .contents_first(true)
+    {
+        let entry = entry.with_context(|| format!("Failed to read: {}", path.display()))?;
+        fs::remove_dir(entry.path()).with_context(|| {
+            format!("Directory is no longer empty: {}", entry.path().display())
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Why `is_safe_to_delete` rejected `path`
+fn refusal(path: &Path, verb: &str) -> anyhow::Error {
+    let home = dirs::home_dir();
+    if let Some(bundle) = crate::scanner::bundle_root(path) {
+        anyhow::anyhow!(
+            "Refusing to {} inside {}; bundles are only removed whole",
+            verb,
+            bundle.display()
+        )
+    } else if home.as_deref() == Some(path) {
+        anyhow::anyhow!("Refusing to {} the home directory", verb)
+    } else if home.is_some_and(|h| path.parent() == Some(h.as_path())) {
+        anyhow::anyhow!("Refusing to {} path directly in home directory", verb)
+    } else {
+        anyhow::anyhow!("Refusing to {} path outside home directory", verb)
+    }
+}
+
+/// Directories the OS owns, which `duster rm` leaves alone along with
+/// everything in them
+const SYSTEM_DIRS: &[&str] = &[
+    "/bin",
+    "/boot",
+    "/dev",
+    "/etc",
+    "/lib",
+    "/lib32",
+    "/lib64",
+    "/private/etc",
+    "/proc",
+    "/sbin",
+    "/sys",
+    "/System",
+    "/usr",
+];
+
+/// Below a system directory, but installed there by the user
+const LOCAL_DIR: &str = "/usr/local";
+
+/// Why `duster rm` refuses `path`, if it does. Like `rm`, it removes what
+/// the user names; it only guards against what no alias should take.
+fn rm_refusal(path: &Path, verb: &str) -> Option<anyhow::Error> {
+    let top_level = path.parent().is_none_or(|p| p.parent().is_none());
+    let system = SYSTEM_DIRS
+        .iter()
+        .map(PathBuf::from)
+        .chain(crate::scanner::windows::system_root().filter(|_| cfg!(windows)))
+        .find(|d| path.starts_with(d) && !path.starts_with(LOCAL_DIR));
+
+    if dirs::home_dir().is_some_and(|h| path == h) {
+        Some(anyhow::anyhow!("Refusing to {} the home directory", verb))
+    } else if top_level {
+        Some(anyhow::anyhow!(
+            "Refusing to {} {}, a top-level directory",
+            verb,
+            path.display()
+        ))
+    } else if let Some(dir) = system {
+        Some(anyhow::anyhow!(
+            "Refusing to {} {}, in system directory {}",
+            verb,
+            path.display(),
+            dir.display()
+        ))
+    } else {
+        crate::scanner::bundle_root(path).map(|_| refusal(path, verb))
+    }
+}
+
+/// Whether `path` is below the root of a drive mounted under `/Volumes`.
+/// The startup disk is linked in there too, and is left out.
+fn is_on_mounted_volume(path: &Path) -> bool {
+    let Ok(rest) = path.strip_prefix("/Volumes") else {
+        return false;
+    };
+    let mut components = rest.components();
+    let Some(std::path::Component::Normal(volume)) = components.next() else {
+        return false;
+    };
+    components.next().is_some()
+        && rest
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+        && fs::symlink_metadata(Path::new("/Volumes").join(volume))
+            .is_ok_and(|m| !m.file_type().is_symlink())
+}
+
 /// Check if a path is safe to delete
 fn is_safe_to_delete(path: &Path) -> bool {
+    // Removing part of an app bundle or library breaks it
+    if crate::scanner::bundle_root(path).is_some() {
+        return false;
+    }
+
     // Must be within home directory
     if let Some(home) = dirs::home_dir() {
+        if path == home {
+            return false;
+        }
         if path.starts_with(&home) {
             // Don't delete direct children of home
             if path.parent() == Some(&home) {
@@ -208,19 +826,84 @@ fn is_safe_to_delete(path: &Path) -> bool {
         }
     }
 
+    // Items in the user's trash on another volume, which lives outside
+    // the home
+    if crate::scanner::trash::volume_trash_dirs()
+        .iter()
+        .any(|d| path.starts_with(d) && path != d)
+    {
+        return true;
+    }
+
     // Allow temp directories
     if path.starts_with("/tmp") || path.starts_with("/var/tmp") || path.starts_with("/var/folders") {
         return true;
     }
 
+    // Windows keeps its temp files and update downloads outside the profile
+    if let Some(root) = crate::scanner::windows::system_root().filter(|_| cfg!(windows)) {
+        if path.starts_with(root.join("Temp"))
+            || crate::scanner::windows::update_download_dir().is_some_and(|d| path.starts_with(d))
+        {
+            return true;
+        }
+    }
+
+    // Windows-side Downloads and Temp, when scanned from WSL
+    if crate::scanner::wsl::windows_downloads_dir()
+        .into_iter()
+        .chain(crate::scanner::wsl::windows_temp_dir())
+        .any(|d| path.starts_with(&d) && path != d)
+    {
+        return true;
+    }
+
     false
 }
 
+/// Build the JSON document for `clean --json`: the deletion plan and the
+/// order it is cleaned in, how many irreplaceable findings `--yes` left out,
+/// and the cleanup result, which is null when nothing was deleted (empty plan
+/// or declined confirmation)
+pub fn json_report(
+    plan: &ScanResult,
+    skipped_irreplaceable: usize,
+    result: Option<&CleanupResult>,
+) -> serde_json::Value {
+    serde_json::json!({
+        "plan": crate::analyzer::json_report(plan),
+        "order": cleanup_plan(&plan.files),
+        "skipped_irreplaceable": skipped_irreplaceable,
+        "result": result,
+    })
+}
+
+/// Print JSON output of a cleanup
+pub fn print_json_report(
+    plan: &ScanResult,
+    skipped_irreplaceable: usize,
+    result: Option<&CleanupResult>,
+) -> Result<()> {
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&json_report(plan, skipped_irreplaceable, result))?
+    );
+
+    Ok(())
+}
+
 /// Print cleanup results
+#[cfg(feature = "cli")]
 pub fn print_cleanup_result(result: &CleanupResult) {
     println!();
 
-    if result.deleted_count > 0 {
+    if result.deleted_count > 0 && result.quarantined {
+        ui::print_success(&format!(
+            "Quarantined {} items ({}); restore with `duster quarantine restore`",
+            ui::format_number(result.deleted_count as u64),
+            ui::format_size(result.freed_bytes)
+        ));
+    } else if result.deleted_count > 0 {
         ui::print_success(&format!(
             "Cleaned {} items, freed {}",
             ui::format_number(result.deleted_count as u64),
@@ -230,17 +913,266 @@ pub fn print_cleanup_result(result: &CleanupResult) {
         ui::print_info("No files were deleted.");
     }
 
+    if result.skipped_count > 0 {
+        ui::print_info(&format!(
+            "{} report-only item(s) left in place; see the hints in the preview.",
+            result.skipped_count
+        ));
+    }
+
     if !result.errors.is_empty() {
         println!();
         ui::print_warning(&format!(
             "{} item(s) could not be deleted:",
             result.errors.len()
         ));
-        for error in result.errors.iter().take(5) {
-            println!("  {}", error.dimmed());
+        print_failures(result);
+    }
+}
+
+/// Print failed items grouped by cause, largest group first, with a hint each
+#[cfg(feature = "cli")]
+fn print_failures(result: &CleanupResult) {
+    let mut groups: HashMap<FailureKind, Vec<(&Path, &str)>> = HashMap::new();
+    for item in &result.items {
+        if let CleanupOutcome::Failed { kind, error } = &item.outcome {
+            groups
+                .entry(*kind)
+                .or_default()
+                .push((item.path.as_path(), error.as_str()));
         }
-        if result.errors.len() > 5 {
-            println!("  ... and {} more errors", result.errors.len() - 5);
+    }
+
+    let mut groups: Vec<_> = groups.into_iter().collect();
+    groups.sort_by_key(|(kind, items)| (std::cmp::Reverse(items.len()), *kind));
+
+    for (kind, items) in groups {
+        println!();
+        println!("  {} ({})", kind.label().bold(), items.len());
+        println!("  {}", kind.hint().dimmed());
+
+        for (path, error) in items.iter().take(3) {
+            // The cause is already in the heading; other errors need their message
+            if kind == FailureKind::Other {
+                println!("    {}", error.dimmed());
+            } else {
+                println!("    {}", ui::format_path(path).dimmed());
+            }
         }
+        if items.len() > 3 {
+            println!("    ... and {} more", items.len() - 3);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::testing::finding;
+
+    #[test]
+    fn test_failure_kind_looks_through_context() {
+        let missing = fs::remove_file("/nonexistent/duster-test")
+            .context("Failed to delete file")
+            .unwrap_err();
+        assert_eq!(FailureKind::classify(&missing), FailureKind::NotFound);
+
+        let refused = anyhow::anyhow!("Refusing to delete path outside home directory");
+        assert_eq!(FailureKind::classify(&refused), FailureKind::Other);
+    }
+
+    #[test]
+    fn test_cleanup_plan_orders_by_priority_and_nesting() {
+        let files = vec![
+            finding("/home/me/movie.mkv", 1, Category::LargeFile),
+            CleanableFile {
+                is_directory: true,
+                ..finding("/home/me/app/node_modules", 1, Category::BuildArtifact)
+            },
+            // Reported on its own, but inside the build artifact above
+            finding("/home/me/app/node_modules/.DS_Store", 1, Category::MetadataJunk),
+            finding("/home/me/.local/share/Trash/files/old.txt", 1, Category::Trash),
+        ];
+
+        let plan = cleanup_plan(&files);
+        let paths: Vec<&str> = plan.iter().map(|p| p.path.to_str().unwrap()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "/home/me/.local/share/Trash/files/old.txt",
+                "/home/me/app/node_modules",
+                "/home/me/app/node_modules/.DS_Store",
+                "/home/me/movie.mkv",
+            ]
+        );
+        assert_eq!(
+            plan[2].covered_by.as_deref(),
+            Some(Path::new("/home/me/app/node_modules"))
+        );
+        assert!(plan[1].covered_by.is_none());
+    }
+
+    #[test]
+    fn test_path_findings_check_every_path_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let build = dir.path().join("build");
+        fs::create_dir_all(build.join("out")).unwrap();
+        fs::write(build.join("out/app.o"), b"object").unwrap();
+        let log = dir.path().join("build.log");
+        fs::write(&log, b"log").unwrap();
+
+        let config = Config::default();
+        let tags = Tags::default();
+        let paths = [build.join("out/../../build"), log.clone()];
+        let found = path_findings(&paths, &config, &tags, Policy::Findings).unwrap();
+        let canonical = dir.path().canonicalize().unwrap();
+        assert_eq!(found[0].path, canonical.join("build"));
+        assert!(found[0].is_directory);
+        assert_eq!(found[0].size, 6);
+        assert_eq!(found[1].path, canonical.join("build.log"));
+        assert_eq!(found[1].category, Category::Explicit);
+
+        let missing = [log.clone(), dir.path().join("missing")];
+        assert!(path_findings(&missing, &config, &tags, Policy::Findings).is_err());
+
+        // A directory holding a kept path is refused too
+        let mut tags = Tags::default();
+        tags.set(canonical.join("build/out"), Tag::Keep);
+        let err = path_findings(
+            std::slice::from_ref(&build),
+            &config,
+            &tags,
+            Policy::Findings,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("which is tagged keep"));
+
+        let mut tags = Tags::default();
+        tags.set(canonical.join("build"), Tag::Keep);
+        // One refused path fails the whole list, whatever its position
+        let err = path_findings(&[log, build], &config, &tags, Policy::Findings).unwrap_err();
+        assert!(err.to_string().contains("is tagged keep"));
+
+        // Never the home itself
+        let home = dirs::home_dir().unwrap();
+        assert!(path_findings(&[home], &config, &Tags::default(), Policy::Findings).is_err());
+    }
+
+    #[test]
+    fn test_rm_refuses_only_dangerous_roots() {
+        let home = dirs::home_dir().unwrap();
+        for path in ["/home/me/notes.txt", "/srv/x/b.txt", "/usr/local/bin/tool"] {
+            assert!(rm_refusal(Path::new(path), "delete").is_none(), "{}", path);
+        }
+        assert!(rm_refusal(&home.join("notes.txt"), "delete").is_none());
+
+        let reason = |path: &Path| rm_refusal(path, "delete").unwrap().to_string();
+        assert_eq!(
+            reason(Path::new("/")),
+            "Refusing to delete /, a top-level directory"
+        );
+        assert_eq!(
+            reason(Path::new("/srv")),
+            "Refusing to delete /srv, a top-level directory"
+        );
+        assert_eq!(reason(&home), "Refusing to delete the home directory");
+        assert_eq!(
+            reason(Path::new("/etc/hosts")),
+            "Refusing to delete /etc/hosts, in system directory /etc"
+        );
+        assert_eq!(
+            reason(Path::new("/srv/Photos.photoslibrary/database")),
+            "Refusing to delete inside /srv/Photos.photoslibrary; bundles are only removed whole"
+        );
+
+        // Scan findings keep the stricter policy, with the real reason
+        assert_eq!(
+            refusal(&home.join("notes.txt"), "delete").to_string(),
+            "Refusing to delete path directly in home directory"
+        );
+        assert_eq!(
+            refusal(Path::new("/srv/x/b.txt"), "delete").to_string(),
+            "Refusing to delete path outside home directory"
+        );
+    }
+
+    #[test]
+    fn test_mounted_volume_roots_are_not_deletable() {
+        assert!(!is_on_mounted_volume(Path::new("/Volumes")));
+        assert!(!is_on_mounted_volume(Path::new("/Volumes/Backup")));
+        assert!(!is_on_mounted_volume(Path::new("/Volumes/Backup/../../etc/hosts")));
+        assert!(!is_on_mounted_volume(Path::new("/Users/me/Volumes/Backup/photo.jpg")));
+    }
+
+    #[test]
+    fn test_only_duplicates_under_given_roots_may_leave_home() {
+        let path = "/Volumes/Backup/photo.jpg";
+        assert!(!may_delete(&finding(path, 1, Category::LargeFile)));
+        assert!(!may_delete(&finding(path, 1, Category::Duplicate)));
+
+        let given = finding(path, 1, Category::Duplicate)
+            .with_extra(extra::DUPLICATE_ROOT, "/Volumes/Backup");
+        assert_eq!(Policy::Findings.for_file(&given), Policy::DuplicateRoot);
+        assert_eq!(Policy::Rm.for_file(&given), Policy::Rm);
+        let bundled = finding(
+            "/Volumes/Backup/A.photoslibrary/x.jpg",
+            1,
+            Category::Duplicate,
+        )
+        .with_extra(extra::DUPLICATE_ROOT, "/Volumes/Backup");
+        assert!(!may_delete(&bundled));
+        let elsewhere = finding("/srv/photo.jpg", 1, Category::Duplicate)
+            .with_extra(extra::DUPLICATE_ROOT, "/srv");
+        assert!(!may_delete(&elsewhere));
+    }
+
+    #[test]
+    fn test_only_real_trash_roots_are_allowed_outside_home() {
+        for path in [
+            "/srv/data/.Trashes/501/report.pdf",
+            "/srv/data/.Trash-1000/files/report.pdf",
+            "/opt/app/.local/share/Trash/files/report.pdf",
+            "/srv/$RECYCLE.BIN/report.pdf",
+        ] {
+            assert!(!is_safe_to_delete(Path::new(path)), "{}", path);
+        }
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_would_clean_matches_refusals() {
+        assert!(would_clean(&finding("/tmp/build.log", 1, Category::Temp)));
+        assert!(!would_clean(&finding("/etc/hosts", 1, Category::LargeFile)));
+        assert!(!would_clean(&finding("/tmp/Tool.app/Contents/Info.plist", 1, Category::Temp)));
+        let report_only = CleanableFile {
+            action: CleanupAction::ReportOnly {
+                hint: String::new(),
+            },
+            ..finding("/tmp/build.log", 1, Category::Temp)
+        };
+        assert!(!would_clean(&report_only));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_replaced_path_is_not_deleted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.pdf");
+        fs::write(&path, b"scanned").unwrap();
+        let mut file = finding(path.to_str().unwrap(), 1, Category::LargeFile).with_identity();
+        assert!(!file.was_replaced());
+
+        // Same name, different file
+        let replacement = dir.path().join("report.pdf.new");
+        fs::write(&replacement, b"new").unwrap();
+        fs::rename(&replacement, &path).unwrap();
+        assert!(file.was_replaced());
+
+        let result = delete_files(std::slice::from_ref(&file), None).unwrap();
+        assert_eq!(result.deleted_count, 0);
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+
+        file = file.with_identity();
+        assert_eq!(delete_files(&[file], None).unwrap().deleted_count, 1);
     }
 }
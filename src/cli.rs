@@ -1,6 +1,6 @@
 //! CLI argument definitions using clap derive
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 /// A developer-focused CLI tool to clean up unused files and free disk space
@@ -10,6 +10,10 @@ use std::path::PathBuf;
 pub struct Cli {
     #[command(subcommand)]
     pub command: Command,
+
+    /// Don't offer the setup wizard when no config file exists
+    #[arg(long, global = true)]
+    pub no_wizard: bool,
 }
 
 #[derive(Subcommand, Debug)]
@@ -23,11 +27,54 @@ pub enum Command {
     /// Show disk usage breakdown by category
     Analyze(AnalyzeOptions),
 
+    /// Show the top easy wins from a fast scan and apply them with one key
+    Suggest(SuggestOptions),
+
+    /// Show how other thresholds would change the last scan's findings,
+    /// without rescanning
+    Whatif(WhatifOptions),
+
+    /// Print reclaimable space for a status bar (waybar, xbar, polybar)
+    Status(StatusOptions),
+
+    /// Keep a live estimate of cleanable space using filesystem events
+    Monitor(MonitorOptions),
+
     /// Check disk space (total / free)
     Space(SpaceOptions),
 
+    /// Serve scan reports over HTTP (read-only)
+    Serve(ServeOptions),
+
+    /// Clean a stale project's artifacts, archive it, and remove the working copy
+    ArchiveProject(ArchiveOptions),
+
+    /// List, restore or purge items moved aside by `clean --quarantine`
+    Quarantine(QuarantineOptions),
+
+    /// Move files into quarantine instead of deleting them; meant as an `rm` alias
+    Rm(RmOptions),
+
+    /// Tag a path keep, later or delete for future scans and cleans, or list tags
+    Tag(TagOptions),
+
+    /// List or restore Windows Recycle Bin items
+    RecycleBin(RecycleBinOptions),
+
+    /// As root, scan every user's home with that user's own config
+    Users(UsersOptions),
+
+    /// Run a Model Context Protocol server on stdio for AI assistants
+    Mcp,
+
+    /// Replace this binary with the latest release, after checking its checksum
+    SelfUpdate(SelfUpdateOptions),
+
+    /// Show how much duster has freed over time (kept locally, never sent)
+    Stats(StatsOptions),
+
     /// Show or edit configuration
-    Config,
+    Config(ConfigOptions),
 }
 
 /// Options shared between scan, clean, and analyze commands
@@ -53,10 +100,18 @@ pub struct ScanOptions {
     #[arg(long)]
     pub downloads: bool,
 
+    /// Include old desktop items
+    #[arg(long)]
+    pub desktop: bool,
+
     /// Include build artifacts (node_modules, target, etc.)
     #[arg(long)]
     pub build: bool,
 
+    /// Include stale workspaces and caches of self-hosted CI runners
+    #[arg(long)]
+    pub ci: bool,
+
     /// Include large files
     #[arg(long)]
     pub large: bool,
@@ -69,6 +124,37 @@ pub struct ScanOptions {
     #[arg(long)]
     pub old: bool,
 
+    /// Include empty directories and broken symlinks
+    #[arg(long)]
+    pub junk: bool,
+
+    /// Include rotated logs and logs not written to in a while
+    #[arg(long)]
+    pub logs: bool,
+
+    /// Include OS metadata files like .DS_Store and Thumbs.db (opt-in, not part of --all)
+    #[arg(long)]
+    pub metadata_junk: bool,
+
+    /// Include unreferenced Git LFS objects and large blobs in git history (opt-in, not part of --all)
+    #[arg(long)]
+    pub git: bool,
+
+    /// Include Docker's dangling images, stopped containers, unused volumes and build
+    /// cache (opt-in, not part of --all)
+    #[arg(long)]
+    pub docker: bool,
+
+    /// Run this scanner (e.g. "known-cache"), even if its category isn't
+    /// selected; other scanners of its category are left out (can be repeated)
+    #[arg(long, value_name = "ID")]
+    pub scanner: Vec<String>,
+
+    /// Don't run this scanner (e.g. "cache-generic"), whatever the category
+    /// flags say (can be repeated)
+    #[arg(long, value_name = "ID")]
+    pub no_scanner: Vec<String>,
+
     /// Minimum age in days for "old" files (default: 30)
     #[arg(long, value_name = "DAYS")]
     pub min_age: Option<u32>,
@@ -77,6 +163,19 @@ pub struct ScanOptions {
     #[arg(long, value_name = "SIZE")]
     pub min_size: Option<String>,
 
+    /// Only report findings covering at least this many files/directories
+    #[arg(long, value_name = "COUNT")]
+    pub min_file_count: Option<u64>,
+
+    /// Only report findings that would free at least this much (e.g. "50MB")
+    #[arg(long, value_name = "SIZE")]
+    pub min_savings: Option<String>,
+
+    /// Only report findings whose scanner detail KEY equals VALUE, e.g.
+    /// `project_root=/home/me/app` (can be repeated)
+    #[arg(long = "where", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub where_extra: Vec<(String, String)>,
+
     /// Consider project "recent" if accessed within X days (default: 14)
     #[arg(long, value_name = "DAYS")]
     pub project_age: Option<u32>,
@@ -85,29 +184,357 @@ pub struct ScanOptions {
     #[arg(long, value_name = "PATH")]
     pub path: Option<PathBuf>,
 
+    /// Only include files owned by this user, or "any" (default: current user)
+    #[arg(long, value_name = "USER")]
+    pub owner: Option<String>,
+
     /// Exclude paths matching pattern (can be repeated)
     #[arg(long, value_name = "PATTERN")]
     pub exclude: Vec<String>,
 
+    /// Look for duplicates across these roots instead of --path, e.g. one per
+    /// drive (can be repeated)
+    #[arg(long, value_name = "PATH")]
+    pub duplicates_path: Vec<PathBuf>,
+
+    /// Recompute duplicate hashes instead of reusing ones from earlier scans
+    #[arg(long)]
+    pub rehash: bool,
+
+    /// Also report and clean inside Dropbox, OneDrive, Google Drive and
+    /// iCloud Drive folders; deletions there sync to every device
+    #[arg(long)]
+    pub include_cloud: bool,
+
+    /// With --path, skip configured roots outside it (`cache_paths`)
+    /// instead of scanning them with a note
+    #[arg(long)]
+    pub strict_path: bool,
+
+    /// Find large and old files through the file index (Spotlight on
+    /// macOS, plocate/locate on Linux, the NTFS MFT on Windows, which needs
+    /// an elevated shell) instead of walking the disk; falls back to the
+    /// walk when there is no usable index
+    #[arg(long)]
+    pub use_index: bool,
+
+    /// Point out caches far larger than on a typical machine, using size
+    /// ranges shipped with duster (offline)
+    #[arg(long)]
+    pub compare_baseline: bool,
+
     /// Output results as JSON
     #[arg(long)]
     pub json: bool,
+
+    /// Print findings as each scanner finishes instead of all at the end
+    #[arg(long, conflicts_with = "json")]
+    pub stream: bool,
+
+    /// Print a single status line of category sizes (for shell prompts and status bars)
+    #[arg(long, conflicts_with_all = ["json", "stream"])]
+    pub oneline: bool,
+
+    /// With --oneline, rescan and print a new line every N seconds
+    #[arg(long, value_name = "SECONDS", requires = "oneline")]
+    pub refresh: Option<u64>,
+
+    /// Skip stable result ordering (faster for very large scans)
+    #[arg(long)]
+    pub no_sort: bool,
+
+    /// Threads for scanners and duplicate hashing (default: `threads` in
+    /// config.toml, or one per CPU core); fewer keep the machine responsive
+    #[arg(long, value_name = "N", value_parser = parse_threads)]
+    pub threads: Option<usize>,
+
+    /// Compare duplicates larger than this by size and sampled chunks
+    /// instead of a full hash, reported as probable (e.g. "10GB")
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub max_hash_size: Option<String>,
+
+    /// Read at most this much per second while hashing duplicates (e.g. "50MB")
+    #[arg(long, value_name = "SIZE", value_parser = parse_size)]
+    pub hash_rate: Option<String>,
+
+    /// When done, report the run's time, directories examined, peak memory
+    /// and bytes read from disk
+    #[arg(long)]
+    pub resources: bool,
 }
 
 #[derive(Parser, Debug)]
 pub struct CleanOptions {
+    #[command(subcommand)]
+    pub target: Option<CleanTarget>,
+
     #[command(flatten)]
     pub scan: ScanOptions,
 
     /// Skip confirmation prompts
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub yes: bool,
+
+    /// With --yes, also delete irreplaceable findings (downloads, desktop,
+    /// large, old and duplicate files, trash); by default only findings that
+    /// can be re-downloaded or rebuilt are touched
+    #[arg(long)]
+    pub include_irreplaceable: bool,
+
+    /// Re-attempt the items that failed in the last cleanup, if a scan still finds them
+    #[arg(long)]
+    pub retry_failed: bool,
+
+    /// Move items into quarantine instead of deleting them; they can be
+    /// restored until purged after `quarantine_days`
+    #[arg(long, global = true)]
+    pub quarantine: bool,
+
+    /// Don't delete anything; show each disk's free space now and after
+    /// the cleanup
+    #[arg(long, conflicts_with = "quarantine")]
+    pub simulate: bool,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CleanTarget {
+    /// Clean the given files and directories instead of scanning, with the
+    /// usual safety checks, preview, quarantine and history
+    Path {
+        /// Files or directories to remove
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+    },
 }
 
 #[derive(Parser, Debug)]
 pub struct AnalyzeOptions {
     #[command(flatten)]
     pub scan: ScanOptions,
+
+    /// Break down findings by file extension
+    #[arg(long)]
+    pub by_extension: bool,
+
+    /// With --by-extension, include every file under the scan path, not just findings
+    #[arg(long, requires = "by_extension")]
+    pub all_files: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct ConfigOptions {
+    /// Run the setup wizard and write a new config.toml
+    #[arg(long)]
+    pub setup: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct QuarantineOptions {
+    #[command(subcommand)]
+    pub action: QuarantineAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QuarantineAction {
+    /// List quarantined items with their original paths
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Move a quarantined item back to its original path
+    Restore {
+        /// Entry id or original path
+        target: String,
+    },
+
+    /// Permanently delete items quarantined more than `quarantine_days` ago
+    Purge {
+        /// Purge everything in quarantine, regardless of age
+        #[arg(long)]
+        all: bool,
+
+        /// Skip confirmation prompts
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct RmOptions {
+    /// Files or directories to remove
+    #[arg(required_unless_present = "undo_last")]
+    pub paths: Vec<PathBuf>,
+
+    /// Remove directories and their contents
+    #[arg(short = 'r', visible_short_alias = 'R', long)]
+    pub recursive: bool,
+
+    /// Ignore paths that don't exist
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Name each path as it is removed
+    #[arg(short, long)]
+    pub verbose: bool,
+
+    /// Put back what the last `duster rm` removed
+    #[arg(long, conflicts_with = "paths")]
+    pub undo_last: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct TagOptions {
+    /// File or directory to tag; the tag covers everything below it
+    pub path: Option<PathBuf>,
+
+    /// keep: never report it; later: report, but only clean when picked by
+    /// hand; delete: clean it, even with --yes; clear: remove the tag
+    #[arg(value_enum)]
+    pub action: Option<TagAction>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TagAction {
+    Keep,
+    Later,
+    Delete,
+    Clear,
+}
+
+#[derive(Parser, Debug)]
+pub struct RecycleBinOptions {
+    #[command(subcommand)]
+    pub action: RecycleBinAction,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum RecycleBinAction {
+    /// List items with their original paths and sizes
+    List,
+
+    /// Put an item back where it was deleted from
+    Restore {
+        /// The item's original path, or its path inside `$Recycle.Bin`
+        path: PathBuf,
+    },
+}
+
+#[derive(Parser, Debug)]
+pub struct UsersOptions {
+    /// Also clean the homes of users whose config sets `system_scan = "clean"`.
+    /// Like `clean --yes`, only recoverable findings are deleted.
+    #[arg(long)]
+    pub clean: bool,
+
+    /// Also report on homes whose config doesn't set `system_scan`; only
+    /// homes that opted in are scanned otherwise
+    #[arg(long)]
+    pub all_users: bool,
+
+    /// Write each user's JSON report to DIR/<user>.json
+    #[arg(long, value_name = "DIR")]
+    pub report_dir: Option<PathBuf>,
+
+    /// Output as JSON
+    #[arg(long)]
+    pub json: bool,
+
+    /// Scan options for each user's scan, e.g. `duster users -- --cache --build`
+    #[arg(last = true)]
+    pub scan_args: Vec<String>,
+}
+
+#[derive(Parser, Debug)]
+pub struct SuggestOptions {
+    /// Number of suggestions to show
+    #[arg(long, default_value_t = 5)]
+    pub limit: usize,
+}
+
+#[derive(Parser, Debug)]
+pub struct WhatifOptions {
+    /// Minimum age in days for old files
+    #[arg(long, value_name = "DAYS")]
+    pub min_age: Option<u32>,
+
+    /// Minimum size for "large" files (e.g., "100MB", "1GB")
+    #[arg(long, value_name = "SIZE")]
+    pub min_size: Option<String>,
+
+    /// Consider a project "recent" if used within this many days
+    #[arg(long, value_name = "DAYS")]
+    pub project_age: Option<u32>,
+
+    /// Output the comparison as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct SelfUpdateOptions {
+    /// Release channel to update from
+    #[arg(long, value_enum, default_value_t = UpdateChannel::Stable)]
+    pub channel: UpdateChannel,
+
+    /// Only report whether a newer release is available
+    #[arg(long)]
+    pub check: bool,
+
+    /// Reinstall even if the running version is the latest
+    #[arg(long)]
+    pub force: bool,
+
+    /// Skip the confirmation prompt
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateChannel {
+    /// Tagged releases
+    Stable,
+    /// Builds of the main branch, published as the `nightly` prerelease
+    Nightly,
+}
+
+#[derive(Parser, Debug)]
+pub struct StatsOptions {
+    /// Output the statistics as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct StatusOptions {
+    /// Output format of the status bar
+    #[arg(long, value_enum, default_value_t = StatusFormat::Waybar)]
+    pub format: StatusFormat,
+}
+
+#[derive(Parser, Debug)]
+pub struct MonitorOptions {
+    #[command(flatten)]
+    pub scan: ScanOptions,
+
+    /// Print each estimate for a status bar instead of as a one-line summary
+    #[arg(long, value_enum)]
+    pub format: Option<StatusFormat>,
+
+    /// Minutes between full rescans, which pick up new findings
+    #[arg(long, value_name = "MINUTES", default_value_t = 60)]
+    pub rescan_minutes: u64,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusFormat {
+    /// JSON for a waybar custom module ("return-type": "json")
+    Waybar,
+    /// xbar/SwiftBar plugin text with a breakdown dropdown
+    Xbar,
+    /// A single plain line for polybar and similar bars
+    Polybar,
 }
 
 #[derive(Parser, Debug)]
@@ -121,46 +548,250 @@ pub struct SpaceOptions {
     pub json: bool,
 }
 
+#[derive(Parser, Debug)]
+pub struct ArchiveOptions {
+    /// Project directory to archive
+    pub path: PathBuf,
+
+    /// Directory to write the archive to (default: archive_dir from config, or ~/Archives)
+    #[arg(long, value_name = "DIR")]
+    pub dest: Option<PathBuf>,
+
+    /// Keep the working copy after archiving
+    #[arg(long)]
+    pub keep: bool,
+
+    /// Skip confirmation prompts
+    #[arg(short, long)]
+    pub yes: bool,
+}
+
+#[derive(Parser, Debug)]
+pub struct ServeOptions {
+    #[command(flatten)]
+    pub scan: ScanOptions,
+
+    /// Port to listen on
+    #[arg(long, default_value_t = 8080)]
+    pub port: u16,
+
+    /// Address to bind (use 0.0.0.0 to allow remote access)
+    #[arg(long, default_value = "127.0.0.1")]
+    pub bind: String,
+}
+
+/// Categories cheap enough to scan often (no full home walk)
+const FAST_CATEGORIES: &[&str] = &["--cache", "--trash", "--temp"];
+
+fn parse_key_value(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .ok_or_else(|| format!("expected KEY=VALUE, got `{}`", s))
+}
+
+fn parse_threads(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) | Err(_) => Err(format!("expected a number of threads of 1 or more, got `{}`", s)),
+        Ok(threads) => Ok(threads),
+    }
+}
+
+fn parse_size(s: &str) -> Result<String, String> {
+    match crate::config::parse_size_mb(s) {
+        Some(0) | None => Err(format!(
+            "expected a size of 1MB or more, like \"10GB\", got `{}`",
+            s
+        )),
+        Some(_) => Ok(s.to_string()),
+    }
+}
+
 impl ScanOptions {
+    /// Options for a fast scan of caches, trash and temp files, as used by
+    /// `suggest` and `status`
+    pub fn fast() -> anyhow::Result<Self> {
+        let argv = std::iter::once("duster").chain(FAST_CATEGORIES.iter().copied());
+        Ok(Self::try_parse_from(argv)?)
+    }
+
     /// Returns true if no specific category was selected (defaults to all)
     pub fn no_categories_selected(&self) -> bool {
         !self.cache
             && !self.trash
             && !self.temp
             && !self.downloads
+            && !self.desktop
             && !self.build
+            && !self.ci
             && !self.large
             && !self.duplicates
             && !self.old
+            && !self.junk
+            && !self.metadata_junk
+            && !self.git
+            && !self.docker
+            && !self.logs
+            && self.scanner.is_empty()
+    }
+
+    /// Apply the config's category preferences when no category flags were
+    /// given. `enabled` replaces the default selection; `disabled` is then
+    /// removed from it. Names are flag names (e.g. "cache", "metadata-junk");
+    /// unknown names are ignored.
+    pub fn apply_config_categories(&mut self, enabled: &[String], disabled: &[String]) {
+        if self.all || !self.no_categories_selected() {
+            return;
+        }
+        if enabled.is_empty() && disabled.is_empty() {
+            return;
+        }
+
+        if enabled.is_empty() {
+            // Spell out the default selection so entries can be removed from it
+            for name in DEFAULT_CATEGORIES {
+                self.set_category(name, true);
+            }
+        }
+        for name in enabled {
+            self.set_category(name, true);
+        }
+        for name in disabled {
+            self.set_category(name, false);
+        }
+    }
+
+    /// Turn a category flag on or off by name
+    fn set_category(&mut self, name: &str, on: bool) {
+        let flag = match name {
+            "cache" => &mut self.cache,
+            "trash" => &mut self.trash,
+            "temp" => &mut self.temp,
+            "downloads" => &mut self.downloads,
+            "desktop" => &mut self.desktop,
+            "build" => &mut self.build,
+            "ci" => &mut self.ci,
+            "large" => &mut self.large,
+            "duplicates" => &mut self.duplicates,
+            "old" => &mut self.old,
+            "junk" => &mut self.junk,
+            "metadata-junk" => &mut self.metadata_junk,
+            "git" => &mut self.git,
+            "docker" => &mut self.docker,
+            "logs" => &mut self.logs,
+            _ => return,
+        };
+        *flag = on;
     }
 
     /// Returns true if a category should be included in the scan
     pub fn should_scan(&self, category: ScanCategory) -> bool {
+        // Opt-in categories are never part of the default or --all selection
+        match category {
+            ScanCategory::MetadataJunk => return self.metadata_junk,
+            ScanCategory::Git => return self.git,
+            ScanCategory::Docker => return self.docker,
+            _ => {}
+        }
+
         if self.all || self.no_categories_selected() {
             return true;
         }
 
+        self.selects(category)
+    }
+
+    /// Returns true if a category's own flag was given
+    pub fn selects(&self, category: ScanCategory) -> bool {
         match category {
             ScanCategory::Cache => self.cache,
             ScanCategory::Trash => self.trash,
             ScanCategory::Temp => self.temp,
             ScanCategory::Downloads => self.downloads,
+            ScanCategory::Desktop => self.desktop,
             ScanCategory::Build => self.build,
+            ScanCategory::Ci => self.ci,
             ScanCategory::Large => self.large,
             ScanCategory::Duplicates => self.duplicates,
             ScanCategory::Old => self.old,
+            ScanCategory::Junk => self.junk,
+            ScanCategory::MetadataJunk => self.metadata_junk,
+            ScanCategory::Git => self.git,
+            ScanCategory::Docker => self.docker,
+            ScanCategory::Logs => self.logs,
         }
     }
 }
 
+/// Category flag names scanned by default (everything except opt-in categories)
+pub const DEFAULT_CATEGORIES: &[&str] = &[
+    "cache",
+    "trash",
+    "temp",
+    "downloads",
+    "desktop",
+    "build",
+    "ci",
+    "large",
+    "duplicates",
+    "old",
+    "junk",
+    "logs",
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ScanCategory {
     Cache,
     Trash,
     Temp,
     Downloads,
+    Desktop,
     Build,
+    Ci,
     Large,
     Duplicates,
     Old,
+    Junk,
+    MetadataJunk,
+    Git,
+    Docker,
+    Logs,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bare_scan() -> ScanOptions {
+        ScanOptions::try_parse_from(["duster"]).unwrap()
+    }
+
+    #[test]
+    fn test_disabled_categories_trim_default_selection() {
+        let mut options = bare_scan();
+        options.apply_config_categories(&[], &["old".to_string()]);
+
+        assert!(options.should_scan(ScanCategory::Cache));
+        assert!(options.should_scan(ScanCategory::Build));
+        assert!(!options.should_scan(ScanCategory::Old));
+        assert!(!options.should_scan(ScanCategory::MetadataJunk));
+    }
+
+    #[test]
+    fn test_enabled_categories_replace_default_selection() {
+        let mut options = bare_scan();
+        options.apply_config_categories(&["cache".to_string(), "git".to_string()], &[]);
+
+        assert!(options.should_scan(ScanCategory::Cache));
+        assert!(options.should_scan(ScanCategory::Git));
+        assert!(!options.should_scan(ScanCategory::Large));
+    }
+
+    #[test]
+    fn test_cli_flags_override_config_categories() {
+        let mut options = ScanOptions::try_parse_from(["duster", "--old"]).unwrap();
+        options.apply_config_categories(&["cache".to_string()], &["old".to_string()]);
+
+        assert!(options.should_scan(ScanCategory::Old));
+        assert!(!options.should_scan(ScanCategory::Cache));
+    }
 }
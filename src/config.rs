@@ -2,10 +2,13 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
 use crate::cli::ScanOptions;
+use crate::scanner::{cloud, trash, Category};
 
 /// Application configuration with sensible defaults
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,17 +29,486 @@ pub struct Config {
     #[serde(default = "default_download_age_days")]
     pub download_age_days: u32,
 
-    /// Paths to always exclude from scanning
+    /// Desktop items older than this are candidates for cleanup (default: 30 days)
+    #[serde(default = "default_desktop_age_days")]
+    pub desktop_age_days: u32,
+
+    /// Only report findings covering at least this many filesystem entries
+    #[serde(default)]
+    pub min_file_count: Option<u64>,
+
+    /// Only report findings that would free at least this much (default:
+    /// report everything)
+    #[serde(default)]
+    pub min_savings_mb: Option<u64>,
+
+    /// `min_savings_mb` for single categories, by flag name (e.g.
+    /// `temp = 100`), overriding it
+    #[serde(default)]
+    pub category_min_savings_mb: BTreeMap<String, u64>,
+
+    /// Only report files owned by this user (name or uid); "any" disables
+    /// the check (default: current user)
+    #[serde(default)]
+    pub owner: Option<String>,
+
+    /// Categories scanned when none are given on the command line, by flag
+    /// name (e.g. ["cache", "build"]); empty means the default selection
+    #[serde(default)]
+    pub enabled_categories: Vec<String>,
+
+    /// Categories left out of a bare `duster scan` (e.g. ["old"]); explicit
+    /// category flags still include them
+    #[serde(default)]
+    pub disabled_categories: Vec<String>,
+
+    /// Paths to always exclude from scanning. Entries starting with `~/`,
+    /// `./` or `/` match that path and everything below it; entries with one
+    /// `*` are prefix/suffix globs; anything else matches as a fragment.
     #[serde(default)]
     pub excluded_paths: Vec<String>,
 
+    /// Preferred locations for the copy of a duplicate to keep, most
+    /// preferred first (e.g. ["~/Pictures/Library"]). Same syntax as
+    /// `excluded_paths`; copies outside all of them are kept oldest-first.
+    #[serde(default)]
+    pub duplicate_keep_paths: Vec<String>,
+
+    /// On a metered or slow connection, rank re-downloadable caches lower
+    /// in suggestions and point out re-download costs
+    #[serde(default)]
+    pub metered_connection: bool,
+
+    /// Compare findings with the typical sizes shipped with duster and point
+    /// out unusually large caches
+    #[serde(default)]
+    pub compare_baseline: bool,
+
+    /// Inside WSL, also scan the Windows user's Downloads and Temp folders
+    /// under `/mnt/c`
+    #[serde(default)]
+    pub wsl_windows_paths: bool,
+
+    /// Look up Downloads findings in Chrome and Firefox download history
+    /// for their source URL and download date
+    #[serde(default)]
+    pub download_history: bool,
+
     /// Additional cache paths to scan beyond system defaults
     #[serde(default)]
     pub cache_paths: Vec<String>,
 
+    /// Newest versions to keep per versioned cache family ("jetbrains",
+    /// "gradle", "electron"); older ones are flagged (default: 1)
+    #[serde(default)]
+    pub keep_versions: BTreeMap<String, usize>,
+
+    /// What root may do in this home with `duster users`: "off", "report"
+    /// or "clean". Unset, a home is only scanned with `--all-users`.
+    #[serde(default)]
+    pub system_scan: Option<SystemScan>,
+
+    /// Quarantined items older than this are purged (default: 30 days)
+    #[serde(default = "default_quarantine_days")]
+    pub quarantine_days: u32,
+
+    /// Threads for running scanners and hashing files (default: one per
+    /// CPU core); also `--threads`
+    #[serde(default)]
+    pub threads: Option<usize>,
+
+    /// Memory the duplicate finder may hold its file list in before moving
+    /// it to a temporary index on disk (default: 256 MB)
+    #[serde(default = "default_memory_budget_mb")]
+    pub memory_budget_mb: u64,
+
+    /// Files larger than this are compared by size and sampled chunks
+    /// rather than hashed in full (default: hash every file)
+    #[serde(default)]
+    pub max_hash_size_mb: Option<u64>,
+
+    /// Cap on how fast duplicate hashing reads, in MB per second (default:
+    /// no cap)
+    #[serde(default)]
+    pub hash_rate_mb: Option<u64>,
+
+    /// Where `archive-project` writes archives (default: ~/Archives)
+    #[serde(default)]
+    pub archive_dir: Option<String>,
+
+    /// Built-in artifact directory names never to flag (e.g. "dist")
+    #[serde(default)]
+    pub disabled_artifact_patterns: Vec<String>,
+
+    /// Extra build artifact patterns, merged with the built-in ones
+    #[serde(default, rename = "artifact_pattern")]
+    pub artifact_patterns: Vec<ArtifactPatternConfig>,
+
+    /// Re-classification rules applied to findings after a scan, in order
+    #[serde(default, rename = "rule")]
+    pub rules: Vec<RuleConfig>,
+
+    /// Scanner-level toggles under the category selection
+    #[serde(default)]
+    pub scanners: ScannersConfig,
+
+    /// Old Files settings (`[old_files]`)
+    #[serde(default)]
+    pub old_files: OldFilesConfig,
+
+    /// Threshold overrides per volume, keyed by mount point
+    /// (`[volume."/Volumes/Data"]`)
+    #[serde(default, rename = "volume")]
+    pub volumes: BTreeMap<PathBuf, VolumeConfig>,
+
     /// Base path for scanning (default: home directory)
     #[serde(skip)]
     pub base_path: Option<PathBuf>,
+
+    /// Where home-based scanners look; `--path` confines them below it
+    #[serde(skip)]
+    pub paths: Paths,
+
+    /// Roots searched for duplicates together (default: base path)
+    #[serde(skip)]
+    pub duplicate_roots: Vec<PathBuf>,
+
+    /// Hash every duplicate candidate instead of reusing the hash index
+    #[serde(skip)]
+    pub rehash: bool,
+
+    /// `--where` filters on findings' scanner details
+    #[serde(skip)]
+    pub where_extra: Vec<(String, String)>,
+
+    /// Also report and clean inside Dropbox, OneDrive, Google Drive and
+    /// iCloud Drive folders (`--include-cloud`)
+    #[serde(skip)]
+    pub include_cloud: bool,
+
+    /// Skip configured roots outside `--path` (`--strict-path`)
+    #[serde(skip)]
+    pub strict_path: bool,
+
+    /// Find large and old files through the platform's file index where
+    /// there is one (`--use-index`)
+    #[serde(skip)]
+    pub use_index: bool,
+}
+
+/// Compiled `excluded_paths`, reused while the pattern list is unchanged
+type ExcludeCache = Option<(Vec<String>, Arc<Vec<PathPattern>>)>;
+static EXCLUDE_CACHE: RwLock<ExcludeCache> = RwLock::new(None);
+
+/// A compiled `excluded_paths` or `duplicate_keep_paths` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum PathPattern {
+    /// `prefix*suffix`
+    Glob { prefix: String, suffix: String },
+    /// An absolute path and everything below it, as written (after `~` and
+    /// relative expansion) and canonicalized, when that differs
+    Path(Vec<PathBuf>),
+    /// A fragment matched anywhere in the path
+    Fragment(String),
+}
+
+impl PathPattern {
+    fn compile(pattern: &str, home: Option<&Path>, cwd: Option<&Path>) -> Self {
+        let is_relative = pattern.starts_with("./") || pattern.starts_with("../");
+        let expanded = match (pattern.strip_prefix("~/"), home, cwd) {
+            (Some(rest), Some(home), _) => home.join(rest).to_string_lossy().to_string(),
+            (None, _, Some(cwd)) if is_relative => cwd.join(pattern).to_string_lossy().to_string(),
+            _ => pattern.to_string(),
+        };
+
+        let parts: Vec<&str> = expanded.split('*').collect();
+        if parts.len() == 2 {
+            return PathPattern::Glob {
+                prefix: fold_case(parts[0]),
+                suffix: fold_case(parts[1]),
+            };
+        }
+
+        if Path::new(&expanded).is_absolute() {
+            let mut forms = vec![PathBuf::from(fold_case(&expanded))];
+            if let Ok(canonical) = Path::new(&expanded).canonicalize() {
+                let canonical = PathBuf::from(fold_case(&canonical.to_string_lossy()));
+                if !forms.contains(&canonical) {
+                    forms.push(canonical);
+                }
+            }
+            return PathPattern::Path(forms);
+        }
+
+        PathPattern::Fragment(fold_case(&expanded))
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            PathPattern::Glob { prefix, suffix } => {
+                path.starts_with(prefix.as_str()) && path.ends_with(suffix.as_str())
+            }
+            PathPattern::Path(forms) => forms.iter().any(|f| Path::new(path).starts_with(f)),
+            PathPattern::Fragment(fragment) => path.contains(fragment.as_str()),
+        }
+    }
+
+    /// Whether it matches anything strictly below `dir`. Paths are compared
+    /// as written; globs and fragments are matched against what is on disk.
+    fn matches_below(&self, dir: &Path) -> bool {
+        let form = match_form(dir);
+        let walk = || {
+            walkdir::WalkDir::new(dir)
+                .min_depth(1)
+                .into_iter()
+                .flatten()
+                .any(|e| self.matches(&match_form(e.path())))
+        };
+        match self {
+            PathPattern::Path(forms) => forms
+                .iter()
+                .any(|f| f.starts_with(&form) && f != Path::new(&form)),
+            PathPattern::Glob { prefix, .. } => {
+                (Path::new(prefix).starts_with(&form) || form.starts_with(prefix.as_str()))
+                    && walk()
+            }
+            PathPattern::Fragment(_) => walk(),
+        }
+    }
+}
+
+/// Compile path patterns against the current home and working directories
+fn compile_patterns(patterns: &[String]) -> Vec<PathPattern> {
+    let home = dirs::home_dir();
+    let cwd = std::env::current_dir().ok();
+    patterns
+        .iter()
+        .map(|p| PathPattern::compile(p, home.as_deref(), cwd.as_deref()))
+        .collect()
+}
+
+/// A path in the form patterns match against: absolute, case-folded
+fn match_form(path: &Path) -> String {
+    if path.is_relative() {
+        if let Ok(cwd) = std::env::current_dir() {
+            return fold_case(&cwd.join(path).to_string_lossy());
+        }
+    }
+    fold_case(&path.to_string_lossy())
+}
+
+/// Paths are case-insensitive on macOS and Windows by default
+fn fold_case(s: &str) -> String {
+    if cfg!(any(target_os = "macos", windows)) {
+        s.to_lowercase()
+    } else {
+        s.to_string()
+    }
+}
+
+/// A user-defined build artifact pattern (`[[artifact_pattern]]` in config.toml)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactPatternConfig {
+    /// Directory name to look for
+    pub dir_name: String,
+    /// File next to the directory that marks a project (empty: none required)
+    #[serde(default)]
+    pub project_file: String,
+    /// Description used in the finding's reason
+    #[serde(default = "default_artifact_description")]
+    pub description: String,
+}
+
+/// A re-classification rule (`[[rule]]` in config.toml). The first rule
+/// matching a finding applies to it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleConfig {
+    /// Paths it covers, as in `excluded_paths`; a trailing `/**` may be
+    /// added for clarity
+    pub path: String,
+    /// Only findings in this category (flag name, e.g. "large")
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Category to report matching findings under instead (flag name)
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub safety: Option<RuleSafety>,
+}
+
+/// How a rule changes the handling of what it matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleSafety {
+    /// Never report or clean it
+    Protect,
+    /// Report it, but only as something to look at; `clean` leaves it
+    Review,
+}
+
+/// `rules` compiled for matching
+pub struct Rules<'a> {
+    rules: Vec<(PathPattern, &'a RuleConfig)>,
+}
+
+impl<'a> Rules<'a> {
+    /// The first rule covering a finding of `category` at `path`
+    pub fn matching(&self, path: &Path, category: Category) -> Option<&'a RuleConfig> {
+        let path = match_form(path);
+        self.rules
+            .iter()
+            .find(|(pattern, rule)| {
+                rule.from
+                    .as_deref()
+                    .is_none_or(|f| f == category.flag_name())
+                    && pattern.matches(&path)
+            })
+            .map(|(_, rule)| *rule)
+    }
+
+    /// The first protect rule covering something strictly below `dir`,
+    /// whatever category it is limited to
+    pub fn protected_below(&self, dir: &Path) -> Option<&'a RuleConfig> {
+        self.rules
+            .iter()
+            .filter(|(_, rule)| rule.safety == Some(RuleSafety::Protect))
+            .find(|(pattern, _)| pattern.matches_below(dir))
+            .map(|(_, rule)| *rule)
+    }
+}
+
+/// The user folders scanners resolve their roots from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paths {
+    pub home: Option<PathBuf>,
+    /// Per-user cache roots (`~/.cache`, and `~/Library/Caches` on macOS)
+    pub caches: Vec<PathBuf>,
+    pub downloads: Option<PathBuf>,
+    pub desktop: Option<PathBuf>,
+    /// Folders holding trashed items; trash on other volumes is also
+    /// scanned when `system` is set
+    pub trash: Vec<PathBuf>,
+    /// Whether scanners may also look outside these folders: `/tmp`, the
+    /// package caches, the journal, the Nix store and the like
+    pub system: bool,
+}
+
+impl Paths {
+    /// The current user's folders, as the platform defines them
+    pub fn from_env() -> Self {
+        let home = dirs::home_dir();
+        let mut paths = match &home {
+            Some(home) => Self::sandboxed(home),
+            None => Self {
+                home: None,
+                caches: Vec::new(),
+                downloads: None,
+                desktop: None,
+                trash: Vec::new(),
+                system: true,
+            },
+        };
+        // Downloads and Desktop may be moved (XDG user dirs, Windows known
+        // folders)
+        paths.downloads = dirs::download_dir();
+        paths.desktop = dirs::desktop_dir();
+        paths.system = true;
+        paths
+    }
+
+    /// Everything laid out below `root` as below a home directory, and
+    /// nothing outside it; for `--path` and hermetic tests
+    pub fn sandboxed(root: &Path) -> Self {
+        let mut caches = Vec::new();
+        if cfg!(target_os = "macos") {
+            caches.push(root.join("Library").join("Caches"));
+        }
+        caches.push(root.join(".cache"));
+
+        Self {
+            home: Some(root.to_path_buf()),
+            caches,
+            downloads: Some(root.join("Downloads")),
+            desktop: Some(root.join("Desktop")),
+            trash: trash::home_trash_dirs(root),
+            system: false,
+        }
+    }
+}
+
+impl Default for Paths {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+/// Overrides for paths on one volume; unset thresholds fall back to the
+/// top-level ones
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VolumeConfig {
+    pub min_age_days: Option<u32>,
+    pub min_large_size_mb: Option<u64>,
+    pub project_recent_days: Option<u32>,
+    pub download_age_days: Option<u32>,
+    pub desktop_age_days: Option<u32>,
+    /// Categories (flag names, e.g. "large") never reported on this volume
+    #[serde(default)]
+    pub disabled_categories: Vec<String>,
+}
+
+/// The `[scanners]` table
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ScannersConfig {
+    /// Scanner ids (e.g. "cache-generic") never run
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+/// The `[old_files]` table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OldFilesConfig {
+    /// Leave out repositories and folders sync or notes apps manage inside
+    /// the user data directories (default: true)
+    #[serde(default = "default_skip_repos")]
+    pub skip_repos: bool,
+}
+
+fn default_skip_repos() -> bool {
+    true
+}
+
+impl Default for OldFilesConfig {
+    fn default() -> Self {
+        Self {
+            skip_repos: default_skip_repos(),
+        }
+    }
+}
+
+/// A user's consent for `duster users`, run by root across all homes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemScan {
+    /// Skip this user entirely
+    Off,
+    /// Scan and report, never delete
+    Report,
+    /// Also clean recoverable findings when run with `--clean`
+    Clean,
+}
+
+impl SystemScan {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SystemScan::Off => "off",
+            SystemScan::Report => "report",
+            SystemScan::Clean => "clean",
+        }
+    }
+}
+
+fn default_artifact_description() -> String {
+    "Build output".to_string()
 }
 
 fn default_min_age_days() -> u32 {
@@ -55,6 +527,18 @@ fn default_download_age_days() -> u32 {
     30
 }
 
+fn default_desktop_age_days() -> u32 {
+    30
+}
+
+fn default_quarantine_days() -> u32 {
+    30
+}
+
+fn default_memory_budget_mb() -> u64 {
+    256
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
@@ -62,9 +546,42 @@ impl Default for Config {
             min_large_size_mb: default_min_large_size_mb(),
             project_recent_days: default_project_recent_days(),
             download_age_days: default_download_age_days(),
+            desktop_age_days: default_desktop_age_days(),
+            min_file_count: None,
+            min_savings_mb: None,
+            category_min_savings_mb: BTreeMap::new(),
+            owner: None,
+            enabled_categories: Vec::new(),
+            disabled_categories: Vec::new(),
             excluded_paths: Vec::new(),
+            duplicate_keep_paths: Vec::new(),
+            metered_connection: false,
+            compare_baseline: false,
+            wsl_windows_paths: false,
+            download_history: false,
             cache_paths: Vec::new(),
+            keep_versions: BTreeMap::new(),
+            system_scan: None,
+            quarantine_days: default_quarantine_days(),
+            threads: None,
+            memory_budget_mb: default_memory_budget_mb(),
+            max_hash_size_mb: None,
+            hash_rate_mb: None,
+            archive_dir: None,
+            disabled_artifact_patterns: Vec::new(),
+            artifact_patterns: Vec::new(),
+            rules: Vec::new(),
+            scanners: ScannersConfig::default(),
+            old_files: OldFilesConfig::default(),
+            volumes: BTreeMap::new(),
             base_path: None,
+            paths: Paths::from_env(),
+            duplicate_roots: Vec::new(),
+            rehash: false,
+            where_extra: Vec::new(),
+            include_cloud: false,
+            strict_path: false,
+            use_index: false,
         }
     }
 }
@@ -75,23 +592,57 @@ impl Config {
         dirs::config_dir().map(|p| p.join("duster").join("config.toml"))
     }
 
+    /// Config file path of the user whose home directory is `home`, for
+    /// reading other users' settings in `duster users`
+    pub fn config_path_in_home(home: &Path) -> PathBuf {
+        let config_dir = if cfg!(target_os = "macos") {
+            home.join("Library").join("Application Support")
+        } else if cfg!(windows) {
+            home.join("AppData").join("Roaming")
+        } else {
+            home.join(".config")
+        };
+        config_dir.join("duster").join("config.toml")
+    }
+
     /// Load configuration from file, falling back to defaults
     pub fn load() -> Result<Self> {
-        let config_path = match Self::config_path() {
-            Some(p) => p,
-            None => return Ok(Self::default()),
-        };
+        match Self::config_path() {
+            Some(p) => Self::load_from(&p),
+            None => Ok(Self::default()),
+        }
+    }
 
+    /// Load configuration from a specific file, falling back to defaults
+    /// when it doesn't exist
+    pub fn load_from(config_path: &Path) -> Result<Self> {
         if !config_path.exists() {
             return Ok(Self::default());
         }
 
-        let contents = fs::read_to_string(&config_path)
+        let contents = fs::read_to_string(config_path)
             .with_context(|| format!("Failed to read config file: {}", config_path.display()))?;
+        Self::parse(&contents, config_path)
+    }
 
-        let config: Config = toml::from_str(&contents)
+    /// Parse the contents of the config file at `config_path`
+    pub fn parse(contents: &str, config_path: &Path) -> Result<Self> {
+        let config: Config = toml::from_str(contents)
             .with_context(|| format!("Failed to parse config file: {}", config_path.display()))?;
 
+        for rule in &config.rules {
+            for name in rule.from.iter().chain(&rule.category) {
+                if Category::from_flag_name(name).is_none() {
+                    anyhow::bail!(
+                        "Unknown category \"{}\" in the [[rule]] for {} ({})",
+                        name,
+                        rule.path,
+                        config_path.display()
+                    );
+                }
+            }
+        }
+
         Ok(config)
     }
 
@@ -114,22 +665,77 @@ impl Config {
 
     /// Apply CLI options to override config values
     pub fn apply_cli_options(&mut self, options: &ScanOptions) {
+        // A threshold given on the command line applies on every volume
         if let Some(min_age) = options.min_age {
             self.min_age_days = min_age;
+            self.volumes.values_mut().for_each(|v| v.min_age_days = None);
+        }
+
+        if let Some(threads) = options.threads {
+            self.threads = Some(threads);
+        }
+
+        if let Some(size_mb) = options.max_hash_size.as_deref().and_then(parse_size_mb) {
+            self.max_hash_size_mb = Some(size_mb);
+        }
+
+        if let Some(rate_mb) = options.hash_rate.as_deref().and_then(parse_size_mb) {
+            self.hash_rate_mb = Some(rate_mb);
         }
 
         if let Some(ref min_size) = options.min_size {
             if let Some(size_mb) = parse_size_mb(min_size) {
                 self.min_large_size_mb = size_mb;
+                self.volumes.values_mut().for_each(|v| v.min_large_size_mb = None);
             }
         }
 
         if let Some(project_age) = options.project_age {
             self.project_recent_days = project_age;
+            self.volumes.values_mut().for_each(|v| v.project_recent_days = None);
         }
 
+        if let Some(min_file_count) = options.min_file_count {
+            self.min_file_count = Some(min_file_count);
+        }
+
+        if let Some(size_mb) = options.min_savings.as_deref().and_then(parse_size_mb) {
+            self.min_savings_mb = Some(size_mb);
+        }
+
+        if !options.where_extra.is_empty() {
+            self.where_extra = options.where_extra.clone();
+        }
+
+        if let Some(ref owner) = options.owner {
+            self.owner = Some(owner.clone());
+        }
+
+        // Scanning somewhere other than the home scans only there, with
+        // trash, caches and Downloads looked for below it
         if let Some(ref path) = options.path {
             self.base_path = Some(path.clone());
+            let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+            if self.paths.home.as_deref().map(canonical) != Some(canonical(path)) {
+                self.paths = Paths::sandboxed(path);
+            }
+        }
+
+        if !options.duplicates_path.is_empty() {
+            self.duplicate_roots = options.duplicates_path.clone();
+        }
+        self.rehash |= options.rehash;
+        self.compare_baseline |= options.compare_baseline;
+        self.include_cloud |= options.include_cloud;
+        self.strict_path |= options.strict_path;
+        self.use_index |= options.use_index;
+
+        // `--scanner` overrides the config's disabled list; `--no-scanner` adds to it
+        self.scanners.disabled.retain(|id| !options.scanner.contains(id));
+        for id in &options.no_scanner {
+            if !self.scanners.disabled.contains(id) {
+                self.scanners.disabled.push(id.clone());
+            }
         }
 
         // Add CLI exclusions to existing ones
@@ -144,35 +750,204 @@ impl Config {
     pub fn get_base_path(&self) -> PathBuf {
         self.base_path
             .clone()
-            .or_else(dirs::home_dir)
+            .or_else(|| self.paths.home.clone())
             .unwrap_or_else(|| PathBuf::from("."))
     }
 
+    /// Get the directory `archive-project` writes to
+    pub fn get_archive_dir(&self) -> PathBuf {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        match &self.archive_dir {
+            Some(dir) => match dir.strip_prefix("~/") {
+                Some(rest) => home.join(rest),
+                None => PathBuf::from(dir),
+            },
+            None => home.join("Archives"),
+        }
+    }
+
     /// Get minimum large file size in bytes
     pub fn min_large_size_bytes(&self) -> u64 {
         self.min_large_size_mb * 1024 * 1024
     }
 
-    /// Check if a path should be excluded
-    pub fn is_excluded(&self, path: &std::path::Path) -> bool {
-        let path_str = path.to_string_lossy();
-        self.excluded_paths.iter().any(|pattern| {
-            // Simple glob-style matching
-            if pattern.contains('*') {
-                // Convert glob pattern to simple matching
-                let parts: Vec<&str> = pattern.split('*').collect();
-                if parts.len() == 2 {
-                    let (prefix, suffix) = (parts[0], parts[1]);
-                    return path_str.starts_with(prefix) && path_str.ends_with(suffix);
+    /// The `[volume]` table for the volume `path` is on: the one with the
+    /// longest mount point containing it
+    pub fn volume_for(&self, path: &Path) -> Option<&VolumeConfig> {
+        if self.volumes.is_empty() {
+            return None;
+        }
+
+        let path = PathBuf::from(match_form(path));
+        self.volumes
+            .iter()
+            .filter(|(mount, _)| path.starts_with(fold_case(&mount.to_string_lossy())))
+            .max_by_key(|(mount, _)| mount.as_os_str().len())
+            .map(|(_, volume)| volume)
+    }
+
+    /// `min_age_days` for a path, honoring its volume's override
+    pub fn min_age_days_for(&self, path: &Path) -> u32 {
+        self.volume_for(path)
+            .and_then(|v| v.min_age_days)
+            .unwrap_or(self.min_age_days)
+    }
+
+    /// `min_large_size_mb` in bytes for a path, honoring its volume's override
+    pub fn min_large_size_bytes_for(&self, path: &Path) -> u64 {
+        self.volume_for(path)
+            .and_then(|v| v.min_large_size_mb)
+            .unwrap_or(self.min_large_size_mb)
+            * 1024
+            * 1024
+    }
+
+    /// `project_recent_days` for a path, honoring its volume's override
+    pub fn project_recent_days_for(&self, path: &Path) -> u32 {
+        self.volume_for(path)
+            .and_then(|v| v.project_recent_days)
+            .unwrap_or(self.project_recent_days)
+    }
+
+    /// `download_age_days` for a path, honoring its volume's override
+    pub fn download_age_days_for(&self, path: &Path) -> u32 {
+        self.volume_for(path)
+            .and_then(|v| v.download_age_days)
+            .unwrap_or(self.download_age_days)
+    }
+
+    /// `desktop_age_days` for a path, honoring its volume's override
+    pub fn desktop_age_days_for(&self, path: &Path) -> u32 {
+        self.volume_for(path)
+            .and_then(|v| v.desktop_age_days)
+            .unwrap_or(self.desktop_age_days)
+    }
+
+    /// Whether the volume a finding is on has its category disabled
+    pub fn is_disabled_on_volume(&self, path: &Path, category: Category) -> bool {
+        self.volume_for(path)
+            .is_some_and(|v| v.disabled_categories.iter().any(|c| c == category.flag_name()))
+    }
+
+    /// Smallest finding of `category` worth reporting, in bytes
+    pub fn min_savings_bytes_for(&self, category: Category) -> u64 {
+        self.category_min_savings_mb
+            .get(category.flag_name())
+            .copied()
+            .or(self.min_savings_mb)
+            .unwrap_or(0)
+            * 1024
+            * 1024
+    }
+
+    /// `rules` compiled against the current home and working directories
+    pub fn compiled_rules(&self) -> Rules<'_> {
+        let home = dirs::home_dir();
+        let cwd = std::env::current_dir().ok();
+        let rules = self
+            .rules
+            .iter()
+            .map(|rule| {
+                let path = rule.path.strip_suffix("/**").unwrap_or(&rule.path);
+                let pattern = PathPattern::compile(path, home.as_deref(), cwd.as_deref());
+                (pattern, rule)
+            })
+            .collect();
+        Rules { rules }
+    }
+
+    /// Get the duplicate finder's memory budget in bytes
+    pub fn memory_budget_bytes(&self) -> u64 {
+        self.memory_budget_mb * 1024 * 1024
+    }
+
+    /// Size above which duplicates are only sampled, in bytes
+    pub fn max_hash_size_bytes(&self) -> Option<u64> {
+        self.max_hash_size_mb.map(|mb| mb * 1024 * 1024)
+    }
+
+    /// Duplicate hashing's read cap in bytes per second
+    pub fn hash_rate_bytes(&self) -> Option<u64> {
+        self.hash_rate_mb.map(|mb| mb * 1024 * 1024)
+    }
+
+    /// `excluded_paths` compiled for matching. Compiling canonicalizes
+    /// patterns, so the result is cached across the scanners' many calls.
+    fn exclude_patterns(&self) -> Arc<Vec<PathPattern>> {
+        if let Ok(cache) = EXCLUDE_CACHE.read() {
+            if let Some((source, patterns)) = cache.as_ref() {
+                if *source == self.excluded_paths {
+                    return Arc::clone(patterns);
                 }
             }
-            path_str.contains(pattern)
-        })
+        }
+
+        let patterns = Arc::new(compile_patterns(&self.excluded_paths));
+
+        if let Ok(mut cache) = EXCLUDE_CACHE.write() {
+            *cache = Some((self.excluded_paths.clone(), Arc::clone(&patterns)));
+        }
+        patterns
+    }
+
+    /// Whether `path` lies outside the `--path` a scan is confined to
+    pub fn is_outside_path(&self, path: &Path) -> bool {
+        !self.paths.system
+            && self
+                .paths
+                .home
+                .as_ref()
+                .is_some_and(|home| !path.starts_with(home))
+    }
+
+    /// Configured `cache_paths` outside `--path`; skipped with
+    /// `--strict-path`, otherwise scanned anyway
+    pub fn cache_paths_outside_path(&self) -> Vec<PathBuf> {
+        self.cache_paths
+            .iter()
+            .map(PathBuf::from)
+            .filter(|p| self.is_outside_path(p))
+            .collect()
+    }
+
+    /// Whether `path` is in a cloud-synced folder that is off limits
+    /// without `--include-cloud`
+    pub fn is_cloud_locked(&self, path: &Path) -> bool {
+        !self.include_cloud && cloud::root_for(path).is_some()
+    }
+
+    /// Check if a path should be excluded
+    pub fn is_excluded(&self, path: &std::path::Path) -> bool {
+        if self.is_cloud_locked(path) || crate::quarantine::is_quarantined(path) {
+            return true;
+        }
+        if self.excluded_paths.is_empty() {
+            return false;
+        }
+
+        let path_str = match_form(path);
+        self.exclude_patterns().iter().any(|p| p.matches(&path_str))
+    }
+
+    /// Rank duplicate copies by `duplicate_keep_paths`: the index of the
+    /// first rule each path falls under, or the rule count if none match
+    pub fn duplicate_keep_ranks(&self, paths: &[PathBuf]) -> Vec<usize> {
+        let rules = compile_patterns(&self.duplicate_keep_paths);
+        paths
+            .iter()
+            .map(|path| {
+                let path_str = match_form(path);
+                rules
+                    .iter()
+                    .position(|r| r.matches(&path_str))
+                    .unwrap_or(rules.len())
+            })
+            .collect()
     }
 }
 
 /// Parse a human-readable size string to megabytes
-fn parse_size_mb(s: &str) -> Option<u64> {
+pub fn parse_size_mb(s: &str) -> Option<u64> {
     let s = s.trim().to_uppercase();
 
     // Try to parse with unit suffix
@@ -218,5 +993,231 @@ mod tests {
         assert_eq!(config.min_age_days, 30);
         assert_eq!(config.min_large_size_mb, 100);
         assert_eq!(config.project_recent_days, 14);
+        assert_eq!(config.desktop_age_days, 30);
+    }
+
+    #[test]
+    fn test_exclude_tilde_and_relative_patterns() {
+        let home = Path::new("/home/dev");
+        let cwd = Path::new("/work");
+
+        let tilde = PathPattern::compile("~/projects/keep", Some(home), Some(cwd));
+        assert!(tilde.matches("/home/dev/projects/keep"));
+        assert!(tilde.matches("/home/dev/projects/keep/node_modules"));
+        assert!(!tilde.matches("/home/dev/projects/keeper"));
+
+        let relative = PathPattern::compile("./vendor", Some(home), Some(cwd));
+        assert!(relative.matches("/work/vendor/lib"));
+        assert!(!relative.matches("/elsewhere/vendor"));
+
+        let fragment = PathPattern::compile("important/node_modules", Some(home), Some(cwd));
+        assert!(fragment.matches("/home/dev/important/node_modules"));
+
+        let glob = PathPattern::compile("~/*.iso", Some(home), Some(cwd));
+        assert!(glob.matches("/home/dev/Downloads/ubuntu.iso"));
+    }
+
+    #[test]
+    fn test_exclude_matches_canonical_form() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().canonicalize().unwrap().join("real");
+        fs::create_dir(&real).unwrap();
+
+        // Written with a `..` detour; scanners report the plain path
+        let written = real.join("..").join("real");
+        let pattern = PathPattern::compile(&written.to_string_lossy(), None, None);
+        assert!(pattern.matches(&fold_case(&real.join("x").to_string_lossy())));
+    }
+
+    #[test]
+    fn test_quarantines_are_always_excluded() {
+        let config = Config::default();
+        assert!(config.is_excluded(Path::new("/mnt/backup/.duster-quarantine/1/item/a.iso")));
+        assert!(!config.is_excluded(Path::new("/mnt/backup/a.iso")));
+        if let Some(data) = dirs::data_dir() {
+            assert!(config.is_excluded(&data.join("duster/quarantine/1/item")));
+        }
+    }
+
+    #[test]
+    fn test_duplicate_keep_ranks_follow_rule_order() {
+        let config = Config {
+            duplicate_keep_paths: vec!["/photos/library".to_string(), "/photos".to_string()],
+            ..Config::default()
+        };
+
+        let paths = [
+            PathBuf::from("/tmp/copy.jpg"),
+            PathBuf::from("/photos/inbox/copy.jpg"),
+            PathBuf::from("/photos/library/copy.jpg"),
+        ];
+        assert_eq!(config.duplicate_keep_ranks(&paths), vec![2, 1, 0]);
+    }
+
+    #[cfg(any(target_os = "macos", windows))]
+    #[test]
+    fn test_exclude_is_case_insensitive() {
+        let pattern = PathPattern::compile("/Users/Dev/Keep", None, None);
+        assert!(pattern.matches(&fold_case("/users/dev/keep/file")));
+    }
+
+    #[test]
+    fn test_artifact_patterns_from_toml() {
+        let config: Config = toml::from_str(
+            r#"
+disabled_artifact_patterns = ["dist"]
+
+[[artifact_pattern]]
+dir_name = "out"
+project_file = "BUILD.gn"
+description = "GN build output"
+
+[[artifact_pattern]]
+dir_name = "_build"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(config.disabled_artifact_patterns, vec!["dist"]);
+        assert_eq!(config.artifact_patterns.len(), 2);
+        assert_eq!(config.artifact_patterns[0].project_file, "BUILD.gn");
+        assert_eq!(config.artifact_patterns[1].project_file, "");
+        assert_eq!(config.artifact_patterns[1].description, "Build output");
+    }
+
+    #[test]
+    fn test_volume_overrides_by_mount_point() {
+        let config: Config = toml::from_str(
+            r#"
+min_large_size_mb = 100
+
+[volume."/"]
+min_large_size_mb = 20
+
+[volume."/mnt/data"]
+min_age_days = 365
+disabled_categories = ["large"]
+"#,
+        )
+        .unwrap();
+
+        let system = Path::new("/home/me/video.mkv");
+        let data = Path::new("/mnt/data/video.mkv");
+        assert_eq!(config.min_large_size_bytes_for(system), 20 * 1024 * 1024);
+        // The data volume's table wins over "/" and falls back to the top level
+        assert_eq!(config.min_large_size_bytes_for(data), 100 * 1024 * 1024);
+        assert_eq!(config.min_age_days_for(data), 365);
+        assert_eq!(config.min_age_days_for(system), 30);
+        assert!(config.is_disabled_on_volume(data, Category::LargeFile));
+        assert!(!config.is_disabled_on_volume(system, Category::LargeFile));
+        // Sibling paths sharing a prefix are a different volume
+        assert_eq!(config.min_age_days_for(Path::new("/mnt/database")), 30);
+    }
+
+    #[test]
+    fn test_threads_from_config_and_cli() {
+        use clap::Parser;
+
+        let config: Config = toml::from_str("threads = 2").unwrap();
+        assert_eq!(config.threads, Some(2));
+        let mut config = Config::default();
+
+        let options = ScanOptions::try_parse_from(["duster", "--threads", "1"]).unwrap();
+        config.apply_cli_options(&options);
+        assert_eq!(config.threads, Some(1));
+        assert!(ScanOptions::try_parse_from(["duster", "--threads", "0"]).is_err());
+    }
+
+    #[test]
+    fn test_hash_limits_from_cli() {
+        use clap::Parser;
+
+        let options = ScanOptions::try_parse_from([
+            "duster",
+            "--max-hash-size",
+            "2GB",
+            "--hash-rate",
+            "50MB",
+        ])
+        .unwrap();
+        let mut config = Config::default();
+        config.apply_cli_options(&options);
+        assert_eq!(config.max_hash_size_mb, Some(2048));
+        assert_eq!(config.hash_rate_mb, Some(50));
+        for bad in [
+            ["--max-hash-size", "huge"],
+            ["--hash-rate", "0"],
+            ["--hash-rate", "10KB"],
+        ] {
+            let argv = std::iter::once("duster").chain(bad);
+            assert!(
+                ScanOptions::try_parse_from(argv).is_err(),
+                "{:?} was accepted",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_rule_with_unknown_category_fails_to_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let rule = "[[rule]]\npath = \"~/Videos\"\ncategory = \"videos\"\n";
+        fs::write(&path, rule).unwrap();
+        let error = Config::load_from(&path).unwrap_err().to_string();
+        assert!(error.contains("Unknown category \"videos\""), "{}", error);
+    }
+
+    #[test]
+    fn test_min_savings_per_category() {
+        use clap::Parser;
+
+        const MB: u64 = 1024 * 1024;
+        let toml = "[category_min_savings_mb]\ntemp = 100";
+        let mut config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.min_savings_bytes_for(Category::Temp), 100 * MB);
+        assert_eq!(config.min_savings_bytes_for(Category::Cache), 0);
+
+        let options = ScanOptions::try_parse_from(["duster", "--min-savings", "50MB"]).unwrap();
+        config.apply_cli_options(&options);
+        assert_eq!(config.min_savings_bytes_for(Category::Temp), 100 * MB);
+        assert_eq!(config.min_savings_bytes_for(Category::Cache), 50 * MB);
+    }
+
+    #[test]
+    fn test_path_confines_scanner_roots() {
+        use clap::Parser;
+
+        let dir = tempfile::tempdir().unwrap();
+        let options =
+            ScanOptions::try_parse_from(["duster", "--path", dir.path().to_str().unwrap()])
+                .unwrap();
+        let mut config = Config::default();
+        config.apply_cli_options(&options);
+
+        assert!(!config.paths.system);
+        assert_eq!(config.paths.home.as_deref(), Some(dir.path()));
+        assert!(config.paths.trash.iter().all(|t| t.starts_with(dir.path())));
+        assert!(config.paths.caches.iter().all(|c| c.starts_with(dir.path())));
+        assert_eq!(config.paths.downloads, Some(dir.path().join("Downloads")));
+
+        config.cache_paths = vec![
+            dir.path().join("cache").to_string_lossy().to_string(),
+            "/var/cache/app".to_string(),
+        ];
+        assert_eq!(
+            config.cache_paths_outside_path(),
+            vec![PathBuf::from("/var/cache/app")]
+        );
+
+        // Pointing --path at the home itself keeps the real layout
+        if let Some(home) = dirs::home_dir() {
+            let options =
+                ScanOptions::try_parse_from(["duster", "--path", home.to_str().unwrap()])
+                    .unwrap();
+            let mut config = Config::default();
+            config.apply_cli_options(&options);
+            assert_eq!(config.paths, Paths::from_env());
+        }
     }
 }
@@ -0,0 +1,151 @@
+//! File extension breakdown of findings or of an entire directory tree
+
+use crate::config::Config;
+use crate::format;
+use crate::scanner::ScanResult;
+#[cfg(feature = "cli")]
+use crate::ui;
+use anyhow::Result;
+#[cfg(feature = "cli")]
+use colored::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Label used for files without an extension
+const NO_EXTENSION: &str = "(none)";
+
+/// Number of extensions shown in the human-readable report
+#[cfg(feature = "cli")]
+const TOP_EXTENSIONS: usize = 25;
+
+/// Aggregated count and size for one extension
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtensionStat {
+    pub extension: String,
+    pub count: u64,
+    pub size: u64,
+}
+
+/// Accumulates per-extension totals
+#[derive(Debug, Default)]
+struct ExtensionTally {
+    stats: HashMap<String, (u64, u64)>,
+}
+
+impl ExtensionTally {
+    fn add(&mut self, path: &Path, size: u64) {
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| NO_EXTENSION.to_string());
+        let entry = self.stats.entry(ext).or_default();
+        entry.0 += 1;
+        entry.1 += size;
+    }
+
+    /// Add every file inside a directory tree
+    fn add_tree(&mut self, root: &Path, config: &Config) {
+        for entry in WalkDir::new(root)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| !config.is_excluded(e.path()))
+            .filter_map(|e| e.ok())
+        {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            self.add(entry.path(), size);
+        }
+    }
+
+    /// Largest extensions first, ties broken by name
+    fn into_sorted(self) -> Vec<ExtensionStat> {
+        let mut stats: Vec<ExtensionStat> = self
+            .stats
+            .into_iter()
+            .map(|(extension, (count, size))| ExtensionStat {
+                extension,
+                count,
+                size,
+            })
+            .collect();
+        stats.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.extension.cmp(&b.extension)));
+        stats
+    }
+}
+
+/// Break down scan findings by extension, looking inside directory findings
+pub fn for_findings(result: &ScanResult, config: &Config) -> Vec<ExtensionStat> {
+    let mut tally = ExtensionTally::default();
+    for file in &result.files {
+        if file.is_directory {
+            tally.add_tree(&file.path, config);
+        } else {
+            tally.add(&file.path, file.size);
+        }
+    }
+    tally.into_sorted()
+}
+
+/// Break down every file under a path by extension
+pub fn for_path(root: &Path, config: &Config) -> Vec<ExtensionStat> {
+    let mut tally = ExtensionTally::default();
+    tally.add_tree(root, config);
+    tally.into_sorted()
+}
+
+/// Print the extension breakdown as a table
+#[cfg(feature = "cli")]
+pub fn print_report(stats: &[ExtensionStat]) {
+    ui::print_header("Extension Breakdown");
+
+    println!(
+        "{:<20} {:>10} {:>12}",
+        "Extension".bold(),
+        "Files".bold(),
+        "Size".bold()
+    );
+    ui::print_table_separator(44);
+
+    for stat in stats.iter().take(TOP_EXTENSIONS) {
+        println!(
+            "{:<20} {:>10} {:>12}",
+            stat.extension,
+            ui::format_number(stat.count),
+            ui::format_size(stat.size)
+        );
+    }
+
+    if stats.len() > TOP_EXTENSIONS {
+        println!(
+            "{} {} more extensions...",
+            "...and".dimmed(),
+            stats.len() - TOP_EXTENSIONS
+        );
+    }
+
+    let total_count: u64 = stats.iter().map(|s| s.count).sum();
+    let total_size: u64 = stats.iter().map(|s| s.size).sum();
+    ui::print_summary(total_count as usize, total_size);
+}
+
+/// Print the extension breakdown as JSON
+pub fn print_json_report(stats: &[ExtensionStat]) -> Result<()> {
+    let output = serde_json::json!({
+        "extensions": stats.iter().map(|s| {
+            serde_json::json!({
+                "extension": s.extension,
+                "count": s.count,
+                "size": s.size,
+                "size_formatted": format::format_size(s.size),
+            })
+        }).collect::<Vec<_>>(),
+    });
+
+    println!("{}", serde_json::to_string_pretty(&output)?);
+
+    Ok(())
+}
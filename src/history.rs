@@ -0,0 +1,143 @@
+//! Log of past cleanups, so failed items can be retried later
+
+use crate::cleaner::{CleanupOutcome, CleanupResult};
+use crate::scanner::CleanableFile;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One finding and what happened to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryItem {
+    pub file: CleanableFile,
+    pub outcome: CleanupOutcome,
+}
+
+/// One cleanup run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub timestamp: DateTime<Utc>,
+    pub freed_bytes: u64,
+    pub items: Vec<HistoryItem>,
+}
+
+/// Cleanup runs kept in the log; older ones are dropped as new ones come in
+const MAX_ENTRIES: usize = 200;
+
+impl HistoryEntry {
+    /// Paths that could not be cleaned in this run. Only the paths: the log
+    /// is a plain file, so how to clean them is worked out again by a scan
+    /// rather than taken from it.
+    pub fn failed_paths(&self) -> Vec<PathBuf> {
+        self.items
+            .iter()
+            .filter(|item| matches!(item.outcome, CleanupOutcome::Failed { .. }))
+            .map(|item| item.file.path.clone())
+            .collect()
+    }
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("duster").join("history.jsonl"))
+}
+
+/// Drop all but the last `MAX_ENTRIES` runs from the log at `path`
+fn rotate(path: &Path) -> Result<()> {
+    let data = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read history: {}", path.display()))?;
+    let lines: Vec<&str> = data.lines().collect();
+    if lines.len() <= MAX_ENTRIES {
+        return Ok(());
+    }
+
+    let mut kept = lines[lines.len() - MAX_ENTRIES..].join("\n");
+    kept.push('\n');
+    let tmp = path.with_extension("jsonl.tmp");
+    fs::write(&tmp, kept)
+        .and_then(|_| fs::rename(&tmp, path))
+        .with_context(|| format!("Failed to write history: {}", path.display()))
+}
+
+/// Append a cleanup run to the history log
+pub fn record(files: &[CleanableFile], result: &CleanupResult) -> Result<()> {
+    let path = match history_path() {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+
+    let by_path: HashMap<&PathBuf, &CleanableFile> = files.iter().map(|f| (&f.path, f)).collect();
+    let items = result
+        .items
+        .iter()
+        .filter_map(|item| {
+            by_path.get(&item.path).map(|file| HistoryItem {
+                file: (*file).clone(),
+                outcome: item.outcome.clone(),
+            })
+        })
+        .collect();
+
+    let entry = HistoryEntry {
+        timestamp: Utc::now(),
+        freed_bytes: result.freed_bytes,
+        items,
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create data dir: {}", parent.display()))?;
+    }
+
+    let mut line = serde_json::to_string(&entry).context("Failed to serialize history entry")?;
+    line.push('\n');
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut f| f.write_all(line.as_bytes()))
+        .with_context(|| format!("Failed to write history: {}", path.display()))?;
+
+    rotate(&path)
+}
+
+/// Every cleanup run logged, oldest first
+pub fn all() -> Vec<HistoryEntry> {
+    let data = history_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .unwrap_or_default();
+    data.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// The most recent cleanup run, if any
+pub fn last() -> Option<HistoryEntry> {
+    let data = fs::read_to_string(history_path()?).ok()?;
+    data.lines()
+        .rev()
+        .find_map(|line| serde_json::from_str(line).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotate_keeps_the_latest_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.jsonl");
+        let lines: Vec<String> = (0..MAX_ENTRIES + 5).map(|i| i.to_string()).collect();
+        fs::write(&path, lines.join("\n") + "\n").unwrap();
+
+        rotate(&path).unwrap();
+        let data = fs::read_to_string(&path).unwrap();
+        let kept: Vec<&str> = data.lines().collect();
+        assert_eq!(kept.len(), MAX_ENTRIES);
+        assert_eq!(kept[0], "5");
+        assert_eq!(kept.last(), Some(&(MAX_ENTRIES + 4).to_string().as_str()));
+    }
+}
@@ -2,10 +2,53 @@
 //!
 //! This library provides the core functionality for scanning, analyzing, and cleaning
 //! various types of files that are safe to remove from a developer's system.
+//!
+//! Terminal output (progress bars, prompts, colored reports) and the `space`,
+//! `suggest`, `status`, `whatif`, `users`, `rm`, `archive-project` and `self-update` commands live behind the default `cli` feature. Disable default features to
+//! embed just the scanners, cleaner, and config. The filesystem-event driven
+//! `monitor` module is behind the `monitor` feature, which `cli` enables.
 
 pub mod analyzer;
+#[cfg(feature = "cli")]
+pub mod archive;
+#[cfg(feature = "async")]
+pub mod async_api;
+pub mod baseline;
 pub mod cleaner;
 pub mod cli;
 pub mod config;
+pub mod extensions;
+pub mod format;
+pub mod history;
+pub mod mcp;
+#[cfg(feature = "monitor")]
+pub mod monitor;
+pub mod owner;
+pub mod passes;
+pub mod quarantine;
+#[cfg(feature = "cli")]
+pub mod resources;
+#[cfg(feature = "cli")]
+pub mod rm;
+pub mod scan_cache;
 pub mod scanner;
+pub mod serve;
+#[cfg(feature = "cli")]
+pub mod space;
+pub mod stats;
+#[cfg(feature = "cli")]
+pub mod status;
+#[cfg(feature = "cli")]
+pub mod suggest;
+pub mod tags;
+#[cfg(feature = "cli")]
 pub mod ui;
+#[cfg(feature = "cli")]
+pub mod update;
+#[cfg(feature = "cli")]
+pub mod users;
+#[cfg(feature = "cli")]
+pub mod whatif;
+#[cfg(feature = "cli")]
+pub mod wizard;
+pub mod worker;
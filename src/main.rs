@@ -3,18 +3,17 @@
 use anyhow::Result;
 use clap::Parser;
 use colored::*;
-
-mod analyzer;
-mod cleaner;
-mod cli;
-mod config;
-mod scan_cache;
-mod scanner;
-mod space;
-mod ui;
-
-use cli::{Cli, Command};
-use config::Config;
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use duster::cli::{CleanTarget, Cli, Command, RecycleBinAction, RecycleBinOptions, ScanOptions};
+use duster::config::{Config, RuleSafety};
+use duster::scanner::{windows, wsl, ScanResult};
+use duster::{
+    analyzer, archive, cleaner, extensions, history, mcp, monitor, quarantine, resources, rm,
+    scan_cache, serve, space, stats, status, suggest, tags, ui, update, users, whatif, wizard,
+};
+use duster::tags::{Tag, Tags};
 
 fn main() -> Result<()> {
     // Set up Ctrl+C handler
@@ -22,24 +21,40 @@ fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    // Load configuration
-    let mut config = Config::load()?;
+    // Load configuration, offering the setup wizard on first run
+    let mut config = if wizard::should_offer(&cli) {
+        wizard::offer()?
+    } else {
+        Config::load()?
+    };
 
     match cli.command {
-        Command::Scan(options) => {
+        Command::Scan(mut options) => {
             // Apply CLI options to config
+            options.apply_config_categories(&config.enabled_categories, &config.disabled_categories);
             config.apply_cli_options(&options);
+            let _resources = options.resources.then(resources::Meter::start);
+
+            if options.oneline {
+                return print_oneline(&options, &config);
+            }
 
             // Run scan
-            let result = analyzer::run_scan(&options, &config)?;
+            let started = std::time::Instant::now();
+            let result = if options.stream {
+                analyzer::run_scan_streaming(&options, &config, &analyzer::print_streamed_findings)?
+            } else {
+                analyzer::run_scan(&options, &config)?
+            };
 
             if result.files.is_empty() {
+                analyzer::print_notes(&result);
                 ui::print_info("No cleanable files found.");
                 return Ok(());
             }
 
             // Cache result for clean to reuse if run within 5 minutes
-            let _ = scan_cache::save(&result, &options);
+            let _ = scan_cache::save(&result, &options, &config, started.elapsed());
 
             // Print report
             if options.json {
@@ -49,70 +64,282 @@ fn main() -> Result<()> {
             }
         }
 
-        Command::Clean(options) => {
+        Command::Clean(mut options) => {
             // Apply CLI options to config
+            options.scan.apply_config_categories(&config.enabled_categories, &config.disabled_categories);
             config.apply_cli_options(&options.scan);
+            let _resources = options.scan.resources.then(resources::Meter::start);
+
+            // Keep stdout a single JSON document; prompts go to stderr
+            let json = options.scan.json;
+
+            // Quarantined items past their retention go first
+            let purged = match options.simulate {
+                true => None,
+                false => quarantine::purge_expired(config.quarantine_days).ok(),
+            };
+            if let Some((count, bytes)) = purged {
+                if count > 0 && !json {
+                    ui::print_info(&format!(
+                        "Purged {} quarantined item(s) older than {} days, freeing {}.",
+                        count,
+                        config.quarantine_days,
+                        ui::format_size(bytes)
+                    ));
+                }
+            }
 
-            // Use cached scan result if a scan was run within the last 5 minutes with same options
-            let result = match scan_cache::load_if_recent_default(&options.scan) {
-                Some(cached) => {
-                    ui::print_info("Using recent scan result (scan was run within 5 minutes).");
-                    cached
+            let mut result = if let Some(CleanTarget::Path { paths }) = &options.target {
+                let mut chosen = ScanResult::new();
+                let tags = Tags::load();
+                let paths =
+                    cleaner::path_findings(paths, &config, &tags, cleaner::Policy::Findings)?;
+                chosen.add_files(paths);
+                chosen
+            } else if options.retry_failed {
+                // The log only says which paths failed; a fresh scan of every
+                // category decides whether and how they are cleaned
+                let failed: HashSet<PathBuf> = history::last()
+                    .map(|e| e.failed_paths())
+                    .unwrap_or_default()
+                    .into_iter()
+                    .collect();
+                let mut retry = ScanResult::new();
+                if !failed.is_empty() {
+                    let scan = ScanOptions {
+                        all: true,
+                        ..options.scan.clone()
+                    };
+                    let found = analyzer::run_scan(&scan, &config)?.files;
+                    retry.add_files(
+                        found
+                            .into_iter()
+                            .filter(|f| failed.contains(&f.path))
+                            .collect(),
+                    );
+                }
+                retry
+            } else {
+                // Use cached scan result if a scan was run within the last 5 minutes with same options
+                match scan_cache::load_if_recent_default(&options.scan, &config) {
+                    Some(cached) => {
+                        if !json {
+                            ui::print_info("Using recent scan result (scan was run within 5 minutes).");
+                        }
+                        cached
+                    }
+                    None => analyzer::run_scan(&options.scan, &config)?,
                 }
-                None => analyzer::run_scan(&options.scan, &config)?,
             };
 
+            if !json {
+                analyzer::print_notes(&result);
+            }
+
+            // Retried items predate the cloud-folder check
+            result.files.retain(|f| !config.is_cloud_locked(&f.path));
+
+            // Tags may have changed since a cached scan
+            let tags = Tags::load();
+            result.files = tags.apply(result.files);
+            if options.yes || options.simulate {
+                let before = result.files.len();
+                result.files.retain(|f| tags.get(&f.path) != Some(Tag::Later));
+                let deferred = before - result.files.len();
+                if deferred > 0 && !json {
+                    ui::print_info(&format!(
+                        "Leaving {} item(s) tagged later for an interactive cleanup.",
+                        deferred
+                    ));
+                }
+            } else {
+                result.files = tags::pick_later(result.files, &tags);
+            }
+
+            // Unattended cleanups only touch data that comes back on its own,
+            // what the user tagged delete, and paths they named
+            let mut skipped = 0;
+            if options.yes && !options.include_irreplaceable && options.target.is_none() {
+                let before = result.files.len();
+                result.files
+                    .retain(|f| f.recoverable || tags.get(&f.path) == Some(Tag::Delete));
+                skipped = before - result.files.len();
+                if skipped > 0 && !json {
+                    ui::print_info(&format!(
+                        "Skipping {} irreplaceable item(s); pass --include-irreplaceable to delete them too.",
+                        skipped
+                    ));
+                }
+            }
+
+            if options.simulate {
+                let volumes = space::predict_free_space(&result.files);
+                return space::print_forecast(&volumes, json);
+            }
+
             if result.files.is_empty() {
-                ui::print_info("No cleanable files found.");
+                if json {
+                    return cleaner::print_json_report(&result, skipped, None);
+                }
+                if options.retry_failed {
+                    ui::print_info("Nothing failed in the last cleanup.");
+                } else {
+                    ui::print_info("No cleanable files found.");
+                }
                 return Ok(());
             }
 
             // Preview what will be deleted
-            cleaner::preview_deletion(&result.files);
+            if !json {
+                cleaner::preview_deletion(&result.files, config.metered_connection);
+            }
 
             // Get confirmation
             let should_delete = if options.yes {
                 true
             } else {
-                println!();
-                ui::confirm("Proceed with deletion?")
+                if !json {
+                    println!();
+                }
+                ui::confirm(if options.quarantine {
+                    "Move these items into quarantine?"
+                } else {
+                    "Proceed with deletion?"
+                })
             };
 
             if !should_delete {
+                if json {
+                    return cleaner::print_json_report(&result, skipped, None);
+                }
                 ui::print_info("Cleanup cancelled.");
-                return Ok(());
+                return tags::offer_tagging(&result.files);
             }
 
-            // Delete files
-            let cleanup_result = cleaner::delete_files(&result.files, None)?;
-            cleaner::print_cleanup_result(&cleanup_result);
+            // Delete (or quarantine) files
+            let cleanup_result = if options.quarantine {
+                cleaner::quarantine_files(&result.files, None)?
+            } else {
+                cleaner::delete_files(&result.files, None)?
+            };
+            let _ = history::record(&result.files, &cleanup_result);
+            let _ = stats::record(&result.files, &cleanup_result);
+            if json {
+                cleaner::print_json_report(&result, skipped, Some(&cleanup_result))?;
+            } else {
+                cleaner::print_cleanup_result(&cleanup_result);
+            }
         }
 
-        Command::Analyze(options) => {
+        Command::Analyze(mut options) => {
             // Apply CLI options to config
+            options.scan.apply_config_categories(&config.enabled_categories, &config.disabled_categories);
             config.apply_cli_options(&options.scan);
 
+            if options.all_files {
+                let stats = extensions::for_path(&config.get_base_path(), &config);
+                return print_extension_report(&stats, options.scan.json);
+            }
+
             // Run scan
             let result = analyzer::run_scan(&options.scan, &config)?;
 
             if result.files.is_empty() {
+                analyzer::print_notes(&result);
                 ui::print_info("No cleanable files found.");
                 return Ok(());
             }
 
+            if options.by_extension {
+                let stats = extensions::for_findings(&result, &config);
+                return print_extension_report(&stats, options.scan.json);
+            }
+
             // Print detailed report
             if options.scan.json {
                 analyzer::print_json_report(&result)?;
             } else {
-                analyzer::print_detailed_report(&result);
+                analyzer::print_notes(&result);
+                analyzer::print_detailed_report(&result, config.metered_connection);
             }
         }
 
+        Command::Suggest(options) => {
+            suggest::run(&options, &config)?;
+        }
+
+        Command::Whatif(options) => {
+            whatif::run(&options)?;
+        }
+
+        Command::Quarantine(options) => {
+            quarantine::run(&options, &config)?;
+        }
+
+        Command::Rm(options) => {
+            rm::run(&options, &config)?;
+        }
+
+        Command::Tag(options) => {
+            tags::run(&options)?;
+        }
+
+        Command::RecycleBin(options) => {
+            run_recycle_bin(&options)?;
+        }
+
+        Command::Users(options) => {
+            users::run(&options)?;
+        }
+
+        Command::Status(options) => {
+            status::run(&options, &config)?;
+        }
+
+        Command::Monitor(mut options) => {
+            options.scan.apply_config_categories(&config.enabled_categories, &config.disabled_categories);
+            config.apply_cli_options(&options.scan);
+
+            let rescan_every = std::time::Duration::from_secs(options.rescan_minutes.max(1) * 60);
+            monitor::watch(&options.scan, &config, rescan_every, |result| {
+                let line = match options.format {
+                    Some(format) => status::render(result, format),
+                    None => analyzer::oneline_summary(result),
+                };
+                println!("{}", line);
+            })?;
+        }
+
         Command::Space(options) => {
             space::run(&options)?;
         }
 
-        Command::Config => {
+        Command::Serve(mut options) => {
+            options.scan.apply_config_categories(&config.enabled_categories, &config.disabled_categories);
+            config.apply_cli_options(&options.scan);
+            serve::run(&options, &config)?;
+        }
+
+        Command::ArchiveProject(options) => {
+            archive::run(&options, &config)?;
+        }
+
+        Command::Mcp => {
+            mcp::run(&config)?;
+        }
+
+        Command::SelfUpdate(options) => {
+            update::run(&options)?;
+        }
+
+        Command::Stats(options) => {
+            stats::run(&options)?;
+        }
+
+        Command::Config(options) => {
+            if options.setup {
+                config = wizard::run()?;
+            }
             show_config(&config)?;
         }
     }
@@ -120,6 +347,60 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+/// Print the one-line summary, repeating every `--refresh` seconds
+fn print_oneline(options: &ScanOptions, config: &Config) -> Result<()> {
+    loop {
+        let result = analyzer::run_scan(options, config)?;
+        println!("{}", analyzer::oneline_summary(&result));
+
+        match options.refresh {
+            Some(seconds) => std::thread::sleep(std::time::Duration::from_secs(seconds.max(1))),
+            None => return Ok(()),
+        }
+    }
+}
+
+/// Print an extension breakdown as a table or JSON
+fn print_extension_report(stats: &[extensions::ExtensionStat], json: bool) -> Result<()> {
+    if json {
+        extensions::print_json_report(stats)?;
+    } else {
+        extensions::print_report(stats);
+    }
+    Ok(())
+}
+
+/// List or restore Windows Recycle Bin items
+fn run_recycle_bin(options: &RecycleBinOptions) -> Result<()> {
+    match &options.action {
+        RecycleBinAction::List => {
+            let items = windows::recycle_bin_items()?;
+            if items.is_empty() {
+                ui::print_info("The Recycle Bin is empty.");
+                return Ok(());
+            }
+
+            ui::print_header("Recycle Bin");
+            for item in &items {
+                println!(
+                    "  {} ({})",
+                    ui::format_path(&item.original_path()),
+                    ui::format_size(item.size).dimmed()
+                );
+            }
+        }
+        RecycleBinAction::Restore { path } => {
+            let item = windows::restore_recycled(path)?;
+            ui::print_success(&format!(
+                "Restored {}",
+                ui::format_path(&item.original_path())
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 /// Show current configuration
 fn show_config(config: &Config) -> Result<()> {
     ui::print_header("Current Configuration");
@@ -140,6 +421,94 @@ fn show_config(config: &Config) -> Result<()> {
         "Download age (days):".bold(),
         config.download_age_days
     );
+    println!(
+        "{:<25} {}",
+        "Desktop age (days):".bold(),
+        config.desktop_age_days
+    );
+    println!(
+        "{:<25} {}",
+        "Quarantine (days):".bold(),
+        config.quarantine_days
+    );
+    println!(
+        "{:<25} {}",
+        "Metered connection:".bold(),
+        config.metered_connection
+    );
+    println!(
+        "{:<25} {}",
+        "Compare with baseline:".bold(),
+        config.compare_baseline
+    );
+    println!(
+        "{:<25} {}",
+        "Threads:".bold(),
+        config
+            .threads
+            .map_or_else(|| "one per CPU core".to_string(), |n| n.to_string())
+    );
+    println!(
+        "{:<25} {} MB",
+        "Memory budget:".bold(),
+        config.memory_budget_mb
+    );
+    println!(
+        "{:<25} {}",
+        "Min savings:".bold(),
+        config
+            .min_savings_mb
+            .map_or_else(|| "none".to_string(), |mb| format!("{} MB", mb))
+    );
+    for (category, mb) in &config.category_min_savings_mb {
+        println!(
+            "{:<25} {} MB",
+            format!("Min savings ({}):", category).bold(),
+            mb
+        );
+    }
+    println!(
+        "{:<25} {}",
+        "Max hash size:".bold(),
+        config
+            .max_hash_size_mb
+            .map_or_else(|| "hash every file".to_string(), |mb| format!("{} MB", mb))
+    );
+    println!(
+        "{:<25} {}",
+        "Hash read limit:".bold(),
+        config
+            .hash_rate_mb
+            .map_or_else(|| "none".to_string(), |mb| format!("{} MB/s", mb))
+    );
+    println!(
+        "{:<25} {}",
+        "Download history:".bold(),
+        config.download_history
+    );
+    if wsl::is_wsl() {
+        println!(
+            "{:<25} {}",
+            "Windows paths (WSL):".bold(),
+            config.wsl_windows_paths
+        );
+    }
+
+    if !config.enabled_categories.is_empty() {
+        println!();
+        println!("{}", "Enabled categories:".bold());
+        for name in &config.enabled_categories {
+            println!("  - {}", name);
+        }
+    }
+
+    if !config.disabled_categories.is_empty() {
+        println!();
+        println!("{}", "Disabled categories:".bold());
+        for name in &config.disabled_categories {
+            println!("  - {}", name);
+        }
+    }
 
     if !config.excluded_paths.is_empty() {
         println!();
@@ -149,6 +518,14 @@ fn show_config(config: &Config) -> Result<()> {
         }
     }
 
+    if !config.duplicate_keep_paths.is_empty() {
+        println!();
+        println!("{}", "Preferred duplicate locations:".bold());
+        for path in &config.duplicate_keep_paths {
+            println!("  - {}", path);
+        }
+    }
+
     if !config.cache_paths.is_empty() {
         println!();
         println!("{}", "Additional cache paths:".bold());
@@ -157,6 +534,77 @@ fn show_config(config: &Config) -> Result<()> {
         }
     }
 
+    if !config.keep_versions.is_empty() {
+        println!();
+        println!("{}", "Versions kept per cache family:".bold());
+        for (family, keep) in &config.keep_versions {
+            println!("  - {}: {}", family, keep);
+        }
+    }
+
+    if !config.artifact_patterns.is_empty() {
+        println!();
+        println!("{}", "Custom artifact patterns:".bold());
+        for pattern in &config.artifact_patterns {
+            if pattern.project_file.is_empty() {
+                println!("  - {} ({})", pattern.dir_name, pattern.description);
+            } else {
+                println!(
+                    "  - {} next to {} ({})",
+                    pattern.dir_name, pattern.project_file, pattern.description
+                );
+            }
+        }
+    }
+
+    if !config.rules.is_empty() {
+        println!();
+        println!("{}", "Rules:".bold());
+        for rule in &config.rules {
+            let mut effects = Vec::new();
+            if let Some(ref from) = rule.from {
+                effects.push(format!("{} findings only", from));
+            }
+            if let Some(ref category) = rule.category {
+                effects.push(format!("reported as {}", category));
+            }
+            match rule.safety {
+                Some(RuleSafety::Protect) => effects.push("protected".to_string()),
+                Some(RuleSafety::Review) => effects.push("for review only".to_string()),
+                None => {}
+            }
+            println!("  - {}: {}", rule.path, effects.join(", "));
+        }
+    }
+
+    if !config.disabled_artifact_patterns.is_empty() {
+        println!();
+        println!("{}", "Disabled artifact patterns:".bold());
+        for name in &config.disabled_artifact_patterns {
+            println!("  - {}", name);
+        }
+    }
+
+    for (mount, volume) in &config.volumes {
+        println!();
+        println!("{} {}", "Volume:".bold(), mount.display());
+        let overrides = [
+            ("Min age (days)", volume.min_age_days.map(|d| d.to_string())),
+            ("Min large size", volume.min_large_size_mb.map(|mb| format!("{} MB", mb))),
+            ("Project recent (days)", volume.project_recent_days.map(|d| d.to_string())),
+            ("Download age (days)", volume.download_age_days.map(|d| d.to_string())),
+            ("Desktop age (days)", volume.desktop_age_days.map(|d| d.to_string())),
+        ];
+        for (label, value) in overrides {
+            if let Some(value) = value {
+                println!("  - {}: {}", label, value);
+            }
+        }
+        if !volume.disabled_categories.is_empty() {
+            println!("  - Disabled: {}", volume.disabled_categories.join(", "));
+        }
+    }
+
     println!();
     if let Some(config_path) = Config::config_path() {
         if config_path.exists() {
@@ -171,27 +619,14 @@ fn show_config(config: &Config) -> Result<()> {
                 "Config file:".dimmed(),
                 config_path.display()
             );
-            println!();
-            println!(
-                "{}",
-                "To customize settings, create this file with your preferences.".dimmed()
-            );
         }
     }
 
     println!();
-    println!("{}", "Example config.toml:".dimmed());
-    println!("{}", "─".repeat(40).dimmed());
     println!(
         "{}",
-        r#"min_age_days = 30
-min_large_size_mb = 100
-project_recent_days = 14
-download_age_days = 30
-excluded_paths = [
-    "important-project/node_modules"
-]"#
-        .dimmed()
+        "Run `duster config --setup` to answer a few questions and write a tailored config."
+            .dimmed()
     );
 
     Ok(())
@@ -0,0 +1,268 @@
+//! Model Context Protocol server over stdio.
+//!
+//! Exposes scanning to AI assistants as tools with JSON schemas, so they can
+//! query disk usage without parsing human-oriented output. Messages are
+//! newline-delimited JSON-RPC 2.0. No tool deletes anything.
+
+use crate::analyzer;
+use crate::cli::ScanOptions;
+use crate::config::Config;
+use crate::format;
+use anyhow::{Context, Result};
+use clap::Parser;
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+
+const PROTOCOL_VERSION: &str = "2024-11-05";
+
+/// Category names accepted by the tools, matching the CLI flags
+const CATEGORIES: &[&str] = &[
+    "cache",
+    "trash",
+    "temp",
+    "downloads",
+    "desktop",
+    "build",
+    "large",
+    "duplicates",
+    "old",
+    "junk",
+    "metadata-junk",
+];
+
+/// Maximum number of individual findings returned by the `scan` tool
+const SCAN_TOP_FILES: usize = 20;
+
+/// Serve MCP requests on stdin/stdout until stdin closes
+pub fn run(config: &Config) -> Result<()> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout();
+
+    for line in stdin.lock().lines() {
+        let line = line.context("Failed to read from stdin")?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle_message(&message, config),
+            Err(e) => Some(error_response(
+                Value::Null,
+                -32700,
+                &format!("Parse error: {}", e),
+            )),
+        };
+
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response).context("Failed to write to stdout")?;
+            stdout.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Handle one JSON-RPC message. Notifications get no response.
+pub fn handle_message(message: &Value, config: &Config) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": PROTOCOL_VERSION,
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "duster", "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tool_definitions() }),
+        "tools/call" => call_tool(&params, config),
+        _ => {
+            return Some(error_response(
+                id,
+                -32601,
+                &format!("Method not found: {}", method),
+            ))
+        }
+    };
+
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": code, "message": message },
+    })
+}
+
+/// Input schema shared by all tools
+fn scan_input_schema() -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "categories": {
+                "type": "array",
+                "items": { "type": "string", "enum": CATEGORIES },
+                "description": "Categories to scan (default: all)",
+            },
+            "path": { "type": "string", "description": "Path to scan (default: home directory)" },
+            "min_age": { "type": "integer", "minimum": 0, "description": "Minimum age in days for old files" },
+            "min_size": { "type": "string", "description": "Minimum size for large files, e.g. \"500MB\"" },
+            "exclude": {
+                "type": "array",
+                "items": { "type": "string" },
+                "description": "Path patterns to exclude",
+            },
+        },
+    })
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "scan",
+            "description": "Find cleanable files and summarize reclaimable space by category, with the largest findings.",
+            "inputSchema": scan_input_schema(),
+        },
+        {
+            "name": "analyze",
+            "description": "Full breakdown of cleanable files: every finding with size, reason, and per-category age histograms.",
+            "inputSchema": scan_input_schema(),
+        },
+        {
+            "name": "clean_dry_run",
+            "description": "List exactly what `duster clean` would delete and how much space it would free. Deletes nothing.",
+            "inputSchema": scan_input_schema(),
+        },
+    ])
+}
+
+/// Turn tool arguments into CLI flags so they are validated like the CLI
+fn scan_options_from_args(args: &Value) -> Result<ScanOptions> {
+    let mut argv = vec!["duster".to_string()];
+
+    if let Some(categories) = args.get("categories").and_then(Value::as_array) {
+        for category in categories.iter().filter_map(Value::as_str) {
+            anyhow::ensure!(
+                CATEGORIES.contains(&category),
+                "Unknown category: {}",
+                category
+            );
+            argv.push(format!("--{}", category));
+        }
+    }
+    if let Some(path) = args.get("path").and_then(Value::as_str) {
+        argv.extend(["--path".to_string(), path.to_string()]);
+    }
+    if let Some(min_age) = args.get("min_age").and_then(Value::as_u64) {
+        argv.extend(["--min-age".to_string(), min_age.to_string()]);
+    }
+    if let Some(min_size) = args.get("min_size").and_then(Value::as_str) {
+        argv.extend(["--min-size".to_string(), min_size.to_string()]);
+    }
+    if let Some(excludes) = args.get("exclude").and_then(Value::as_array) {
+        for pattern in excludes.iter().filter_map(Value::as_str) {
+            argv.extend(["--exclude".to_string(), pattern.to_string()]);
+        }
+    }
+
+    ScanOptions::try_parse_from(argv).context("Invalid tool arguments")
+}
+
+fn call_tool(params: &Value, config: &Config) -> Value {
+    let name = params.get("name").and_then(Value::as_str).unwrap_or("");
+    let args = params
+        .get("arguments")
+        .cloned()
+        .unwrap_or_else(|| json!({}));
+
+    match run_tool(name, &args, config) {
+        Ok(output) => json!({
+            "content": [{ "type": "text", "text": output.to_string() }],
+            "isError": false,
+        }),
+        Err(e) => json!({
+            "content": [{ "type": "text", "text": format!("{:#}", e) }],
+            "isError": true,
+        }),
+    }
+}
+
+fn run_tool(name: &str, args: &Value, config: &Config) -> Result<Value> {
+    anyhow::ensure!(
+        matches!(name, "scan" | "analyze" | "clean_dry_run"),
+        "Unknown tool: {}",
+        name
+    );
+
+    let options = scan_options_from_args(args)?;
+    let mut config = config.clone();
+    config.apply_cli_options(&options);
+
+    let result = analyzer::run_scan(&options, &config)?;
+    let mut report = analyzer::json_report(&result);
+
+    match name {
+        "scan" => {
+            if let Some(files) = report.get_mut("files").and_then(Value::as_array_mut) {
+                files.sort_by_key(|f| std::cmp::Reverse(f["size"].as_u64().unwrap_or(0)));
+                files.truncate(SCAN_TOP_FILES);
+            }
+            Ok(report)
+        }
+        "analyze" => Ok(report),
+        _ => {
+            let totals = result.reconcile();
+            Ok(json!({
+                "dry_run": true,
+                "would_delete": result.files.iter().map(|f| {
+                    json!({
+                        "path": f.path.display().to_string(),
+                        "size": f.size,
+                        "is_directory": f.is_directory,
+                        "reason": f.reason,
+                    })
+                }).collect::<Vec<_>>(),
+                "would_free": totals.reclaimable_size,
+                "would_free_formatted": format::format_size(totals.reclaimable_size),
+            }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tools_list() {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": "tools/list" });
+        let response = handle_message(&request, &Config::default()).unwrap();
+
+        let names: Vec<_> = response["result"]["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|t| t["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["scan", "analyze", "clean_dry_run"]);
+    }
+
+    #[test]
+    fn test_notifications_get_no_response() {
+        let notification = json!({ "jsonrpc": "2.0", "method": "notifications/initialized" });
+        assert!(handle_message(&notification, &Config::default()).is_none());
+    }
+
+    #[test]
+    fn test_scan_options_from_args() {
+        let args = json!({ "categories": ["cache", "metadata-junk"], "min_age": 90 });
+        let options = scan_options_from_args(&args).unwrap();
+        assert!(options.cache && options.metadata_junk && !options.trash);
+        assert_eq!(options.min_age, Some(90));
+
+        assert!(scan_options_from_args(&json!({ "categories": ["nope"] })).is_err());
+    }
+}
@@ -0,0 +1,174 @@
+//! Long-running estimate of cleanable space kept current with filesystem events.
+//!
+//! After an initial scan, the findings and a few key roots are watched. When
+//! something changes, only the affected findings are re-measured; a full
+//! rescan runs periodically to pick up new findings. Embedders (status bars,
+//! GUIs) receive every new estimate through a callback.
+
+use crate::analyzer;
+use crate::cli::ScanOptions;
+use crate::config::Config;
+use crate::scanner::{calculate_dir_stats, ScanResult};
+use anyhow::{Context, Result};
+use notify::{RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// Quiet period after the last event before re-measuring
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// inotify needs one watch per directory, so recursive watches over caches
+/// and node_modules trees can exhaust the limit; elsewhere they are cheap
+const WATCH_MODE: RecursiveMode = if cfg!(target_os = "linux") {
+    RecursiveMode::NonRecursive
+} else {
+    RecursiveMode::Recursive
+};
+
+/// Watch until an error occurs, calling `on_update` with the initial scan and
+/// every changed estimate. Runs a full rescan every `rescan_every`.
+pub fn watch<F>(
+    options: &ScanOptions,
+    config: &Config,
+    rescan_every: Duration,
+    mut on_update: F,
+) -> Result<()>
+where
+    F: FnMut(&ScanResult),
+{
+    loop {
+        let mut result = analyzer::run_scan(options, config)?;
+        on_update(&result);
+
+        let (sender, receiver) = mpsc::channel();
+        let mut watcher =
+            notify::recommended_watcher(sender).context("Failed to start filesystem watcher")?;
+        for target in watch_targets(&result) {
+            // Paths can vanish between the scan and here
+            let _ = watcher.watch(&target, WATCH_MODE);
+        }
+
+        let rescan_at = Instant::now() + rescan_every;
+        let mut changed: HashSet<PathBuf> = HashSet::new();
+
+        loop {
+            let until_rescan = rescan_at.saturating_duration_since(Instant::now());
+            let timeout = if changed.is_empty() {
+                until_rescan
+            } else {
+                DEBOUNCE.min(until_rescan)
+            };
+
+            match receiver.recv_timeout(timeout) {
+                Ok(Ok(event)) => changed.extend(event.paths),
+                // Overflowed or dropped events: the periodic rescan catches up
+                Ok(Err(_)) => {}
+                Err(RecvTimeoutError::Timeout) => {
+                    if !changed.is_empty() {
+                        if refresh(&mut result, &changed) {
+                            on_update(&result);
+                        }
+                        changed.clear();
+                    }
+                    if Instant::now() >= rescan_at {
+                        break;
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+}
+
+/// Directories to watch: each directory finding, the folders holding file
+/// findings, and the cache and Downloads roots where new findings appear
+fn watch_targets(result: &ScanResult) -> Vec<PathBuf> {
+    let mut targets: Vec<PathBuf> = Vec::new();
+    let roots = [dirs::cache_dir(), dirs::download_dir()];
+
+    let findings = result.files.iter().filter_map(|f| {
+        if f.is_directory {
+            Some(f.path.clone())
+        } else {
+            f.path.parent().map(Path::to_path_buf)
+        }
+    });
+
+    for path in roots.into_iter().flatten().chain(findings) {
+        if !targets.contains(&path) {
+            targets.push(path);
+        }
+    }
+    targets
+}
+
+/// Re-measure findings touched by the changed paths and drop the ones that
+/// are gone. Returns whether the estimate changed.
+fn refresh(result: &mut ScanResult, changed: &HashSet<PathBuf>) -> bool {
+    let mut updated = false;
+
+    result.files.retain_mut(|file| {
+        let affected = changed
+            .iter()
+            .any(|p| p.starts_with(&file.path) || file.path.starts_with(p));
+        if !affected {
+            return true;
+        }
+
+        let (size, file_count) = match std::fs::symlink_metadata(&file.path) {
+            Err(_) => {
+                updated = true;
+                return false;
+            }
+            Ok(_) if file.is_directory => {
+                let stats = calculate_dir_stats(&file.path);
+                (stats.size, stats.file_count)
+            }
+            Ok(metadata) => (metadata.len(), 1),
+        };
+
+        if size != file.size || file_count != file.file_count {
+            file.size = size;
+            file.file_count = file_count;
+            updated = true;
+        }
+        true
+    });
+
+    updated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Category, CleanableFile};
+
+    #[test]
+    fn test_refresh_remeasures_and_drops_findings() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = dir.path().join("cache");
+        std::fs::create_dir(&cache).unwrap();
+        std::fs::write(cache.join("blob"), vec![0u8; 100]).unwrap();
+
+        let finding = |path: PathBuf| CleanableFile {
+            is_directory: true,
+            recoverable: true,
+            ..CleanableFile::new(path, 1, Category::Cache, "")
+        };
+        let mut result = ScanResult::new();
+        result.add_files(vec![
+            finding(cache.clone()),
+            finding(dir.path().join("gone")),
+        ]);
+
+        let changed: HashSet<PathBuf> = [cache.join("blob"), dir.path().join("gone")]
+            .into_iter()
+            .collect();
+        assert!(refresh(&mut result, &changed));
+
+        assert_eq!(result.files.len(), 1);
+        assert_eq!(result.files[0].size, 100);
+    }
+}
@@ -0,0 +1,84 @@
+//! File ownership checks for multi-user machines.
+//!
+//! On shared build servers, files owned by other users usually can't be
+//! deleted, so by default findings are limited to the current user's files.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+use std::process::Command;
+
+/// `--owner` value that disables ownership filtering
+pub const ANY_OWNER: &str = "any";
+
+/// Resolve the owner filter: `None` means the current user, `"any"` disables
+/// filtering, anything else is a user name or numeric uid.
+/// Returns the uid findings must belong to, or `None` for no filtering.
+pub fn resolve_filter(owner: Option<&str>) -> Result<Option<u32>> {
+    if !cfg!(unix) {
+        return Ok(None);
+    }
+
+    match owner {
+        Some(ANY_OWNER) => Ok(None),
+        Some(user) => match user.parse::<u32>() {
+            Ok(uid) => Ok(Some(uid)),
+            Err(_) => lookup_uid(Some(user)).map(Some),
+        },
+        None => lookup_uid(None).map(Some),
+    }
+}
+
+/// Look up a user's uid (or the current user's) with `id -u`
+fn lookup_uid(user: Option<&str>) -> Result<u32> {
+    let mut cmd = Command::new("id");
+    cmd.arg("-u");
+    if let Some(user) = user {
+        cmd.arg(user);
+    }
+
+    let output = cmd.output().context("Failed to run `id` to resolve file owner")?;
+    if !output.status.success() {
+        bail!("Unknown user: {}", user.unwrap_or("current user"));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .context("Unexpected output from `id -u`")
+}
+
+/// Whether duster is running as root
+pub fn is_root() -> bool {
+    cfg!(unix) && lookup_uid(None).is_ok_and(|uid| uid == 0)
+}
+
+/// Get the uid owning a path (without following symlinks)
+#[cfg(unix)]
+pub fn owner_uid(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::symlink_metadata(path).ok().map(|m| m.uid())
+}
+
+/// Get the uid owning a path (ownership is not tracked on this platform)
+#[cfg(not(unix))]
+pub fn owner_uid(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Check whether a path belongs to the given uid. Paths whose owner can't be
+/// determined are kept, matching the scanners' other safe defaults.
+pub fn is_owned_by(path: &Path, uid: u32) -> bool {
+    owner_uid(path).is_none_or(|owner| owner == uid)
+}
+
+/// Get the user name for a uid with `id -nu`, falling back to the number
+pub fn user_name(uid: u32) -> String {
+    Command::new("id")
+        .args(["-nu", &uid.to_string()])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| uid.to_string())
+}
@@ -0,0 +1,416 @@
+//! Analysis passes run over a scan's findings once every scanner is done.
+//! Scanners each see only what they found; a pass sees the whole result, so
+//! it can cross-reference categories, drop or re-classify findings and
+//! annotate them. [`builtin`] lists the passes every scan runs, in order.
+
+use crate::baseline;
+use crate::config::{Config, RuleSafety};
+use crate::scanner::{duplicates, extra, Category, CleanableFile, CleanupAction, ScanResult};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// A post-scan pass that reads the full [`ScanResult`] and may add, change,
+/// drop or annotate findings
+pub trait Analyzer: Send + Sync {
+    /// Short, stable id naming the pass, e.g. "dedup-paths"
+    fn name(&self) -> &'static str;
+
+    fn analyze(&self, result: &mut ScanResult, config: &Config);
+}
+
+/// The built-in passes, in the order scans run them
+pub fn builtin() -> Vec<Box<dyn Analyzer>> {
+    vec![
+        Box::new(DuplicateCopies),
+        Box::new(DedupPaths),
+        Box::new(ConfigRules),
+        Box::new(MinSavings),
+        Box::new(Baseline),
+    ]
+}
+
+/// Marks downloads and old files that have an organized copy elsewhere as
+/// safe, using the duplicates scanner's hashes
+pub struct DuplicateCopies;
+
+impl Analyzer for DuplicateCopies {
+    fn name(&self) -> &'static str {
+        "duplicate-copies"
+    }
+
+    fn analyze(&self, result: &mut ScanResult, _config: &Config) {
+        cross_reference_duplicates(&mut result.files);
+    }
+}
+
+/// Keeps the first finding for each path, as scanners may overlap
+pub struct DedupPaths;
+
+impl Analyzer for DedupPaths {
+    fn name(&self) -> &'static str {
+        "dedup-paths"
+    }
+
+    fn analyze(&self, result: &mut ScanResult, _config: &Config) {
+        let mut seen_paths = HashSet::new();
+        result.files.retain(|f| seen_paths.insert(f.path.clone()));
+    }
+}
+
+/// Applies the config's `[[rule]]`s
+pub struct ConfigRules;
+
+impl Analyzer for ConfigRules {
+    fn name(&self) -> &'static str {
+        "rules"
+    }
+
+    fn analyze(&self, result: &mut ScanResult, config: &Config) {
+        apply_rules(&mut result.files, config);
+    }
+}
+
+/// Drops findings below `min_savings_mb`. Runs once each path counts once.
+pub struct MinSavings;
+
+impl Analyzer for MinSavings {
+    fn name(&self) -> &'static str {
+        "min-savings"
+    }
+
+    fn analyze(&self, result: &mut ScanResult, config: &Config) {
+        result
+            .files
+            .retain(|f| f.size >= config.min_savings_bytes_for(f.category));
+    }
+}
+
+/// Notes tools whose total is unusually large, with `compare_baseline`
+pub struct Baseline;
+
+impl Analyzer for Baseline {
+    fn name(&self) -> &'static str {
+        "baseline"
+    }
+
+    fn analyze(&self, result: &mut ScanResult, config: &Config) {
+        if config.compare_baseline {
+            baseline::annotate(&mut result.files);
+        }
+    }
+}
+
+/// Whether a finding is a file left lying around, which an organized copy
+/// elsewhere makes safe to delete
+fn is_loose_file(file: &CleanableFile) -> bool {
+    !file.is_directory && matches!(file.category, Category::Downloads | Category::OldFile)
+}
+
+/// Cross-reference downloads and old files with the duplicates scanner's
+/// hashes. A loose file with an exact copy that no other finding covers is
+/// marked safe to delete, and that copy is no longer offered as a duplicate.
+/// Duplicate findings for loose files are dropped, as their own category
+/// already reports them.
+fn cross_reference_duplicates(files: &mut Vec<CleanableFile>) {
+    // Every copy in each exact set, the kept one first
+    let mut sets: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for file in files.iter() {
+        if !file.extra_matches(extra::DUPLICATE_CONFIDENCE, duplicates::CONFIDENCE_EXACT) {
+            continue;
+        }
+        let (Some(id), Some(kept)) = (
+            file.extra
+                .get(extra::DUPLICATE_SET_ID)
+                .and_then(|v| v.as_str()),
+            file.extra.get(extra::DUPLICATE_OF).and_then(|v| v.as_str()),
+        ) else {
+            continue;
+        };
+        let copies = sets
+            .entry(id.to_string())
+            .or_insert_with(|| vec![PathBuf::from(kept)]);
+        copies.push(file.path.clone());
+    }
+    if sets.is_empty() {
+        return;
+    }
+
+    let loose: HashSet<PathBuf> = files
+        .iter()
+        .filter(|f| is_loose_file(f))
+        .map(|f| f.path.clone())
+        .collect();
+    // A copy at or below another finding could be cleaned along with the
+    // loose file. Its own duplicate finding is dropped if it's kept.
+    let found: HashSet<&Path> = files
+        .iter()
+        .filter(|f| !matches!(f.category, Category::Duplicate | Category::RenamedDuplicate))
+        .map(|f| f.path.as_path())
+        .collect();
+    let covered = |path: &Path| path.ancestors().any(|a| found.contains(a));
+    let mut organized_copy: HashMap<PathBuf, PathBuf> = HashMap::new();
+    let mut organized: HashSet<PathBuf> = HashSet::new();
+    for copies in sets.values() {
+        let Some(keep) = copies.iter().find(|p| !covered(p)) else {
+            continue;
+        };
+        for copy in copies.iter().filter(|p| loose.contains(*p)) {
+            organized_copy.insert(copy.clone(), keep.clone());
+            organized.insert(keep.clone());
+        }
+    }
+
+    files.retain_mut(|file| {
+        if matches!(
+            file.category,
+            Category::Duplicate | Category::RenamedDuplicate
+        ) {
+            return !loose.contains(&file.path) && !organized.contains(&file.path);
+        }
+        let copy = organized_copy.get(&file.path);
+        if let Some(copy) = copy.filter(|_| is_loose_file(file)) {
+            file.reason = format!(
+                "{}; safe: identical copy exists at {}",
+                file.reason,
+                copy.display()
+            );
+            file.recoverable = true;
+            let copy = copy.to_string_lossy().to_string();
+            file.extra
+                .insert(extra::IDENTICAL_COPY.to_string(), copy.into());
+        }
+        true
+    });
+}
+
+/// Apply the config's `[[rule]]`s: move findings to another category, drop
+/// protected ones and turn ones marked for review into report-only findings.
+/// A directory holding a protected path is only reported, since cleaning it
+/// would take the protected path along.
+fn apply_rules(files: &mut Vec<CleanableFile>, config: &Config) {
+    if config.rules.is_empty() {
+        return;
+    }
+    let rules = config.compiled_rules();
+    files.retain_mut(|file| {
+        if let Some(rule) = rules.matching(&file.path, file.category) {
+            if let Some(category) = rule.category.as_deref().and_then(Category::from_flag_name) {
+                file.category = category;
+            }
+            match rule.safety {
+                Some(RuleSafety::Protect) => return false,
+                Some(RuleSafety::Review) => {
+                    file.action = CleanupAction::ReportOnly {
+                        hint: format!("a config rule marks {} for review", rule.path),
+                    };
+                    file.recoverable = false;
+                }
+                None => {}
+            }
+        }
+        if !file.is_directory || matches!(file.action, CleanupAction::ReportOnly { .. }) {
+            return true;
+        }
+        if let Some(rule) = rules.protected_below(&file.path) {
+            file.action = CleanupAction::ReportOnly {
+                hint: format!(
+                    "holds {}, which a config rule protects; clean around it",
+                    rule.path
+                ),
+            };
+            file.recoverable = false;
+        }
+        true
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::testing::file_aged;
+
+    #[test]
+    fn test_rules_reclassify_protect_and_mark_for_review() {
+        let config: Config = toml::from_str(
+            r#"
+            [[rule]]
+            path = "/home/me/Videos/raw-footage/**"
+            from = "large"
+            safety = "protect"
+
+            [[rule]]
+            path = "/home/me/Videos"
+            category = "old"
+
+            [[rule]]
+            path = "/home/me/.cache/app"
+            safety = "review"
+            "#,
+        )
+        .unwrap();
+        let at = |path: &str, category: Category| CleanableFile {
+            path: PathBuf::from(path),
+            category,
+            recoverable: true,
+            ..file_aged(1, 100)
+        };
+        let mut files = vec![
+            at("/home/me/Videos/raw-footage/day1.mov", Category::LargeFile),
+            at("/home/me/Videos/raw-footage/notes.txt", Category::Duplicate),
+            at("/home/me/Videos/trip.mov", Category::LargeFile),
+            at("/home/me/.cache/app/blobs", Category::Cache),
+            at("/home/me/.cache/other", Category::Cache),
+        ];
+
+        apply_rules(&mut files, &config);
+
+        let summary: Vec<(&str, Category)> = files
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.category))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                // Only large files are protected there
+                ("/home/me/Videos/raw-footage/notes.txt", Category::OldFile),
+                ("/home/me/Videos/trip.mov", Category::OldFile),
+                ("/home/me/.cache/app/blobs", Category::Cache),
+                ("/home/me/.cache/other", Category::Cache),
+            ]
+        );
+        assert!(matches!(files[2].action, CleanupAction::ReportOnly { .. }));
+        assert!(!files[2].recoverable);
+        assert_eq!(files[3].action, CleanupAction::Delete);
+    }
+
+    #[test]
+    fn test_directories_holding_protected_paths_are_only_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join(".cache/app/models")).unwrap();
+        std::fs::write(root.join(".cache/app/models/weights.bin"), b"weights").unwrap();
+        std::fs::create_dir_all(root.join(".cache/other")).unwrap();
+        let config: Config = toml::from_str(&format!(
+            r#"
+            [[rule]]
+            path = "{videos}/raw-footage/**"
+            from = "large"
+            safety = "protect"
+
+            [[rule]]
+            path = "weights.bin"
+            safety = "protect"
+            "#,
+            videos = root.join("Videos").display()
+        ))
+        .unwrap();
+        let directory = |path: &str, category: Category| CleanableFile {
+            path: root.join(path),
+            category,
+            is_directory: true,
+            recoverable: true,
+            ..file_aged(1, 100)
+        };
+        let mut files = vec![
+            directory("Videos", Category::OldFile),
+            directory(".cache/app", Category::Cache),
+            directory(".cache/other", Category::Cache),
+            directory("Videos/raw-footage/day1", Category::OldFile),
+        ];
+
+        apply_rules(&mut files, &config);
+
+        let hints: Vec<Option<&str>> = files
+            .iter()
+            .map(|f| match &f.action {
+                CleanupAction::ReportOnly { hint } => Some(hint.as_str()),
+                _ => None,
+            })
+            .collect();
+        let videos = format!(
+            "holds {}/raw-footage/**, which a config rule protects; clean around it",
+            root.join("Videos").display()
+        );
+        assert_eq!(
+            hints,
+            vec![
+                Some(videos.as_str()),
+                Some("holds weights.bin, which a config rule protects; clean around it"),
+                None,
+                // Not large, so the rule leaves it be
+                None,
+            ]
+        );
+        assert!(!files[0].recoverable);
+    }
+
+    #[test]
+    fn test_downloads_with_an_organized_copy_are_safe() {
+        let at = |path: &str, category: Category| CleanableFile {
+            path: PathBuf::from(path),
+            category,
+            reason: "Old download".to_string(),
+            ..file_aged(90, 100)
+        };
+        let duplicate = |path: &str, kept: &str, set: &str| {
+            at(path, Category::Duplicate)
+                .with_extra(extra::DUPLICATE_SET_ID, set)
+                .with_extra(extra::DUPLICATE_OF, kept)
+                .with_extra(extra::DUPLICATE_CONFIDENCE, duplicates::CONFIDENCE_EXACT)
+        };
+        let mut files = vec![
+            at("/home/me/Downloads/logo.png", Category::Downloads),
+            at("/home/me/Downloads/report.pdf", Category::Downloads),
+            at("/home/me/Downloads/setup.dmg", Category::Downloads),
+            // The download was kept, so the organized copy was the spare
+            duplicate("/home/me/site/logo.png", "/home/me/Downloads/logo.png", "a"),
+            duplicate("/home/me/Downloads/report.pdf", "/home/me/report.pdf", "b"),
+            duplicate("/home/me/Music/a.mp3", "/home/me/Music/b.mp3", "c"),
+            // Copies other findings would clean too
+            at("/home/me/Downloads/photo.jpg", Category::Downloads),
+            duplicate(
+                "/home/me/old/photo.jpg",
+                "/home/me/Downloads/photo.jpg",
+                "d",
+            ),
+            CleanableFile {
+                is_directory: true,
+                ..at("/home/me/old", Category::OldFile)
+            },
+            at("/home/me/Downloads/movie.mkv", Category::Downloads),
+            duplicate("/home/me/movie.mkv", "/home/me/Downloads/movie.mkv", "e"),
+            at("/home/me/movie.mkv", Category::LargeFile),
+        ];
+
+        cross_reference_duplicates(&mut files);
+
+        let summary: Vec<(&str, &str)> = files
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "/home/me/Downloads/logo.png",
+                    "Old download; safe: identical copy exists at /home/me/site/logo.png"
+                ),
+                (
+                    "/home/me/Downloads/report.pdf",
+                    "Old download; safe: identical copy exists at /home/me/report.pdf"
+                ),
+                ("/home/me/Downloads/setup.dmg", "Old download"),
+                ("/home/me/Music/a.mp3", "Old download"),
+                ("/home/me/Downloads/photo.jpg", "Old download"),
+                ("/home/me/old/photo.jpg", "Old download"),
+                ("/home/me/old", "Old download"),
+                ("/home/me/Downloads/movie.mkv", "Old download"),
+                ("/home/me/movie.mkv", "Old download"),
+                ("/home/me/movie.mkv", "Old download"),
+            ]
+        );
+        assert!(files[0].recoverable && files[1].recoverable);
+        assert!(!files[2].recoverable);
+        assert_eq!(files[1].category, Category::Downloads);
+    }
+}
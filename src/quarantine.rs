@@ -0,0 +1,402 @@
+//! Quarantine: `clean --quarantine` renames items into a directory on their
+//! own volume instead of deleting them, so they can be restored until they
+//! are purged after `quarantine_days`
+
+use crate::scanner::CleanableFile;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+#[cfg(feature = "cli")]
+use crate::cli::{QuarantineAction, QuarantineOptions};
+#[cfg(feature = "cli")]
+use crate::config::Config;
+#[cfg(feature = "cli")]
+use crate::ui;
+#[cfg(feature = "cli")]
+use colored::*;
+
+/// Directory created at the top of volumes other than the home one, holding
+/// a quarantine for each user by uid
+const VOLUME_DIR: &str = ".duster-quarantine";
+
+/// Each entry is a directory holding the moved item and its metadata
+const ITEM: &str = "item";
+const META: &str = "meta.json";
+
+/// A quarantined item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarantineEntry {
+    pub id: String,
+    pub original_path: PathBuf,
+    pub size: u64,
+    pub is_directory: bool,
+    pub reason: String,
+    pub quarantined_at: DateTime<Utc>,
+    /// Directory of this entry inside its quarantine
+    #[serde(skip)]
+    pub location: PathBuf,
+}
+
+impl QuarantineEntry {
+    /// Whether this entry is older than `days`
+    pub fn is_expired(&self, days: u32) -> bool {
+        self.quarantined_at < Utc::now() - Duration::days(days as i64)
+    }
+}
+
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("duster"))
+}
+
+/// Quarantine for items on the same volume as the data directory
+fn home_quarantine() -> Option<PathBuf> {
+    data_dir().map(|p| p.join("quarantine"))
+}
+
+/// Whether `path` is in a quarantine. Scanners leave quarantined items
+/// alone; they are restored or purged from there.
+pub fn is_quarantined(path: &Path) -> bool {
+    static HOME: OnceLock<Option<PathBuf>> = OnceLock::new();
+    path.components().any(|c| c.as_os_str() == VOLUME_DIR)
+        || HOME
+            .get_or_init(home_quarantine)
+            .as_ref()
+            .is_some_and(|q| path.starts_with(q))
+}
+
+/// Quarantines created on other volumes, one path per line
+fn volumes_file() -> Option<PathBuf> {
+    data_dir().map(|p| p.join("quarantine-volumes"))
+}
+
+fn known_quarantines() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = home_quarantine().into_iter().collect();
+    if let Some(data) = volumes_file().and_then(|p| fs::read_to_string(p).ok()) {
+        for line in data.lines().filter(|l| !l.is_empty()) {
+            let root = PathBuf::from(line);
+            if !roots.contains(&root) {
+                roots.push(root);
+            }
+        }
+    }
+    roots
+}
+
+fn register_quarantine(root: &Path) -> Result<()> {
+    if known_quarantines().iter().any(|r| r == root) {
+        return Ok(());
+    }
+    let path = match volumes_file() {
+        Some(p) => p,
+        None => return Ok(()),
+    };
+
+    let mut data = fs::read_to_string(&path).unwrap_or_default();
+    data.push_str(&format!("{}\n", root.display()));
+    fs::write(&path, data).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Quarantine directory on the same volume as `path`, so moving it there is
+/// a rename rather than a copy
+#[cfg(unix)]
+fn quarantine_for(path: &Path) -> Result<PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let home = home_quarantine().context("Could not determine the data directory")?;
+    let device = fs::symlink_metadata(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .dev();
+
+    // The home quarantine may not exist yet; compare its closest ancestor
+    let home_device = home
+        .ancestors()
+        .find_map(|p| fs::metadata(p).ok())
+        .map(|m| m.dev());
+    if home_device == Some(device) {
+        return Ok(home);
+    }
+
+    // Topmost ancestor on the same device is the volume's mount point
+    let mount = path
+        .ancestors()
+        .skip(1)
+        .take_while(|p| fs::metadata(p).is_ok_and(|m| m.dev() == device))
+        .last()
+        .context("Could not find the volume of this path")?;
+    Ok(mount.join(VOLUME_DIR).join(current_uid()?.to_string()))
+}
+
+#[cfg(not(unix))]
+fn quarantine_for(_path: &Path) -> Result<PathBuf> {
+    home_quarantine().context("Could not determine the data directory")
+}
+
+#[cfg(unix)]
+fn current_uid() -> Result<u32> {
+    static UID: OnceLock<Option<u32>> = OnceLock::new();
+    UID.get_or_init(|| crate::owner::resolve_filter(None).ok().flatten())
+        .context("Could not determine the current user")
+}
+
+/// Whether `path` itself, not a link to it, belongs to the current user.
+/// Others can write into a volume's shared quarantine directory, so nothing
+/// there is trusted otherwise.
+#[cfg(unix)]
+fn is_own(path: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    fs::symlink_metadata(path)
+        .is_ok_and(|m| !m.file_type().is_symlink() && current_uid().is_ok_and(|uid| m.uid() == uid))
+}
+
+#[cfg(not(unix))]
+fn is_own(path: &Path) -> bool {
+    fs::symlink_metadata(path).is_ok_and(|m| !m.file_type().is_symlink())
+}
+
+/// Create the quarantine `root`, readable by its owner only, or check the
+/// one already there. A volume's shared directory is left open to every
+/// user, like `/tmp`, so each can add their own.
+#[cfg(unix)]
+fn prepare_root(root: &Path) -> Result<()> {
+    use std::io::ErrorKind;
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let parent = root
+        .parent()
+        .context("Quarantine has no parent directory")?;
+    if parent.file_name() == Some(VOLUME_DIR.as_ref()) {
+        match fs::create_dir(parent) {
+            Ok(()) => fs::set_permissions(parent, fs::Permissions::from_mode(0o1777)),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Ok(()),
+            Err(e) => Err(e),
+        }
+    } else {
+        fs::create_dir_all(parent)
+    }
+    .with_context(|| format!("Failed to create quarantine: {}", parent.display()))?;
+
+    match fs::DirBuilder::new().mode(0o700).create(root) {
+        Err(e) if e.kind() != ErrorKind::AlreadyExists => {
+            return Err(e)
+                .with_context(|| format!("Failed to create quarantine: {}", root.display()));
+        }
+        _ => {}
+    }
+    if !is_own(root) || !root.is_dir() {
+        anyhow::bail!(
+            "Refusing to use quarantine {}: not a directory of the current user",
+            root.display()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn prepare_root(root: &Path) -> Result<()> {
+    fs::create_dir_all(root)
+        .with_context(|| format!("Failed to create quarantine: {}", root.display()))
+}
+
+/// Move a finding into quarantine
+pub fn quarantine(file: &CleanableFile) -> Result<()> {
+    let root = quarantine_for(&file.path)?;
+    let now = Utc::now();
+    let id = format!(
+        "{}-{}",
+        now.format("%Y%m%d%H%M%S"),
+        &blake3::hash(format!("{}{:?}", file.path.display(), now).as_bytes()).to_hex()[..8]
+    );
+    let location = root.join(&id);
+
+    prepare_root(&root)?;
+    fs::create_dir(&location)
+        .with_context(|| format!("Failed to create quarantine: {}", location.display()))?;
+    if root != home_quarantine().unwrap_or_default() {
+        register_quarantine(&root)?;
+    }
+
+    let entry = QuarantineEntry {
+        id,
+        original_path: file.path.clone(),
+        size: file.size,
+        is_directory: file.is_directory,
+        reason: file.reason.clone(),
+        quarantined_at: now,
+        location: location.clone(),
+    };
+    fs::write(location.join(META), serde_json::to_vec_pretty(&entry)?)
+        .with_context(|| format!("Failed to write quarantine metadata: {}", location.display()))?;
+
+    if let Err(e) = fs::rename(&file.path, location.join(ITEM)) {
+        let _ = fs::remove_dir_all(&location);
+        return Err(e)
+            .with_context(|| format!("Failed to quarantine: {}", file.path.display()));
+    }
+
+    Ok(())
+}
+
+/// Everything in quarantine, oldest first. Quarantines and entries of
+/// other users are skipped.
+pub fn list() -> Vec<QuarantineEntry> {
+    let mut entries: Vec<QuarantineEntry> = known_quarantines()
+        .iter()
+        .filter(|root| is_own(root))
+        .filter_map(|root| fs::read_dir(root).ok())
+        .flat_map(|dir| dir.flatten())
+        .filter_map(|dir_entry| {
+            let location = dir_entry.path();
+            let meta = location.join(META);
+            if !is_own(&location) || !is_own(&meta) {
+                return None;
+            }
+            let data = fs::read(meta).ok()?;
+            let mut entry: QuarantineEntry = serde_json::from_slice(&data).ok()?;
+            entry.location = location;
+            Some(entry)
+        })
+        .collect();
+
+    entries.sort_by_key(|e| e.quarantined_at);
+    entries
+}
+
+/// Move a quarantined item back. `target` is an entry id or original path;
+/// for a path quarantined more than once, the latest entry is restored.
+pub fn restore(target: &str) -> Result<QuarantineEntry> {
+    let path = std::path::absolute(target).unwrap_or_else(|_| PathBuf::from(target));
+    let entry = list()
+        .into_iter()
+        .rev()
+        .find(|e| e.id == target || e.original_path == path)
+        .with_context(|| format!("Nothing in quarantine matches {}", target))?;
+
+    if entry.original_path.symlink_metadata().is_ok() {
+        anyhow::bail!(
+            "{} already exists; move it away first",
+            entry.original_path.display()
+        );
+    }
+    if let Some(parent) = entry.original_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create {}", parent.display()))?;
+    }
+
+    fs::rename(entry.location.join(ITEM), &entry.original_path)
+        .with_context(|| format!("Failed to restore {}", entry.original_path.display()))?;
+    fs::remove_dir_all(&entry.location)
+        .with_context(|| format!("Failed to remove {}", entry.location.display()))?;
+
+    Ok(entry)
+}
+
+/// Delete quarantined items for good; returns how many and their size
+pub fn purge(entries: &[QuarantineEntry]) -> Result<(usize, u64)> {
+    let mut count = 0;
+    let mut bytes = 0;
+    for entry in entries {
+        fs::remove_dir_all(&entry.location)
+            .with_context(|| format!("Failed to purge {}", entry.location.display()))?;
+        count += 1;
+        bytes += entry.size;
+    }
+    Ok((count, bytes))
+}
+
+/// Purge items quarantined more than `days` ago
+pub fn purge_expired(days: u32) -> Result<(usize, u64)> {
+    let expired: Vec<QuarantineEntry> = list()
+        .into_iter()
+        .filter(|e| e.is_expired(days))
+        .collect();
+    purge(&expired)
+}
+
+/// Run the quarantine command
+#[cfg(feature = "cli")]
+pub fn run(options: &QuarantineOptions, config: &Config) -> Result<()> {
+    match &options.action {
+        QuarantineAction::List { json } => {
+            let entries = list();
+            if *json {
+                println!("{}", serde_json::to_string_pretty(&entries)?);
+            } else {
+                print_entries(&entries, config.quarantine_days);
+            }
+        }
+        QuarantineAction::Restore { target } => {
+            let entry = restore(target)?;
+            ui::print_success(&format!(
+                "Restored {}",
+                ui::format_path(&entry.original_path)
+            ));
+        }
+        QuarantineAction::Purge { all, yes } => {
+            let entries: Vec<QuarantineEntry> = list()
+                .into_iter()
+                .filter(|e| *all || e.is_expired(config.quarantine_days))
+                .collect();
+            if entries.is_empty() {
+                ui::print_info("Nothing to purge.");
+                return Ok(());
+            }
+
+            let size = entries.iter().map(|e| e.size).sum();
+            let prompt = format!(
+                "Permanently delete {} quarantined item(s) ({})?",
+                entries.len(),
+                ui::format_size(size)
+            );
+            if !*yes && !ui::confirm(&prompt) {
+                ui::print_info("Purge cancelled.");
+                return Ok(());
+            }
+
+            let (count, bytes) = purge(&entries)?;
+            ui::print_success(&format!(
+                "Purged {} item(s), freed {}",
+                ui::format_number(count as u64),
+                ui::format_size(bytes)
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn print_entries(entries: &[QuarantineEntry], days: u32) {
+    if entries.is_empty() {
+        ui::print_info("Quarantine is empty.");
+        return;
+    }
+
+    ui::print_header("Quarantine");
+    for entry in entries {
+        let expires = entry.quarantined_at + Duration::days(days as i64);
+        let days_left = (expires - Utc::now()).num_days().max(0);
+        println!(
+            "  {}  {}  {}",
+            entry.id.cyan(),
+            ui::format_size(entry.size).yellow(),
+            ui::format_path(&entry.original_path)
+        );
+        println!(
+            "      {} (purged in {} day(s))",
+            entry.reason.dimmed(),
+            days_left
+        );
+    }
+
+    let total = entries.iter().map(|e| e.size).sum();
+    println!();
+    ui::print_info(&format!(
+        "{} item(s), {}. Restore one with `duster quarantine restore <id|path>`.",
+        entries.len(),
+        ui::format_size(total)
+    ));
+}
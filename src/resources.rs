@@ -0,0 +1,119 @@
+//! `--resources`: what a scan or clean cost, printed when it ends. Memory
+//! and I/O come from OS counters where the platform keeps them, so they
+//! are best-effort.
+
+use crate::analyzer;
+use crate::ui;
+use colored::*;
+use std::time::{Duration, Instant};
+
+/// What the process has used so far
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Counters {
+    /// Peak resident memory; the current one where no peak is kept
+    memory: Option<u64>,
+    /// Bytes read from storage
+    bytes_read: Option<u64>,
+}
+
+/// `VmHWM` from `/proc/self/status` and `read_bytes` from `/proc/self/io`
+#[cfg(target_os = "linux")]
+fn counters() -> Counters {
+    let field = |file: &str, name: &str| -> Option<u64> {
+        let contents = std::fs::read_to_string(file).ok()?;
+        let line = contents.lines().find(|l| l.starts_with(name))?;
+        line[name.len()..].split_whitespace().next()?.parse().ok()
+    };
+    Counters {
+        memory: field("/proc/self/status", "VmHWM:").map(|kb| kb * 1024),
+        bytes_read: field("/proc/self/io", "read_bytes:"),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn counters() -> Counters {
+    use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System};
+
+    let pid = Pid::from_u32(std::process::id());
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        ProcessRefreshKind::new().with_memory().with_disk_usage(),
+    );
+    match system.process(pid) {
+        Some(process) => Counters {
+            memory: Some(process.memory()),
+            bytes_read: Some(process.disk_usage().total_read_bytes),
+        },
+        None => Counters::default(),
+    }
+}
+
+/// Measures from its creation and prints the usage when dropped, so every
+/// way out of a command reports it
+pub struct Meter {
+    started: Instant,
+    examined: u64,
+    read: Option<u64>,
+}
+
+impl Meter {
+    pub fn start() -> Self {
+        Self {
+            started: Instant::now(),
+            examined: analyzer::examined(),
+            read: counters().bytes_read,
+        }
+    }
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed.as_secs() < 60 {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    } else {
+        crate::format::format_duration(elapsed.as_secs())
+    }
+}
+
+impl Drop for Meter {
+    fn drop(&mut self) {
+        let now = counters();
+        let unknown = || "unknown".to_string();
+        let memory = now.memory.map_or_else(unknown, ui::format_size);
+        let read = match (self.read, now.bytes_read) {
+            (Some(start), Some(end)) => ui::format_size(end.saturating_sub(start)),
+            _ => unknown(),
+        };
+
+        // On stderr, so JSON output stays a single document
+        eprintln!();
+        eprintln!("{}", "Resources".bold());
+        eprintln!(
+            "  {:<22} {}",
+            "Time:",
+            format_elapsed(self.started.elapsed())
+        );
+        eprintln!(
+            "  {:<22} {}",
+            "Directories examined:",
+            analyzer::examined() - self.examined
+        );
+        eprintln!("  {:<22} {}", "Peak memory:", memory);
+        eprintln!("  {:<22} {}", "Read from disk:", read);
+        eprintln!(
+            "  {}",
+            "Tune parallelism with --threads, or `threads` in config.toml.".dimmed()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elapsed_format() {
+        assert_eq!(format_elapsed(Duration::from_millis(1234)), "1.23s");
+        assert_eq!(format_elapsed(Duration::from_secs(125)), "2m 5s");
+    }
+}
@@ -0,0 +1,108 @@
+//! `duster rm`: an `rm` replacement for shell aliases. Paths go into
+//! quarantine, with a history entry, so `duster rm --undo-last` or
+//! `duster quarantine restore` can bring them back. Anything the user names
+//! may go, under the cleaner's `Policy::Rm`: only the filesystem root and
+//! the directories right below it, the home itself, system directories and
+//! the inside of bundles are refused.
+
+use crate::cleaner::{self, CleanupOutcome, Policy};
+use crate::cli::RmOptions;
+use crate::config::Config;
+use crate::history;
+use crate::quarantine;
+use crate::tags::Tags;
+use crate::ui;
+use anyhow::{Context, Result};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Reason recorded for what `duster rm` quarantines, which `--undo-last`
+/// looks for in the history
+const RM_REASON: &str = "Removed with `duster rm`";
+
+/// Run the rm command
+pub fn run(options: &RmOptions, config: &Config) -> Result<()> {
+    if options.undo_last {
+        return undo_last(options.verbose);
+    }
+
+    // As with `rm -f`, paths that don't exist are passed over quietly
+    let paths: Vec<PathBuf> = options
+        .paths
+        .iter()
+        .filter(|p| !options.force || p.symlink_metadata().is_ok())
+        .cloned()
+        .collect();
+    if paths.is_empty() {
+        return Ok(());
+    }
+
+    let mut files = cleaner::path_findings(&paths, config, &Tags::load(), Policy::Rm)?;
+    if !options.recursive {
+        if let Some(dir) = files.iter().find(|f| f.is_directory) {
+            anyhow::bail!(
+                "{} is a directory; pass -r to remove it",
+                dir.path.display()
+            );
+        }
+    }
+    for file in &mut files {
+        file.reason = RM_REASON.to_string();
+    }
+
+    let result = cleaner::quarantine_removals(&files)?;
+    let _ = history::record(&files, &result);
+
+    // Silent on success, like rm
+    if options.verbose {
+        for item in &result.items {
+            if matches!(item.outcome, CleanupOutcome::Cleaned) {
+                println!("removed {}", ui::format_path(&item.path));
+            }
+        }
+    }
+    if !result.errors.is_empty() {
+        for error in &result.errors {
+            ui::print_error(error);
+        }
+        anyhow::bail!("{} path(s) could not be removed", result.errors.len());
+    }
+
+    Ok(())
+}
+
+/// Restore what the latest `duster rm` with items still in quarantine moved
+/// there, so repeating it steps further back
+fn undo_last(verbose: bool) -> Result<()> {
+    let quarantined: HashSet<PathBuf> = quarantine::list()
+        .into_iter()
+        .map(|e| e.original_path)
+        .collect();
+    let removed: Vec<PathBuf> = history::all()
+        .into_iter()
+        .rev()
+        .map(|entry| {
+            entry
+                .items
+                .into_iter()
+                .filter(|item| {
+                    item.file.reason == RM_REASON
+                        && matches!(item.outcome, CleanupOutcome::Cleaned)
+                        && quarantined.contains(&item.file.path)
+                })
+                .map(|item| item.file.path)
+                .collect::<Vec<_>>()
+        })
+        .find(|paths| !paths.is_empty())
+        .context("Nothing removed with `duster rm` is left in quarantine")?;
+
+    for path in &removed {
+        let entry = quarantine::restore(&path.to_string_lossy())?;
+        if verbose {
+            println!("restored {}", ui::format_path(&entry.original_path));
+        }
+    }
+    ui::print_success(&format!("Restored {} item(s)", removed.len()));
+
+    Ok(())
+}
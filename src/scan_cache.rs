@@ -1,22 +1,176 @@
-//! Cache of recent scan results so clean can reuse them when run shortly after scan.
+//! Cache of recent scan results so clean can reuse them when run shortly after scan,
+//! and the persistent index of file hashes used by duplicate detection.
 
 use crate::cli::ScanOptions;
-use crate::scanner::ScanResult;
+use crate::config::Config;
+use crate::scanner::{extra, CleanupAction, ScanResult};
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 const CACHE_MAX_AGE_SECS: u64 = 300; // 5 minutes
 
+/// Free space moving by more than this since the scan means the disk changed a lot
+const FREE_SPACE_CHANGE_LIMIT: u64 = 1024 * 1024 * 1024; // 1 GiB
+
+/// How many findings' parent directories to fingerprint, largest findings first
+const MAX_HINT_PARENTS: usize = 64;
+
 #[derive(Debug, Serialize, Deserialize)]
 struct CacheEnvelope {
     timestamp_secs: u64,
     options_key: String,
+    /// Where and with what the scan ran; missing in caches from older versions
+    #[serde(default)]
+    environment: Option<ScanEnvironment>,
+    #[serde(default)]
+    hints: ChangeHints,
+    /// Thresholds the scan ran with; missing in caches from older versions
+    #[serde(default)]
+    thresholds: Option<Thresholds>,
     result: ScanResult,
 }
 
+/// The thresholds a cached scan applied, so `duster whatif` can tell which
+/// changes it can re-evaluate without rescanning
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Thresholds {
+    pub min_age_days: u32,
+    pub min_large_size_mb: u64,
+    pub project_recent_days: u32,
+}
+
+impl Thresholds {
+    pub fn of(config: &Config) -> Self {
+        Self {
+            min_age_days: config.min_age_days,
+            min_large_size_mb: config.min_large_size_mb,
+            project_recent_days: config.project_recent_days,
+        }
+    }
+}
+
+/// The most recent cached scan, however old and whatever it was run with
+#[derive(Debug)]
+pub struct LastScan {
+    pub result: ScanResult,
+    pub age_secs: u64,
+    pub thresholds: Option<Thresholds>,
+}
+
+/// Cheap signals that the filesystem changed since a scan
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ChangeHints {
+    /// Free bytes on the scanned root's filesystem
+    free_bytes: Option<u64>,
+    /// Modification times of the directories holding the largest findings
+    parent_mtimes: Vec<(PathBuf, u64)>,
+}
+
+impl ChangeHints {
+    fn capture(result: &ScanResult, config: &Config) -> Self {
+        let mut files: Vec<_> = result.files.iter().collect();
+        files.sort_by_key(|f| std::cmp::Reverse(f.size));
+
+        let mut parent_mtimes: Vec<(PathBuf, u64)> = Vec::new();
+        for parent in files.iter().filter_map(|f| f.path.parent()) {
+            if parent_mtimes.len() >= MAX_HINT_PARENTS {
+                break;
+            }
+            if parent_mtimes.iter().any(|(p, _)| p == parent) {
+                continue;
+            }
+            if let Ok(metadata) = fs::metadata(parent) {
+                parent_mtimes.push((parent.to_path_buf(), mtime_ns(&metadata)));
+            }
+        }
+
+        Self {
+            free_bytes: free_space(&config.get_base_path()),
+            parent_mtimes,
+        }
+    }
+
+    /// Whether the disk clearly changed since these hints were captured
+    fn disk_changed(&self, config: &Config) -> bool {
+        if let (Some(then), Some(now)) = (self.free_bytes, free_space(&config.get_base_path())) {
+            if then.abs_diff(now) > FREE_SPACE_CHANGE_LIMIT {
+                return true;
+            }
+        }
+
+        self.parent_mtimes.iter().any(|(parent, mtime)| {
+            fs::metadata(parent).map_or(true, |m| mtime_ns(&m) != *mtime)
+        })
+    }
+}
+
+/// Available bytes on the filesystem holding `path`, from `df`
+fn free_space(path: &Path) -> Option<u64> {
+    let output = Command::new("df").arg("-Pk").arg(path).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    // Second line: filesystem, 1024-blocks, used, available, capacity, mount
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let available: u64 = stdout.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(available * 1024)
+}
+
+/// Circumstances of a cached scan. A cache is only reused when everything
+/// but the duration matches the current run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct ScanEnvironment {
+    hostname: String,
+    duster_version: String,
+    config_hash: String,
+    roots: Vec<PathBuf>,
+    duration_ms: u64,
+}
+
+impl ScanEnvironment {
+    fn current(config: &Config, duration: Duration) -> Self {
+        let mut roots = vec![config.get_base_path()];
+        roots.extend(config.duplicate_roots.iter().cloned());
+
+        Self {
+            hostname: hostname(),
+            duster_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_hash: config_hash(config),
+            roots,
+            duration_ms: duration.as_millis() as u64,
+        }
+    }
+
+    fn is_compatible_with(&self, current: &ScanEnvironment) -> bool {
+        self.hostname == current.hostname
+            && self.duster_version == current.duster_version
+            && self.config_hash == current.config_hash
+            && self.roots == current.roots
+    }
+}
+
+/// Machine name from `hostname`, or empty if it can't be determined
+fn hostname() -> String {
+    Command::new("hostname")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Hash of the settings that shape scan results (config file plus CLI overrides)
+fn config_hash(config: &Config) -> String {
+    let serialized = serde_json::to_string(config).unwrap_or_default();
+    blake3::hash(serialized.as_bytes()).to_hex().to_string()
+}
+
 /// Build a deterministic key from scan options so we can match cached scans.
 fn options_fingerprint(options: &ScanOptions) -> String {
     let path = options
@@ -26,22 +180,43 @@ fn options_fingerprint(options: &ScanOptions) -> String {
         .unwrap_or_default();
     let mut exclude = options.exclude.clone();
     exclude.sort();
+    let mut duplicate_roots = options.duplicates_path.clone();
+    duplicate_roots.sort();
     format!(
-        "path={} all={} cache={} trash={} temp={} downloads={} build={} large={} duplicates={} old={} min_age={:?} min_size={:?} project_age={:?} exclude={:?}",
+        "path={} all={} cache={} trash={} temp={} downloads={} desktop={} build={} ci={} large={} duplicates={} old={} junk={} logs={} metadata_junk={} git={} docker={} scanner={:?} no_scanner={:?} min_age={:?} min_size={:?} min_file_count={:?} min_savings={:?} where={:?} project_age={:?} owner={:?} exclude={:?} duplicate_roots={:?} compare_baseline={} include_cloud={} strict_path={} use_index={} max_hash_size={:?}",
         path,
         options.all,
         options.cache,
         options.trash,
         options.temp,
         options.downloads,
+        options.desktop,
         options.build,
+        options.ci,
         options.large,
         options.duplicates,
         options.old,
+        options.junk,
+        options.logs,
+        options.metadata_junk,
+        options.git,
+        options.docker,
+        options.scanner,
+        options.no_scanner,
         options.min_age,
         options.min_size,
+        options.min_file_count,
+        options.min_savings,
+        options.where_extra,
         options.project_age,
+        options.owner,
         exclude,
+        duplicate_roots,
+        options.compare_baseline,
+        options.include_cloud,
+        options.strict_path,
+        options.use_index,
+        options.max_hash_size,
     )
 }
 
@@ -49,8 +224,14 @@ fn cache_path() -> Option<PathBuf> {
     dirs::cache_dir().map(|p| p.join("duster").join("last_scan.json"))
 }
 
-/// Save a scan result for potential reuse by clean.
-pub fn save(result: &ScanResult, options: &ScanOptions) -> Result<()> {
+/// Save a scan result for potential reuse by clean, along with the config it
+/// was produced with and how long the scan took.
+pub fn save(
+    result: &ScanResult,
+    options: &ScanOptions,
+    config: &Config,
+    duration: Duration,
+) -> Result<()> {
     let path = match cache_path() {
         Some(p) => p,
         None => return Ok(()),
@@ -61,25 +242,38 @@ pub fn save(result: &ScanResult, options: &ScanOptions) -> Result<()> {
         .map(|d| d.as_secs())
         .unwrap_or(0);
 
+    // Create the cache dir first so it doesn't register as a change below
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache dir: {}", parent.display()))?;
+    }
+
     let envelope = CacheEnvelope {
         timestamp_secs,
         options_key: options_fingerprint(options),
+        environment: Some(ScanEnvironment::current(config, duration)),
+        hints: ChangeHints::capture(result, config),
+        thresholds: Some(Thresholds::of(config)),
         result: result.clone(),
     };
 
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent)
-            .with_context(|| format!("Failed to create cache dir: {}", parent.display()))?;
-    }
-
     let data = serde_json::to_string_pretty(&envelope).context("Failed to serialize scan cache")?;
     fs::write(&path, data).with_context(|| format!("Failed to write cache: {}", path.display()))?;
 
     Ok(())
 }
 
-/// Load cached scan result if it exists, is no older than max_age_secs, and options match.
-pub fn load_if_recent(options: &ScanOptions, max_age_secs: u64) -> Option<ScanResult> {
+/// Load cached scan result if it exists, is no older than max_age_secs, and
+/// options match. Caches from another machine, another duster version or a
+/// different config are never reused, nor are ones the disk has clearly
+/// moved on from (free space shifted by over 1 GiB, or the directories
+/// holding the largest findings were modified), nor ones with findings
+/// cleaned by running a command.
+pub fn load_if_recent(
+    options: &ScanOptions,
+    config: &Config,
+    max_age_secs: u64,
+) -> Option<ScanResult> {
     let path = cache_path()?;
     let data = fs::read_to_string(&path).ok()?;
     let envelope: CacheEnvelope = serde_json::from_str(&data).ok()?;
@@ -97,10 +291,188 @@ pub fn load_if_recent(options: &ScanOptions, max_age_secs: u64) -> Option<ScanRe
         return None;
     }
 
+    let current = ScanEnvironment::current(config, Duration::ZERO);
+    if !envelope
+        .environment
+        .is_some_and(|env| env.is_compatible_with(&current))
+    {
+        return None;
+    }
+
+    if envelope.hints.disk_changed(config) {
+        return None;
+    }
+
+    // Clean would run a cached command as given, so anyone able to write
+    // the cache file could pick it; findings cleaned by a command, or
+    // allowed onto external drives, are only trusted straight from a scan
+    if envelope.result.files.iter().any(|f| {
+        matches!(f.action, CleanupAction::Command { .. })
+            || f.extra.contains_key(extra::DUPLICATE_ROOT)
+    }) {
+        return None;
+    }
+
     Some(envelope.result)
 }
 
 /// Load cached scan result if it exists, is no older than 5 minutes, and options match.
-pub fn load_if_recent_default(options: &ScanOptions) -> Option<ScanResult> {
-    load_if_recent(options, CACHE_MAX_AGE_SECS)
+pub fn load_if_recent_default(options: &ScanOptions, config: &Config) -> Option<ScanResult> {
+    load_if_recent(options, config, CACHE_MAX_AGE_SECS)
+}
+
+/// Load the last cached scan without checking its age, options or
+/// environment
+pub fn load_last() -> Option<LastScan> {
+    let data = fs::read_to_string(cache_path()?).ok()?;
+    let envelope: CacheEnvelope = serde_json::from_str(&data).ok()?;
+    let now_secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    Some(LastScan {
+        result: envelope.result,
+        age_secs: now_secs.saturating_sub(envelope.timestamp_secs),
+        thresholds: envelope.thresholds,
+    })
+}
+
+fn hash_index_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|p| p.join("duster").join("hashes.json"))
+}
+
+/// Rough memory one index entry takes beyond its path
+const HASH_ENTRY_OVERHEAD: u64 = 160;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HashEntry {
+    size: u64,
+    mtime_ns: u64,
+    hash: String,
+    /// Added or refreshed in this scan
+    #[serde(skip)]
+    current: bool,
+}
+
+/// Content hashes from earlier duplicate scans, valid while a file's size
+/// and modification time are unchanged
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HashIndex {
+    entries: HashMap<PathBuf, HashEntry>,
+    /// Memory the index may take, if limited
+    #[serde(skip)]
+    budget: Option<u64>,
+}
+
+impl HashIndex {
+    /// Load the index from the cache dir, keeping it within `budget` bytes;
+    /// a missing, unreadable or oversized index is empty
+    pub fn load(budget: u64) -> Self {
+        let mut index: Self = hash_index_path()
+            .filter(|path| fs::metadata(path).is_ok_and(|m| m.len() <= budget))
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default();
+        index.budget = Some(budget);
+        index
+    }
+
+    /// Hash recorded for this file, if it hasn't changed since
+    pub fn get(&self, path: &Path, size: u64, mtime_ns: u64) -> Option<&str> {
+        self.entries
+            .get(path)
+            .filter(|e| e.size == size && e.mtime_ns == mtime_ns)
+            .map(|e| e.hash.as_str())
+    }
+
+    pub fn insert(&mut self, path: PathBuf, size: u64, mtime_ns: u64, hash: String) {
+        self.entries.insert(
+            path,
+            HashEntry {
+                size,
+                mtime_ns,
+                hash,
+                current: true,
+            },
+        );
+    }
+
+    /// Drop entries until the index fits in `budget` bytes, those from
+    /// earlier scans first
+    fn trim(&mut self, budget: u64) {
+        let entry_bytes = |path: &Path, entry: &HashEntry| {
+            path.as_os_str().len() as u64 + entry.hash.len() as u64 + HASH_ENTRY_OVERHEAD
+        };
+        let mut bytes: u64 = self.entries.iter().map(|(p, e)| entry_bytes(p, e)).sum();
+        for keep_current in [true, false] {
+            self.entries.retain(|path, entry| {
+                if bytes <= budget || (keep_current && entry.current) {
+                    return true;
+                }
+                bytes -= entry_bytes(path, entry);
+                false
+            });
+        }
+    }
+
+    /// Drop entries for deleted files, and the oldest past the budget, and
+    /// write the index to the cache dir
+    pub fn save(&mut self) -> Result<()> {
+        let path = match hash_index_path() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+
+        self.entries.retain(|p, _| p.exists());
+        if let Some(budget) = self.budget {
+            self.trim(budget);
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create cache dir: {}", parent.display()))?;
+        }
+
+        let data = serde_json::to_string(&self).context("Failed to serialize hash index")?;
+        fs::write(&path, data)
+            .with_context(|| format!("Failed to write hash index: {}", path.display()))?;
+
+        Ok(())
+    }
+}
+
+/// Modification time in nanoseconds since the epoch, as stored in the hash index
+pub fn mtime_ns(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_index_trims_earlier_scans_first() {
+        let mut index: HashIndex = serde_json::from_str(
+            r#"{"entries": {
+                "/old/a": {"size": 1, "mtime_ns": 1, "hash": "aa"},
+                "/old/b": {"size": 1, "mtime_ns": 1, "hash": "bb"}
+            }}"#,
+        )
+        .unwrap();
+        index.insert(PathBuf::from("/new/c"), 1, 1, "cc".to_string());
+
+        let one_entry = 6 + 2 + HASH_ENTRY_OVERHEAD;
+        index.trim(2 * one_entry);
+        assert_eq!(index.entries.len(), 2);
+        assert!(index.get(Path::new("/new/c"), 1, 1).is_some());
+
+        index.trim(0);
+        assert!(index.entries.is_empty());
+    }
 }
@@ -0,0 +1,404 @@
+//! Android development leftovers: emulator images (AVDs), SDK system images
+//! and platforms, and Gradle wrapper distributions, each reported when it
+//! hasn't been used within `min_age_days`.
+//!
+//! System images an AVD still boots from are never reported, and the
+//! newest SDK platform is kept, as builds compile against it. AVDs are
+//! deleted through `avdmanager`, which also drops the `<name>.ini` that
+//! lists them in Android Studio.
+
+use super::{
+    calculate_dir_stats, get_last_accessed, get_last_modified, subdirs, Category, CleanableFile,
+    CleanupAction, ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Emulator images, relative to the home
+const AVD_DIR: &str = ".android/avd";
+
+/// Gradle wrapper downloads, relative to the home
+const GRADLE_DISTS_DIR: &str = ".gradle/wrapper/dists";
+
+/// Android's AVD tool, relative to the SDK
+const AVDMANAGER: &str = if cfg!(windows) {
+    "cmdline-tools/latest/bin/avdmanager.bat"
+} else {
+    "cmdline-tools/latest/bin/avdmanager"
+};
+
+/// Where Android Studio installs the SDK, relative to the home
+fn default_sdk_dirs() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &["Library/Android/sdk"]
+    } else if cfg!(windows) {
+        &["AppData/Local/Android/Sdk"]
+    } else {
+        &["Android/Sdk"]
+    }
+}
+
+pub struct AndroidScanner;
+
+impl AndroidScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for AndroidScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An emulator image and the system image it boots
+struct Avd {
+    path: PathBuf,
+    name: String,
+    /// `image.sysdir.1` from its config.ini, relative to the SDK
+    sysdir: Option<PathBuf>,
+}
+
+fn avds(avd_dir: &Path) -> Vec<Avd> {
+    let mut avds: Vec<Avd> = fs::read_dir(avd_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && p.extension().is_some_and(|e| e == "avd"))
+        .map(|path| {
+            let sysdir = fs::read_to_string(path.join("config.ini"))
+                .ok()
+                .and_then(|ini| {
+                    ini.lines().find_map(|line| {
+                        let value = line.strip_prefix("image.sysdir.1")?;
+                        let value = value.trim_start().strip_prefix('=')?.trim();
+                        Some(PathBuf::from(value.replace('\\', "/")))
+                    })
+                });
+            let name = path
+                .file_stem()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            Avd { path, name, sysdir }
+        })
+        .collect();
+    avds.sort_by(|a, b| a.path.cmp(&b.path));
+    avds
+}
+
+/// When anything at the top of `path` was last written or, with `reads`,
+/// when a file there was last read. Emulators write to an AVD's disk
+/// images and read a system image's; Gradle touches a distribution's lock
+/// file each time it runs. Directory access times are left out, as listing
+/// a directory (this scan included) updates them.
+fn last_used(path: &Path, depth: usize, reads: bool) -> Option<DateTime<Utc>> {
+    WalkDir::new(path)
+        .max_depth(depth)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .flat_map(|e| {
+            let accessed = (reads && e.file_type().is_file())
+                .then(|| get_last_accessed(e.path()))
+                .flatten();
+            [accessed, get_last_modified(e.path())]
+        })
+        .flatten()
+        .max()
+}
+
+/// "29" for "android-29"
+fn api_level(dir: &Path) -> String {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    name.strip_prefix("android-").unwrap_or(&name).to_string()
+}
+
+/// How a kind of entry shows it was used, and whether it can be had again
+struct Kind {
+    /// Levels below the entry whose times count
+    depth: usize,
+    /// Whether reading its files counts as use, or only writing
+    reads: bool,
+    recoverable: bool,
+}
+
+/// Emulators write to their disks, and this scan reads their config.ini.
+/// Their disks hold the apps and data installed on them.
+const AVD: Kind = Kind {
+    depth: 1,
+    reads: false,
+    recoverable: false,
+};
+
+/// System images and platforms, which are only read
+const SDK_PACKAGE: Kind = Kind {
+    depth: 1,
+    reads: true,
+    recoverable: true,
+};
+
+/// Gradle distributions, one hash directory down
+const GRADLE_DIST: Kind = Kind {
+    depth: 2,
+    reads: true,
+    recoverable: true,
+};
+
+/// A finding for `path` if it wasn't used within `min_age_days`; `what`
+/// starts the reason
+fn unused(path: PathBuf, what: String, kind: &Kind, config: &Config) -> Option<CleanableFile> {
+    if config.is_excluded(&path) {
+        return None;
+    }
+    let last_used = last_used(&path, kind.depth, kind.reads)?;
+    let days = (Utc::now() - last_used).num_days();
+    if days < config.min_age_days_for(&path) as i64 {
+        return None;
+    }
+    let stats = calculate_dir_stats(&path);
+    if stats.size == 0 {
+        return None;
+    }
+
+    Some(CleanableFile {
+        last_accessed: last_used,
+        is_directory: true,
+        file_count: stats.file_count,
+        recoverable: kind.recoverable,
+        ..CleanableFile::new(
+            path,
+            stats.size,
+            Category::Cache,
+            format!("{}, not used in {} days", what, days),
+        )
+    })
+}
+
+/// Findings for the AVDs in `avd_dir`, system images and platforms of the
+/// SDKs in `sdks`, and the Gradle distributions in `dists_dir`
+fn scan_android(
+    avd_dir: &Path,
+    sdks: &[PathBuf],
+    dists_dir: &Path,
+    config: &Config,
+    visitor: &mut dyn ScanVisitor,
+) -> Result<()> {
+    // Deleting just the directory would leave Android Studio listing a
+    // broken AVD
+    let avdmanager = sdks
+        .iter()
+        .map(|s| s.join(AVDMANAGER))
+        .find(|p| p.is_file());
+    let avds = avds(avd_dir);
+    for avd in &avds {
+        visitor.progress(&avd.path);
+        let what = format!("Android emulator {}", avd.name);
+        if let Some(file) = unused(avd.path.clone(), what, &AVD, config) {
+            let action = match &avdmanager {
+                Some(tool) => CleanupAction::command(
+                    &tool.to_string_lossy(),
+                    &["delete", "avd", "-n", &avd.name],
+                    None,
+                ),
+                None => CleanupAction::ReportOnly {
+                    hint: "delete it in Android Studio's Device Manager".to_string(),
+                },
+            };
+            visitor.finding(CleanableFile { action, ..file })?;
+        }
+    }
+
+    for sdk in sdks {
+        let booted: HashSet<PathBuf> = avds
+            .iter()
+            .filter_map(|a| a.sysdir.as_ref())
+            .map(|s| sdk.join(s))
+            .collect();
+
+        // system-images/android-29/google_apis/x86_64
+        for api in subdirs(&sdk.join("system-images")) {
+            for tag in subdirs(&api) {
+                for abi in subdirs(&tag) {
+                    visitor.progress(&abi);
+                    if booted.iter().any(|b| b.starts_with(&abi)) {
+                        continue;
+                    }
+                    let what = format!(
+                        "Android {} system image ({}, {})",
+                        api_level(&api),
+                        tag.file_name().unwrap_or_default().to_string_lossy(),
+                        abi.file_name().unwrap_or_default().to_string_lossy()
+                    );
+                    if let Some(file) = unused(abi, what, &SDK_PACKAGE, config) {
+                        visitor.finding(file)?;
+                    }
+                }
+            }
+        }
+
+        // platforms/android-29; the newest one is what builds compile against
+        let mut platforms = subdirs(&sdk.join("platforms"));
+        platforms.sort_by_key(|p| {
+            let level = api_level(p);
+            (level.parse::<u32>().unwrap_or(0), level)
+        });
+        platforms.pop();
+        for platform in platforms {
+            visitor.progress(&platform);
+            let what = format!("Android {} SDK platform", api_level(&platform));
+            if let Some(file) = unused(platform, what, &SDK_PACKAGE, config) {
+                visitor.finding(file)?;
+            }
+        }
+    }
+
+    // gradle-8.5-bin/<hash>/{gradle-8.5, gradle-8.5-bin.zip.lck, ...}
+    for dist in subdirs(dists_dir) {
+        visitor.progress(&dist);
+        let what = format!(
+            "Gradle distribution {}",
+            dist.file_name().unwrap_or_default().to_string_lossy()
+        );
+        if let Some(file) = unused(dist, what, &GRADLE_DIST, config) {
+            visitor.finding(file)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Scanner for AndroidScanner {
+    fn name(&self) -> &'static str {
+        "Android Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let Some(home) = &config.paths.home else {
+            return Ok(());
+        };
+
+        let mut sdks: Vec<PathBuf> = default_sdk_dirs().iter().map(|d| home.join(d)).collect();
+        // An SDK elsewhere is only looked at when scans may leave the home
+        if config.paths.system {
+            for var in ["ANDROID_HOME", "ANDROID_SDK_ROOT"] {
+                sdks.extend(std::env::var_os(var).map(PathBuf::from));
+            }
+        }
+        let mut seen = HashSet::new();
+        sdks.retain(|s| s.is_dir() && seen.insert(s.canonicalize().unwrap_or_else(|_| s.clone())));
+
+        scan_android(
+            &home.join(AVD_DIR),
+            &sdks,
+            &home.join(GRADLE_DISTS_DIR),
+            config,
+            visitor,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, FileTimes};
+    use std::time::{Duration, SystemTime};
+
+    /// Write `path` and date it and its parents below `root` 120 days back
+    fn write_unused(root: &Path, path: &str, contents: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        let then = SystemTime::now() - Duration::from_secs(120 * 24 * 60 * 60);
+        let times = FileTimes::new().set_accessed(then).set_modified(then);
+        let below_root = |p: &&Path| p.starts_with(root) && *p != root;
+        for entry in path.ancestors().take_while(below_root) {
+            File::open(entry).unwrap().set_times(times).unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unused_android_images_and_gradle_distributions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let sdk = root.join("sdk");
+        write_unused(
+            root,
+            "avd/Pixel_API_33.avd/config.ini",
+            "image.sysdir.1=system-images/android-33/google_apis/arm64-v8a/\n",
+        );
+        for file in [
+            "avd/Pixel_API_33.avd/userdata-qemu.img",
+            "sdk/system-images/android-29/google_apis/x86_64/system.img",
+            "sdk/system-images/android-33/google_apis/arm64-v8a/system.img",
+            "sdk/platforms/android-29/android.jar",
+            "sdk/platforms/android-34/android.jar",
+            "dists/gradle-7.6-bin/abc123/gradle-7.6-bin.zip.lck",
+        ] {
+            write_unused(root, file, "image");
+        }
+        // A distribution the last build used
+        let lock = root.join("dists/gradle-8.5-bin/def456/gradle-8.5-bin.zip.lck");
+        fs::create_dir_all(lock.parent().unwrap()).unwrap();
+        fs::write(lock, b"").unwrap();
+
+        let scan = || {
+            let mut found: Vec<CleanableFile> = Vec::new();
+            scan_android(
+                &root.join("avd"),
+                std::slice::from_ref(&sdk),
+                &root.join("dists"),
+                &Config::default(),
+                &mut found,
+            )
+            .unwrap();
+            found
+        };
+        let found = scan();
+
+        let reasons: Vec<(&Path, &str)> = found
+            .iter()
+            .map(|f| (f.path.strip_prefix(root).unwrap(), f.reason.as_str()))
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                (
+                    Path::new("avd/Pixel_API_33.avd"),
+                    "Android emulator Pixel_API_33, not used in 120 days"
+                ),
+                (
+                    Path::new("sdk/system-images/android-29/google_apis/x86_64"),
+                    "Android 29 system image (google_apis, x86_64), not used in 120 days"
+                ),
+                (
+                    Path::new("sdk/platforms/android-29"),
+                    "Android 29 SDK platform, not used in 120 days"
+                ),
+                (
+                    Path::new("dists/gradle-7.6-bin"),
+                    "Gradle distribution gradle-7.6-bin, not used in 120 days"
+                ),
+            ]
+        );
+        assert!(!found[0].recoverable);
+        assert!(matches!(found[0].action, CleanupAction::ReportOnly { .. }));
+
+        let avdmanager = sdk.join(AVDMANAGER);
+        fs::create_dir_all(avdmanager.parent().unwrap()).unwrap();
+        fs::write(&avdmanager, b"").unwrap();
+        assert_eq!(
+            scan()[0].action.describe().unwrap(),
+            format!("runs `{} delete avd -n Pixel_API_33`", avdmanager.display())
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Bazel output base scanner with stale-workspace detection
+
+use super::{
+    calculate_dir_stats, get_last_modified, open_projects, read_root, was_modified_within_days,
+    Category, CleanableFile, CleanupAction, ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+pub struct BazelScanner;
+
+impl BazelScanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Bazel's output user roots (`_bazel_<user>`), one per user
+    fn output_user_roots(config: &Config) -> Vec<PathBuf> {
+        let mut parents = Vec::new();
+        if let Some(home) = &config.paths.home {
+            parents.push(home.join(".cache").join("bazel"));
+        }
+        // macOS default
+        if config.paths.system {
+            parents.push(PathBuf::from("/private/var/tmp"));
+        }
+
+        parents
+            .iter()
+            .filter_map(|p| std::fs::read_dir(p).ok())
+            .flat_map(|entries| entries.flatten())
+            .map(|e| e.path())
+            .filter(|p| {
+                p.is_dir()
+                    && p.file_name()
+                        .is_some_and(|n| n.to_string_lossy().starts_with("_bazel_"))
+            })
+            .collect()
+    }
+}
+
+impl Default for BazelScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Output bases are named by the MD5 of their workspace path
+fn is_output_base(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| {
+        let n = n.to_string_lossy();
+        n.len() == 32 && n.chars().all(|c| c.is_ascii_hexdigit())
+    })
+}
+
+/// Workspace an output base belongs to, recorded by Bazel in `DO_NOT_BUILD_HERE`
+fn workspace_of(output_base: &Path) -> Option<PathBuf> {
+    let content = std::fs::read_to_string(output_base.join("DO_NOT_BUILD_HERE")).ok()?;
+    let workspace = content.trim();
+    (!workspace.is_empty()).then(|| PathBuf::from(workspace))
+}
+
+/// Whether the Bazel server for this output base is still running
+fn has_live_server(output_base: &Path) -> bool {
+    let pid = match std::fs::read_to_string(output_base.join("server").join("server.pid.txt")) {
+        Ok(p) => p,
+        Err(_) => return false,
+    };
+
+    if cfg!(target_os = "linux") {
+        return Path::new("/proc").join(pid.trim()).exists();
+    }
+
+    // Can't cheaply check elsewhere; a pid file means the server may be up
+    true
+}
+
+impl Scanner for BazelScanner {
+    fn name(&self) -> &'static str {
+        "Bazel Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        for root in Self::output_user_roots(config) {
+            let Some(entries) = read_root(&root, visitor) else {
+                continue;
+            };
+
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if !path.is_dir() || !is_output_base(&path) || config.is_excluded(&path) {
+                    continue;
+                }
+
+                // Bazel rewrites command.log on every invocation
+                let command_log = path.join("command.log");
+                let last_used = if command_log.exists() {
+                    command_log
+                } else {
+                    path.clone()
+                };
+
+                let workspace = workspace_of(&path);
+                let workspace_exists = workspace.as_ref().is_some_and(|w| w.exists());
+
+                // A workspace that's gone makes the output base stale regardless of age
+                if workspace_exists
+                    && (was_modified_within_days(&last_used, config.project_recent_days_for(&path))
+                        || workspace.as_deref().is_some_and(open_projects::is_open))
+                {
+                    continue;
+                }
+
+                if has_live_server(&path) {
+                    continue;
+                }
+
+                let stats = calculate_dir_stats(&path);
+                if stats.size < 1024 * 1024 {
+                    continue;
+                }
+
+                let workspace_name = workspace
+                    .as_ref()
+                    .map(|w| w.display().to_string())
+                    .unwrap_or_else(|| "unknown workspace".to_string());
+
+                // `bazel clean --expunge` also stops the server and handles the
+                // read-only files Bazel leaves in the execroot
+                let (reason, action) = match workspace.filter(|_| workspace_exists) {
+                    Some(ws) => (
+                        format!("Bazel output base for {}", workspace_name),
+                        CleanupAction::command("bazel", &["clean", "--expunge"], Some(&ws)),
+                    ),
+                    None => (
+                        format!("Bazel output base for deleted workspace {}", workspace_name),
+                        CleanupAction::Delete,
+                    ),
+                };
+
+                visitor.finding(CleanableFile {
+                    last_accessed: get_last_modified(&last_used).unwrap_or_else(Utc::now),
+                    is_directory: true,
+                    file_count: stats.file_count,
+                    action,
+                    recoverable: true,
+                    ..CleanableFile::new(path, stats.size, Category::BuildArtifact, reason)
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
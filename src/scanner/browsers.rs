@@ -0,0 +1,269 @@
+//! Browser caches, one finding per profile: Chrome, Chromium and Brave keep
+//! a cache directory per profile, and Firefox a `cache2` inside each one.
+//! Profiles are found where the browsers put them on macOS and Linux.
+
+use super::{
+    calculate_dir_stats, get_last_accessed, subdirs, Category, CleanableFile, ScanVisitor, Scanner,
+    MIN_CACHE_SIZE,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A Chromium-based browser: its cache root, whose subdirectories are
+/// profiles, and the directory holding its `Local State`, which names them.
+/// Both are relative to the home, for macOS and then Linux.
+struct Chromium {
+    name: &'static str,
+    cache: [&'static str; 2],
+    config: [&'static str; 2],
+}
+
+const CHROMIUM_BROWSERS: &[Chromium] = &[
+    Chromium {
+        name: "Chrome",
+        cache: ["Library/Caches/Google/Chrome", ".cache/google-chrome"],
+        config: [
+            "Library/Application Support/Google/Chrome",
+            ".config/google-chrome",
+        ],
+    },
+    Chromium {
+        name: "Chromium",
+        cache: ["Library/Caches/Chromium", ".cache/chromium"],
+        config: ["Library/Application Support/Chromium", ".config/chromium"],
+    },
+    Chromium {
+        name: "Brave",
+        cache: [
+            "Library/Caches/BraveSoftware/Brave-Browser",
+            ".cache/BraveSoftware/Brave-Browser",
+        ],
+        config: [
+            "Library/Application Support/BraveSoftware/Brave-Browser",
+            ".config/BraveSoftware/Brave-Browser",
+        ],
+    },
+];
+
+/// Directories holding Firefox profiles that have a `cache2`, relative to
+/// the home. Linux builds moved the cache out of `~/.mozilla` at some point.
+fn firefox_roots() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &["Library/Caches/Firefox/Profiles"]
+    } else {
+        &[".cache/mozilla/firefox", ".mozilla/firefox"]
+    }
+}
+
+/// Where the known browsers keep their profile caches below `home`
+pub fn cache_roots(home: &Path) -> Vec<PathBuf> {
+    CHROMIUM_BROWSERS
+        .iter()
+        .map(|b| for_platform(b.cache))
+        .chain(firefox_roots().iter().copied())
+        .map(|r| home.join(r))
+        .collect()
+}
+
+/// Pick the macOS or Linux entry of a path pair
+fn for_platform(paths: [&'static str; 2]) -> &'static str {
+    if cfg!(target_os = "macos") {
+        paths[0]
+    } else {
+        paths[1]
+    }
+}
+
+pub struct BrowserCacheScanner;
+
+impl BrowserCacheScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for BrowserCacheScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The name a user gave the profile in directory `dir` ("Profile 1"), from
+/// `Local State`
+fn chromium_profile_name(local_state: &serde_json::Value, dir: &str) -> Option<String> {
+    local_state
+        .get("profile")?
+        .get("info_cache")?
+        .get(dir)?
+        .get("name")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// A finding for a profile's cache at `path`, unless it is excluded or small
+fn profile_cache(
+    path: PathBuf,
+    browser: &str,
+    profile: &str,
+    config: &Config,
+) -> Option<CleanableFile> {
+    if config.is_excluded(&path) {
+        return None;
+    }
+    let stats = calculate_dir_stats(&path);
+    if stats.size < MIN_CACHE_SIZE {
+        return None;
+    }
+
+    Some(CleanableFile {
+        last_accessed: get_last_accessed(&path).unwrap_or_else(Utc::now),
+        is_directory: true,
+        file_count: stats.file_count,
+        recoverable: true,
+        ..CleanableFile::new(
+            path,
+            stats.size,
+            Category::Cache,
+            format!("{} cache, profile {}", browser, profile),
+        )
+    })
+}
+
+/// Findings for the profile caches of every known browser below `home`
+fn scan_browsers(home: &Path, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+    for browser in CHROMIUM_BROWSERS {
+        let root = home.join(for_platform(browser.cache));
+        let local_state =
+            fs::read_to_string(home.join(for_platform(browser.config)).join("Local State"))
+                .ok()
+                .and_then(|s| serde_json::from_str(&s).ok())
+                .unwrap_or_default();
+
+        // Other entries (crash reports, component data) have no `Cache`
+        for profile in subdirs(&root)
+            .into_iter()
+            .filter(|p| p.join("Cache").is_dir())
+        {
+            visitor.progress(&profile);
+            let dir = profile
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let label = match chromium_profile_name(&local_state, &dir) {
+                Some(name) if name != dir => format!("{} ({})", name, dir),
+                _ => dir,
+            };
+            if let Some(file) = profile_cache(profile, browser.name, &label, config) {
+                visitor.finding(file)?;
+            }
+        }
+    }
+
+    // Profile directories are named "<salt>.<name>", e.g. "x1y2z3.default-release"
+    for root in firefox_roots() {
+        for profile in subdirs(&home.join(root)) {
+            let cache = profile.join("cache2");
+            if !cache.is_dir() {
+                continue;
+            }
+            visitor.progress(&cache);
+            let dir = profile
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let name = dir.split_once('.').map_or(dir.as_str(), |(_, name)| name);
+            if let Some(file) = profile_cache(cache, "Firefox", name, config) {
+                visitor.finding(file)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+impl Scanner for BrowserCacheScanner {
+    fn name(&self) -> &'static str {
+        "Browser Cache Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        match &config.paths.home {
+            Some(home) => scan_browsers(home, config, visitor),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_caches_per_browser_profile() {
+        let home = tempfile::tempdir().unwrap();
+        let home = home.path();
+        let write = |path: &str, size: usize| {
+            let path = home.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![0u8; size]).unwrap();
+        };
+        let mb = 1024 * 1024;
+        write(
+            ".cache/google-chrome/Default/Cache/Cache_Data/data_1",
+            2 * mb,
+        );
+        write(
+            ".cache/google-chrome/Profile 1/Cache/Cache_Data/data_1",
+            3 * mb,
+        );
+        // Too small to report
+        write(
+            ".cache/google-chrome/Profile 2/Cache/Cache_Data/data_1",
+            1024,
+        );
+        write(".cache/google-chrome/Crashpad/reports/a.dmp", 2 * mb);
+        fs::create_dir_all(home.join(".config/google-chrome")).unwrap();
+        fs::write(
+            home.join(".config/google-chrome/Local State"),
+            r#"{"profile": {"info_cache": {"Profile 1": {"name": "Work"}}}}"#,
+        )
+        .unwrap();
+        write(
+            ".mozilla/firefox/x1y2z3.default-release/cache2/entries/A1",
+            2 * mb,
+        );
+        write(
+            ".mozilla/firefox/x1y2z3.default-release/places.sqlite",
+            2 * mb,
+        );
+
+        let mut found: Vec<CleanableFile> = Vec::new();
+        scan_browsers(home, &Config::default(), &mut found).unwrap();
+
+        let summary: Vec<(&Path, &str)> = found
+            .iter()
+            .map(|f| (f.path.strip_prefix(home).unwrap(), f.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Path::new(".cache/google-chrome/Default"),
+                    "Chrome cache, profile Default"
+                ),
+                (
+                    Path::new(".cache/google-chrome/Profile 1"),
+                    "Chrome cache, profile Work (Profile 1)"
+                ),
+                (
+                    Path::new(".mozilla/firefox/x1y2z3.default-release/cache2"),
+                    "Firefox cache, profile default-release"
+                ),
+            ]
+        );
+    }
+}
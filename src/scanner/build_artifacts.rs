@@ -1,6 +1,9 @@
 //! Build artifacts scanner with smart "recently used" detection
 
-use super::{calculate_dir_size, get_last_modified, was_modified_within_days, Category, CleanableFile, Scanner};
+use super::{
+    calculate_dir_stats, extra, get_last_modified, open_projects, was_modified_within_days,
+    Category, CleanableFile, RebuildCost, ScanVisitor, Scanner,
+};
 use crate::config::Config;
 use anyhow::Result;
 use chrono::Utc;
@@ -22,16 +25,17 @@ impl Default for BuildArtifactsScanner {
 }
 
 /// Build artifact patterns to scan for
-struct ArtifactPattern {
+#[derive(Clone, Copy)]
+struct ArtifactPattern<'a> {
     /// Directory name to look for
-    dir_name: &'static str,
+    dir_name: &'a str,
     /// Project file that indicates an active project
-    project_file: &'static str,
+    project_file: &'a str,
     /// Description of the artifact
-    description: &'static str,
+    description: &'a str,
 }
 
-const ARTIFACT_PATTERNS: &[ArtifactPattern] = &[
+const ARTIFACT_PATTERNS: &[ArtifactPattern<'static>] = &[
     ArtifactPattern {
         dir_name: "node_modules",
         project_file: "package.json",
@@ -77,6 +81,16 @@ const ARTIFACT_PATTERNS: &[ArtifactPattern] = &[
         project_file: "package.json",
         description: "Build distribution",
     },
+    ArtifactPattern {
+        dir_name: "buck-out",
+        project_file: ".buckconfig",
+        description: "Buck build output",
+    },
+    ArtifactPattern {
+        dir_name: ".terraform",
+        project_file: "",
+        description: "Terraform providers and modules",
+    },
     ArtifactPattern {
         dir_name: "vendor",
         project_file: "composer.json",
@@ -104,8 +118,213 @@ const ARTIFACT_PATTERNS: &[ArtifactPattern] = &[
     },
 ];
 
+/// Patterns from the config followed by the built-ins that aren't disabled.
+/// User patterns come first so they win when a directory matches both.
+fn artifact_patterns(config: &Config) -> Vec<ArtifactPattern<'_>> {
+    let custom = config.artifact_patterns.iter().map(|p| ArtifactPattern {
+        dir_name: &p.dir_name,
+        project_file: &p.project_file,
+        description: &p.description,
+    });
+
+    let built_in = ARTIFACT_PATTERNS
+        .iter()
+        .filter(|p| {
+            !config
+                .disabled_artifact_patterns
+                .iter()
+                .any(|d| d == p.dir_name)
+        })
+        .copied();
+
+    custom.chain(built_in).collect()
+}
+
+/// Extensions of compiler and bundler outputs
+const COMPILED_EXTENSIONS: &[&str] = &[
+    "js", "mjs", "cjs", "map", "class", "jar", "war", "o", "a", "so", "dylib", "dll", "exe",
+    "wasm", "whl", "egg", "pyc", "tgz", "gz", "zip", "node",
+];
+
+/// How many entries to look at before giving up on finding compiled outputs
+const CONTENT_SAMPLE_LIMIT: usize = 1000;
+
+/// Check whether a directory contains anything that looks like build output
+fn has_compiled_outputs(path: &Path) -> bool {
+    WalkDir::new(path)
+        .max_depth(4)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .take(CONTENT_SAMPLE_LIMIT)
+        .filter(|e| e.file_type().is_file())
+        .any(|e| {
+            e.path().extension().is_some_and(|ext| {
+                COMPILED_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str())
+            })
+        })
+}
+
+/// Check whether git tracks any file inside the directory. Generated
+/// directories are ignored, so tracked contents mean it's source.
+fn has_tracked_files(path: &Path) -> bool {
+    let (parent, name) = match (path.parent(), path.file_name()) {
+        (Some(p), Some(n)) => (p, n),
+        _ => return false,
+    };
+
+    std::process::Command::new("git")
+        .arg("-C")
+        .arg(parent)
+        .args(["ls-files", "-z", "--"])
+        .arg(name)
+        .stderr(std::process::Stdio::null())
+        .output()
+        .is_ok_and(|o| o.status.success() && !o.stdout.is_empty())
+}
+
+/// Verify a directory's contents match what its generic name suggests.
+/// Names like `dist`, `build` and `venv` are also used for hand-written
+/// files, so they need evidence beyond the name.
+fn contents_look_generated(dir_name: &str, path: &Path) -> bool {
+    let content_ok = match dir_name {
+        "venv" | ".venv" => path.join("pyvenv.cfg").is_file(),
+        "dist" | "build" | "out" => has_compiled_outputs(path),
+        _ => true,
+    };
+
+    content_ok && !has_tracked_files(path)
+}
+
+/// Check whether any directory near the top of a tree changed recently.
+/// Depth 3 reaches `lib/python3.x/site-packages` in a venv.
+fn has_recent_dir_changes(path: &Path, days: u32) -> bool {
+    WalkDir::new(path)
+        .max_depth(3)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_dir())
+        .any(|e| was_modified_within_days(e.path(), days))
+}
+
+/// Marker in a project root holding its full build time, e.g. "40m" or "2400"
+const BUILD_TIME_MARKER: &str = ".duster-buildtime";
+
+/// Parse a duration like "2400", "90s", "40m" or "1.5h" into seconds
+fn parse_build_time(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (number, unit) = match text.char_indices().last()? {
+        (i, c) if c.is_ascii_alphabetic() => (&text[..i], c),
+        _ => (text, 's'),
+    };
+    let value: f64 = number.trim().parse().ok()?;
+    let scale = match unit {
+        's' => 1.0,
+        'm' => 60.0,
+        'h' => 3600.0,
+        _ => return None,
+    };
+    Some((value * scale).round() as u64)
+}
+
+/// Total time of the last `cargo build --timings` run in this target dir
+fn cargo_timing(target: &Path) -> Option<u64> {
+    let report = target.join("cargo-timings").join("cargo-timing.html");
+    let html = std::fs::read_to_string(report).ok()?;
+    let rest = &html[html.find("Total time:")? + "Total time:".len()..];
+
+    // Skip the markup between the label and the value, e.g. "</td><td>"
+    let mut text = String::new();
+    let mut in_tag = false;
+    for c in rest.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' => in_tag = false,
+            _ if in_tag => {}
+            _ if c.is_ascii_digit() || c == '.' => text.push(c),
+            _ if text.is_empty() && c.is_whitespace() => {}
+            _ => break,
+        }
+    }
+    parse_build_time(&text)
+}
+
+/// Rough rebuild throughput in bytes per second: compiled outputs are slow to
+/// regenerate, installed dependencies mostly wait on downloads
+fn rebuild_rate(dir_name: &str) -> u64 {
+    const MB: u64 = 1024 * 1024;
+    match dir_name {
+        "target" | "build" | "buck-out" => 5 * MB,
+        "node_modules" | "vendor" | "Pods" | "venv" | ".venv" | ".tox" | ".terraform" => 25 * MB,
+        "__pycache__" | ".pytest_cache" => 50 * MB,
+        _ => 10 * MB,
+    }
+}
+
+/// Expected time to regenerate an artifact: a recorded build duration when
+/// the project has one, otherwise a guess from its size
+fn rebuild_cost(project_root: &Path, artifact: &Path, dir_name: &str, size: u64) -> RebuildCost {
+    let recorded = std::fs::read_to_string(project_root.join(BUILD_TIME_MARKER))
+        .ok()
+        .and_then(|text| parse_build_time(&text));
+    let recorded = match recorded {
+        None if dir_name == "target" => cargo_timing(artifact),
+        other => other,
+    };
+
+    match recorded {
+        Some(seconds) => RebuildCost {
+            seconds,
+            measured: true,
+        },
+        None => RebuildCost {
+            seconds: (size / rebuild_rate(dir_name)).max(1),
+            measured: false,
+        },
+    }
+}
+
+/// Check whether poetry or pipenv in `project_root` still points at `venv`.
+/// Only asks the tool whose project file is present.
+fn is_venv_referenced_by_tooling(project_root: &Path, venv: &Path) -> bool {
+    let mut queries: Vec<(&str, &[&str])> = Vec::new();
+    if project_root.join("poetry.lock").exists()
+        || std::fs::read_to_string(project_root.join("pyproject.toml"))
+            .is_ok_and(|s| s.contains("[tool.poetry]"))
+    {
+        queries.push(("poetry", &["env", "list", "--full-path"]));
+    }
+    if project_root.join("Pipfile").exists() {
+        queries.push(("pipenv", &["--venv"]));
+    }
+
+    let venv = venv.canonicalize().unwrap_or_else(|_| venv.to_path_buf());
+
+    queries.into_iter().any(|(program, args)| {
+        let output = match std::process::Command::new(program)
+            .args(args)
+            .current_dir(project_root)
+            .stderr(std::process::Stdio::null())
+            .output()
+        {
+            Ok(o) if o.status.success() => o,
+            _ => return false,
+        };
+
+        // poetry marks the active env with " (Activated)"
+        String::from_utf8_lossy(&output.stdout).lines().any(|line| {
+            let listed = Path::new(line.trim().trim_end_matches(" (Activated)"));
+            listed.canonicalize().is_ok_and(|p| p == venv)
+        })
+    })
+}
+
 /// Check if a project was recently used by examining project files
-fn is_project_recently_used(project_root: &Path, days: u32) -> bool {
+pub fn is_project_recently_used(project_root: &Path, days: u32) -> bool {
+    // An open project is in use even if nothing was edited in weeks
+    if open_projects::is_open(project_root) {
+        return true;
+    }
+
     // Check common project files for recent modifications
     let project_files = [
         "package.json",
@@ -116,12 +335,21 @@ fn is_project_recently_used(project_root: &Path, days: u32) -> bool {
         "Cargo.lock",
         "requirements.txt",
         "pyproject.toml",
+        "poetry.lock",
+        "Pipfile",
+        "Pipfile.lock",
+        "setup.py",
+        "setup.cfg",
+        ".python-version",
         "build.gradle",
         "pom.xml",
         "go.mod",
         "composer.json",
         "Gemfile",
         "Podfile",
+        ".buckconfig",
+        ".terraform.lock.hcl",
+        "Pulumi.yaml",
         ".git/HEAD",
         ".git/index",
     ];
@@ -134,16 +362,18 @@ fn is_project_recently_used(project_root: &Path, days: u32) -> bool {
     }
 
     // Also check if any source files were modified recently
-    let source_extensions = ["rs", "js", "ts", "tsx", "jsx", "py", "go", "java", "rb", "php"];
-    
+    let source_extensions = [
+        "rs", "js", "ts", "tsx", "jsx", "py", "go", "java", "rb", "php", "tf",
+    ];
+
     if let Ok(entries) = std::fs::read_dir(project_root) {
         for entry in entries.flatten() {
             let path = entry.path();
             if let Some(ext) = path.extension() {
-                if source_extensions.contains(&ext.to_string_lossy().as_ref()) {
-                    if was_modified_within_days(&path, days) {
-                        return true;
-                    }
+                if source_extensions.contains(&ext.to_string_lossy().as_ref())
+                    && was_modified_within_days(&path, days)
+                {
+                    return true;
                 }
             }
         }
@@ -157,28 +387,31 @@ impl Scanner for BuildArtifactsScanner {
         "Build Artifacts Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
-
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
         let base_path = config.get_base_path();
+        let patterns = artifact_patterns(config);
 
         // Walk the directory tree looking for build artifacts
         for entry in WalkDir::new(&base_path)
             .follow_links(false)
             .into_iter()
             .filter_entry(|e| {
+                // Trashed projects are counted as Trash only
+                if super::trash::is_trash_dir(e.path()) {
+                    return false;
+                }
                 // Skip hidden directories (except specific ones we care about)
                 let name = e.file_name().to_string_lossy();
                 if name.starts_with('.') {
-                    // Allow specific hidden dirs we want to scan
-                    return matches!(
-                        name.as_ref(),
-                        ".next" | ".nuxt" | ".gradle" | ".tox" | ".venv" | ".pytest_cache"
-                    );
+                    // Allow hidden dirs that are themselves artifacts
+                    return patterns.iter().any(|p| p.dir_name == name);
                 }
                 // Skip node_modules subdirectories (we handle the whole dir)
-                if e.path().components().any(|c| c.as_os_str() == "node_modules") 
-                    && e.file_name() != "node_modules" {
+                if e.path()
+                    .components()
+                    .any(|c| c.as_os_str() == "node_modules")
+                    && e.file_name() != "node_modules"
+                {
                     return false;
                 }
                 true
@@ -186,11 +419,12 @@ impl Scanner for BuildArtifactsScanner {
             .filter_map(|e| e.ok())
         {
             let path = entry.path();
-            
+
             // Only look at directories
             if !entry.file_type().is_dir() {
                 continue;
             }
+            visitor.progress(path);
 
             let dir_name = match path.file_name() {
                 Some(n) => n.to_string_lossy(),
@@ -198,7 +432,7 @@ impl Scanner for BuildArtifactsScanner {
             };
 
             // Check if this matches any artifact pattern
-            for pattern in ARTIFACT_PATTERNS {
+            for pattern in &patterns {
                 if dir_name != pattern.dir_name {
                     continue;
                 }
@@ -222,11 +456,31 @@ impl Scanner for BuildArtifactsScanner {
                 }
 
                 // Check if project was recently used
-                if is_project_recently_used(parent, config.project_recent_days) {
+                if is_project_recently_used(parent, config.project_recent_days_for(parent)) {
                     continue;
                 }
 
-                let size = calculate_dir_size(path);
+                // Without a project file the parent may not look like a project at
+                // all, so also treat recent writes inside the artifact (a venv
+                // installing packages, bytecode being regenerated) as activity
+                if pattern.project_file.is_empty()
+                    && has_recent_dir_changes(path, config.project_recent_days_for(path))
+                {
+                    continue;
+                }
+
+                if matches!(pattern.dir_name, "venv" | ".venv")
+                    && is_venv_referenced_by_tooling(parent, path)
+                {
+                    continue;
+                }
+
+                if !contents_look_generated(pattern.dir_name, path) {
+                    continue;
+                }
+
+                let stats = calculate_dir_stats(path);
+                let size = stats.size;
                 let last_modified = get_last_modified(path).unwrap_or_else(Utc::now);
 
                 // Skip small directories (less than 1MB)
@@ -239,23 +493,28 @@ impl Scanner for BuildArtifactsScanner {
                     .map(|n| n.to_string_lossy().to_string())
                     .unwrap_or_else(|| "Unknown".to_string());
 
-                results.push(CleanableFile {
-                    path: path.to_path_buf(),
-                    size,
-                    category: Category::BuildArtifact,
+                let finding = CleanableFile {
                     last_accessed: last_modified,
-                    reason: format!("{} in project '{}'", pattern.description, project_name),
                     is_directory: true,
-                });
+                    file_count: stats.file_count,
+                    recoverable: true,
+                    rebuild: Some(rebuild_cost(parent, path, pattern.dir_name, size)),
+                    ..CleanableFile::new(
+                        path.to_path_buf(),
+                        size,
+                        Category::BuildArtifact,
+                        format!("{} in project '{}'", pattern.description, project_name),
+                    )
+                };
+                visitor.finding(
+                    finding.with_extra(extra::PROJECT_ROOT, parent.display().to_string()),
+                )?;
 
                 break; // Don't match multiple patterns for the same directory
             }
         }
 
-        // Sort by size descending
-        results.sort_by(|a, b| b.size.cmp(&a.size));
-
-        Ok(results)
+        Ok(())
     }
 }
 
@@ -279,12 +538,10 @@ impl Scanner for GlobalCacheScanner {
         "Global Cache Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
-
-        let home = match dirs::home_dir() {
-            Some(h) => h,
-            None => return Ok(results),
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let home = match &config.paths.home {
+            Some(h) => h.clone(),
+            None => return Ok(()),
         };
 
         // Global caches that can be cleaned
@@ -295,15 +552,17 @@ impl Scanner for GlobalCacheScanner {
             (".npm/_cacache", "npm cache"),
             (".yarn/cache", "Yarn cache"),
             (".pnpm-store", "pnpm store"),
-            (".gradle/caches", "Gradle caches"),
+            (".gradle/caches/modules-2", "Gradle dependency cache"),
             (".m2/repository", "Maven repository"),
             (".cache/pip", "pip cache"),
             (".cache/go-build", "Go build cache"),
+            (".terraform.d/plugin-cache", "Terraform plugin cache"),
+            (".pulumi/plugins", "Pulumi plugins"),
         ];
 
         for (rel_path, description) in &global_caches {
             let path = home.join(rel_path);
-            
+
             if !path.exists() {
                 continue;
             }
@@ -312,7 +571,8 @@ impl Scanner for GlobalCacheScanner {
                 continue;
             }
 
-            let size = calculate_dir_size(&path);
+            let stats = calculate_dir_stats(&path);
+            let size = stats.size;
             let last_modified = get_last_modified(&path).unwrap_or_else(Utc::now);
 
             // Only include if it's significant (>10MB)
@@ -320,18 +580,97 @@ impl Scanner for GlobalCacheScanner {
                 continue;
             }
 
-            results.push(CleanableFile {
-                path,
-                size,
-                category: Category::BuildArtifact,
+            visitor.finding(CleanableFile {
                 last_accessed: last_modified,
-                reason: description.to_string(),
                 is_directory: true,
-            });
+                file_count: stats.file_count,
+                recoverable: true,
+                ..CleanableFile::new(path, size, Category::BuildArtifact, *description)
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_dist_of_release_notes_is_not_generated() {
+        let dir = tempfile::tempdir().unwrap();
+        let dist = dir.path().join("dist");
+        fs::create_dir(&dist).unwrap();
+        fs::write(dist.join("RELEASE_NOTES.md"), "# 1.0").unwrap();
+        assert!(!contents_look_generated("dist", &dist));
+
+        fs::create_dir(dist.join("assets")).unwrap();
+        fs::write(dist.join("assets").join("index-3f2a.js"), "x").unwrap();
+        assert!(contents_look_generated("dist", &dist));
+    }
+
+    #[test]
+    fn test_rebuild_cost_prefers_recorded_times() {
+        assert_eq!(parse_build_time("2400\n"), Some(2400));
+        assert_eq!(parse_build_time("40m"), Some(2400));
+        assert_eq!(parse_build_time("1.5h"), Some(5400));
+        assert_eq!(parse_build_time("soon"), None);
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("target");
+        fs::create_dir_all(target.join("cargo-timings")).unwrap();
+
+        let guess = rebuild_cost(dir.path(), &target, "target", 600 * 1024 * 1024);
+        assert_eq!((guess.seconds, guess.measured), (120, false));
+
+        fs::write(
+            target.join("cargo-timings").join("cargo-timing.html"),
+            "<tr><td>Total time:</td><td>95.3s (1m 35.3s)</td></tr>",
+        )
+        .unwrap();
+        let timed = rebuild_cost(dir.path(), &target, "target", 600 * 1024 * 1024);
+        assert_eq!((timed.seconds, timed.measured), (95, true));
+
+        fs::write(dir.path().join(BUILD_TIME_MARKER), "40m").unwrap();
+        let marked = rebuild_cost(dir.path(), &target, "target", 600 * 1024 * 1024);
+        assert_eq!((marked.seconds, marked.measured), (2400, true));
+    }
+
+    #[test]
+    fn test_venv_requires_pyvenv_cfg() {
+        let dir = tempfile::tempdir().unwrap();
+        let venv = dir.path().join("venv");
+        fs::create_dir(&venv).unwrap();
+        fs::write(venv.join("notes.txt"), "not a venv").unwrap();
+        assert!(!contents_look_generated("venv", &venv));
+
+        fs::write(venv.join("pyvenv.cfg"), "home = /usr/bin").unwrap();
+        assert!(contents_look_generated("venv", &venv));
+    }
+
+    #[test]
+    fn test_tracked_build_dir_is_source() {
+        let dir = tempfile::tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .arg("-C")
+                .arg(dir.path())
+                .args(args)
+                .output()
+                .is_ok_and(|o| o.status.success())
+        };
+        if !git(&["init", "-q"]) {
+            return; // git unavailable
         }
 
-        results.sort_by(|a, b| b.size.cmp(&a.size));
+        let build = dir.path().join("build");
+        fs::create_dir(&build).unwrap();
+        fs::write(build.join("webpack.config.js"), "module.exports = {}").unwrap();
+        assert!(contents_look_generated("build", &build));
 
-        Ok(results)
+        assert!(git(&["add", "build"]));
+        assert!(!contents_look_generated("build", &build));
     }
 }
@@ -1,10 +1,31 @@
 //! System and application cache scanner
 
-use super::{calculate_dir_size, get_last_accessed, Category, CleanableFile, Scanner};
+use super::{
+    browsers, calculate_dir_stats, extra, get_last_accessed, read_root, Category, CleanableFile,
+    ScanVisitor, Scanner,
+};
 use crate::config::Config;
 use anyhow::Result;
 use chrono::Utc;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Bundle identifier of the macOS app owning a `~/Library/Caches` entry,
+/// when the entry is named after one (e.g. `com.spotify.client`)
+fn bundle_id(path: &Path) -> Option<String> {
+    if !path.parent()?.ends_with("Library/Caches") {
+        return None;
+    }
+    let name = path.file_name()?.to_str()?;
+    let parts: Vec<&str> = name.split('.').collect();
+    let valid = parts.len() >= 2
+        && parts[0].chars().all(|c| c.is_ascii_lowercase())
+        && parts.iter().all(|p| {
+            !p.is_empty()
+                && p.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        });
+    valid.then(|| name.to_string())
+}
 
 pub struct CacheScanner;
 
@@ -15,28 +36,20 @@ impl CacheScanner {
 
     /// Get cache directories to scan based on the platform
     fn get_cache_dirs(&self, config: &Config) -> Vec<PathBuf> {
-        let mut dirs = Vec::new();
-
-        if let Some(home) = dirs::home_dir() {
-            // macOS
-            #[cfg(target_os = "macos")]
-            {
-                let library_caches = home.join("Library").join("Caches");
-                if library_caches.exists() {
-                    dirs.push(library_caches);
-                }
-            }
-
-            // Linux / fallback
-            let cache_dir = home.join(".cache");
-            if cache_dir.exists() {
-                dirs.push(cache_dir);
-            }
-        }
+        let mut dirs: Vec<PathBuf> = config
+            .paths
+            .caches
+            .iter()
+            .filter(|d| d.exists())
+            .cloned()
+            .collect();
 
         // Add any custom cache paths from config
         for path in &config.cache_paths {
             let p = PathBuf::from(path);
+            if config.strict_path && config.is_outside_path(&p) {
+                continue;
+            }
             if p.exists() {
                 dirs.push(p);
             }
@@ -57,30 +70,36 @@ impl Scanner for CacheScanner {
         "Cache Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
         let cache_dirs = self.get_cache_dirs(config);
+        // Reported per profile by the browsers scanner
+        let browser_roots = config
+            .paths
+            .home
+            .as_deref()
+            .map(browsers::cache_roots)
+            .unwrap_or_default();
 
         for cache_dir in cache_dirs {
             // Scan top-level directories in cache
-            let entries = match std::fs::read_dir(&cache_dir) {
-                Ok(e) => e,
-                Err(_) => continue,
+            let Some(entries) = read_root(&cache_dir, visitor) else {
+                continue;
             };
 
             for entry in entries.flatten() {
                 let path = entry.path();
 
-                // Skip if excluded
-                if config.is_excluded(&path) {
+                // Skip if excluded, or if it holds a browser's caches
+                if config.is_excluded(&path) || browser_roots.iter().any(|r| r.starts_with(&path)) {
                     continue;
                 }
 
                 // Calculate size
-                let size = if path.is_dir() {
-                    calculate_dir_size(&path)
+                let (size, file_count) = if path.is_dir() {
+                    let stats = calculate_dir_stats(&path);
+                    (stats.size, stats.file_count)
                 } else {
-                    entry.metadata().map(|m| m.len()).unwrap_or(0)
+                    (entry.metadata().map(|m| m.len()).unwrap_or(0), 1)
                 };
 
                 // Skip very small cache entries (less than 1MB)
@@ -95,21 +114,27 @@ impl Scanner for CacheScanner {
                     .map(|n| n.to_string_lossy().to_string())
                     .unwrap_or_else(|| "Unknown".to_string());
 
-                results.push(CleanableFile {
-                    path: path.clone(),
-                    size,
-                    category: Category::Cache,
+                let bundle_id = bundle_id(&path);
+                let finding = CleanableFile {
                     last_accessed,
-                    reason: format!("Cache directory: {}", name),
                     is_directory: path.is_dir(),
-                });
+                    file_count,
+                    recoverable: true,
+                    ..CleanableFile::new(
+                        path.clone(),
+                        size,
+                        Category::Cache,
+                        format!("Cache directory: {}", name),
+                    )
+                };
+                visitor.finding(match bundle_id {
+                    Some(id) => finding.with_extra(extra::APP_BUNDLE_ID, id),
+                    None => finding,
+                })?;
             }
         }
 
-        // Sort by size descending
-        results.sort_by(|a, b| b.size.cmp(&a.size));
-
-        Ok(results)
+        Ok(())
     }
 }
 
@@ -130,14 +155,13 @@ impl KnownCacheScanner {
             (".yarn/cache", "Yarn cache"),
             (".pnpm-store", "pnpm cache"),
             (".cargo/registry/cache", "Cargo registry cache"),
-            (".gradle/caches", "Gradle cache"),
+            (".gradle/caches/modules-2", "Gradle dependency cache"),
             (".m2/repository", "Maven cache"),
             (".nuget/packages", "NuGet cache"),
             (".cache/pip", "pip cache"),
             (".cache/go-build", "Go build cache"),
             // IDEs and editors
             ("Library/Caches/com.apple.dt.Xcode", "Xcode cache"),
-            ("Library/Caches/JetBrains", "JetBrains IDEs cache"),
             ("Library/Caches/com.microsoft.VSCode", "VS Code cache"),
             (".vscode-server", "VS Code Server"),
             // Browsers
@@ -179,12 +203,10 @@ impl Scanner for KnownCacheScanner {
         "Known Cache Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
-
-        let home = match dirs::home_dir() {
-            Some(h) => h,
-            None => return Ok(results),
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let home = match &config.paths.home {
+            Some(h) => h.clone(),
+            None => return Ok(()),
         };
 
         for (rel_path, description) in Self::known_caches() {
@@ -198,24 +220,22 @@ impl Scanner for KnownCacheScanner {
                 continue;
             }
 
-            let size = calculate_dir_size(&path);
+            let stats = calculate_dir_stats(&path);
+            let size = stats.size;
             let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
 
             // Only include if it's at least 10MB
             if size >= 10 * 1024 * 1024 {
-                results.push(CleanableFile {
-                    path,
-                    size,
-                    category: Category::Cache,
+                visitor.finding(CleanableFile {
                     last_accessed,
-                    reason: description.to_string(),
                     is_directory: true,
-                });
+                    file_count: stats.file_count,
+                    recoverable: true,
+                    ..CleanableFile::new(path, size, Category::Cache, description)
+                })?;
             }
         }
 
-        results.sort_by(|a, b| b.size.cmp(&a.size));
-
-        Ok(results)
+        Ok(())
     }
 }
@@ -0,0 +1,200 @@
+//! Self-hosted CI runner scanner (GitHub Actions, GitLab Runner, Jenkins)
+
+use super::{
+    calculate_dir_stats, get_last_modified, open_projects, subdirs, was_modified_within_days,
+    Category, CleanableFile, ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+/// Runner directories hold state the runner itself manages; never flag these
+const GITHUB_INTERNAL_DIRS: &[&str] = &["_temp", "_PipelineMapping", "_diag"];
+
+pub struct CiRunnerScanner;
+
+impl CiRunnerScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CiRunnerScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Which CI runner a path belongs to, if recognizable
+pub fn runner_kind(path: &Path) -> Option<&'static str> {
+    path.components()
+        .find_map(|c| match c.as_os_str().to_str()? {
+            "_work" => Some("GitHub Actions"),
+            "builds" | "cache" if is_gitlab_home(path) => Some("GitLab Runner"),
+            "workspace" => Some("Jenkins"),
+            _ => None,
+        })
+}
+
+fn is_gitlab_home(path: &Path) -> bool {
+    path.ancestors()
+        .any(|a| a.join(".gitlab-runner").is_dir() || a.ends_with("gitlab-runner"))
+}
+
+/// Check whether a running process has its working directory inside `path`.
+/// CI jobs run with their workspace as cwd, so this catches jobs in progress.
+fn has_running_process(path: &Path) -> bool {
+    open_projects::process_cwds()
+        .iter()
+        .any(|cwd| cwd.starts_with(path))
+}
+
+/// Check whether a job may be using this directory: a process is working in
+/// it, a lock file is present, or it was touched within the last day
+pub fn is_in_use(path: &Path) -> bool {
+    if was_modified_within_days(path, 1) || has_running_process(path) {
+        return true;
+    }
+
+    // Jenkins keeps `<job>@tmp` next to an active workspace; lock files cover the rest
+    let tmp_sibling = path
+        .file_name()
+        .map(|n| path.with_file_name(format!("{}@tmp", n.to_string_lossy())));
+    if tmp_sibling.is_some_and(|p| p.exists() && was_modified_within_days(&p, 1)) {
+        return true;
+    }
+
+    std::fs::read_dir(path).is_ok_and(|entries| {
+        entries.flatten().any(|e| {
+            let name = e.file_name();
+            let name = name.to_string_lossy();
+            name.ends_with(".lock") || name.ends_with(".pid")
+        })
+    })
+}
+
+/// Find per-job directories for each runner installed under `home`, paired
+/// with a short description
+fn job_dirs(home: &Path) -> Vec<(PathBuf, String)> {
+    let mut jobs = Vec::new();
+
+    // GitHub Actions: <runner>/_work/<repo>, next to the runner's `.runner` config
+    for runner in subdirs(home) {
+        let work = runner.join("_work");
+        if !runner.join(".runner").exists() || !work.is_dir() {
+            continue;
+        }
+        for dir in subdirs(&work) {
+            let name = dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            if GITHUB_INTERNAL_DIRS.contains(&name.as_str()) {
+                continue;
+            }
+            let what = match name.as_str() {
+                "_actions" => "GitHub Actions action cache".to_string(),
+                "_tool" => "GitHub Actions tool cache".to_string(),
+                _ => format!("GitHub Actions workspace '{}'", name),
+            };
+            jobs.push((dir, what));
+        }
+    }
+
+    // GitLab Runner (shell executor): builds/<token>/<n>/<group>/<project>, cache/<group>/<project>
+    if home.join(".gitlab-runner").is_dir() || home.ends_with("gitlab-runner") {
+        for token in subdirs(&home.join("builds")) {
+            for slot in subdirs(&token) {
+                for group in subdirs(&slot) {
+                    for project in subdirs(&group) {
+                        let what = format!(
+                            "GitLab build dir '{}/{}'",
+                            group.file_name().unwrap_or_default().to_string_lossy(),
+                            project.file_name().unwrap_or_default().to_string_lossy()
+                        );
+                        jobs.push((project, what));
+                    }
+                }
+            }
+        }
+        for group in subdirs(&home.join("cache")) {
+            for project in subdirs(&group) {
+                let what = format!(
+                    "GitLab cache '{}/{}'",
+                    group.file_name().unwrap_or_default().to_string_lossy(),
+                    project.file_name().unwrap_or_default().to_string_lossy()
+                );
+                jobs.push((project, what));
+            }
+        }
+    }
+
+    // Jenkins: $JENKINS_HOME/workspace/<job>, where JENKINS_HOME is the jenkins
+    // user's home or ~/.jenkins for a personal install
+    for jenkins_home in [home.to_path_buf(), home.join(".jenkins")] {
+        if !jenkins_home.join("jobs").is_dir() {
+            continue;
+        }
+        for dir in subdirs(&jenkins_home.join("workspace")) {
+            let name = dir
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            jobs.push((dir, format!("Jenkins workspace '{}'", name)));
+        }
+    }
+
+    jobs
+}
+
+impl Scanner for CiRunnerScanner {
+    fn name(&self) -> &'static str {
+        "CI Runner Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let home = match &config.paths.home {
+            Some(h) => h.clone(),
+            None => return Ok(()),
+        };
+
+        for (path, what) in job_dirs(&home) {
+            if config.is_excluded(&path) {
+                continue;
+            }
+
+            // Keep workspaces of recent or running jobs
+            if was_modified_within_days(&path, config.project_recent_days_for(&path))
+                || is_in_use(&path)
+            {
+                continue;
+            }
+
+            let stats = calculate_dir_stats(&path);
+            if stats.size == 0 {
+                continue;
+            }
+
+            let last_modified = get_last_modified(&path).unwrap_or_else(Utc::now);
+            let days = (Utc::now() - last_modified).num_days();
+
+            visitor.finding(CleanableFile {
+                last_accessed: last_modified,
+                is_directory: true,
+                file_count: stats.file_count,
+                recoverable: true,
+                ..CleanableFile::new(
+                    path,
+                    stats.size,
+                    Category::CiCache,
+                    format!("{} (last job {} days ago)", what, days),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+//! Folders synced by Dropbox, OneDrive, Google Drive and iCloud Drive. A
+//! local delete there propagates to the cloud and every other device, so
+//! nothing inside them is reported or cleaned without `--include-cloud`.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// The root of a synced folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloudRoot {
+    pub provider: &'static str,
+    pub path: PathBuf,
+}
+
+static CLOUD_ROOTS: OnceLock<Vec<CloudRoot>> = OnceLock::new();
+
+/// Synced folders of the current user, detected once per run
+pub fn roots() -> &'static [CloudRoot] {
+    CLOUD_ROOTS.get_or_init(|| match dirs::home_dir() {
+        Some(home) => detect_roots(&home),
+        None => Vec::new(),
+    })
+}
+
+/// The synced folder `path` is in, if any
+pub fn root_for(path: &Path) -> Option<&'static CloudRoot> {
+    roots().iter().find(|r| path.starts_with(&r.path))
+}
+
+fn detect_roots(home: &Path) -> Vec<CloudRoot> {
+    let mut roots = Vec::new();
+    let mut add = |provider: &'static str, path: PathBuf| {
+        if path.is_dir() && !roots.iter().any(|r: &CloudRoot| r.path == path) {
+            roots.push(CloudRoot { provider, path });
+        }
+    };
+
+    // Dropbox records its folders, which may live anywhere
+    let mut dropbox_info = vec![home.join(".dropbox").join("info.json")];
+    for var in ["APPDATA", "LOCALAPPDATA"] {
+        if let Some(dir) = std::env::var_os(var) {
+            dropbox_info.push(PathBuf::from(dir).join("Dropbox").join("info.json"));
+        }
+    }
+    for info in dropbox_info {
+        if let Ok(contents) = fs::read_to_string(info) {
+            parse_dropbox_info(&contents)
+                .into_iter()
+                .for_each(|p| add("Dropbox", p));
+        }
+    }
+    add("Dropbox", home.join("Dropbox"));
+
+    // The OneDrive client on Windows exports its folders
+    for var in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+        if let Some(dir) = std::env::var_os(var) {
+            add("OneDrive", PathBuf::from(dir));
+        }
+    }
+    add("iCloud Drive", home.join("iCloudDrive"));
+    add("Google Drive", home.join("Google Drive"));
+    add(
+        "iCloud Drive",
+        home.join("Library").join("Mobile Documents"),
+    );
+
+    // Folder names carry the account ("OneDrive - Contoso", and on macOS
+    // "Library/CloudStorage/GoogleDrive-me@example.com")
+    for dir in [
+        home.to_path_buf(),
+        home.join("Library").join("CloudStorage"),
+    ] {
+        for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if let Some(provider) = provider_for_folder(&name) {
+                add(provider, entry.path());
+            }
+        }
+    }
+
+    roots
+}
+
+/// Provider of a synced folder named like the clients name them
+fn provider_for_folder(name: &str) -> Option<&'static str> {
+    const PREFIXES: &[(&str, &str)] = &[
+        ("OneDrive", "OneDrive"),
+        ("GoogleDrive", "Google Drive"),
+        ("Dropbox", "Dropbox"),
+        ("iCloud", "iCloud Drive"),
+        ("Box", "Box"),
+    ];
+    PREFIXES.iter().find_map(|(prefix, provider)| {
+        let rest = name.strip_prefix(prefix)?;
+        (rest.is_empty() || rest.starts_with('-') || rest.starts_with(" - ")).then_some(*provider)
+    })
+}
+
+/// Folder paths from Dropbox's `info.json`
+/// (`{"personal": {"path": ...}, "business": {"path": ...}}`)
+fn parse_dropbox_info(contents: &str) -> Vec<PathBuf> {
+    let info: serde_json::Value = match serde_json::from_str(contents) {
+        Ok(v) => v,
+        Err(_) => return Vec::new(),
+    };
+    info.as_object()
+        .into_iter()
+        .flat_map(|accounts| accounts.values())
+        .filter_map(|account| account.get("path")?.as_str())
+        .map(PathBuf::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cloud_root_detection() {
+        let home = tempfile::tempdir().unwrap();
+        let team = home.path().join("work").join("Team Dropbox");
+        fs::create_dir_all(&team).unwrap();
+        fs::create_dir_all(home.path().join(".dropbox")).unwrap();
+        fs::write(
+            home.path().join(".dropbox").join("info.json"),
+            format!(
+                r#"{{"business": {{"path": "{}", "host": 1}}}}"#,
+                team.display()
+            ),
+        )
+        .unwrap();
+        fs::create_dir_all(home.path().join("OneDrive - Contoso")).unwrap();
+        fs::create_dir_all(
+            home.path()
+                .join("Library/CloudStorage/GoogleDrive-me@example.com"),
+        )
+        .unwrap();
+        fs::create_dir_all(home.path().join("Boxes")).unwrap();
+
+        let mut found: Vec<(&str, PathBuf)> = detect_roots(home.path())
+            .into_iter()
+            .map(|r| {
+                (
+                    r.provider,
+                    r.path.strip_prefix(home.path()).unwrap().to_path_buf(),
+                )
+            })
+            .collect();
+        found.sort();
+        assert_eq!(
+            found,
+            vec![
+                ("Dropbox", PathBuf::from("work/Team Dropbox")),
+                (
+                    "Google Drive",
+                    PathBuf::from("Library/CloudStorage/GoogleDrive-me@example.com")
+                ),
+                ("OneDrive", PathBuf::from("OneDrive - Contoso")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Desktop clutter scanner
+
+use super::{
+    get_last_accessed, read_root, was_accessed_within_days, Category, CleanableFile, ScanVisitor,
+    Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::PathBuf;
+use walkdir::WalkDir;
+
+pub struct DesktopScanner;
+
+impl DesktopScanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Get the desktop directory
+    fn get_desktop_dir(&self, config: &Config) -> Option<PathBuf> {
+        config.paths.desktop.clone()
+    }
+}
+
+impl Default for DesktopScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for DesktopScanner {
+    fn name(&self) -> &'static str {
+        "Desktop Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let desktop_dir = match self.get_desktop_dir(config) {
+            Some(d) if read_root(&d, visitor).is_some() => d,
+            _ => return Ok(()),
+        };
+
+        // Walk the desktop directory (shallow - only top level)
+        for entry in WalkDir::new(&desktop_dir)
+            .max_depth(1)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path().to_path_buf();
+
+            // Skip the desktop directory itself
+            if path == desktop_dir {
+                continue;
+            }
+
+            // Skip if excluded
+            if config.is_excluded(&path) {
+                continue;
+            }
+
+            // Skip hidden files
+            if let Some(name) = path.file_name() {
+                if name.to_string_lossy().starts_with('.') {
+                    continue;
+                }
+            }
+
+            // Skip recently accessed files
+            if was_accessed_within_days(&path, config.desktop_age_days_for(&path)) {
+                continue;
+            }
+
+            let metadata = match entry.metadata() {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+
+            let (size, file_count) = if metadata.is_dir() {
+                let stats = super::calculate_dir_stats(&path);
+                (stats.size, stats.file_count)
+            } else {
+                (metadata.len(), 1)
+            };
+
+            let is_dir = metadata.is_dir();
+            let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
+
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            let age_days = (Utc::now() - last_accessed).num_days();
+
+            visitor.finding(CleanableFile {
+                last_accessed,
+                is_directory: is_dir,
+                file_count,
+                ..CleanableFile::new(
+                    path,
+                    size,
+                    Category::Desktop,
+                    format!("Desktop item not accessed in {} days: {}", age_days, name),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
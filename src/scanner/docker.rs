@@ -0,0 +1,395 @@
+//! Docker's reclaimable space: dangling images, stopped containers, unused
+//! volumes and build cache. Docker owns that data (inside a VM on macOS and
+//! Windows), so it is sized through the `docker` CLI and cleaned with
+//! Docker's own commands, never deleted from disk. Those commands are
+//! scoped to what the scan found: containers and volumes are removed by
+//! name, images and build cache only if they are older than the scan.
+
+use super::{extra, Category, CleanableFile, CleanupAction, ScanVisitor, Scanner};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+pub struct DockerScanner;
+
+impl DockerScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for DockerScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One line of `docker system df --format '{{json .}}'`; Docker prints
+/// every field as a string
+#[derive(Debug, Deserialize)]
+struct DiskUsage {
+    #[serde(rename = "Type")]
+    kind: String,
+    #[serde(rename = "TotalCount")]
+    total: String,
+    #[serde(rename = "Active")]
+    active: String,
+    /// e.g. "1.2GB (45%)"
+    #[serde(rename = "Reclaimable")]
+    reclaimable: String,
+}
+
+/// One line of `docker image ls --format '{{json .}}'`
+#[derive(Debug, Deserialize)]
+struct Image {
+    #[serde(rename = "Size")]
+    size: String,
+    /// e.g. "2024-01-01 10:00:00 +0000 UTC"
+    #[serde(rename = "CreatedAt")]
+    created: String,
+}
+
+/// Lists the containers that aren't running, which `docker system df`
+/// counts as reclaimable
+const STOPPED_CONTAINERS: &[&str] = &[
+    "container",
+    "ls",
+    "--all",
+    "--filter",
+    "status=created",
+    "--filter",
+    "status=exited",
+    "--filter",
+    "status=dead",
+    "--format",
+    "{{.ID}}",
+];
+
+/// Lists the volumes no container uses
+const UNUSED_VOLUMES: &[&str] = &[
+    "volume",
+    "ls",
+    "--filter",
+    "dangling=true",
+    "--format",
+    "{{.Name}}",
+];
+
+/// Run `docker` and return stdout, or `None` without a CLI or daemon
+fn docker_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("docker")
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Bytes in a size as Docker prints it ("1.2GB", "512kB", "0B"), which
+/// counts in powers of 1000
+fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size.find(|c: char| c.is_ascii_alphabetic())?;
+    let (number, unit) = size.split_at(split);
+    let multiplier = match unit.to_ascii_uppercase().as_str() {
+        "B" => 1e0,
+        "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "PB" => 1e15,
+        _ => return None,
+    };
+    Some((number.trim().parse::<f64>().ok()? * multiplier) as u64)
+}
+
+/// Parse each JSON line of a `--format '{{json .}}'` listing
+fn parse_lines<T: for<'de> Deserialize<'de>>(output: &str) -> Vec<T> {
+    output
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+/// The filter keeping a prune to what existed at `scanned_at`
+fn until(scanned_at: DateTime<Utc>) -> String {
+    format!("until={}", scanned_at.timestamp())
+}
+
+/// Findings aren't files, so each gets a path naming what it stands for
+fn docker_path(resource: &str) -> PathBuf {
+    PathBuf::from(format!("docker://{}", resource))
+}
+
+fn finding(
+    resource: &str,
+    size: u64,
+    count: u64,
+    reason: String,
+    args: &[&str],
+    recoverable: bool,
+    last_accessed: DateTime<Utc>,
+) -> CleanableFile {
+    CleanableFile {
+        last_accessed,
+        file_count: count,
+        action: CleanupAction::command("docker", args, None),
+        recoverable,
+        ..CleanableFile::new(docker_path(resource), size, Category::Docker, reason)
+    }
+}
+
+/// A finding for the dangling images in `docker image ls` output, dated by
+/// the newest of them
+fn dangling_images(output: &str, scanned_at: DateTime<Utc>) -> Option<CleanableFile> {
+    let images: Vec<Image> = parse_lines(output);
+    let size: u64 = images.iter().filter_map(|i| parse_size(&i.size)).sum();
+    if size == 0 {
+        return None;
+    }
+    let newest = images
+        .iter()
+        .filter_map(|i| {
+            let created = i.created.trim_end_matches(" UTC");
+            DateTime::parse_from_str(created, "%Y-%m-%d %H:%M:%S %z").ok()
+        })
+        .max()
+        .map_or_else(Utc::now, |t| t.with_timezone(&Utc));
+
+    Some(finding(
+        "images",
+        size,
+        images.len() as u64,
+        format!("Docker: {} dangling image(s)", images.len()),
+        &["image", "prune", "--force", "--filter", &until(scanned_at)],
+        true,
+        newest,
+    ))
+}
+
+/// A finding that runs `docker <command>` on the `names` (one per line) of
+/// the `size` bytes of `what`, if there are any
+fn removal(
+    resource: &str,
+    size: u64,
+    what: &str,
+    command: [&str; 2],
+    names: &str,
+    scanned_at: DateTime<Utc>,
+) -> Option<CleanableFile> {
+    let names: Vec<&str> = names.lines().filter(|l| !l.is_empty()).collect();
+    if size == 0 || names.is_empty() {
+        return None;
+    }
+    let args: Vec<&str> = command.into_iter().chain(names.iter().copied()).collect();
+    Some(finding(
+        resource,
+        size,
+        names.len() as u64,
+        format!("Docker: {} {}", names.len(), what),
+        &args,
+        false,
+        scanned_at,
+    ))
+}
+
+/// Findings for the reclaimable containers, volumes and build cache in
+/// `docker system df` output, given the ids of the `stopped` containers and
+/// the names of the `unused` volumes, one per line
+fn prunable(
+    output: &str,
+    stopped: &str,
+    unused: &str,
+    scanned_at: DateTime<Utc>,
+) -> Vec<CleanableFile> {
+    let usage: Vec<DiskUsage> = parse_lines(output);
+    let row = |kind: &str| usage.iter().find(|u| u.kind == kind);
+    let reclaimable = |kind: &str| {
+        row(kind)
+            .and_then(|u| u.reclaimable.split_whitespace().next())
+            .and_then(parse_size)
+            .unwrap_or(0)
+    };
+    let mut findings = Vec::new();
+
+    // Removed by id, so a container stopped since the scan is left alone,
+    // and one started since fails to be removed
+    findings.extend(removal(
+        "containers",
+        reclaimable("Containers"),
+        "stopped container(s)",
+        ["container", "rm"],
+        stopped,
+        scanned_at,
+    ));
+    findings.extend(
+        removal(
+            "local-volumes",
+            reclaimable("Local Volumes"),
+            "unused volume(s)",
+            ["volume", "rm"],
+            unused,
+            scanned_at,
+        )
+        .map(|f| {
+            f.with_extra(
+                extra::RISK,
+                "volumes can hold data that can't be rebuilt, such as a database's",
+            )
+        }),
+    );
+
+    let size = reclaimable("Build Cache");
+    if let Some(row) = row("Build Cache").filter(|_| size > 0) {
+        let count = |s: &str| s.trim().parse::<u64>().unwrap_or(0);
+        let unused = count(&row.total).saturating_sub(count(&row.active));
+        findings.push(finding(
+            "build-cache",
+            size,
+            unused,
+            format!("Docker: {} unused build cache entries", unused),
+            &[
+                "builder",
+                "prune",
+                "--all",
+                "--force",
+                "--filter",
+                &until(scanned_at),
+            ],
+            true,
+            scanned_at,
+        ));
+    }
+    findings
+}
+
+impl Scanner for DockerScanner {
+    fn name(&self) -> &'static str {
+        "Docker Scanner"
+    }
+
+    fn scan_with(&self, _config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        // Without the CLI or a running daemon there is nothing to ask
+        let Some(usage) = docker_output(&["system", "df", "--format", "{{json .}}"]) else {
+            return Ok(());
+        };
+        let scanned_at = Utc::now();
+
+        let dangling = docker_output(&[
+            "image",
+            "ls",
+            "--filter",
+            "dangling=true",
+            "--format",
+            "{{json .}}",
+        ]);
+        if let Some(images) = dangling
+            .as_deref()
+            .and_then(|d| dangling_images(d, scanned_at))
+        {
+            visitor.finding(images)?;
+        }
+        let stopped = docker_output(STOPPED_CONTAINERS).unwrap_or_default();
+        let unused = docker_output(UNUSED_VOLUMES).unwrap_or_default();
+        for file in prunable(&usage, &stopped, &unused, scanned_at) {
+            visitor.finding(file)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docker_disk_usage() {
+        let scanned_at = DateTime::parse_from_rfc3339("2024-03-01T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(parse_size("1.5GB"), Some(1_500_000_000));
+        assert_eq!(parse_size("512kB"), Some(512_000));
+        assert_eq!(parse_size("0B"), Some(0));
+        assert_eq!(parse_size("lots"), None);
+
+        let images = concat!(
+            r#"{"CreatedAt":"2024-01-01 10:00:00 +0000 UTC","Size":"1.2GB"}"#,
+            "\n",
+            r#"{"CreatedAt":"2024-02-01 10:00:00 +0000 UTC","Size":"300MB"}"#,
+            "\n"
+        );
+        let found = dangling_images(images, scanned_at).unwrap();
+        assert_eq!(found.path, PathBuf::from("docker://images"));
+        assert_eq!(found.size, 1_500_000_000);
+        assert_eq!(found.reason, "Docker: 2 dangling image(s)");
+        assert_eq!(
+            found.last_accessed.to_rfc3339(),
+            "2024-02-01T10:00:00+00:00"
+        );
+        assert_eq!(
+            found.action.describe().unwrap(),
+            "runs `docker image prune --force --filter until=1709287200`"
+        );
+        assert!(dangling_images("", scanned_at).is_none());
+
+        let usage = concat!(
+            r#"{"Active":"2","Reclaimable":"3GB (60%)","TotalCount":"6","Type":"Images"}"#,
+            "\n",
+            r#"{"Active":"1","Reclaimable":"20MB (80%)","TotalCount":"4","Type":"Containers"}"#,
+            "\n",
+            r#"{"Active":"1","Reclaimable":"1GB (50%)","TotalCount":"2","Type":"Local Volumes"}"#,
+            "\n",
+            r#"{"Active":"0","Reclaimable":"2.5GB","TotalCount":"40","Type":"Build Cache"}"#,
+            "\n"
+        );
+        let found = prunable(usage, "a1b2c3\nd4e5f6\n", "pgdata\n", scanned_at);
+        let summary: Vec<(&str, u64, &str)> = found
+            .iter()
+            .map(|f| (f.path.to_str().unwrap(), f.size, f.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "docker://containers",
+                    20_000_000,
+                    "Docker: 2 stopped container(s)"
+                ),
+                (
+                    "docker://local-volumes",
+                    1_000_000_000,
+                    "Docker: 1 unused volume(s)"
+                ),
+                (
+                    "docker://build-cache",
+                    2_500_000_000,
+                    "Docker: 40 unused build cache entries"
+                ),
+            ]
+        );
+        assert_eq!(
+            found[0].action.describe().unwrap(),
+            "runs `docker container rm a1b2c3 d4e5f6`"
+        );
+        assert_eq!(
+            found[1].action.describe().unwrap(),
+            "runs `docker volume rm pgdata`"
+        );
+        assert!(found[1].extra.contains_key(extra::RISK));
+        assert_eq!(
+            found[2].action.describe().unwrap(),
+            "runs `docker builder prune --all --force --filter until=1709287200`"
+        );
+        // Nothing listed, nothing to remove
+        assert_eq!(prunable(usage, "", "", scanned_at).len(), 1);
+    }
+}
@@ -0,0 +1,214 @@
+//! Browser download history (Chrome, Chromium and Firefox), used to tell
+//! where a file in Downloads came from and when it was downloaded.
+//!
+//! The history databases are copied before they are read, so a running
+//! browser's lock never gets in the way, and queried with the `sqlite3`
+//! command-line tool. A missing tool or an unreadable database just means
+//! no history.
+
+use super::percent_decode;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Where and when a file was downloaded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Download {
+    pub url: String,
+    pub downloaded: DateTime<Utc>,
+}
+
+impl Download {
+    /// The host the file came from, for reasons ("from example.com")
+    pub fn host(&self) -> Option<&str> {
+        let rest = self.url.split_once("://")?.1;
+        let host = rest.split(['/', '?', '#']).next()?;
+        let host = host.rsplit('@').next()?;
+        (!host.is_empty()).then_some(host)
+    }
+}
+
+/// Chrome stores times as microseconds since 1601-01-01
+const CHROME_EPOCH_OFFSET_MICROS: i64 = 11_644_473_600_000_000;
+
+/// The final URL of each download, after redirects
+const CHROME_QUERY: &str = "SELECT d.target_path, \
+     (SELECT c.url FROM downloads_url_chains c WHERE c.id = d.id \
+      ORDER BY c.chain_index DESC LIMIT 1), d.start_time FROM downloads d";
+
+/// Firefox keeps downloads as annotations on the visited place
+const FIREFOX_QUERY: &str = "SELECT a.content, p.url, a.dateAdded FROM moz_annos a \
+     JOIN moz_anno_attributes n ON n.id = a.anno_attribute_id \
+     JOIN moz_places p ON p.id = a.place_id \
+     WHERE n.name = 'downloads/destinationFileURI'";
+
+/// Chromium-based browsers' profile folders, relative to the home
+fn chrome_roots() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &[
+            "Library/Application Support/Google/Chrome",
+            "Library/Application Support/Chromium",
+        ]
+    } else {
+        &[".config/google-chrome", ".config/chromium"]
+    }
+}
+
+fn firefox_root() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Library/Application Support/Firefox/Profiles"
+    } else {
+        ".mozilla/firefox"
+    }
+}
+
+/// `file` in every profile folder below `root` that has one
+fn profile_files(root: &Path, file: &str) -> Vec<PathBuf> {
+    fs::read_dir(root)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path().join(file))
+        .filter(|p| p.is_file())
+        .collect()
+}
+
+/// Rows of `query` against a copy of `db`, columns in order
+fn query_copy(db: &Path, query: &str) -> Vec<Vec<String>> {
+    // Private to this user and removed when dropped
+    let Ok(work_dir) = tempfile::Builder::new().prefix("duster-history-").tempdir() else {
+        return Vec::new();
+    };
+    let copy = work_dir.path().join("history.sqlite");
+    let mut rows = Vec::new();
+    if fs::copy(db, &copy).is_ok() {
+        // Recent downloads may still sit in the write-ahead log
+        let mut wal = db.as_os_str().to_owned();
+        wal.push("-wal");
+        let _ = fs::copy(&wal, work_dir.path().join("history.sqlite-wal"));
+
+        let output = Command::new("sqlite3")
+            .arg("-readonly")
+            .arg("-ascii")
+            .arg(&copy)
+            .arg(query)
+            .output();
+        match output {
+            Ok(output) if output.status.success() => {
+                rows = parse_ascii(&String::from_utf8_lossy(&output.stdout));
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+/// Split `sqlite3 -ascii` output: unit separators between columns, record
+/// separators between rows
+fn parse_ascii(output: &str) -> Vec<Vec<String>> {
+    output
+        .split('\x1e')
+        .filter(|row| !row.is_empty())
+        .map(|row| row.split('\x1f').map(String::from).collect())
+        .collect()
+}
+
+fn chrome_download(row: &[String]) -> Option<(PathBuf, Download)> {
+    let [path, url, start] = row else {
+        return None;
+    };
+    let micros = start.parse::<i64>().ok()? - CHROME_EPOCH_OFFSET_MICROS;
+    let downloaded = DateTime::from_timestamp_micros(micros)?;
+    (!path.is_empty() && !url.is_empty()).then(|| {
+        let url = url.clone();
+        (PathBuf::from(path), Download { url, downloaded })
+    })
+}
+
+fn firefox_download(row: &[String]) -> Option<(PathBuf, Download)> {
+    let [uri, url, added] = row else {
+        return None;
+    };
+    let path = percent_decode(uri.strip_prefix("file://")?)?;
+    let downloaded = DateTime::from_timestamp_micros(added.parse().ok()?)?;
+    let url = url.clone();
+    Some((PathBuf::from(path), Download { url, downloaded }))
+}
+
+/// Downloads recorded by the browsers of `home`, by the path they were
+/// saved to. The newest download wins when a path was reused.
+pub fn load(home: &Path) -> HashMap<PathBuf, Download> {
+    let mut rows = Vec::new();
+    for root in chrome_roots() {
+        for db in profile_files(&home.join(root), "History") {
+            rows.extend(
+                query_copy(&db, CHROME_QUERY)
+                    .iter()
+                    .filter_map(|r| chrome_download(r)),
+            );
+        }
+    }
+    for db in profile_files(&home.join(firefox_root()), "places.sqlite") {
+        rows.extend(
+            query_copy(&db, FIREFOX_QUERY)
+                .iter()
+                .filter_map(|r| firefox_download(r)),
+        );
+    }
+
+    let mut downloads: HashMap<PathBuf, Download> = HashMap::new();
+    for (path, download) in rows {
+        match downloads.get(&path) {
+            Some(existing) if existing.downloaded >= download.downloaded => {}
+            _ => {
+                downloads.insert(path, download);
+            }
+        }
+    }
+    downloads
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn test_history_rows() {
+        let rows = parse_ascii("a\x1fb\x1fc\x1ed\x1fe\x1ff\x1e");
+        assert_eq!(rows, vec![row(&["a", "b", "c"]), row(&["d", "e", "f"])]);
+
+        // 2024-01-01T00:00:00Z in both browsers' clocks
+        let (path, download) = chrome_download(&row(&[
+            "/home/me/Downloads/setup.exe",
+            "https://user@dl.example.com/files/setup.exe?v=2",
+            "13348540800000000",
+        ]))
+        .unwrap();
+        assert_eq!(path, PathBuf::from("/home/me/Downloads/setup.exe"));
+        assert_eq!(
+            download.downloaded.to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+        assert_eq!(download.host(), Some("dl.example.com"));
+
+        let (path, download) = firefox_download(&row(&[
+            "file:///home/me/Downloads/My%20Report.pdf",
+            "https://example.org/report.pdf",
+            "1704067200000000",
+        ]))
+        .unwrap();
+        assert_eq!(path, PathBuf::from("/home/me/Downloads/My Report.pdf"));
+        assert_eq!(
+            download.downloaded.to_rfc3339(),
+            "2024-01-01T00:00:00+00:00"
+        );
+
+        assert!(chrome_download(&row(&["", "https://x", "0"])).is_none());
+    }
+}
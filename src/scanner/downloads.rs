@@ -1,22 +1,42 @@
 //! Old downloads scanner
 
-use super::{get_last_accessed, was_accessed_within_days, Category, CleanableFile, Scanner};
+use super::{
+    download_history, extra, get_last_accessed, read_root,
+    usage::{self, Usage, UsageProvider},
+    Category, CleanableFile, ScanVisitor, Scanner,
+};
 use crate::config::Config;
 use anyhow::Result;
 use chrono::Utc;
 use std::path::PathBuf;
 use walkdir::WalkDir;
 
-pub struct DownloadsScanner;
+pub struct DownloadsScanner {
+    usage: Box<dyn UsageProvider>,
+}
 
 impl DownloadsScanner {
     pub fn new() -> Self {
-        Self
+        Self {
+            usage: usage::default_provider(),
+        }
     }
 
-    /// Get the downloads directory
-    fn get_downloads_dir(&self) -> Option<PathBuf> {
-        dirs::download_dir()
+    /// Use another source of "last opened" evidence than the platform's
+    pub fn with_usage_provider(mut self, usage: Box<dyn UsageProvider>) -> Self {
+        self.usage = usage;
+        self
+    }
+
+    /// Get the downloads directories: the user's, plus the Windows one when
+    /// running in WSL with `wsl_windows_paths` on
+    fn get_downloads_dirs(&self, config: &Config) -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = config.paths.downloads.iter().cloned().collect();
+        if config.wsl_windows_paths && config.paths.system {
+            dirs.extend(super::wsl::windows_downloads_dir());
+        }
+        dirs.retain(|d| d.exists());
+        dirs
     }
 }
 
@@ -31,82 +51,120 @@ impl Scanner for DownloadsScanner {
         "Downloads Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
-
-        let downloads_dir = match self.get_downloads_dir() {
-            Some(d) if d.exists() => d,
-            _ => return Ok(results),
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let history = match &config.paths.home {
+            Some(home) if config.download_history => download_history::load(home),
+            _ => Default::default(),
         };
 
-        let age_threshold = config.download_age_days;
-
-        // Walk the downloads directory (shallow - only top level)
-        for entry in WalkDir::new(&downloads_dir)
-            .max_depth(1)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
-            let path = entry.path().to_path_buf();
-
-            // Skip the downloads directory itself
-            if path == downloads_dir {
+        for downloads_dir in self.get_downloads_dirs(config) {
+            if read_root(&downloads_dir, visitor).is_none() {
                 continue;
             }
+            // Walk the downloads directory (shallow - only top level)
+            for entry in WalkDir::new(&downloads_dir)
+                .max_depth(1)
+                .follow_links(false)
+                .into_iter()
+                .filter_map(|e| e.ok())
+            {
+                let path = entry.path().to_path_buf();
+
+                // Skip the downloads directory itself
+                if path == downloads_dir {
+                    continue;
+                }
 
-            // Skip if excluded
-            if config.is_excluded(&path) {
-                continue;
-            }
+                // Skip if excluded
+                if config.is_excluded(&path) {
+                    continue;
+                }
+
+                // Skip hidden files, and Windows folder settings
+                if let Some(name) = path.file_name() {
+                    let name = name.to_string_lossy();
+                    if name.starts_with('.') || name.eq_ignore_ascii_case("desktop.ini") {
+                        continue;
+                    }
+                }
 
-            // Skip hidden files
-            if let Some(name) = path.file_name() {
-                if name.to_string_lossy().starts_with('.') {
+                // A file is no older than its download or its last recorded
+                // use, whatever its atime says (browsers may keep the
+                // server's timestamps); with no time at all, assume it's recent
+                let download = history.get(&path);
+                let min_age_days = config.download_age_days_for(&path) as i64;
+                let known = [get_last_accessed(&path), download.map(|d| d.downloaded)]
+                    .into_iter()
+                    .flatten()
+                    .max();
+                // Recorded usage can only make a file more recent, and looking
+                // it up is slow, so files recent by their own times skip it
+                if known.is_some_and(|time| (Utc::now() - time).num_days() < min_age_days) {
+                    continue;
+                }
+                let usage = self.usage.usage(&path);
+                let last_used = match usage {
+                    Usage::LastUsed(time) => Some(time),
+                    _ => None,
+                };
+                let last_accessed = match known.into_iter().chain(last_used).max() {
+                    Some(time) => time,
+                    None => continue,
+                };
+
+                // Skip recently accessed files
+                let age_days = (Utc::now() - last_accessed).num_days();
+                if age_days < min_age_days {
                     continue;
                 }
-            }
 
-            // Skip recently accessed files
-            if was_accessed_within_days(&path, age_threshold) {
-                continue;
-            }
+                let metadata = match entry.metadata() {
+                    Ok(m) => m,
+                    Err(_) => continue,
+                };
+
+                let (size, file_count) = if metadata.is_dir() {
+                    let stats = super::calculate_dir_stats(&path);
+                    (stats.size, stats.file_count)
+                } else {
+                    (metadata.len(), 1)
+                };
+
+                let is_dir = metadata.is_dir();
+
+                let mut name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "Unknown".to_string());
+                if let Some(host) = download.and_then(|d| d.host()) {
+                    name = format!("{} (from {})", name, host);
+                }
 
-            let metadata = match entry.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
-
-            let size = if metadata.is_dir() {
-                super::calculate_dir_size(&path)
-            } else {
-                metadata.len()
-            };
-
-            let is_dir = metadata.is_dir();
-            let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
-
-            let name = path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| "Unknown".to_string());
-
-            // Calculate age in days
-            let age_days = (Utc::now() - last_accessed).num_days();
-
-            results.push(CleanableFile {
-                path,
-                size,
-                category: Category::Downloads,
-                last_accessed,
-                reason: format!("Download not accessed in {} days: {}", age_days, name),
-                is_directory: is_dir,
-            });
+                let never_opened = usage == Usage::NeverOpened;
+                let reason = if never_opened {
+                    format!("Download never opened in {} days: {}", age_days, name)
+                } else {
+                    format!("Download not accessed in {} days: {}", age_days, name)
+                };
+
+                let mut finding = CleanableFile {
+                    last_accessed,
+                    is_directory: is_dir,
+                    file_count,
+                    ..CleanableFile::new(path, size, Category::Downloads, reason)
+                };
+                if let Some(d) = download {
+                    finding = finding
+                        .with_extra(extra::DOWNLOAD_URL, d.url.as_str())
+                        .with_extra(extra::DOWNLOADED_AT, d.downloaded.to_rfc3339());
+                }
+                if never_opened {
+                    finding = finding.with_extra(extra::NEVER_OPENED, true);
+                }
+                visitor.finding(finding)?;
+            }
         }
 
-        // Sort by size descending (prioritize large files)
-        results.sort_by(|a, b| b.size.cmp(&a.size));
-
-        Ok(results)
+        Ok(())
     }
 }
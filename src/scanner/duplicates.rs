@@ -1,16 +1,68 @@
-//! Duplicate files scanner using blake3 hashing
+//! Duplicate files scanner using blake3 hashing. Files above
+//! `max_hash_size_mb` are only sampled, and matches among them are reported
+//! as probable duplicates, which are never deleted.
 
-use super::{get_last_accessed, Category, CleanableFile, Scanner};
+use super::{
+    extra, get_last_accessed, Category, CleanableFile, CleanupAction, ScanVisitor, Scanner,
+};
 use crate::config::Config;
-use anyhow::Result;
+use crate::scan_cache::{self, HashIndex};
+use anyhow::{Context, Result};
 use chrono::Utc;
 use rayon::prelude::*;
-use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, Read};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
+/// Chunks read from a file too large to hash in full, spread from its
+/// start to its end
+const SAMPLE_CHUNKS: u64 = 16;
+
+/// Bytes read per sampled chunk
+const SAMPLE_CHUNK_SIZE: u64 = 64 * 1024;
+
+/// Marks a hash of sampled chunks, both in results and in the hash index
+const SAMPLE_PREFIX: &str = "sample:";
+
+/// `extra::DUPLICATE_CONFIDENCE` of a set whose files were hashed in full
+pub const CONFIDENCE_EXACT: &str = "exact";
+
+/// `extra::DUPLICATE_CONFIDENCE` of a set matched on size and samples only
+const CONFIDENCE_PROBABLE: &str = "probable";
+
+/// Caps how fast hashing reads, across all hashing threads
+struct Throttle {
+    bytes_per_sec: Option<u64>,
+    started: Instant,
+    read: AtomicU64,
+}
+
+impl Throttle {
+    fn new(bytes_per_sec: Option<u64>) -> Self {
+        Self {
+            bytes_per_sec,
+            started: Instant::now(),
+            read: AtomicU64::new(0),
+        }
+    }
+
+    /// Count `bytes` just read, sleeping while reads are ahead of the rate
+    fn consume(&self, bytes: u64) {
+        let Some(rate) = self.bytes_per_sec.filter(|r| *r > 0) else {
+            return;
+        };
+        let read = self.read.fetch_add(bytes, Ordering::Relaxed) + bytes;
+        let due = Duration::from_secs_f64(read as f64 / rate as f64);
+        if let Some(wait) = due.checked_sub(self.started.elapsed()) {
+            std::thread::sleep(wait);
+        }
+    }
+}
+
 pub struct DuplicatesScanner;
 
 impl DuplicatesScanner {
@@ -20,6 +72,16 @@ impl DuplicatesScanner {
 
     /// Directories to skip when scanning for duplicates
     fn should_skip_dir(path: &Path) -> bool {
+        // Trashed items are counted as Trash only
+        if super::trash::is_trash_dir(path) {
+            return true;
+        }
+
+        // A copy inside a bundle is part of it, not a spare
+        if super::is_bundle(path) {
+            return true;
+        }
+
         let name = match path.file_name() {
             Some(n) => n.to_string_lossy(),
             None => return false,
@@ -33,14 +95,13 @@ impl DuplicatesScanner {
                 | ".svn"
                 | ".hg"
                 | "Library"
-                | ".Trash"
                 | ".cache"
                 | "Caches"
         )
     }
 
     /// Compute blake3 hash of a file
-    fn hash_file(path: &Path) -> Option<String> {
+    fn hash_file(path: &Path, throttle: &Throttle) -> Option<String> {
         let file = File::open(path).ok()?;
         let mut reader = BufReader::with_capacity(1024 * 1024, file);
         let mut hasher = blake3::Hasher::new();
@@ -51,6 +112,7 @@ impl DuplicatesScanner {
                 Ok(0) => break,
                 Ok(n) => {
                     hasher.update(&buffer[..n]);
+                    throttle.consume(n as u64);
                 }
                 Err(_) => return None,
             }
@@ -58,6 +120,348 @@ impl DuplicatesScanner {
 
         Some(hasher.finalize().to_hex().to_string())
     }
+
+    /// Hash a file's size and `SAMPLE_CHUNKS` chunks spread over it, for
+    /// files too large to read in full
+    fn sample_file(path: &Path, size: u64, throttle: &Throttle) -> Option<String> {
+        let mut file = File::open(path).ok()?;
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(&size.to_le_bytes());
+
+        let chunk = SAMPLE_CHUNK_SIZE.min(size);
+        let mut buffer = vec![0u8; chunk as usize];
+        for i in 0..SAMPLE_CHUNKS {
+            let offset = (size - chunk) * i / (SAMPLE_CHUNKS - 1);
+            file.seek(SeekFrom::Start(offset)).ok()?;
+            file.read_exact(&mut buffer).ok()?;
+            hasher.update(&buffer);
+            throttle.consume(chunk);
+        }
+
+        Some(format!("{}{}", SAMPLE_PREFIX, hasher.finalize().to_hex()))
+    }
+
+    /// Roots to search together; files on different drives are compared too
+    fn roots(config: &Config) -> Vec<PathBuf> {
+        if config.duplicate_roots.is_empty() {
+            vec![config.get_base_path()]
+        } else {
+            config.duplicate_roots.clone()
+        }
+    }
+
+    /// Hash same-size files and turn each set of identical files into
+    /// findings for all but the copy to keep
+    fn find_duplicates(
+        size_groups: SizeGroupMap,
+        index: &mut HashIndex,
+        config: &Config,
+        throttle: &Throttle,
+    ) -> Vec<CleanableFile> {
+        // Step 2: For files with matching sizes, compute hashes
+        let potential_duplicates: Vec<_> = size_groups
+            .into_iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .collect();
+
+        let rehash = config.rehash;
+        let max_hash_size = config.max_hash_size_bytes();
+
+        // Compute the rest in parallel
+        let hash_results: Vec<(PathBuf, u64, u64, Option<String>)> = potential_duplicates
+            .into_par_iter()
+            .flat_map(|(size, paths)| {
+                let index = &*index;
+                paths
+                    .into_par_iter()
+                    .map(move |(path, mtime)| {
+                        let sampled = max_hash_size.is_some_and(|max| size > max);
+                        // A cached hash only counts if it was taken the same way
+                        let cached = index
+                            .get(&path, size, mtime)
+                            .filter(|h| !rehash && h.starts_with(SAMPLE_PREFIX) == sampled);
+                        let hash = match cached {
+                            Some(hash) => Some(hash.to_string()),
+                            None if sampled => Self::sample_file(&path, size, throttle),
+                            None => Self::hash_file(&path, throttle),
+                        };
+                        (path, size, mtime, hash)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        // Step 3: Group by hash
+        let mut hash_groups: HashMap<String, Vec<(PathBuf, u64)>> = HashMap::new();
+
+        for (path, size, mtime, hash) in hash_results {
+            if let Some(h) = hash {
+                index.insert(path.clone(), size, mtime, h.clone());
+                hash_groups.entry(h).or_default().push((path, size));
+            }
+        }
+
+        // Step 4: Create cleanable files from duplicates (keep the copy in the
+        // most preferred location, then the oldest one)
+        let mut results = Vec::new();
+
+        for (hash, files) in hash_groups {
+            if files.len() < 2 {
+                continue;
+            }
+
+            let paths: Vec<PathBuf> = files.iter().map(|(p, _)| p.clone()).collect();
+            let ranks = config.duplicate_keep_ranks(&paths);
+            let mut files: Vec<_> = files.into_iter().zip(ranks).collect();
+            files.sort_by_cached_key(|((path, _), rank)| {
+                (*rank, get_last_accessed(path).unwrap_or_else(Utc::now))
+            });
+            let files: Vec<(PathBuf, u64)> = files.into_iter().map(|(f, _)| f).collect();
+
+            // Keep the first (oldest) file, mark the rest as duplicates
+            let original_path = files[0].0.clone();
+            let original_name = original_path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Unknown".to_string());
+            let probable = hash.starts_with(SAMPLE_PREFIX);
+
+            for (path, size) in files.into_iter().skip(1) {
+                let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
+
+                // A copy under another name is often a deliberate export or rename
+                let same_name = path
+                    .file_name()
+                    .is_some_and(|n| n.to_string_lossy() == original_name);
+                let (category, mut reason) = if same_name {
+                    (Category::Duplicate, format!("Duplicate of: {}", original_name))
+                } else {
+                    (Category::RenamedDuplicate, format!("Same content as: {}", original_name))
+                };
+                if probable {
+                    reason = format!("Probable {} (size+sample match)", lowercase_first(&reason));
+                }
+
+                let explicit_root = config
+                    .duplicate_roots
+                    .iter()
+                    .find(|root| path.starts_with(root))
+                    .map(|root| root.to_string_lossy().to_string());
+                let finding = CleanableFile {
+                    last_accessed,
+                    ..CleanableFile::new(path, size, category, reason)
+                };
+                let confidence = if probable {
+                    CONFIDENCE_PROBABLE
+                } else {
+                    CONFIDENCE_EXACT
+                };
+                let mut finding = finding
+                    .with_extra(extra::DUPLICATE_SET_ID, hash.clone())
+                    .with_extra(extra::DUPLICATE_OF, original_path.to_string_lossy())
+                    .with_extra(extra::DUPLICATE_CONFIDENCE, confidence);
+                if let Some(root) = explicit_root {
+                    finding = finding.with_extra(extra::DUPLICATE_ROOT, root);
+                }
+
+                // Roots can be anywhere; a copy clean would refuse is still
+                // worth knowing about. Files that only matched on samples may
+                // still differ elsewhere.
+                if probable {
+                    finding.action = CleanupAction::ReportOnly {
+                        hint: "only sampled; compare the files before removing one".to_string(),
+                    };
+                } else if !crate::cleaner::may_delete(&finding) {
+                    finding.action = CleanupAction::ReportOnly {
+                        hint: "outside where duster deletes; remove it by hand".to_string(),
+                    };
+                }
+                results.push(finding);
+            }
+        }
+
+        results
+    }
+}
+
+/// "Duplicate of: x" as "duplicate of: x", to follow "Probable"
+fn lowercase_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Device and inode of a file. Hardlinks share one, and deleting one of them
+/// frees nothing, so they aren't duplicates.
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Files of one size, with their modification times
+type SizeGroupMap = HashMap<u64, Vec<(PathBuf, u64)>>;
+
+/// Buckets a spilled file list is split into
+const SPILL_BUCKETS: usize = 64;
+
+/// Rough memory one listed file takes beyond its path
+const ENTRY_OVERHEAD: u64 = 64;
+
+static SPILL_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Candidate files grouped by size. Past the memory budget the list moves to
+/// temporary bucket files split by size and is grouped one bucket at a time;
+/// files of equal size always land in the same bucket.
+struct SizeGroups {
+    groups: SizeGroupMap,
+    bytes: u64,
+    budget: u64,
+    spill: Option<SpillDir>,
+}
+
+impl SizeGroups {
+    fn new(budget: u64) -> Self {
+        Self {
+            groups: HashMap::new(),
+            bytes: 0,
+            budget,
+            spill: None,
+        }
+    }
+
+    fn push(&mut self, size: u64, path: PathBuf, mtime: u64) -> Result<()> {
+        if let Some(spill) = &mut self.spill {
+            return spill.write(size, &path, mtime);
+        }
+
+        self.bytes += path.as_os_str().len() as u64 + ENTRY_OVERHEAD;
+        self.groups.entry(size).or_default().push((path, mtime));
+
+        if self.bytes > self.budget {
+            let mut spill = SpillDir::create()?;
+            for (size, files) in std::mem::take(&mut self.groups) {
+                for (path, mtime) in files {
+                    spill.write(size, &path, mtime)?;
+                }
+            }
+            self.spill = Some(spill);
+        }
+        Ok(())
+    }
+
+    /// Hand over the groups: all at once, or one spilled bucket at a time
+    fn for_each_partition(self, mut f: impl FnMut(SizeGroupMap) -> Result<()>) -> Result<()> {
+        let mut spill = match self.spill {
+            Some(spill) => spill,
+            None => return f(self.groups),
+        };
+
+        for bucket in 0..SPILL_BUCKETS {
+            f(spill.read_bucket(bucket)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Temporary directory of bucket files, removed when dropped
+struct SpillDir {
+    dir: PathBuf,
+    buckets: Vec<BufWriter<File>>,
+}
+
+impl SpillDir {
+    fn create() -> Result<Self> {
+        let dir = std::env::temp_dir().join(format!(
+            "duster-duplicates-{}-{}",
+            std::process::id(),
+            SPILL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        let buckets = (0..SPILL_BUCKETS)
+            .map(|i| File::create(dir.join(i.to_string())).map(BufWriter::new))
+            .collect::<std::io::Result<_>>()
+            .with_context(|| {
+                format!("Failed to create the duplicate index in {}", dir.display())
+            })?;
+        Ok(Self { dir, buckets })
+    }
+
+    /// Spread sizes evenly, even when many are multiples of a block size
+    fn bucket(size: u64) -> usize {
+        (size.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 58) as usize % SPILL_BUCKETS
+    }
+
+    fn write(&mut self, size: u64, path: &Path, mtime: u64) -> Result<()> {
+        let path = path_to_bytes(path);
+        let writer = &mut self.buckets[Self::bucket(size)];
+        writer
+            .write_all(&size.to_le_bytes())
+            .and_then(|_| writer.write_all(&mtime.to_le_bytes()))
+            .and_then(|_| writer.write_all(&(path.len() as u64).to_le_bytes()))
+            .and_then(|_| writer.write_all(&path))
+            .context("Failed to write the duplicate index")
+    }
+
+    fn read_bucket(&mut self, bucket: usize) -> Result<SizeGroupMap> {
+        self.buckets[bucket]
+            .flush()
+            .context("Failed to write the duplicate index")?;
+        let file = File::open(self.dir.join(bucket.to_string()))
+            .context("Failed to read the duplicate index")?;
+        let mut reader = BufReader::new(file);
+
+        let mut groups = SizeGroupMap::new();
+        let mut word = [0u8; 8];
+        while reader.read_exact(&mut word).is_ok() {
+            let size = u64::from_le_bytes(word);
+            reader.read_exact(&mut word)?;
+            let mtime = u64::from_le_bytes(word);
+            reader.read_exact(&mut word)?;
+            let mut path = vec![0u8; u64::from_le_bytes(word) as usize];
+            reader.read_exact(&mut path)?;
+            groups
+                .entry(size)
+                .or_default()
+                .push((path_from_bytes(path), mtime));
+        }
+        Ok(groups)
+    }
+}
+
+impl Drop for SpillDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+#[cfg(unix)]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    path.as_os_str().as_bytes().to_vec()
+}
+
+#[cfg(unix)]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    PathBuf::from(std::ffi::OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+fn path_to_bytes(path: &Path) -> Vec<u8> {
+    path.to_string_lossy().into_owned().into_bytes()
+}
+
+#[cfg(not(unix))]
+fn path_from_bytes(bytes: Vec<u8>) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
 }
 
 impl Default for DuplicatesScanner {
@@ -71,26 +475,34 @@ impl Scanner for DuplicatesScanner {
         "Duplicates Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let base_path = config.get_base_path();
-
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
         // Minimum size for duplicate detection (skip small files)
         let min_size = 1024 * 1024; // 1MB
 
         // Step 1: Collect files and group by size
-        let mut size_groups: HashMap<u64, Vec<PathBuf>> = HashMap::new();
-
-        for entry in WalkDir::new(&base_path)
-            .follow_links(false)
-            .into_iter()
-            .filter_entry(|e| {
-                if e.file_type().is_dir() {
-                    return !Self::should_skip_dir(e.path());
-                }
-                true
+        let mut size_groups = SizeGroups::new(config.memory_budget_bytes());
+        let mut seen_ids = HashSet::new();
+
+        let roots = Self::roots(config);
+        for entry in roots
+            .iter()
+            .flat_map(|root| {
+                WalkDir::new(root)
+                    .follow_links(false)
+                    .into_iter()
+                    .filter_entry(|e| {
+                        if e.file_type().is_dir() {
+                            return !Self::should_skip_dir(e.path());
+                        }
+                        true
+                    })
             })
             .filter_map(|e| e.ok())
         {
+            if entry.file_type().is_dir() {
+                visitor.progress(entry.path());
+            }
+
             if !entry.file_type().is_file() {
                 continue;
             }
@@ -109,6 +521,11 @@ impl Scanner for DuplicatesScanner {
                 }
             }
 
+            // A copy of a catalog is usually a deliberate backup
+            if super::library_package_hint(path).is_some() {
+                continue;
+            }
+
             let metadata = match entry.metadata() {
                 Ok(m) => m,
                 Err(_) => continue,
@@ -121,80 +538,103 @@ impl Scanner for DuplicatesScanner {
                 continue;
             }
 
-            size_groups
-                .entry(size)
-                .or_default()
-                .push(path.to_path_buf());
-        }
-
-        // Step 2: For files with matching sizes, compute hashes
-        let potential_duplicates: Vec<_> = size_groups
-            .into_iter()
-            .filter(|(_, paths)| paths.len() > 1)
-            .collect();
-
-        // Compute hashes in parallel
-        let hash_results: Vec<(PathBuf, u64, Option<String>)> = potential_duplicates
-            .into_par_iter()
-            .flat_map(|(size, paths)| {
-                paths
-                    .into_par_iter()
-                    .map(move |path| {
-                        let hash = Self::hash_file(&path);
-                        (path, size, hash)
-                    })
-                    .collect::<Vec<_>>()
-            })
-            .collect();
-
-        // Step 3: Group by hash
-        let mut hash_groups: HashMap<String, Vec<(PathBuf, u64)>> = HashMap::new();
-
-        for (path, size, hash) in hash_results {
-            if let Some(h) = hash {
-                hash_groups.entry(h).or_default().push((path, size));
+            // Skip other links to a file already seen (also covers overlapping roots)
+            if let Some(id) = file_id(&metadata) {
+                if !seen_ids.insert(id) {
+                    continue;
+                }
             }
-        }
 
-        // Step 4: Create cleanable files from duplicates (keep the oldest one)
-        let mut results = Vec::new();
+            size_groups.push(size, path.to_path_buf(), scan_cache::mtime_ns(&metadata))?;
+        }
 
-        for (_hash, mut files) in hash_groups {
-            if files.len() < 2 {
-                continue;
+        // Reuse hashes of files unchanged since an earlier scan
+        let mut index = HashIndex::load(config.memory_budget_bytes());
+        let throttle = Throttle::new(config.hash_rate_bytes());
+        let found = size_groups.for_each_partition(|groups| {
+            for file in Self::find_duplicates(groups, &mut index, config, &throttle) {
+                visitor.finding(file)?;
             }
+            Ok(())
+        });
 
-            // Sort by modification time (oldest first)
-            files.sort_by(|a, b| {
-                let time_a = get_last_accessed(&a.0).unwrap_or_else(Utc::now);
-                let time_b = get_last_accessed(&b.0).unwrap_or_else(Utc::now);
-                time_a.cmp(&time_b)
-            });
+        // Missing the index only costs rehashing next time, and hashes
+        // computed before a cancel are still worth keeping
+        let _ = index.save();
 
-            // Keep the first (oldest) file, mark the rest as duplicates
-            let (original_path, _) = &files[0];
-            let original_name = original_path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| "Unknown".to_string());
+        found
+    }
+}
 
-            for (path, size) in files.into_iter().skip(1) {
-                let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                results.push(CleanableFile {
-                    path,
-                    size,
-                    category: Category::Duplicate,
-                    last_accessed,
-                    reason: format!("Duplicate of: {}", original_name),
-                    is_directory: false,
-                });
-            }
+    #[test]
+    fn test_size_groups_spill_to_disk_over_budget() {
+        let mut groups = SizeGroups::new(200);
+        for (size, name) in [(10, "a"), (20, "b"), (10, "c"), (30, "d"), (20, "e")] {
+            groups.push(size, PathBuf::from(name), 1).unwrap();
         }
+        assert!(groups.spill.is_some());
+        let dir = groups.spill.as_ref().unwrap().dir.clone();
+
+        let mut merged = SizeGroupMap::new();
+        groups
+            .for_each_partition(|part| {
+                for (size, files) in part {
+                    assert!(merged.insert(size, files).is_none());
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        let names =
+            |size| -> Vec<PathBuf> { merged[&size].iter().map(|(p, _)| p.clone()).collect() };
+        assert_eq!(names(10), vec![PathBuf::from("a"), PathBuf::from("c")]);
+        assert_eq!(names(20), vec![PathBuf::from("b"), PathBuf::from("e")]);
+        assert_eq!(merged.len(), 3);
+        assert!(!dir.exists());
+    }
+
+    #[test]
+    fn test_files_over_max_hash_size_are_sampled() {
+        let dir = tempfile::tempdir().unwrap();
+        let size = 3 * 1024 * 1024;
+        let original = dir.path().join("a").join("movie.mkv");
+        let copy = dir.path().join("b").join("movie.mkv");
+        fs::create_dir_all(original.parent().unwrap()).unwrap();
+        fs::create_dir_all(copy.parent().unwrap()).unwrap();
+        fs::write(&original, vec![7u8; size]).unwrap();
+        // Differs only between the first two sampled chunks
+        let mut content = vec![7u8; size];
+        content[100_000] = 8;
+        fs::write(&copy, content).unwrap();
+
+        let groups = || {
+            let files = vec![(original.clone(), 1), (copy.clone(), 1)];
+            SizeGroupMap::from([(size as u64, files)])
+        };
+        let find = |config: &Config| {
+            let mut index = HashIndex::default();
+            DuplicatesScanner::find_duplicates(groups(), &mut index, config, &Throttle::new(None))
+        };
 
-        // Sort by size descending
-        results.sort_by(|a, b| b.size.cmp(&a.size));
+        // Hashed in full, the files differ
+        assert!(find(&Config::default()).is_empty());
 
-        Ok(results)
+        let config = Config {
+            max_hash_size_mb: Some(1),
+            ..Config::default()
+        };
+        let found = find(&config);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].reason,
+            "Probable duplicate of: movie.mkv (size+sample match)"
+        );
+        assert!(found[0].extra_matches(extra::DUPLICATE_CONFIDENCE, CONFIDENCE_PROBABLE));
+        // The files differ, so the match must not be deletable
+        assert!(matches!(found[0].action, CleanupAction::ReportOnly { .. }));
     }
 }
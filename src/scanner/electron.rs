@@ -0,0 +1,144 @@
+//! Caches of Electron apps (Slack, Discord, Notion, Teams, VS Code, ...).
+//! Chromium gives each app the same cache directories in its data folder,
+//! so they are found by name across every app rather than app by app.
+
+use super::{
+    calculate_dir_stats, get_last_modified, subdirs, Category, CleanableFile, ScanVisitor, Scanner,
+    MIN_CACHE_SIZE,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::Path;
+
+/// Cache directories Chromium creates in an Electron app's data folder
+const CACHE_DIRS: &[&str] = &["Cache", "Code Cache", "GPUCache"];
+
+/// Directories only Chromium creates; an app folder with one of them is an
+/// Electron app, rather than an app that happens to have a `Cache`
+const CHROMIUM_MARKERS: &[&str] = &["Code Cache", "GPUCache"];
+
+/// Where apps keep their data, relative to the home
+fn app_data_dir() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Library/Application Support"
+    } else if cfg!(windows) {
+        "AppData/Roaming"
+    } else {
+        ".config"
+    }
+}
+
+pub struct ElectronCacheScanner;
+
+impl ElectronCacheScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ElectronCacheScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Findings for the caches of the Electron apps with folders in `data_dir`
+fn scan_apps(data_dir: &Path, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+    for app in subdirs(data_dir) {
+        if !CHROMIUM_MARKERS.iter().any(|m| app.join(m).is_dir()) {
+            continue;
+        }
+        visitor.progress(&app);
+        let name = app
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        for cache in CACHE_DIRS {
+            let path = app.join(cache);
+            if !path.is_dir() || config.is_excluded(&path) {
+                continue;
+            }
+            let stats = calculate_dir_stats(&path);
+            if stats.size < MIN_CACHE_SIZE {
+                continue;
+            }
+
+            visitor.finding(CleanableFile {
+                last_accessed: get_last_modified(&path).unwrap_or_else(Utc::now),
+                is_directory: true,
+                file_count: stats.file_count,
+                recoverable: true,
+                ..CleanableFile::new(
+                    path,
+                    stats.size,
+                    Category::Cache,
+                    format!("{} {} (Electron app)", name, cache),
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+impl Scanner for ElectronCacheScanner {
+    fn name(&self) -> &'static str {
+        "Electron Cache Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        match &config.paths.home {
+            Some(home) => scan_apps(&home.join(app_data_dir()), config, visitor),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_caches_of_electron_apps() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let write = |path: &str, size: usize| {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, vec![0u8; size]).unwrap();
+        };
+        let mb = 1024 * 1024;
+        write("Slack/Cache/Cache_Data/data_1", 2 * mb);
+        write("Slack/Code Cache/js/index", 3 * mb);
+        // Too small to report
+        write("Slack/GPUCache/data_0", 1024);
+        write("Slack/Local Storage/leveldb/000003.log", 2 * mb);
+        write("discord/GPUCache/data_1", 2 * mb);
+        // Not an Electron app
+        write("Photos/Cache/thumbnails.db", 2 * mb);
+
+        let mut found: Vec<CleanableFile> = Vec::new();
+        scan_apps(root, &Config::default(), &mut found).unwrap();
+
+        let summary: Vec<(&Path, &str)> = found
+            .iter()
+            .map(|f| (f.path.strip_prefix(root).unwrap(), f.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Path::new("Slack/Cache"), "Slack Cache (Electron app)"),
+                (
+                    Path::new("Slack/Code Cache"),
+                    "Slack Code Cache (Electron app)"
+                ),
+                (
+                    Path::new("discord/GPUCache"),
+                    "discord GPUCache (Electron app)"
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Index-backed file discovery for `--use-index`: Spotlight on macOS, the
+//! plocate/locate database on Linux and the NTFS master file table on
+//! Windows. Asking an index which files exist is far faster than walking a
+//! large tree on a slow disk.
+//!
+//! Indexes can lag behind the disk (locate's database is usually rebuilt
+//! daily), so matches that no longer exist are dropped, and files created
+//! since the last update are only found by a walk. Scanners fall back to
+//! walking whenever no index is usable.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Enumerates the MFT of each root's volume through the USN API (needs an
+/// elevated shell) and prints the files below the roots. `$roots` is set
+/// before this runs.
+#[cfg_attr(not(windows), allow(dead_code))]
+const LIST_MFT_FILES: &str = r#"
+$ErrorActionPreference = 'Stop'
+[Console]::OutputEncoding = [Text.Encoding]::UTF8
+Add-Type -TypeDefinition @'
+using System;
+using System.Collections.Generic;
+using System.Runtime.InteropServices;
+using Microsoft.Win32.SafeHandles;
+
+public static class Mft {
+    [DllImport("kernel32.dll", SetLastError = true, CharSet = CharSet.Unicode)]
+    static extern SafeFileHandle CreateFile(string name, uint access, uint share,
+        IntPtr security, uint creation, uint flags, IntPtr template);
+
+    [DllImport("kernel32.dll", SetLastError = true)]
+    static extern bool DeviceIoControl(SafeFileHandle device, uint code, byte[] input,
+        int inputSize, byte[] output, int outputSize, out int returned, IntPtr overlapped);
+
+    const uint FSCTL_ENUM_USN_DATA = 0x000900b3;
+    const uint DIRECTORY = 0x10;
+
+    public static List<string> Files(string volume) {
+        var handle = CreateFile(@"\\.\" + volume, 0x80000000, 3, IntPtr.Zero, 3, 0, IntPtr.Zero);
+        if (handle.IsInvalid) throw new System.ComponentModel.Win32Exception();
+
+        var parents = new Dictionary<ulong, ulong>();
+        var names = new Dictionary<ulong, string>();
+        var files = new List<ulong>();
+
+        // MFT_ENUM_DATA_V0: start reference, lowest and highest USN
+        var input = new byte[24];
+        BitConverter.GetBytes(long.MaxValue).CopyTo(input, 16);
+        var output = new byte[1 << 16];
+        int returned;
+        while (DeviceIoControl(handle, FSCTL_ENUM_USN_DATA, input, input.Length,
+                output, output.Length, out returned, IntPtr.Zero)) {
+            // USN_RECORD_V2 entries follow the next start reference
+            for (int offset = 8; offset < returned; ) {
+                int length = BitConverter.ToInt32(output, offset);
+                ulong reference = BitConverter.ToUInt64(output, offset + 8);
+                parents[reference] = BitConverter.ToUInt64(output, offset + 16);
+                uint attributes = BitConverter.ToUInt32(output, offset + 52);
+                int nameLength = BitConverter.ToUInt16(output, offset + 56);
+                int nameOffset = BitConverter.ToUInt16(output, offset + 58);
+                names[reference] = System.Text.Encoding.Unicode.GetString(
+                    output, offset + nameOffset, nameLength);
+                if ((attributes & DIRECTORY) == 0) files.Add(reference);
+                offset += length;
+            }
+            Array.Copy(output, 0, input, 0, 8);
+        }
+        handle.Dispose();
+
+        var dirs = new Dictionary<ulong, string>();
+        Func<ulong, string> dirPath = null;
+        dirPath = reference => {
+            string path;
+            if (dirs.TryGetValue(reference, out path)) return path;
+            ulong parent;
+            if (!names.ContainsKey(reference) || !parents.TryGetValue(reference, out parent)
+                    || parent == reference) {
+                path = volume;
+            } else {
+                path = dirPath(parent) + "\\" + names[reference];
+            }
+            dirs[reference] = path;
+            return path;
+        };
+
+        var paths = new List<string>(files.Count);
+        foreach (var reference in files) {
+            paths.Add(dirPath(parents[reference]) + "\\" + names[reference]);
+        }
+        return paths;
+    }
+}
+'@
+
+foreach ($volume in @($roots | ForEach-Object { $_.Substring(0, 2) } | Sort-Object -Unique)) {
+    foreach ($path in [Mft]::Files($volume)) {
+        foreach ($root in $roots) {
+            if ($path.StartsWith($root + '\', [StringComparison]::OrdinalIgnoreCase)) {
+                $path
+                break
+            }
+        }
+    }
+}
+"#;
+
+/// Paths of files below any of `roots` according to the platform's index,
+/// or `None` when there is no usable index. Where the index knows sizes,
+/// files under `min_size` bytes are left out, but bundles are listed
+/// whatever their size, as what makes them large may be many small files.
+pub fn indexed_files(roots: &[PathBuf], min_size: u64) -> Option<Vec<PathBuf>> {
+    if roots.is_empty() {
+        return Some(Vec::new());
+    }
+    if cfg!(target_os = "macos") {
+        spotlight_files(roots, min_size)
+    } else if cfg!(target_os = "linux") {
+        locate_files(roots)
+    } else if cfg!(windows) {
+        mft_files(roots)
+    } else {
+        None
+    }
+}
+
+/// Spotlight, when every root's volume is indexed
+fn spotlight_files(roots: &[PathBuf], min_size: u64) -> Option<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for root in roots {
+        let status = Command::new("mdutil").arg("-s").arg(root).output().ok()?;
+        if !String::from_utf8_lossy(&status.stdout).contains("Indexing enabled") {
+            return None;
+        }
+        let output = Command::new("mdfind")
+            .arg("-onlyin")
+            .arg(root)
+            .arg(spotlight_query(min_size))
+            .output()
+            .ok()
+            .filter(|o| o.status.success())?;
+        files.extend(
+            String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .filter(|l| !l.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+    Some(files)
+}
+
+/// Items of at least `min_size` bytes, and bundles
+fn spotlight_query(min_size: u64) -> String {
+    let mut query = format!("kMDItemFSSize >= {}", min_size);
+    for extension in super::BUNDLE_EXTENSIONS {
+        query.push_str(&format!(" || kMDItemFSName == \"*.{}\"c", extension));
+    }
+    query
+}
+
+/// plocate, or a classic locate, whose database has to exist
+fn locate_files(roots: &[PathBuf]) -> Option<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for root in roots {
+        let output = ["plocate", "locate"].iter().find_map(|program| {
+            Command::new(program)
+                .arg("-0")
+                .arg("--")
+                .arg(root.join(""))
+                .output()
+                .ok()
+        })?;
+        // No match exits with 1 too, but quietly
+        if !output.status.success() && !output.stderr.is_empty() {
+            return None;
+        }
+        files.extend(parse_locate(&output.stdout, root));
+    }
+    Some(files)
+}
+
+/// `locate -0` output, which matches anywhere in a path, narrowed to the
+/// paths below `root`
+fn parse_locate(output: &[u8], root: &Path) -> Vec<PathBuf> {
+    output
+        .split(|b| *b == 0)
+        .filter(|p| !p.is_empty())
+        .map(|p| PathBuf::from(String::from_utf8_lossy(p).as_ref()))
+        .filter(|p| p.starts_with(root) && p != root)
+        .collect()
+}
+
+#[cfg(windows)]
+fn mft_files(roots: &[PathBuf]) -> Option<Vec<PathBuf>> {
+    use super::windows::{powershell, ps_quote};
+
+    let roots: Vec<String> = roots
+        .iter()
+        .map(|r| ps_quote(r.to_string_lossy().trim_end_matches('\\')))
+        .collect();
+    let script = format!("$roots = @({})\n{}", roots.join(", "), LIST_MFT_FILES);
+    let output = powershell(&script).ok()?;
+    Some(
+        output
+            .lines()
+            .filter(|l| !l.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+    )
+}
+
+#[cfg(not(windows))]
+fn mft_files(_roots: &[PathBuf]) -> Option<Vec<PathBuf>> {
+    None
+}
+
+/// A file, or a bundle sized as a whole, that a walk reaches
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Reached {
+    pub path: PathBuf,
+    pub is_bundle: bool,
+}
+
+/// Which index matches a walk of `root` would reach: files at most
+/// `max_depth` below it and outside directories `skip_dir` prunes. With
+/// `bundles`, anything inside a bundle stands for the bundle itself, as
+/// walks size bundles rather than entering them.
+pub fn reachable(
+    root: &Path,
+    matches: &[PathBuf],
+    max_depth: usize,
+    bundles: bool,
+    skip_dir: impl Fn(&Path) -> bool,
+) -> Vec<Reached> {
+    let mut seen = std::collections::HashSet::new();
+    let mut reached = Vec::new();
+    'matches: for path in matches {
+        let relative = match path.strip_prefix(root) {
+            Ok(r) if r.components().count() <= max_depth => r,
+            _ => continue,
+        };
+        let mut dir = root.to_path_buf();
+        let mut found = None;
+        for component in relative.components() {
+            dir.push(component);
+            if &dir == path && !dir.is_dir() {
+                break;
+            }
+            if skip_dir(&dir) {
+                continue 'matches;
+            }
+            if bundles && super::is_bundle(&dir) {
+                found = Some(Reached {
+                    path: dir.clone(),
+                    is_bundle: true,
+                });
+                break;
+            }
+        }
+        let found = match found {
+            Some(bundle) => bundle,
+            None if path.symlink_metadata().is_ok_and(|m| m.is_file()) => Reached {
+                path: path.clone(),
+                is_bundle: false,
+            },
+            None => continue,
+        };
+        if seen.insert(found.clone()) {
+            reached.push(found);
+        }
+    }
+    reached
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spotlight_lists_bundles_of_any_size() {
+        let query = spotlight_query(1024);
+        assert!(query.starts_with("kMDItemFSSize >= 1024 || "));
+        assert!(query.contains(" || kMDItemFSName == \"*.photoslibrary\"c"));
+    }
+
+    #[test]
+    fn test_locate_output_is_narrowed_to_root() {
+        let output = b"/home/me/docs/a.pdf\0/home/me/docs\0/home/me/docs-old/b.pdf\0\
+/srv/home/me/docs/c.pdf\0";
+        assert_eq!(
+            parse_locate(output, Path::new("/home/me/docs")),
+            vec![PathBuf::from("/home/me/docs/a.pdf")]
+        );
+    }
+}
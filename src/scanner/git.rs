@@ -0,0 +1,269 @@
+//! Git repository scanner: unreferenced LFS objects and large blobs in history
+
+use super::{get_last_accessed, Category, CleanableFile, CleanupAction, ScanVisitor, Scanner};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+pub struct GitScanner;
+
+impl GitScanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Directories that never contain repositories worth inspecting
+    fn should_skip_dir(path: &Path) -> bool {
+        // Trashed items are counted as Trash only
+        if super::trash::is_trash_dir(path) {
+            return true;
+        }
+
+        let name = match path.file_name() {
+            Some(n) => n.to_string_lossy(),
+            None => return false,
+        };
+
+        matches!(
+            name.as_ref(),
+            "node_modules" | "target" | "Library" | ".cache" | "vendor"
+        )
+    }
+
+    /// Find repositories (directories containing `.git`) under the base path
+    fn find_repos(base_path: &Path, config: &Config) -> Vec<PathBuf> {
+        let mut repos = Vec::new();
+        let mut walker = WalkDir::new(base_path)
+            .follow_links(false)
+            .max_depth(6)
+            .into_iter();
+
+        while let Some(entry) = walker.next() {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+
+            let path = entry.path();
+            if Self::should_skip_dir(path) || config.is_excluded(path) {
+                walker.skip_current_dir();
+                continue;
+            }
+
+            if path.join(".git").is_dir() {
+                repos.push(path.to_path_buf());
+                // Nested repos (submodules) keep their objects under the parent's .git
+                walker.skip_current_dir();
+            }
+        }
+
+        repos
+    }
+}
+
+impl Default for GitScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for GitScanner {
+    fn name(&self) -> &'static str {
+        "Git Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        // Without git there is nothing to inspect
+        if !git_available() {
+            return Ok(());
+        }
+
+        for repo in Self::find_repos(&config.get_base_path(), config) {
+            if let Some(file) = unreferenced_lfs_objects(&repo) {
+                visitor.finding(file)?;
+            }
+            for blob in large_history_blobs(&repo, config.min_large_size_bytes_for(&repo)) {
+                visitor.finding(blob)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn git_available() -> bool {
+    Command::new("git")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+/// Run git in a repository and return stdout, or `None` on failure
+fn git_output(repo: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Sum the LFS objects in `.git/lfs/objects` that no ref points to anymore.
+/// These are what `git lfs prune` removes.
+fn unreferenced_lfs_objects(repo: &Path) -> Option<CleanableFile> {
+    let objects_dir = repo.join(".git").join("lfs").join("objects");
+    if !objects_dir.is_dir() {
+        return None;
+    }
+
+    // `--long` prints full object ids; `--all` covers every ref, not just HEAD
+    let listing = git_output(repo, &["lfs", "ls-files", "--all", "--long"])?;
+    let referenced: HashSet<&str> = listing
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .collect();
+
+    let mut size = 0;
+    let mut count = 0;
+    for entry in WalkDir::new(&objects_dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let oid = entry.file_name().to_string_lossy();
+        if referenced.contains(oid.as_ref()) {
+            continue;
+        }
+        if let Ok(m) = entry.metadata() {
+            size += m.len();
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    let name = repo
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "repository".to_string());
+
+    Some(CleanableFile {
+        last_accessed: get_last_accessed(&objects_dir).unwrap_or_else(Utc::now),
+        is_directory: true,
+        file_count: count,
+        action: CleanupAction::command("git", &["lfs", "prune"], Some(repo)),
+        recoverable: true,
+        ..CleanableFile::new(
+            objects_dir,
+            size,
+            Category::GitObjects,
+            format!("{} unreferenced LFS object(s) in {}", count, name),
+        )
+    })
+}
+
+/// Find blobs in history whose on-disk size is at least `min_size`. Freeing
+/// them requires rewriting history, so they are reported, never deleted.
+fn large_history_blobs(repo: &Path, min_size: u64) -> Vec<CleanableFile> {
+    let mut results = Vec::new();
+
+    let rev_list = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args(["rev-list", "--objects", "--all"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn();
+    let mut rev_list = match rev_list {
+        Ok(child) => child,
+        Err(_) => return results,
+    };
+    let objects = match rev_list.stdout.take() {
+        Some(stdout) => stdout,
+        None => return results,
+    };
+
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .args([
+            "cat-file",
+            "--batch-check=%(objecttype) %(objectname) %(objectsize:disk) %(rest)",
+        ])
+        .stdin(objects)
+        .stderr(Stdio::null())
+        .output();
+    let _ = rev_list.wait();
+
+    let output = match output {
+        Ok(o) if o.status.success() => o,
+        _ => return results,
+    };
+
+    let last_accessed = get_last_accessed(&repo.join(".git")).unwrap_or_else(Utc::now);
+    let mut seen = HashSet::new();
+
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let mut parts = line.splitn(4, ' ');
+        let (kind, oid, size, rest) = match (parts.next(), parts.next(), parts.next(), parts.next())
+        {
+            (Some(k), Some(o), Some(s), rest) => (k, o, s, rest.unwrap_or("")),
+            _ => continue,
+        };
+
+        if kind != "blob" {
+            continue;
+        }
+        let size: u64 = match size.parse() {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        if size < min_size || !seen.insert(oid.to_string()) {
+            continue;
+        }
+
+        let short = &oid[..oid.len().min(10)];
+        let name = if rest.is_empty() { short } else { rest };
+
+        results.push(CleanableFile {
+            last_accessed,
+            action: CleanupAction::ReportOnly {
+                hint: format!(
+                    "rewrite history, e.g. `git filter-repo --path {} --invert-paths`",
+                    name
+                ),
+            },
+            // Objects may live in packs; the id path keeps findings unique
+            ..CleanableFile::new(
+                repo.join(".git").join("objects").join(oid),
+                size,
+                Category::GitObjects,
+                format!("Large blob in history: {} ({})", name, short),
+            )
+        });
+    }
+
+    results
+}
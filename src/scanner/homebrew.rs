@@ -0,0 +1,366 @@
+//! Homebrew leftovers: formula versions in the Cellar that a newer one has
+//! replaced, and cached cask downloads for versions that aren't installed.
+//! Both are cleaned through `brew cleanup --prune=all <name>`, which also
+//! keeps Homebrew's links and records consistent. That command cleans up a
+//! whole formula or cask at once, so each gets one finding sized by all it
+//! removes.
+//!
+//! Pinned formulae are left alone, as `brew cleanup` skips them too.
+
+use super::{
+    calculate_dir_stats, get_last_accessed, get_last_modified, Category, CleanableFile,
+    CleanupAction, ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where Homebrew installs itself: Apple silicon, Intel Macs, and Linux
+const PREFIXES: &[&str] = &["/opt/homebrew", "/usr/local", "/home/linuxbrew/.linuxbrew"];
+
+/// Homebrew's download cache, relative to the home
+fn default_cache_dir() -> &'static str {
+    if cfg!(target_os = "macos") {
+        "Library/Caches/Homebrew"
+    } else {
+        ".cache/Homebrew"
+    }
+}
+
+pub struct HomebrewScanner;
+
+impl HomebrewScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for HomebrewScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Names of the entries in `dir`, sorted
+fn entry_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names
+}
+
+/// The `brew cleanup` that removes what Homebrew keeps of `name`
+fn cleanup(brew: &str, name: &str) -> CleanupAction {
+    CleanupAction::command(brew, &["cleanup", "--prune=all", name], None)
+}
+
+/// Entries of `dir` named starting with one of `prefixes`
+fn entries_starting_with(dir: &Path, prefixes: &[String]) -> Vec<PathBuf> {
+    entry_names(dir)
+        .into_iter()
+        .filter(|n| prefixes.iter().any(|p| n.starts_with(p.as_str())))
+        .map(|n| dir.join(n))
+        .collect()
+}
+
+/// Bytes taken by cached downloads, which are usually links into
+/// `downloads/`
+fn downloads_size(downloads: &[PathBuf]) -> u64 {
+    downloads
+        .iter()
+        .filter_map(|d| fs::metadata(d).ok())
+        .map(|m| m.len())
+        .sum()
+}
+
+/// Findings for the formulae in `prefix`'s Cellar with kegs other than the
+/// one the `opt` link points to. Each covers what `brew cleanup
+/// --prune=all <formula>` removes: those kegs, and every download of the
+/// formula in `cache`, the current version's included.
+fn old_formulae(
+    prefix: &Path,
+    cache: Option<&Path>,
+    brew: &str,
+    config: &Config,
+) -> Vec<CleanableFile> {
+    let cellar = prefix.join("Cellar");
+    let pinned = prefix.join("var/homebrew/pinned");
+
+    let mut findings = Vec::new();
+    for formula in entry_names(&cellar) {
+        if pinned.join(&formula).exists() {
+            continue;
+        }
+        // Without the link there's no telling which version is in use
+        let Ok(current) = fs::read_link(prefix.join("opt").join(&formula)) else {
+            continue;
+        };
+        let Some(current) = current.file_name().map(|n| n.to_string_lossy().to_string()) else {
+            continue;
+        };
+
+        let path = cellar.join(&formula);
+        let (versions, kegs): (Vec<String>, Vec<PathBuf>) = entry_names(&path)
+            .into_iter()
+            .filter(|v| *v != current && path.join(v).is_dir())
+            .map(|v| (v.clone(), path.join(v)))
+            .unzip();
+        if kegs.is_empty() {
+            continue;
+        }
+        let downloads = cache.map_or_else(Vec::new, |cache| {
+            let names = [
+                format!("{}--", formula),
+                format!("{}_bottle_manifest--", formula),
+            ];
+            entries_starting_with(cache, &names)
+        });
+        // The command takes all of them or none
+        if kegs.iter().chain(&downloads).any(|p| config.is_excluded(p)) {
+            continue;
+        }
+
+        let mut size = downloads_size(&downloads);
+        let mut file_count = downloads.len() as u64;
+        for keg in &kegs {
+            let stats = calculate_dir_stats(keg);
+            size += stats.size;
+            file_count += stats.file_count + 1;
+        }
+        let mut reason = format!(
+            "Homebrew {} {}, replaced by {}",
+            formula,
+            versions.join(", "),
+            current
+        );
+        if !downloads.is_empty() {
+            reason.push_str(&format!(", and {} cached download(s)", downloads.len()));
+        }
+
+        findings.push(CleanableFile {
+            last_accessed: kegs
+                .iter()
+                .filter_map(|k| get_last_modified(k))
+                .max()
+                .unwrap_or_else(Utc::now),
+            is_directory: true,
+            file_count,
+            action: cleanup(brew, &formula),
+            // Homebrew only installs a formula's latest version
+            recoverable: false,
+            ..CleanableFile::new(path, size, Category::Cache, reason)
+        });
+    }
+    findings
+}
+
+/// Findings for the casks with downloads in `cache`
+/// (`Cask/<token>--<version>.<ext>`) of a version that isn't installed in
+/// `prefix`'s Caskroom. Each covers
+/// what `brew cleanup --prune=all <token>` removes: every download of the
+/// cask, the installed version's included. It is listed under the first
+/// download of a version that isn't installed.
+fn stale_cask_downloads(
+    prefix: &Path,
+    cache: &Path,
+    brew: &str,
+    config: &Config,
+) -> Vec<CleanableFile> {
+    let cask_dir = cache.join("Cask");
+    let tokens: BTreeSet<String> = entry_names(&cask_dir)
+        .iter()
+        .filter_map(|n| n.split_once("--").map(|(token, _)| token.to_string()))
+        .collect();
+
+    let mut findings = Vec::new();
+    for token in tokens {
+        let downloads = entries_starting_with(&cask_dir, &[format!("{}--", token)]);
+        let installed = entry_names(&prefix.join("Caskroom").join(&token));
+        let is_installed = |download: &&PathBuf| {
+            let name = download.file_name().unwrap_or_default().to_string_lossy();
+            let version = &name[token.len() + 2..];
+            installed.iter().any(|v| {
+                version
+                    .strip_prefix(v.as_str())
+                    .is_some_and(|ext| ext.is_empty() || ext.starts_with('.'))
+            })
+        };
+        let Some(stale) = downloads.iter().find(|d| !is_installed(d)) else {
+            continue;
+        };
+        // The command takes all of them or none
+        if downloads.iter().any(|d| config.is_excluded(d)) {
+            continue;
+        }
+        let status = match installed.last() {
+            Some(version) => format!("{} is installed", version),
+            None => "not installed".to_string(),
+        };
+
+        findings.push(CleanableFile {
+            last_accessed: downloads
+                .iter()
+                .filter_map(|d| get_last_accessed(d).or_else(|| get_last_modified(d)))
+                .max()
+                .unwrap_or_else(Utc::now),
+            file_count: downloads.len() as u64,
+            action: cleanup(brew, &token),
+            recoverable: true,
+            ..CleanableFile::new(
+                stale.clone(),
+                downloads_size(&downloads),
+                Category::Cache,
+                format!(
+                    "Homebrew downloads of cask {}: {} ({})",
+                    token,
+                    downloads.len(),
+                    status
+                ),
+            )
+        });
+    }
+    findings
+}
+
+impl Scanner for HomebrewScanner {
+    fn name(&self) -> &'static str {
+        "Homebrew Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        // The Cellar is outside the home, and cleaning needs brew itself
+        if !config.paths.system {
+            return Ok(());
+        }
+        let prefix = std::env::var_os("HOMEBREW_PREFIX")
+            .map(PathBuf::from)
+            .into_iter()
+            .chain(PREFIXES.iter().map(PathBuf::from))
+            .find(|p| p.join("bin/brew").is_file() && p.join("Cellar").is_dir());
+        let Some(prefix) = prefix else {
+            return Ok(());
+        };
+        let brew = prefix.join("bin/brew").to_string_lossy().to_string();
+
+        let cache = std::env::var_os("HOMEBREW_CACHE")
+            .map(PathBuf::from)
+            .or_else(|| {
+                config
+                    .paths
+                    .home
+                    .as_ref()
+                    .map(|h| h.join(default_cache_dir()))
+            });
+
+        visitor.progress(&prefix.join("Cellar"));
+        for file in old_formulae(&prefix, cache.as_deref(), &brew, config) {
+            visitor.finding(file)?;
+        }
+        if let Some(cache) = cache {
+            visitor.progress(&cache);
+            for file in stale_cask_downloads(&prefix, &cache, &brew, config) {
+                visitor.finding(file)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_old_formulae_and_stale_cask_downloads() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = dir.path().join("homebrew");
+        let cache = dir.path().join("cache");
+        for keg in [
+            "node/19.0.0",
+            "node/20.1.0",
+            "node/21.0.0",
+            "go/1.21",
+            "go/1.22",
+            "jq/1.7",
+        ] {
+            fs::create_dir_all(prefix.join("Cellar").join(keg).join("bin")).unwrap();
+            fs::write(prefix.join("Cellar").join(keg).join("bin/tool"), b"binary").unwrap();
+        }
+        fs::create_dir_all(prefix.join("opt")).unwrap();
+        symlink("../Cellar/node/21.0.0", prefix.join("opt/node")).unwrap();
+        symlink("../Cellar/go/1.22", prefix.join("opt/go")).unwrap();
+        symlink("../Cellar/jq/1.7", prefix.join("opt/jq")).unwrap();
+        // Pinned, so its old version stays
+        fs::create_dir_all(prefix.join("var/homebrew/pinned")).unwrap();
+        symlink(
+            "../../../Cellar/go/1.21",
+            prefix.join("var/homebrew/pinned/go"),
+        )
+        .unwrap();
+
+        fs::create_dir_all(prefix.join("Caskroom/firefox/121.0")).unwrap();
+        fs::create_dir_all(cache.join("downloads")).unwrap();
+        fs::create_dir_all(cache.join("Cask")).unwrap();
+        for (name, download) in [
+            ("firefox--120.0.dmg", "aaa--Firefox 120.0.dmg"),
+            ("firefox--121.0.dmg", "bbb--Firefox 121.0.dmg"),
+            ("zoom--5.17.zip", "ccc--zoom.zip"),
+        ] {
+            fs::write(cache.join("downloads").join(download), b"download").unwrap();
+            let target = Path::new("../downloads").join(download);
+            symlink(target, cache.join("Cask").join(name)).unwrap();
+        }
+        // The current version's bottle goes with the old ones; another
+        // formula's stays
+        for (name, download) in [
+            (
+                "node--21.0.0.arm64_sonoma.bottle.tar.gz",
+                "ddd--node.tar.gz",
+            ),
+            ("node_bottle_manifest--21.0.0", "eee--node_bottle_manifest"),
+            ("nodenv--1.4.1.tar.gz", "fff--nodenv.tar.gz"),
+        ] {
+            fs::write(cache.join("downloads").join(download), b"download").unwrap();
+            symlink(Path::new("downloads").join(download), cache.join(name)).unwrap();
+        }
+
+        let config = Config::default();
+        let formulae = old_formulae(&prefix, Some(&cache), "brew", &config);
+        assert_eq!(formulae.len(), 1);
+        assert_eq!(formulae[0].path, prefix.join("Cellar/node"));
+        assert_eq!(
+            formulae[0].reason,
+            "Homebrew node 19.0.0, 20.1.0, replaced by 21.0.0, and 2 cached download(s)"
+        );
+        // Two kegs and two downloads
+        assert_eq!(formulae[0].size, 2 * 6 + 2 * 8);
+        assert_eq!(
+            formulae[0].action.describe().unwrap(),
+            "runs `brew cleanup --prune=all node`"
+        );
+
+        let downloads = stale_cask_downloads(&prefix, &cache, "brew", &config);
+        let reasons: Vec<&str> = downloads.iter().map(|f| f.reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "Homebrew downloads of cask firefox: 2 (121.0 is installed)",
+                "Homebrew downloads of cask zoom: 1 (not installed)",
+            ]
+        );
+        assert_eq!(downloads[0].path, cache.join("Cask/firefox--120.0.dmg"));
+        // The installed version's download goes too
+        assert_eq!(downloads[0].size, 2 * 8);
+    }
+}
@@ -0,0 +1,174 @@
+//! Empty directory and broken symlink scanner
+
+use super::{
+    get_last_modified, was_modified_within_days, Category, CleanableFile, ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::fs;
+use std::path::Path;
+use walkdir::WalkDir;
+
+pub struct JunkScanner;
+
+impl JunkScanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Directories to skip when scanning for junk
+    fn should_skip_dir(path: &Path) -> bool {
+        // Trashed items are counted as Trash only
+        if super::trash::is_trash_dir(path) {
+            return true;
+        }
+
+        // Empty folders and symlinks in a bundle are part of its layout
+        if super::is_bundle(path) {
+            return true;
+        }
+
+        let name = match path.file_name() {
+            Some(n) => n.to_string_lossy(),
+            None => return false,
+        };
+
+        // Hidden directories are usually tool-managed (.git/refs, .cache, ...)
+        if name.starts_with('.') {
+            return true;
+        }
+
+        matches!(
+            name.as_ref(),
+            "node_modules" | "target" | "Library" | "Applications" | "Volumes" | "System"
+        )
+    }
+
+    /// Count the directories in a tree that contains no files or symlinks.
+    /// Returns `None` as soon as anything other than a directory is found.
+    fn empty_tree_dir_count(path: &Path) -> Option<usize> {
+        let mut count = 0;
+        for entry in WalkDir::new(path).follow_links(false) {
+            let entry = entry.ok()?;
+            if !entry.file_type().is_dir() {
+                return None;
+            }
+            count += 1;
+        }
+        Some(count)
+    }
+
+    /// Check if a symlink points to a target that no longer exists
+    fn is_broken_symlink(path: &Path) -> bool {
+        fs::symlink_metadata(path)
+            .map(|m| m.file_type().is_symlink())
+            .unwrap_or(false)
+            && fs::metadata(path).is_err()
+    }
+}
+
+impl Default for JunkScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for JunkScanner {
+    fn name(&self) -> &'static str {
+        "Junk Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let base_path = config.get_base_path();
+
+        let mut walker = WalkDir::new(&base_path).follow_links(false).into_iter();
+
+        while let Some(entry) = walker.next() {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+
+            let path = entry.path();
+
+            // Never report the scan root itself
+            if entry.depth() == 0 {
+                continue;
+            }
+
+            if entry.file_type().is_dir() {
+                if Self::should_skip_dir(path) {
+                    walker.skip_current_dir();
+                    continue;
+                }
+                visitor.progress(path);
+            }
+
+            // Skip if excluded
+            if config.is_excluded(path) {
+                if entry.file_type().is_dir() {
+                    walker.skip_current_dir();
+                }
+                continue;
+            }
+
+            let last_accessed = get_last_modified(path).unwrap_or_else(Utc::now);
+
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            if entry.path_is_symlink() {
+                if !Self::is_broken_symlink(path) {
+                    continue;
+                }
+
+                let target = fs::read_link(path)
+                    .map(|t| t.display().to_string())
+                    .unwrap_or_else(|_| "unknown target".to_string());
+
+                visitor.finding(CleanableFile {
+                    last_accessed,
+                    ..CleanableFile::new(
+                        path.to_path_buf(),
+                        entry.metadata().map(|m| m.len()).unwrap_or(0),
+                        Category::Junk,
+                        format!("Broken symlink: {} -> {}", name, target),
+                    )
+                })?;
+                continue;
+            }
+
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+
+            // Freshly created empty directories may be about to be used
+            if was_modified_within_days(path, 1) {
+                continue;
+            }
+
+            if let Some(dir_count) = Self::empty_tree_dir_count(path) {
+                let reason = if dir_count > 1 {
+                    format!("Empty directory tree: {} ({} directories)", name, dir_count)
+                } else {
+                    format!("Empty directory: {}", name)
+                };
+
+                visitor.finding(CleanableFile {
+                    last_accessed,
+                    is_directory: true,
+                    file_count: dir_count as u64,
+                    ..CleanableFile::new(path.to_path_buf(), 0, Category::Junk, reason)
+                })?;
+
+                // The whole tree is reported as one item
+                walker.skip_current_dir();
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,14 @@
 //! Large files scanner
 
-use super::{get_last_accessed, Category, CleanableFile, Scanner};
+use super::{
+    extra,
+    file_index::{self, Reached},
+    get_last_accessed, Category, CleanableFile, CleanupAction, ScanVisitor, Scanner,
+};
 use crate::config::Config;
 use anyhow::Result;
 use chrono::Utc;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
 pub struct LargeFilesScanner;
@@ -16,6 +20,11 @@ impl LargeFilesScanner {
 
     /// Directories to skip when scanning for large files
     fn should_skip_dir(path: &Path) -> bool {
+        // Trashed items are counted as Trash only
+        if super::trash::is_trash_dir(path) {
+            return true;
+        }
+
         let name = match path.file_name() {
             Some(n) => n.to_string_lossy(),
             None => return false,
@@ -31,7 +40,6 @@ impl LargeFilesScanner {
                 | ".hg"
                 | "Library"
                 | "Applications"
-                | ".Trash"
                 | "Volumes"
                 | "System"
         )
@@ -67,22 +75,23 @@ impl Default for LargeFilesScanner {
     }
 }
 
-impl Scanner for LargeFilesScanner {
-    fn name(&self) -> &'static str {
-        "Large Files Scanner"
-    }
-
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
+impl LargeFilesScanner {
+    /// Walk `base_path` for large files and bundles
+    fn walk(
+        &self,
+        base_path: &Path,
+        config: &Config,
+        visitor: &mut dyn ScanVisitor,
+    ) -> Vec<CleanableFile> {
         let mut results = Vec::new();
-
-        let base_path = config.get_base_path();
-        let min_size = config.min_large_size_bytes();
-
-        // Walk the directory tree
-        for entry in WalkDir::new(&base_path)
+        for entry in WalkDir::new(base_path)
             .follow_links(false)
             .into_iter()
             .filter_entry(|e| {
+                // Bundles are sized as a whole, never entered
+                if e.path().parent().is_some_and(super::is_bundle) {
+                    return false;
+                }
                 // Skip certain directories
                 if e.file_type().is_dir() {
                     return !Self::should_skip_dir(e.path());
@@ -91,85 +100,239 @@ impl Scanner for LargeFilesScanner {
             })
             .filter_map(|e| e.ok())
         {
-            // Only look at files
-            if !entry.file_type().is_file() {
-                continue;
+            let is_bundle = entry.file_type().is_dir() && super::is_bundle(entry.path());
+            if entry.file_type().is_dir() && !is_bundle {
+                visitor.progress(entry.path());
             }
 
-            let path = entry.path();
-
-            // Skip if excluded
-            if config.is_excluded(path) {
-                continue;
+            // Only look at files and bundles
+            if entry.file_type().is_file() || is_bundle {
+                let reached = Reached {
+                    path: entry.into_path(),
+                    is_bundle,
+                };
+                results.extend(Self::evaluate(&reached, config));
             }
+        }
+        results
+    }
 
-            // Skip hidden files
-            if let Some(name) = path.file_name() {
-                if name.to_string_lossy().starts_with('.') {
-                    continue;
-                }
-            }
+    /// Findings among the files an index matched below `base_path`
+    fn from_index(base_path: &Path, matches: &[PathBuf], config: &Config) -> Vec<CleanableFile> {
+        file_index::reachable(base_path, matches, usize::MAX, true, Self::should_skip_dir)
+            .iter()
+            .filter_map(|r| Self::evaluate(r, config))
+            .collect()
+    }
 
-            let metadata = match entry.metadata() {
-                Ok(m) => m,
-                Err(_) => continue,
-            };
+    /// The smallest size threshold in effect on any volume
+    fn index_min_size(config: &Config) -> u64 {
+        config
+            .volumes
+            .values()
+            .filter_map(|v| v.min_large_size_mb)
+            .fold(config.min_large_size_mb, u64::min)
+            * 1024
+            * 1024
+    }
 
-            let size = metadata.len();
+    /// The finding for a file or bundle, if it is large and not commonly needed
+    fn evaluate(reached: &Reached, config: &Config) -> Option<CleanableFile> {
+        let path = reached.path.as_path();
+        let is_bundle = reached.is_bundle;
 
-            // Skip files smaller than threshold
-            if size < min_size {
-                continue;
-            }
+        // Skip if excluded
+        if config.is_excluded(path) {
+            return None;
+        }
 
-            // Skip commonly needed large files
-            if Self::is_common_needed_large_file(path) {
-                continue;
+        // Skip hidden files
+        if let Some(name) = path.file_name() {
+            if name.to_string_lossy().starts_with('.') {
+                return None;
             }
+        }
 
-            let last_accessed = get_last_accessed(path).unwrap_or_else(Utc::now);
-
-            let name = path
-                .file_name()
-                .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| "Unknown".to_string());
-
-            let ext = path
-                .extension()
-                .map(|e| e.to_string_lossy().to_string())
-                .unwrap_or_default();
-
-            let file_type = match ext.to_lowercase().as_str() {
-                "dmg" => "Disk image",
-                "iso" => "ISO image",
-                "zip" | "tar" | "gz" | "bz2" | "xz" | "7z" | "rar" => "Archive",
-                "pkg" => "Installer package",
-                "app" => "Application bundle",
-                "mov" | "mp4" | "avi" | "mkv" | "wmv" => "Video file",
-                "wav" | "aiff" | "flac" => "Audio file",
-                "psd" | "ai" | "sketch" => "Design file",
-                "vmdk" | "vdi" | "vhd" => "Virtual disk",
-                "log" => "Log file",
-                "csv" | "json" | "xml" if size > 100 * 1024 * 1024 => "Data file",
-                _ => "Large file",
-            };
-
-            results.push(CleanableFile {
-                path: path.to_path_buf(),
-                size,
-                category: Category::LargeFile,
-                last_accessed,
-                reason: format!("{}: {}", file_type, name),
-                is_directory: false,
-            });
+        let (size, file_count) = if is_bundle {
+            let stats = super::calculate_dir_stats(path);
+            (stats.size, stats.file_count)
+        } else {
+            (path.symlink_metadata().ok()?.len(), 1)
+        };
+
+        // Skip files smaller than threshold
+        if size < config.min_large_size_bytes_for(path) {
+            return None;
         }
 
-        // Sort by size descending
-        results.sort_by(|a, b| b.size.cmp(&a.size));
+        // Skip commonly needed large files
+        if Self::is_common_needed_large_file(path) {
+            return None;
+        }
+
+        let last_accessed = get_last_accessed(path).unwrap_or_else(Utc::now);
+
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let ext = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let file_type = match ext.to_lowercase().as_str() {
+            "dmg" => "Disk image",
+            "iso" => "ISO image",
+            "zip" | "tar" | "gz" | "bz2" | "xz" | "7z" | "rar" => "Archive",
+            "pkg" => "Installer package",
+            "app" => "Application bundle",
+            _ if super::library_package_hint(path).is_some() => "Library package",
+            _ if is_bundle => "Bundle",
+            "mov" | "mp4" | "avi" | "mkv" | "wmv" => "Video file",
+            "wav" | "aiff" | "flac" => "Audio file",
+            "psd" | "ai" | "sketch" => "Design file",
+            "vmdk" | "vdi" | "vhd" => "Virtual disk",
+            "log" => "Log file",
+            "csv" | "json" | "xml" if size > 100 * 1024 * 1024 => "Data file",
+            _ => "Large file",
+        };
+
+        let finding = CleanableFile {
+            last_accessed,
+            is_directory: is_bundle,
+            file_count,
+            ..CleanableFile::new(
+                path.to_path_buf(),
+                size,
+                Category::LargeFile,
+                format!("{}: {}", file_type, name),
+            )
+        };
+        // Libraries are shrunk from their app, never deleted by duster
+        Some(match super::library_package_hint(path) {
+            Some(hint) => CleanableFile {
+                action: CleanupAction::ReportOnly {
+                    hint: hint.to_string(),
+                },
+                ..finding
+            }
+            .with_extra(
+                extra::RISK,
+                "deleting it loses everything in the library, including originals and edits",
+            ),
+            None => finding,
+        })
+    }
+
+    /// The largest of `results`
+    fn largest(mut results: Vec<CleanableFile>) -> Vec<CleanableFile> {
+        // Sort by size descending, then by path so the walk and the index agree
+        results.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
 
         // Limit to top 100 largest files
         results.truncate(100);
+        results
+    }
+}
+
+impl Scanner for LargeFilesScanner {
+    fn name(&self) -> &'static str {
+        "Large Files Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let base_path = config.get_base_path();
+
+        // Only the largest files are reported, so findings are emitted once
+        // every candidate is known
+        let indexed = if config.use_index {
+            let roots = std::slice::from_ref(&base_path);
+            file_index::indexed_files(roots, Self::index_min_size(config))
+        } else {
+            None
+        };
+        let results = match indexed {
+            Some(matches) => Self::from_index(&base_path, &matches, config),
+            None => self.walk(&base_path, config, visitor),
+        };
 
-        Ok(results)
+        for file in Self::largest(results) {
+            visitor.finding(file)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_index_matches_walk() {
+        let dir = tempfile::Builder::new()
+            .prefix("duster-test")
+            .tempdir()
+            .unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let big = vec![1u8; 2 * 1024 * 1024];
+        for file in [
+            "videos/holiday.mov",
+            "isos/ubuntu.iso",
+            "project/node_modules/pkg/blob.bin",
+            "Photos.photoslibrary/originals/a.heic",
+            "Photos.photoslibrary/originals/b.heic",
+            "Tool.app/Contents/MacOS/tool",
+            "notes/.hidden.bin",
+            ".local/share/Trash/files/old.iso",
+        ] {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, &big).unwrap();
+        }
+        fs::write(root.join("small.txt"), b"small").unwrap();
+        // Large only in total
+        for i in 0..40 {
+            let path = root.join(format!("Thumbs.photoslibrary/thumbs/{}.jpg", i));
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, vec![1u8; 64 * 1024]).unwrap();
+        }
+
+        let config = Config {
+            base_path: Some(root.clone()),
+            min_large_size_mb: 1,
+            ..Config::default()
+        };
+        let scanner = LargeFilesScanner::new();
+        let walked = LargeFilesScanner::largest(scanner.walk(&root, &config, &mut Vec::new()));
+
+        // Spotlight matches every large file and every bundle, wherever they are
+        let indexed: Vec<PathBuf> = WalkDir::new(&root)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                crate::scanner::is_bundle(e.path())
+                    || e.file_type().is_file() && e.metadata().is_ok_and(|m| m.len() >= 1024 * 1024)
+            })
+            .map(|e| e.into_path())
+            .collect();
+        let from_index =
+            LargeFilesScanner::largest(LargeFilesScanner::from_index(&root, &indexed, &config));
+
+        let paths = |files: &[CleanableFile]| -> Vec<PathBuf> {
+            files.iter().map(|f| f.path.clone()).collect()
+        };
+        assert_eq!(paths(&from_index), paths(&walked));
+        assert_eq!(
+            from_index.iter().map(|f| f.size).collect::<Vec<_>>(),
+            walked.iter().map(|f| f.size).collect::<Vec<_>>()
+        );
+        assert_eq!(walked.len(), 5);
+        assert!(from_index
+            .iter()
+            .any(|f| f.path.ends_with("Thumbs.photoslibrary") && f.file_count == 41));
     }
 }
@@ -0,0 +1,260 @@
+//! Log files that are no longer written: rotations (`app.log.1`,
+//! `syslog.2.gz`, `messages-20240101`) and plain `*.log` files untouched for
+//! `min_age_days`. Only log directories are looked in: `~/Library/Logs`, the
+//! `log`/`logs` directories apps keep among their data, and, when running as
+//! root, the system's (`/var/log`, `/Library/Logs`).
+//!
+//! The systemd journal is left to the `journal` scanner, which vacuums it.
+
+use super::{get_last_modified, read_root, Category, CleanableFile, ScanVisitor, Scanner};
+use crate::config::Config;
+use crate::owner;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Log directories, relative to the home
+fn home_log_dirs() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &["Library/Logs"]
+    } else {
+        &[]
+    }
+}
+
+/// Where apps keep their data, relative to the home; `log` and `logs`
+/// directories in here count as log directories
+fn app_data_dirs() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &["Library/Application Support"]
+    } else {
+        &[".local/state", ".local/share", ".config"]
+    }
+}
+
+/// How far below an app data directory its log directories are looked for
+const APP_LOG_DIR_DEPTH: usize = 3;
+
+/// The system's log directories
+fn system_log_dirs() -> &'static [&'static str] {
+    if cfg!(target_os = "macos") {
+        &["/Library/Logs", "/var/log"]
+    } else {
+        &["/var/log"]
+    }
+}
+
+/// Left to the `journal` scanner
+const JOURNAL_DIR: &str = "/var/log/journal";
+
+/// Rotated logs are done with, but one rotated out moments ago may still be
+/// held open by the program that wrote it
+const ROTATED_MIN_AGE_DAYS: i64 = 1;
+
+/// Compressed or retired rotations
+const ROTATED_EXTENSIONS: &[&str] = &["gz", "bz2", "xz", "zst", "old"];
+
+pub struct LogsScanner;
+
+impl LogsScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for LogsScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a file in a log directory is, going by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogKind {
+    /// A rotation, no longer written to
+    Rotated,
+    /// A `*.log`, which may still be written to
+    Log,
+}
+
+fn log_kind(name: &str) -> Option<LogKind> {
+    let (stem, extension) = name.rsplit_once('.').unwrap_or((name, ""));
+    let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+
+    if ROTATED_EXTENSIONS.contains(&extension) && !stem.is_empty() {
+        return Some(LogKind::Rotated);
+    }
+    // "app.log.1", "syslog.3"
+    if all_digits(extension) && !stem.is_empty() {
+        return Some(LogKind::Rotated);
+    }
+    // "messages-20240101", "app.log-20240101"
+    if name
+        .rsplit_once('-')
+        .is_some_and(|(stem, date)| !stem.is_empty() && date.len() == 8 && all_digits(date))
+    {
+        return Some(LogKind::Rotated);
+    }
+    if extension == "log" && !stem.is_empty() {
+        return Some(LogKind::Log);
+    }
+    None
+}
+
+/// `log` and `logs` directories up to `APP_LOG_DIR_DEPTH` levels below `dir`
+fn app_log_dirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut walker = WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(APP_LOG_DIR_DEPTH)
+        .sort_by_file_name()
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_lowercase();
+        if name == "log" || name == "logs" {
+            dirs.push(entry.into_path());
+            walker.skip_current_dir();
+        }
+    }
+    dirs
+}
+
+/// Findings for the rotated and idle logs below `dir`
+fn scan_log_dir(dir: &Path, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+    visitor.progress(dir);
+    let mut walker = WalkDir::new(dir)
+        .min_depth(1)
+        .sort_by_file_name()
+        .into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        if entry.file_type().is_dir() {
+            if path == Path::new(JOURNAL_DIR) || config.is_excluded(path) {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Some(kind) = log_kind(&entry.file_name().to_string_lossy()) else {
+            continue;
+        };
+        let Some(modified) = get_last_modified(path) else {
+            continue;
+        };
+        let days = (Utc::now() - modified).num_days();
+        let min_age = match kind {
+            LogKind::Rotated => ROTATED_MIN_AGE_DAYS,
+            LogKind::Log => config.min_age_days_for(path) as i64,
+        };
+        if days < min_age || config.is_excluded(path) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let reason = match kind {
+            LogKind::Rotated => format!("Rotated log, {} days old", days),
+            LogKind::Log => format!("Log not written in {} days", days),
+        };
+
+        visitor.finding(CleanableFile {
+            last_accessed: modified,
+            ..CleanableFile::new(entry.into_path(), metadata.len(), Category::Logs, reason)
+        })?;
+    }
+    Ok(())
+}
+
+impl Scanner for LogsScanner {
+    fn name(&self) -> &'static str {
+        "Logs Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        if let Some(home) = &config.paths.home {
+            dirs.extend(home_log_dirs().iter().map(|d| home.join(d)));
+            for data in app_data_dirs() {
+                dirs.extend(app_log_dirs(&home.join(data)));
+            }
+        }
+        // Only root can delete what's in there
+        if config.paths.system && owner::is_root() {
+            dirs.extend(system_log_dirs().iter().map(PathBuf::from));
+        }
+
+        for dir in &dirs {
+            if read_root(dir, visitor).is_none() {
+                continue;
+            }
+            scan_log_dir(dir, config, visitor)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File, FileTimes};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_log_kind() {
+        assert_eq!(log_kind("app.log.1"), Some(LogKind::Rotated));
+        assert_eq!(log_kind("syslog.2.gz"), Some(LogKind::Rotated));
+        assert_eq!(log_kind("messages-20240101"), Some(LogKind::Rotated));
+        assert_eq!(log_kind("install.log"), Some(LogKind::Log));
+        assert_eq!(log_kind("lastlog"), None);
+        assert_eq!(log_kind(".log"), None);
+    }
+
+    #[test]
+    fn test_rotated_and_idle_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        let write = |name: &str, days_old: u64| {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"log line\n").unwrap();
+            let then = SystemTime::now() - Duration::from_secs(days_old * 24 * 60 * 60);
+            let file = File::options().write(true).open(&path).unwrap();
+            file.set_times(FileTimes::new().set_modified(then)).unwrap();
+        };
+        write("app/app.log", 0);
+        write("app/app.log.1", 3);
+        write("app/app.log.2.gz", 10);
+        write("app/old.log", 90);
+        // Just rotated out
+        write("app/other.log.1", 0);
+        write("app/state.db", 90);
+
+        let mut found: Vec<CleanableFile> = Vec::new();
+        scan_log_dir(root, &Config::default(), &mut found).unwrap();
+
+        let summary: Vec<(&Path, &str)> = found
+            .iter()
+            .map(|f| (f.path.strip_prefix(root).unwrap(), f.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Path::new("app/app.log.1"), "Rotated log, 3 days old"),
+                (Path::new("app/app.log.2.gz"), "Rotated log, 10 days old"),
+                (Path::new("app/old.log"), "Log not written in 90 days"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! OS-generated metadata file scanner (.DS_Store, Thumbs.db, desktop.ini)
+
+use super::{get_last_modified, Category, CleanableFile, ScanVisitor, Scanner};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::Path;
+use walkdir::WalkDir;
+
+pub struct MetadataJunkScanner;
+
+impl MetadataJunkScanner {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Directories to skip when sweeping for metadata files
+    fn should_skip_dir(path: &Path) -> bool {
+        // Trashed items are counted as Trash only
+        if super::trash::is_trash_dir(path) {
+            return true;
+        }
+
+        // Bundles are only ever removed whole
+        if super::is_bundle(path) {
+            return true;
+        }
+
+        let name = match path.file_name() {
+            Some(n) => n.to_string_lossy(),
+            None => return false,
+        };
+
+        matches!(
+            name.as_ref(),
+            "node_modules" | "target" | ".git" | ".svn" | ".hg" | "Library"
+        )
+    }
+}
+
+/// Identify the kind of OS metadata file, if this is one
+pub fn metadata_kind(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy();
+
+    if name == ".DS_Store" {
+        return Some(".DS_Store");
+    }
+    if name.starts_with("._") {
+        return Some("AppleDouble (._*)");
+    }
+
+    match name.to_lowercase().as_str() {
+        "thumbs.db" | "ehthumbs.db" => Some("Thumbs.db"),
+        "desktop.ini" => Some("desktop.ini"),
+        _ => None,
+    }
+}
+
+impl Default for MetadataJunkScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for MetadataJunkScanner {
+    fn name(&self) -> &'static str {
+        "Metadata Junk Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let base_path = config.get_base_path();
+
+        for entry in WalkDir::new(&base_path)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| {
+                if e.file_type().is_dir() {
+                    return !Self::should_skip_dir(e.path());
+                }
+                true
+            })
+            .filter_map(|e| e.ok())
+        {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+
+            let path = entry.path();
+
+            let kind = match metadata_kind(path) {
+                Some(k) => k,
+                None => continue,
+            };
+
+            // Skip if excluded
+            if config.is_excluded(path) {
+                continue;
+            }
+
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            let last_accessed = get_last_modified(path).unwrap_or_else(Utc::now);
+
+            let folder = path
+                .parent()
+                .and_then(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Unknown".to_string());
+
+            visitor.finding(CleanableFile {
+                last_accessed,
+                recoverable: true,
+                ..CleanableFile::new(
+                    path.to_path_buf(),
+                    size,
+                    Category::MetadataJunk,
+                    format!("{} in '{}'", kind, folder),
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,19 +1,48 @@
 //! Scanner infrastructure and common types
 
+pub mod android;
+pub mod bazel;
+pub mod browsers;
 pub mod build_artifacts;
 pub mod cache;
+pub mod ci_runner;
+pub mod cloud;
+pub mod desktop;
+pub mod docker;
+pub mod download_history;
 pub mod downloads;
 pub mod duplicates;
+pub mod electron;
+pub mod file_index;
+pub mod git;
+pub mod homebrew;
+pub mod junk;
 pub mod large_files;
+pub mod logs;
+pub mod metadata_junk;
+pub mod nix;
 pub mod old_files;
+pub mod open_projects;
+pub mod package_cache;
+pub mod python_envs;
+pub mod registry;
+pub mod simulators;
+pub mod systemd;
 pub mod temp;
 pub mod trash;
+pub mod usage;
+pub mod versioned_cache;
+pub mod volumes;
+pub mod windows;
+pub mod wsl;
 
 use crate::config::Config;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 
 /// Represents a file that can be cleaned up
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,22 +59,301 @@ pub struct CleanableFile {
     pub reason: String,
     /// Whether this is a directory (for proper deletion)
     pub is_directory: bool,
+    /// Number of filesystem entries (inodes) covered: 1 for a file, every
+    /// file, directory, and symlink inside for a directory
+    #[serde(default)]
+    pub file_count: u64,
+    /// How `clean` frees this space
+    #[serde(default)]
+    pub action: CleanupAction,
+    /// Whether the data comes back on its own (re-downloaded, rebuilt,
+    /// regenerated). Irreplaceable findings need explicit opt-in with `clean --yes`.
+    #[serde(default)]
+    pub recoverable: bool,
+    /// Estimated time to regenerate a build artifact after cleanup
+    #[serde(default)]
+    pub rebuild: Option<RebuildCost>,
+    /// Scanner-specific details, keyed by the names in [`extra`]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra: BTreeMap<String, serde_json::Value>,
+    /// Which file the path named when it was scanned; cleanup refuses a
+    /// path that has since been replaced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<FileIdentity>,
 }
 
-/// Categories of cleanable files
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+/// Device and inode of a file, which survive renames but not replacement
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileIdentity {
+    pub device: u64,
+    pub inode: u64,
+}
+
+impl FileIdentity {
+    /// Identity of whatever is at `path`, without following symlinks.
+    /// `None` if it is missing or the platform has no stable file ids.
+    pub fn of(path: &Path) -> Option<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let metadata = std::fs::symlink_metadata(path).ok()?;
+            Some(Self {
+                device: metadata.dev(),
+                inode: metadata.ino(),
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            None
+        }
+    }
+}
+
+/// Keys of [`CleanableFile::extra`] set by the built-in scanners
+pub mod extra {
+    /// Root of the project a build artifact belongs to
+    pub const PROJECT_ROOT: &str = "project_root";
+    /// Content hash shared by every copy in a set of duplicates
+    pub const DUPLICATE_SET_ID: &str = "duplicate_set_id";
+    /// "exact" when duplicates were hashed in full, "probable" when files
+    /// over `max_hash_size_mb` matched on size and sampled chunks only
+    pub const DUPLICATE_CONFIDENCE: &str = "duplicate_confidence";
+    /// The copy kept of a duplicate's set
+    pub const DUPLICATE_OF: &str = "duplicate_of";
+    /// The `--duplicates-path` root a duplicate was found under
+    pub const DUPLICATE_ROOT: &str = "duplicate_root";
+    /// An organized copy of a download or old file, found by hashing
+    pub const IDENTICAL_COPY: &str = "identical_copy";
+    /// Where a trashed item was deleted from
+    pub const ORIGINAL_TRASH_PATH: &str = "original_trash_path";
+    /// Bundle identifier of the macOS app owning a cache
+    pub const APP_BUNDLE_ID: &str = "app_bundle_id";
+    /// Triage tag set with `duster tag` ("later" or "delete")
+    pub const TAG: &str = "tag";
+    /// How a tool's total compares with its typical size, set by
+    /// `--compare-baseline` when it is unusually large
+    pub const BASELINE: &str = "baseline";
+    /// What is lost if the finding is deleted anyway
+    pub const RISK: &str = "risk";
+    /// Where a file in Downloads was downloaded from, per browser history
+    pub const DOWNLOAD_URL: &str = "download_url";
+    /// When a file in Downloads was downloaded, per browser history
+    pub const DOWNLOADED_AT: &str = "downloaded_at";
+    /// A download the OS has no record of ever being opened
+    pub const NEVER_OPENED: &str = "never_opened";
+    /// Largest files inside a directory finding, as `{path, size}` objects
+    /// with paths relative to it
+    pub const LARGEST_FILES: &str = "largest_files";
+}
+
+/// How long rebuilding a deleted build artifact is expected to take
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RebuildCost {
+    pub seconds: u64,
+    /// Taken from a recorded build duration rather than guessed from size
+    pub measured: bool,
+}
+
+impl RebuildCost {
+    /// Short label shown next to a finding's size, e.g. "rebuild ~12m 0s"
+    pub fn label(&self) -> String {
+        let duration = crate::format::format_duration(self.seconds);
+        if self.measured {
+            format!("rebuild {}", duration)
+        } else {
+            format!("rebuild ~{}", duration)
+        }
+    }
+}
+
+/// How a finding is cleaned up
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CleanupAction {
+    /// Delete the path
+    #[default]
+    Delete,
+    /// Run a tool that frees the space itself (e.g. `git lfs prune`), since
+    /// deleting files behind its back would corrupt its state
+    Command {
+        program: String,
+        args: Vec<String>,
+        cwd: Option<PathBuf>,
+    },
+    /// Informational only; `clean` never touches it
+    ReportOnly { hint: String },
+}
+
+impl CleanupAction {
+    /// Build a command action
+    pub fn command(program: &str, args: &[&str], cwd: Option<&std::path::Path>) -> Self {
+        CleanupAction::Command {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            cwd: cwd.map(|p| p.to_path_buf()),
+        }
+    }
+
+    /// Short description for previews, or `None` for plain deletion
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            CleanupAction::Delete => None,
+            CleanupAction::Command { program, args, .. } => {
+                Some(format!("runs `{} {}`", program, args.join(" ")))
+            }
+            CleanupAction::ReportOnly { hint } => Some(format!("report only: {}", hint)),
+        }
+    }
+}
+
+/// Decode `%XX` escapes, as in file URIs and `.trashinfo` paths
+pub fn percent_decode(encoded: &str) -> Option<String> {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%')
+            .then(|| encoded.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match hex {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Directories with at least this many entries strain backups and filesystems
+pub const INODE_HEAVY_THRESHOLD: u64 = 200_000;
+
+impl CleanableFile {
+    /// A finding for the file at `path`, deleted on cleanup, last accessed
+    /// now and not recoverable. Anything else is set with struct update
+    /// syntax (`CleanableFile { recoverable: true, ..CleanableFile::new(..) }`).
+    pub fn new(
+        path: impl Into<PathBuf>,
+        size: u64,
+        category: Category,
+        reason: impl Into<String>,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            size,
+            category,
+            last_accessed: Utc::now(),
+            reason: reason.into(),
+            is_directory: false,
+            file_count: 1,
+            action: CleanupAction::Delete,
+            recoverable: false,
+            rebuild: None,
+            extra: BTreeMap::new(),
+            identity: None,
+        }
+    }
+
+    /// Whether this finding covers an extreme number of filesystem entries
+    pub fn is_inode_heavy(&self) -> bool {
+        self.file_count >= INODE_HEAVY_THRESHOLD
+    }
+
+    /// Bytes that would be downloaded again if this finding is needed after
+    /// cleanup. Recoverable caches are re-fetched in full; rebuilt or
+    /// irreplaceable findings cost no bandwidth.
+    pub fn redownload_bytes(&self) -> Option<u64> {
+        (self.category == Category::Cache && self.recoverable).then_some(self.size)
+    }
+
+    /// Record which file the path names now
+    pub fn with_identity(mut self) -> Self {
+        self.identity = FileIdentity::of(&self.path);
+        self
+    }
+
+    /// Whether the path now names a different file than when it was
+    /// scanned. A missing path or one scanned without an identity counts
+    /// as unchanged.
+    pub fn was_replaced(&self) -> bool {
+        match (self.identity, FileIdentity::of(&self.path)) {
+            (Some(scanned), Some(current)) => scanned != current,
+            _ => false,
+        }
+    }
+
+    /// Attach a scanner-specific detail
+    pub fn with_extra(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.extra.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Whether the detail `key` equals `value`; strings compare as is,
+    /// other values by their JSON text
+    pub fn extra_matches(&self, key: &str, value: &str) -> bool {
+        match self.extra.get(key) {
+            Some(serde_json::Value::String(s)) => s == value,
+            Some(other) => serde_json::to_string(other).is_ok_and(|s| s == value),
+            None => false,
+        }
+    }
+}
+
+/// Categories of cleanable files. Declaration order is the report order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum Category {
     Cache,
     Trash,
     Temp,
     Downloads,
+    Desktop,
     BuildArtifact,
+    CiCache,
     LargeFile,
     Duplicate,
+    RenamedDuplicate,
     OldFile,
+    Junk,
+    MetadataJunk,
+    GitObjects,
+    Docker,
+    Logs,
+    Explicit,
 }
 
 impl Category {
+    /// Every category, in declaration order
+    pub const ALL: [Category; 17] = [
+        Category::Cache,
+        Category::Trash,
+        Category::Temp,
+        Category::Downloads,
+        Category::Desktop,
+        Category::BuildArtifact,
+        Category::CiCache,
+        Category::LargeFile,
+        Category::Duplicate,
+        Category::RenamedDuplicate,
+        Category::OldFile,
+        Category::Junk,
+        Category::MetadataJunk,
+        Category::GitObjects,
+        Category::Docker,
+        Category::Logs,
+        Category::Explicit,
+    ];
+
+    /// The category with this `flag_name`; "duplicates" is exact duplicates
+    pub fn from_flag_name(name: &str) -> Option<Category> {
+        Category::ALL.into_iter().find(|c| c.flag_name() == name)
+    }
+
     /// Get the display name for this category
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -53,10 +361,89 @@ impl Category {
             Category::Trash => "Trash",
             Category::Temp => "Temp Files",
             Category::Downloads => "Old Downloads",
+            Category::Desktop => "Desktop Clutter",
             Category::BuildArtifact => "Build Artifacts",
+            Category::CiCache => "CI Runner Caches",
             Category::LargeFile => "Large Files",
-            Category::Duplicate => "Duplicates",
+            Category::Duplicate => "Exact Duplicates",
+            Category::RenamedDuplicate => "Renamed Duplicates",
             Category::OldFile => "Old Files",
+            Category::Junk => "Empty Dirs & Broken Links",
+            Category::MetadataJunk => "OS Metadata Files",
+            Category::GitObjects => "Git Objects",
+            Category::Docker => "Docker",
+            Category::Logs => "Log Files",
+            Category::Explicit => "Chosen Paths",
+        }
+    }
+
+    /// Short lowercase label for compact output like `scan --oneline`
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            Category::Cache => "cache",
+            Category::Trash => "trash",
+            Category::Temp => "temp",
+            Category::Downloads => "downloads",
+            Category::Desktop => "desktop",
+            Category::BuildArtifact => "build",
+            Category::CiCache => "ci",
+            Category::LargeFile => "large",
+            Category::Duplicate => "duplicates",
+            Category::RenamedDuplicate => "renamed",
+            Category::OldFile => "old",
+            Category::Junk => "junk",
+            Category::MetadataJunk => "metadata",
+            Category::GitObjects => "git",
+            Category::Docker => "docker",
+            Category::Logs => "logs",
+            Category::Explicit => "path",
+        }
+    }
+
+    /// Name of the scan flag that selects this category, as used in the
+    /// config's category lists
+    pub fn flag_name(&self) -> &'static str {
+        match self {
+            Category::Cache => "cache",
+            Category::Trash => "trash",
+            Category::Temp => "temp",
+            Category::Downloads => "downloads",
+            Category::Desktop => "desktop",
+            Category::BuildArtifact => "build",
+            Category::CiCache => "ci",
+            Category::LargeFile => "large",
+            Category::Duplicate | Category::RenamedDuplicate => "duplicates",
+            Category::OldFile => "old",
+            Category::Junk => "junk",
+            Category::MetadataJunk => "metadata-junk",
+            Category::GitObjects => "git",
+            Category::Docker => "docker",
+            Category::Logs => "logs",
+            Category::Explicit => "path",
+        }
+    }
+
+    /// Position in the cleanup order, lowest first: items that are cheap to
+    /// lose go before anything the user might still want back
+    pub fn cleanup_priority(&self) -> u8 {
+        match self {
+            Category::Trash => 0,
+            Category::Temp => 1,
+            Category::MetadataJunk => 2,
+            Category::Junk => 3,
+            Category::Logs => 4,
+            Category::Cache => 5,
+            Category::BuildArtifact => 6,
+            Category::CiCache => 7,
+            Category::GitObjects => 8,
+            Category::Docker => 9,
+            Category::Duplicate => 10,
+            Category::RenamedDuplicate => 11,
+            Category::OldFile => 12,
+            Category::LargeFile => 13,
+            Category::Downloads => 14,
+            Category::Desktop => 15,
+            Category::Explicit => 16,
         }
     }
 
@@ -67,10 +454,67 @@ impl Category {
             Category::Trash => "Files in the trash bin",
             Category::Temp => "Temporary files from /tmp and similar",
             Category::Downloads => "Old files in Downloads folder",
+            Category::Desktop => "Old files left on the Desktop",
             Category::BuildArtifact => "Build outputs and dependencies (node_modules, target, etc.)",
+            Category::CiCache => "Stale job workspaces and caches of self-hosted CI runners",
             Category::LargeFile => "Large files that may not be needed",
-            Category::Duplicate => "Duplicate files wasting space",
+            Category::Duplicate => "Identical files with the same name",
+            Category::RenamedDuplicate => "Same content under a different name (often intentional)",
             Category::OldFile => "Files not accessed for a long time",
+            Category::Junk => "Empty directories and dangling symlinks",
+            Category::MetadataJunk => "OS-generated clutter (.DS_Store, Thumbs.db, desktop.ini)",
+            Category::GitObjects => "Unreferenced Git LFS objects and large blobs in history",
+            Category::Docker => "Dangling Docker images, stopped containers, volumes, build cache",
+            Category::Logs => "Rotated logs and logs no longer written to",
+            Category::Explicit => "Files and directories named with `duster clean path`",
+        }
+    }
+}
+
+/// Returned by a [`ScanVisitor`] to stop the scan it is visiting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("scan cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Receives a scanner's output as it is produced
+pub trait ScanVisitor {
+    /// A finding. Returning `Err(Cancelled)` stops the scanner, which then
+    /// returns that error.
+    fn finding(&mut self, file: CleanableFile) -> std::result::Result<(), Cancelled>;
+
+    /// The scanner moved on to `path` (a root, project or directory; how
+    /// often depends on the scanner)
+    fn progress(&mut self, _path: &Path) {}
+
+    /// Something couldn't be checked, but the scan goes on
+    fn error(&mut self, _error: anyhow::Error) {}
+}
+
+/// Collects findings, ignoring progress and errors
+impl ScanVisitor for Vec<CleanableFile> {
+    fn finding(&mut self, file: CleanableFile) -> std::result::Result<(), Cancelled> {
+        self.push(file);
+        Ok(())
+    }
+}
+
+/// The entries of the scanner root `dir`. A root that exists but can't be
+/// read is reported to `visitor`, since nothing in it gets checked; a
+/// missing one just has nothing to scan.
+pub fn read_root(dir: &Path, visitor: &mut dyn ScanVisitor) -> Option<std::fs::ReadDir> {
+    match std::fs::read_dir(dir) {
+        Ok(entries) => Some(entries),
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::NotADirectory) => None,
+        Err(e) => {
+            visitor.error(anyhow::anyhow!("Failed to read {}: {}", dir.display(), e));
+            None
         }
     }
 }
@@ -80,19 +524,137 @@ pub trait Scanner: Send + Sync {
     /// Get the name of this scanner
     fn name(&self) -> &'static str;
 
-    /// Scan for cleanable files
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>>;
+    /// Scan for cleanable files, handing each to `visitor` as it is found
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()>;
+
+    /// Scan for cleanable files, largest first
+    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
+        let mut files = Vec::new();
+        self.scan_with(config, &mut files)?;
+        files.sort_by_key(|f| std::cmp::Reverse(f.size));
+        Ok(files)
+    }
+}
+
+/// Total size and entry count of a directory tree
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirStats {
+    /// Sum of file sizes in bytes
+    pub size: u64,
+    /// Number of entries below the root (files, directories, symlinks)
+    pub file_count: u64,
+}
+
+/// Extensions of macOS bundles and packages: directories Finder shows as
+/// one item, which break when files inside them are deleted one by one
+const BUNDLE_EXTENSIONS: &[&str] = &[
+    "app",
+    "appex",
+    "bundle",
+    "framework",
+    "kext",
+    "plugin",
+    "xpc",
+    "prefpane",
+    "qlgenerator",
+    "photoslibrary",
+    "photolibrary",
+    "musiclibrary",
+    "tvlibrary",
+    "imovielibrary",
+    "aplibrary",
+    "fcpbundle",
+    "lrlibrary",
+    "lrdata",
+    "lrcat-data",
+    "logicx",
+    "band",
+    "xcarchive",
+    "xcodeproj",
+    "xcworkspace",
+    "sparsebundle",
+    "rtfd",
+    "pages",
+    "numbers",
+    "key",
+];
+
+/// Media and catalog libraries, with how to shrink each safely. Their
+/// contents are never reported; the library is, for review, when oversized.
+const LIBRARY_PACKAGES: &[(&str, &str)] = &[
+    ("photoslibrary", "remove photos in Photos, or move the library to another drive"),
+    ("photolibrary", "remove photos in iPhoto, or move the library to another drive"),
+    ("aplibrary", "remove photos in Aperture, or move the library to another drive"),
+    ("musiclibrary", "remove media in Music, or move the library to another drive"),
+    ("tvlibrary", "remove media in TV, or move the library to another drive"),
+    ("imovielibrary", "delete projects and clips in iMovie"),
+    ("fcpbundle", "delete render files and unused media in Final Cut Pro"),
+    ("lrcat", "remove photos in Lightroom Classic; the catalog holds every edit"),
+    ("lrcat-data", "remove photos in Lightroom Classic; the catalog holds every edit"),
+    ("lrdata", "discard previews in Lightroom Classic (Library > Previews)"),
+    ("lrlibrary", "remove photos in Lightroom, or move the library to another drive"),
+    ("sparsebundle", "a disk image, often a Time Machine backup; manage it in its app"),
+];
+
+/// How to shrink the library package at `path`, if it is one
+pub fn library_package_hint(path: &std::path::Path) -> Option<&'static str> {
+    let ext = path.extension()?.to_string_lossy().to_lowercase();
+    LIBRARY_PACKAGES
+        .iter()
+        .find(|(e, _)| *e == ext)
+        .map(|(_, hint)| *hint)
+}
+
+/// Whether `path` is named like a bundle
+pub fn is_bundle(path: &std::path::Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .is_some_and(|e| BUNDLE_EXTENSIONS.contains(&e.as_str()))
+}
+
+/// The outermost bundle `path` is inside of, not counting `path` itself
+pub fn bundle_root(path: &std::path::Path) -> Option<&std::path::Path> {
+    path.ancestors().skip(1).filter(|p| is_bundle(p)).last()
 }
 
 /// Calculate the total size of a directory recursively
 pub fn calculate_dir_size(path: &std::path::Path) -> u64 {
-    walkdir::WalkDir::new(path)
+    calculate_dir_stats(path).size
+}
+
+/// Caches smaller than this aren't worth a line in the report
+pub const MIN_CACHE_SIZE: u64 = 1024 * 1024;
+
+/// Entries of `dir` that are directories rather than links to one, sorted;
+/// nothing if it can't be read
+pub fn subdirs(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// Calculate the total size and entry count of a directory recursively
+pub fn calculate_dir_stats(path: &std::path::Path) -> DirStats {
+    let mut stats = DirStats::default();
+    for entry in walkdir::WalkDir::new(path)
+        .min_depth(1)
         .into_iter()
         .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter_map(|e| e.metadata().ok())
-        .map(|m| m.len())
-        .sum()
+    {
+        stats.file_count += 1;
+        if entry.file_type().is_file() {
+            if let Ok(m) = entry.metadata() {
+                stats.size += m.len();
+            }
+        }
+    }
+    stats
 }
 
 /// Get the last modified time of a file or directory
@@ -100,7 +662,7 @@ pub fn get_last_modified(path: &std::path::Path) -> Option<DateTime<Utc>> {
     path.metadata()
         .ok()
         .and_then(|m| m.modified().ok())
-        .map(|t| DateTime::<Utc>::from(t))
+        .map(DateTime::<Utc>::from)
 }
 
 /// Get the last accessed time of a file
@@ -108,7 +670,7 @@ pub fn get_last_accessed(path: &std::path::Path) -> Option<DateTime<Utc>> {
     path.metadata()
         .ok()
         .and_then(|m| m.accessed().ok())
-        .map(|t| DateTime::<Utc>::from(t))
+        .map(DateTime::<Utc>::from)
 }
 
 /// Check if a path was accessed within the given number of days
@@ -136,6 +698,9 @@ pub fn was_modified_within_days(path: &std::path::Path, days: u32) -> bool {
 pub struct ScanResult {
     pub files: Vec<CleanableFile>,
     pub errors: Vec<String>,
+    /// What to know about the scan as a whole, e.g. paths it left out
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
 }
 
 impl ScanResult {
@@ -143,6 +708,7 @@ impl ScanResult {
         Self {
             files: Vec::new(),
             errors: Vec::new(),
+            notes: Vec::new(),
         }
     }
 
@@ -154,6 +720,10 @@ impl ScanResult {
         self.errors.push(error);
     }
 
+    pub fn add_note(&mut self, note: String) {
+        self.notes.push(note);
+    }
+
     pub fn total_size(&self) -> u64 {
         self.files.iter().map(|f| f.size).sum()
     }
@@ -162,13 +732,465 @@ impl ScanResult {
         self.files.len()
     }
 
-    /// Group files by category
-    pub fn by_category(&self) -> std::collections::HashMap<Category, Vec<&CleanableFile>> {
-        let mut map = std::collections::HashMap::new();
+    /// Sort files into a stable order: category, then size descending, then path.
+    /// Scanners run in parallel, so this keeps reports and cached results
+    /// identical across runs over the same filesystem state.
+    pub fn sort_stable(&mut self) {
+        self.files.sort_by(|a, b| {
+            a.category
+                .cmp(&b.category)
+                .then(b.size.cmp(&a.size))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+    }
+
+    /// Group files by category, in category order
+    pub fn by_category(&self) -> std::collections::BTreeMap<Category, Vec<&CleanableFile>> {
+        let mut map = std::collections::BTreeMap::new();
         for file in &self.files {
             map.entry(file.category).or_insert_with(Vec::new).push(file);
         }
         map
     }
+
+    /// Reconcile per-category totals with the flat file list.
+    ///
+    /// `total_size` always equals `categorized_size + uncategorized_size`.
+    /// Findings nested inside another directory finding are counted in both,
+    /// so their bytes are reported as `overlap_adjustment` and subtracted to
+    /// get `reclaimable_size`.
+    pub fn reconcile(&self) -> Reconciliation {
+        let total_size = self.total_size();
+
+        let categorized_size: u64 = self
+            .by_category()
+            .values()
+            .flat_map(|files| files.iter().map(|f| f.size))
+            .sum();
+        let uncategorized_size = total_size.saturating_sub(categorized_size);
+
+        let dir_paths: std::collections::HashSet<&std::path::Path> = self
+            .files
+            .iter()
+            .filter(|f| f.is_directory)
+            .map(|f| f.path.as_path())
+            .collect();
+
+        let overlap_adjustment: u64 = self
+            .files
+            .iter()
+            .filter(|f| f.path.ancestors().skip(1).any(|a| dir_paths.contains(a)))
+            .map(|f| f.size)
+            .sum();
+
+        let reconciliation = Reconciliation {
+            total_size,
+            categorized_size,
+            uncategorized_size,
+            overlap_adjustment,
+            reclaimable_size: total_size.saturating_sub(overlap_adjustment),
+        };
+
+        debug_assert_eq!(
+            reconciliation.categorized_size + reconciliation.uncategorized_size,
+            reconciliation.total_size
+        );
+        debug_assert!(reconciliation.reclaimable_size <= reconciliation.total_size);
+
+        reconciliation
+    }
 }
 
+/// How per-category totals add up to the overall total
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Reconciliation {
+    /// Sum of all finding sizes
+    pub total_size: u64,
+    /// Sum of sizes attributed to a category
+    pub categorized_size: u64,
+    /// Bytes in the flat list not attributed to any category
+    pub uncategorized_size: u64,
+    /// Bytes counted twice because a finding lies inside another directory finding
+    pub overlap_adjustment: u64,
+    /// Bytes actually freed by deleting everything (`total_size - overlap_adjustment`)
+    pub reclaimable_size: u64,
+}
+
+/// Findings for the tests of every module
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::{Category, CleanableFile};
+    use chrono::{Duration, Utc};
+
+    /// A finding for the file at `path`
+    pub(crate) fn finding(path: &str, size: u64, category: Category) -> CleanableFile {
+        CleanableFile::new(path, size, category, "")
+    }
+
+    /// An old file of `size` bytes, last accessed `days` ago
+    pub(crate) fn file_aged(days: i64, size: u64) -> CleanableFile {
+        CleanableFile {
+            last_accessed: Utc::now() - Duration::days(days),
+            ..finding("/f", size, Category::OldFile)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::finding;
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_sort_stable() {
+        let mut result = ScanResult::new();
+        result.add_files(vec![
+            finding("/b", 10, Category::Temp),
+            finding("/a", 10, Category::Temp),
+            finding("/c", 99, Category::Temp),
+            finding("/z", 1, Category::Cache),
+        ]);
+
+        result.sort_stable();
+
+        let paths: Vec<_> = result.files.iter().map(|f| f.path.to_str().unwrap()).collect();
+        assert_eq!(paths, vec!["/z", "/c", "/a", "/b"]);
+    }
+
+    #[test]
+    fn test_extra_details_round_trip_and_match() {
+        let trashed = finding("/a", 10, Category::Trash)
+            .with_extra(extra::ORIGINAL_TRASH_PATH, "/home/me/notes.txt")
+            .with_extra("generation", 3);
+        assert!(trashed.extra_matches(extra::ORIGINAL_TRASH_PATH, "/home/me/notes.txt"));
+        assert!(trashed.extra_matches("generation", "3"));
+        assert!(!trashed.extra_matches(extra::PROJECT_ROOT, "/home/me"));
+
+        let json = serde_json::to_string(&trashed).unwrap();
+        let back: CleanableFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.extra, trashed.extra);
+        // Findings without details leave the field out
+        assert!(!serde_json::to_string(&finding("/b", 1, Category::Temp))
+            .unwrap()
+            .contains("extra"));
+    }
+
+    #[test]
+    fn test_reconcile_totals_add_up() {
+        let mut result = ScanResult::new();
+        result.add_files(vec![
+            finding("/a", 10, Category::Temp),
+            finding("/b", 20, Category::Cache),
+            finding("/c", 30, Category::LargeFile),
+        ]);
+
+        let r = result.reconcile();
+        assert_eq!(r.total_size, 60);
+        assert_eq!(r.categorized_size, 60);
+        assert_eq!(r.uncategorized_size, 0);
+        assert_eq!(r.overlap_adjustment, 0);
+        assert_eq!(r.reclaimable_size, 60);
+    }
+
+    #[test]
+    fn test_reconcile_nested_findings() {
+        let mut parent = finding("/cache", 100, Category::Cache);
+        parent.is_directory = true;
+
+        let mut result = ScanResult::new();
+        result.add_files(vec![
+            parent,
+            finding("/cache/pip/wheel", 40, Category::BuildArtifact),
+            finding("/cache-other", 5, Category::Temp),
+        ]);
+
+        let r = result.reconcile();
+        assert_eq!(r.total_size, 145);
+        assert_eq!(r.overlap_adjustment, 40);
+        assert_eq!(r.reclaimable_size, 105);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_excludes_apply_across_scanners() {
+        let dir = tempfile::Builder::new().prefix("duster-test").tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+
+        for sub in ["keep", "skip"] {
+            let sub = root.join(sub);
+            fs::create_dir(&sub).unwrap();
+            fs::write(sub.join(".DS_Store"), b"x").unwrap();
+            fs::write(sub.join("disk.iso"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+            std::os::unix::fs::symlink(sub.join("missing"), sub.join("dangling")).unwrap();
+        }
+
+        // Written the long way round; scanners see the plain path
+        let config = Config {
+            base_path: Some(root.clone()),
+            min_large_size_mb: 1,
+            excluded_paths: vec![root.join("keep/../skip").to_string_lossy().to_string()],
+            ..Config::default()
+        };
+
+        let scanners: Vec<Box<dyn Scanner>> = vec![
+            Box::new(junk::JunkScanner::new()),
+            Box::new(metadata_junk::MetadataJunkScanner::new()),
+            Box::new(large_files::LargeFilesScanner::new()),
+        ];
+        for scanner in scanners {
+            let found = scanner.scan(&config).unwrap();
+            assert!(
+                found.iter().any(|f| f.path.starts_with(root.join("keep"))),
+                "{} found nothing",
+                scanner.name()
+            );
+            assert!(
+                !found.iter().any(|f| f.path.starts_with(root.join("skip"))),
+                "{} ignored the exclude",
+                scanner.name()
+            );
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_bundles_are_never_split() {
+        let dir = tempfile::Builder::new().prefix("duster-test").tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let bundle = root.join("Tool.app");
+        let contents = bundle.join("Contents").join("MacOS");
+        fs::create_dir_all(&contents).unwrap();
+        fs::write(contents.join("tool"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+        fs::write(contents.join(".DS_Store"), b"x").unwrap();
+        std::os::unix::fs::symlink(contents.join("missing"), contents.join("dangling")).unwrap();
+
+        assert_eq!(bundle_root(&contents.join("tool")), Some(bundle.as_path()));
+        assert_eq!(bundle_root(&bundle), None);
+
+        let config = Config {
+            base_path: Some(root.clone()),
+            min_large_size_mb: 1,
+            ..Config::default()
+        };
+        let scanners: Vec<Box<dyn Scanner>> = vec![
+            Box::new(junk::JunkScanner::new()),
+            Box::new(metadata_junk::MetadataJunkScanner::new()),
+            Box::new(large_files::LargeFilesScanner::new()),
+        ];
+        for scanner in scanners {
+            let found = scanner.scan(&config).unwrap();
+            assert!(
+                !found.iter().any(|f| bundle_root(&f.path).is_some()),
+                "{} reported a file inside the bundle",
+                scanner.name()
+            );
+        }
+
+        // Large bundles are reported whole, libraries only for review
+        let library = root.join("Photos Library.photoslibrary");
+        fs::create_dir_all(library.join("originals")).unwrap();
+        fs::write(library.join("originals/IMG_0001.HEIC"), vec![0u8; 2 * 1024 * 1024]).unwrap();
+        let large = large_files::LargeFilesScanner::new().scan(&config).unwrap();
+        assert_eq!(large.len(), 2);
+        let tool = large.iter().find(|f| f.path == bundle).unwrap();
+        assert!(tool.is_directory);
+        assert_eq!(tool.action, CleanupAction::Delete);
+        let photos = large.iter().find(|f| f.path == library).unwrap();
+        assert!(matches!(photos.action, CleanupAction::ReportOnly { .. }));
+        assert!(photos.extra.contains_key(extra::RISK));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_trash_is_only_counted_as_trash() {
+        let dir = tempfile::Builder::new().prefix("duster-test").tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        let trashes = [
+            root.join(".local/share/Trash/files"),
+            root.join("drive/.Trash-1000/files"),
+            root.join("drive/.Trashes/501"),
+            root.join("drive/$RECYCLE.BIN/S-1-5-21"),
+        ];
+        for trash in &trashes {
+            fs::create_dir_all(trash).unwrap();
+            fs::write(trash.join("disk.iso"), vec![7u8; 2 * 1024 * 1024]).unwrap();
+            fs::write(trash.join(".DS_Store"), b"x").unwrap();
+        }
+        fs::write(root.join("disk.iso"), vec![7u8; 2 * 1024 * 1024]).unwrap();
+
+        let config = Config {
+            base_path: Some(root.clone()),
+            min_large_size_mb: 1,
+            ..Config::default()
+        };
+        let scanners: Vec<Box<dyn Scanner>> = vec![
+            Box::new(metadata_junk::MetadataJunkScanner::new()),
+            Box::new(large_files::LargeFilesScanner::new()),
+            Box::new(duplicates::DuplicatesScanner::new()),
+        ];
+        for scanner in scanners {
+            let found = scanner.scan(&config).unwrap();
+            assert!(
+                !found
+                    .iter()
+                    .any(|f| trashes.iter().any(|t| f.path.starts_with(t))),
+                "{} reported a trashed item",
+                scanner.name()
+            );
+        }
+        assert_eq!(
+            trash::home_trash_dirs(&root).first() == Some(&trashes[0]),
+            cfg!(target_os = "linux")
+        );
+    }
+
+    #[test]
+    fn test_browser_caches_are_reported_once() {
+        let dir = tempfile::Builder::new()
+            .prefix("duster-test")
+            .tempdir()
+            .unwrap();
+        let root = dir.path();
+        let chrome = &browsers::cache_roots(root)[0];
+        for path in [
+            chrome.join("Default/Cache/data"),
+            root.join(".cache/pip/wheel"),
+        ] {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, vec![0u8; 2 * 1024 * 1024]).unwrap();
+        }
+
+        let config = Config {
+            paths: crate::config::Paths::sandboxed(root),
+            ..Config::default()
+        };
+        let mut found = cache::CacheScanner::new().scan(&config).unwrap();
+        found.extend(browsers::BrowserCacheScanner::new().scan(&config).unwrap());
+
+        let mut paths: Vec<PathBuf> = found.into_iter().map(|f| f.path).collect();
+        paths.sort();
+        let mut expected = vec![chrome.join("Default"), root.join(".cache/pip")];
+        expected.sort();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_downloads_use_recorded_usage() {
+        struct Recorded;
+        impl usage::UsageProvider for Recorded {
+            fn usage(&self, path: &std::path::Path) -> usage::Usage {
+                match path.file_name().and_then(|n| n.to_str()) {
+                    Some("installer.dmg") => usage::Usage::NeverOpened,
+                    Some("opened.pdf") => usage::Usage::LastUsed(Utc::now()),
+                    Some("recent.txt") => panic!("looked up usage of a recent file"),
+                    _ => usage::Usage::Unknown,
+                }
+            }
+        }
+
+        let dir = tempfile::Builder::new().prefix("duster-test").tempdir().unwrap();
+        let root = dir.path();
+        let then = std::time::SystemTime::now() - std::time::Duration::from_secs(90 * 86400);
+        for name in ["installer.dmg", "opened.pdf", "notes.txt"] {
+            let path = root.join("Downloads").join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"x").unwrap();
+            fs::File::open(&path)
+                .unwrap()
+                .set_times(fs::FileTimes::new().set_accessed(then).set_modified(then))
+                .unwrap();
+        }
+        fs::write(root.join("Downloads/recent.txt"), b"x").unwrap();
+
+        let config = Config {
+            paths: crate::config::Paths::sandboxed(root),
+            ..Config::default()
+        };
+        let scanner = downloads::DownloadsScanner::new().with_usage_provider(Box::new(Recorded));
+        let mut found = scanner.scan(&config).unwrap();
+        found.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let names: Vec<_> = found.iter().map(|f| f.path.file_name().unwrap()).collect();
+        assert_eq!(names, ["installer.dmg", "notes.txt"]);
+        assert!(found[0].extra_matches(extra::NEVER_OPENED, "true"));
+        assert!(found[0].reason.starts_with("Download never opened"));
+        assert!(!found[1].extra.contains_key(extra::NEVER_OPENED));
+    }
+
+    /// Stops after the first finding, counting progress callbacks
+    struct FirstOnly {
+        found: Vec<CleanableFile>,
+        progress: usize,
+    }
+
+    impl ScanVisitor for FirstOnly {
+        fn finding(&mut self, file: CleanableFile) -> std::result::Result<(), Cancelled> {
+            self.found.push(file);
+            Err(Cancelled)
+        }
+
+        fn progress(&mut self, _path: &Path) {
+            self.progress += 1;
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_visitor_can_cancel_scan() {
+        let dir = tempfile::Builder::new().prefix("duster-test").tempdir().unwrap();
+        let sub = dir.path().join("links");
+        fs::create_dir(&sub).unwrap();
+        for name in ["a", "b", "c"] {
+            std::os::unix::fs::symlink(sub.join("missing"), sub.join(name)).unwrap();
+        }
+        let config = Config {
+            base_path: Some(dir.path().to_path_buf()),
+            ..Config::default()
+        };
+
+        let scanner = junk::JunkScanner::new();
+        assert_eq!(scanner.scan(&config).unwrap().len(), 3);
+
+        let mut visitor = FirstOnly {
+            found: Vec::new(),
+            progress: 0,
+        };
+        let err = scanner.scan_with(&config, &mut visitor).unwrap_err();
+        assert!(err.is::<Cancelled>());
+        assert_eq!(visitor.found.len(), 1);
+        assert!(visitor.progress > 0);
+    }
+
+    /// Keeps the errors scanners report
+    #[derive(Default)]
+    struct Errors(Vec<String>);
+
+    impl ScanVisitor for Errors {
+        fn finding(&mut self, _file: CleanableFile) -> std::result::Result<(), Cancelled> {
+            Ok(())
+        }
+
+        fn error(&mut self, error: anyhow::Error) {
+            self.0.push(error.to_string());
+        }
+    }
+
+    #[test]
+    fn test_unreadable_roots_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("notes.txt");
+        fs::write(&file, b"notes").unwrap();
+
+        let mut errors = Errors::default();
+        assert!(read_root(dir.path(), &mut errors).is_some());
+        // Nothing to scan in either
+        assert!(read_root(&dir.path().join("missing"), &mut errors).is_none());
+        assert!(read_root(&file, &mut errors).is_none());
+        assert!(errors.0.is_empty());
+
+        assert!(read_root(Path::new("bad\0name"), &mut errors).is_none());
+        assert_eq!(errors.0.len(), 1);
+        assert!(errors.0[0].starts_with("Failed to read bad"));
+    }
+}
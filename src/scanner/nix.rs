@@ -0,0 +1,105 @@
+//! Nix store scanner. The store is never deleted directly: paths are shared
+//! between profiles and removing one by hand breaks every dependent, so
+//! cleanup goes through `nix-collect-garbage`.
+
+use super::{
+    calculate_dir_stats, get_last_modified, Category, CleanableFile, CleanupAction, ScanVisitor,
+    Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+const NIX_STORE: &str = "/nix/store";
+
+pub struct NixStoreScanner;
+
+impl NixStoreScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for NixStoreScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Store paths no GC root references, i.e. what garbage collection would free
+fn dead_paths() -> Option<Vec<PathBuf>> {
+    let output = Command::new("nix-store")
+        .args(["--gc", "--print-dead"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| PathBuf::from(line.trim()))
+            .filter(|p| p.starts_with(NIX_STORE))
+            .collect(),
+    )
+}
+
+impl Scanner for NixStoreScanner {
+    fn name(&self) -> &'static str {
+        "Nix Store Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let store = Path::new(NIX_STORE);
+        if !config.paths.system || !store.is_dir() || config.is_excluded(store) {
+            return Ok(());
+        }
+
+        let dead = match dead_paths() {
+            Some(d) if !d.is_empty() => d,
+            _ => return Ok(()),
+        };
+
+        let mut size = 0;
+        let mut file_count = 0;
+        for path in &dead {
+            let metadata = match std::fs::symlink_metadata(path) {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            if metadata.is_dir() {
+                let stats = calculate_dir_stats(path);
+                size += stats.size;
+                file_count += stats.file_count + 1;
+            } else {
+                size += metadata.len();
+                file_count += 1;
+            }
+        }
+
+        if size == 0 {
+            return Ok(());
+        }
+
+        visitor.finding(CleanableFile {
+            last_accessed: get_last_modified(store).unwrap_or_else(Utc::now),
+            is_directory: true,
+            file_count,
+            action: CleanupAction::command("nix-collect-garbage", &[], None),
+            recoverable: true,
+            ..CleanableFile::new(
+                store.to_path_buf(),
+                size,
+                Category::Cache,
+                format!("Nix store: {} unreachable path(s)", dead.len()),
+            )
+        })?;
+
+        Ok(())
+    }
+}
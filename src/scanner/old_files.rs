@@ -1,12 +1,47 @@
-//! Old files scanner for files not accessed in a long time
-
-use super::{get_last_accessed, was_accessed_within_days, Category, CleanableFile, Scanner};
+//! Old files scanner for files not accessed in a long time. A top-level
+//! folder of a user data directory in which nothing was used recently is
+//! reported as one finding rather than file by file.
+
+use super::{
+    extra, file_index, get_last_accessed, read_root, was_accessed_within_days, Category,
+    CleanableFile, ScanVisitor, Scanner,
+};
 use crate::config::Config;
 use anyhow::Result;
-use chrono::Utc;
-use std::path::Path;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// How deep below a user data directory files are looked for
+const MAX_DEPTH: usize = 5;
+
+/// Smaller files aren't worth reporting
+const MIN_SIZE: u64 = 10 * 1024;
+
+/// Largest files listed in a folder finding's detail
+const FOLDER_DETAIL_FILES: usize = 5;
+
+/// Entries marking a repository root; its files are source code, old or not
+const REPO_MARKERS: &[&str] = &[".git", ".hg", ".svn"];
+
+/// Entries marking a folder a sync or notes app keeps up to date itself
+const SYNCED_MARKERS: &[&str] = &[
+    ".obsidian", // Obsidian vault
+    ".stfolder", // Syncthing
+    ".sync",     // Resilio Sync
+    "logseq",    // Logseq graph
+];
+
+/// App folders commonly kept in Documents, whose files the app manages
+const APP_FOLDERS: &[&str] = &[
+    "Calibre Library",
+    "Zotero",
+    "Microsoft User Data",
+    "Outlook Files",
+    "My Games",
+];
+
 pub struct OldFilesScanner;
 
 impl OldFilesScanner {
@@ -21,6 +56,11 @@ impl OldFilesScanner {
 
     /// Directories to skip
     fn should_skip_dir(path: &Path) -> bool {
+        // Trashed items are counted as Trash only
+        if super::trash::is_trash_dir(path) {
+            return true;
+        }
+
         let name = match path.file_name() {
             Some(n) => n.to_string_lossy(),
             None => return false,
@@ -31,6 +71,12 @@ impl OldFilesScanner {
             return true;
         }
 
+        // Files in a bundle age together with it, and a bundle's own access
+        // time says nothing about its use
+        if super::is_bundle(path) {
+            return true;
+        }
+
         // Skip common non-user directories
         matches!(
             name.as_ref(),
@@ -38,7 +84,6 @@ impl OldFilesScanner {
                 | "target"
                 | "Library"
                 | "Applications"
-                | ".Trash"
                 | "Volumes"
                 | "System"
                 | "bin"
@@ -48,6 +93,26 @@ impl OldFilesScanner {
         )
     }
 
+    /// Repositories and app-managed folders, skipped with
+    /// `old_files.skip_repos`
+    fn is_managed_dir(path: &Path) -> bool {
+        if path
+            .file_name()
+            .is_some_and(|n| APP_FOLDERS.iter().any(|a| n == *a))
+        {
+            return true;
+        }
+        REPO_MARKERS
+            .iter()
+            .chain(SYNCED_MARKERS)
+            .any(|marker| path.join(marker).exists())
+    }
+
+    /// Directories a walk doesn't enter under `config`
+    fn skip_dir(path: &Path, config: &Config) -> bool {
+        Self::should_skip_dir(path) || (config.old_files.skip_repos && Self::is_managed_dir(path))
+    }
+
     /// File extensions that are typically system/config files
     fn is_system_file(path: &Path) -> bool {
         let ext = match path.extension() {
@@ -68,112 +133,413 @@ impl Default for OldFilesScanner {
     }
 }
 
-impl Scanner for OldFilesScanner {
-    fn name(&self) -> &'static str {
-        "Old Files Scanner"
-    }
-
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
+impl OldFilesScanner {
+    /// The finding for a file, if it is old enough and worth reporting
+    fn evaluate(path: &Path, config: &Config) -> Option<CleanableFile> {
+        // Skip if excluded
+        if config.is_excluded(path) {
+            return None;
+        }
 
-        let home = match dirs::home_dir() {
-            Some(h) => h,
-            None => return Ok(results),
-        };
+        // Skip hidden files
+        if let Some(name) = path.file_name() {
+            if name.to_string_lossy().starts_with('.') {
+                return None;
+            }
+        }
 
-        let min_age_days = config.min_age_days;
+        // Skip system files and library catalogs
+        if Self::is_system_file(path) || super::library_package_hint(path).is_some() {
+            return None;
+        }
 
-        // Scan user data directories
-        for dir_name in Self::user_data_dirs() {
-            let dir_path = home.join(dir_name);
+        // Skip recently accessed files
+        if was_accessed_within_days(path, config.min_age_days_for(path)) {
+            return None;
+        }
 
-            if !dir_path.exists() {
-                continue;
-            }
+        let size = path.symlink_metadata().ok()?.len();
 
-            for entry in WalkDir::new(&dir_path)
-                .follow_links(false)
-                .max_depth(5) // Don't go too deep
-                .into_iter()
-                .filter_entry(|e| {
-                    if e.file_type().is_dir() {
-                        return !Self::should_skip_dir(e.path());
-                    }
-                    true
-                })
-                .filter_map(|e| e.ok())
-            {
-                // Only look at files
-                if !entry.file_type().is_file() {
-                    continue;
-                }
+        // Skip very small files (less than 10KB)
+        if size < MIN_SIZE {
+            return None;
+        }
 
-                let path = entry.path();
+        let last_accessed = get_last_accessed(path).unwrap_or_else(Utc::now);
 
-                // Skip if excluded
-                if config.is_excluded(path) {
-                    continue;
-                }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
 
-                // Skip hidden files
-                if let Some(name) = path.file_name() {
-                    if name.to_string_lossy().starts_with('.') {
-                        continue;
-                    }
-                }
+        let age_days = (Utc::now() - last_accessed).num_days();
 
-                // Skip system files
-                if Self::is_system_file(path) {
-                    continue;
-                }
+        Some(CleanableFile {
+            last_accessed,
+            ..CleanableFile::new(
+                path.to_path_buf(),
+                size,
+                Category::OldFile,
+                format!("Not accessed in {} days: {}", age_days, name),
+            )
+        })
+    }
 
-                // Skip recently accessed files
-                if was_accessed_within_days(path, min_age_days) {
-                    continue;
+    /// Walk a user data directory for old files
+    fn walk(
+        dir_path: &Path,
+        config: &Config,
+        visitor: &mut dyn ScanVisitor,
+        results: &mut Vec<CleanableFile>,
+    ) {
+        for entry in WalkDir::new(dir_path)
+            .follow_links(false)
+            .max_depth(MAX_DEPTH) // Don't go too deep
+            .into_iter()
+            .filter_entry(|e| {
+                if e.file_type().is_dir() {
+                    return !Self::skip_dir(e.path(), config);
                 }
+                true
+            })
+            .filter_map(|e| e.ok())
+        {
+            if entry.file_type().is_dir() {
+                visitor.progress(entry.path());
+            }
 
-                let metadata = match entry.metadata() {
-                    Ok(m) => m,
-                    Err(_) => continue,
-                };
-
-                let size = metadata.len();
+            // Only look at files
+            if entry.file_type().is_file() {
+                results.extend(Self::evaluate(entry.path(), config));
+            }
+        }
+    }
 
-                // Skip very small files (less than 10KB)
-                if size < 10 * 1024 {
-                    continue;
+    /// Totals for a folder in which nothing was used within the age
+    /// threshold, or `None` if it holds anything a walk would leave alone
+    /// (recent or excluded files, repositories, bundles), so deleting it
+    /// whole loses only old files
+    fn untouched_folder(folder: &Path, config: &Config) -> Option<CleanableFile> {
+        let min_age = config.min_age_days_for(folder);
+        let mut size = 0;
+        let mut file_count = 0;
+        let mut newest: Option<DateTime<Utc>> = None;
+        let mut files: Vec<(PathBuf, u64)> = Vec::new();
+
+        for entry in WalkDir::new(folder).follow_links(false) {
+            let entry = entry.ok()?;
+            let path = entry.path();
+            if config.is_excluded(path) {
+                return None;
+            }
+            if entry.file_type().is_dir() {
+                if Self::skip_dir(path, config) {
+                    return None;
                 }
+                continue;
+            }
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            if was_accessed_within_days(path, min_age) {
+                return None;
+            }
+            let len = entry.metadata().ok()?.len();
+            size += len;
+            file_count += 1;
+            newest = newest.max(get_last_accessed(path));
+            files.push((path.strip_prefix(folder).ok()?.to_path_buf(), len));
+        }
 
-                let last_accessed = get_last_accessed(path).unwrap_or_else(Utc::now);
-
-                let name = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "Unknown".to_string());
+        let last_accessed = newest?;
+        let name = folder
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let age_days = (Utc::now() - last_accessed).num_days();
+
+        files.sort_by_key(|(_, len)| std::cmp::Reverse(*len));
+        let largest: Vec<serde_json::Value> = files
+            .iter()
+            .take(FOLDER_DETAIL_FILES)
+            .map(|(path, len)| serde_json::json!({ "path": path, "size": len }))
+            .collect();
+
+        let finding = CleanableFile {
+            last_accessed,
+            is_directory: true,
+            file_count,
+            ..CleanableFile::new(
+                folder.to_path_buf(),
+                size,
+                Category::OldFile,
+                format!(
+                    "Untouched for {}: {} ({} files)",
+                    crate::format::format_age_days(age_days),
+                    name,
+                    file_count
+                ),
+            )
+        };
+        Some(finding.with_extra(extra::LARGEST_FILES, largest))
+    }
 
-                let age_days = (Utc::now() - last_accessed).num_days();
+    /// Replace the old files of each top-level folder of `dirs` with one
+    /// finding for the folder, where nothing in it was used recently
+    fn group_by_folder(
+        results: Vec<CleanableFile>,
+        dirs: &[PathBuf],
+        config: &Config,
+    ) -> Vec<CleanableFile> {
+        let mut grouped = Vec::new();
+        let mut folders: BTreeMap<PathBuf, Vec<CleanableFile>> = BTreeMap::new();
+        for file in results {
+            let folder = dirs.iter().find_map(|dir| {
+                let relative = file.path.strip_prefix(dir).ok()?;
+                let mut components = relative.components();
+                let top = components.next()?;
+                components.next().map(|_| dir.join(top))
+            });
+            match folder {
+                Some(folder) => folders.entry(folder).or_default().push(file),
+                None => grouped.push(file),
+            }
+        }
 
-                results.push(CleanableFile {
-                    path: path.to_path_buf(),
-                    size,
-                    category: Category::OldFile,
-                    last_accessed,
-                    reason: format!("Not accessed in {} days: {}", age_days, name),
-                    is_directory: false,
-                });
+        for (folder, files) in folders {
+            let finding = (files.len() > 1)
+                .then(|| Self::untouched_folder(&folder, config))
+                .flatten();
+            match finding {
+                Some(finding) => grouped.push(finding),
+                None => grouped.extend(files),
             }
         }
+        grouped
+    }
 
+    /// Oldest first, then largest, capped so the output stays readable
+    fn oldest(mut results: Vec<CleanableFile>) -> Vec<CleanableFile> {
         // Sort by last accessed (oldest first) then by size
         results.sort_by(|a, b| {
             a.last_accessed
                 .cmp(&b.last_accessed)
                 .then(b.size.cmp(&a.size))
+                .then_with(|| a.path.cmp(&b.path))
         });
 
         // Limit results to avoid overwhelming output
         results.truncate(200);
+        results
+    }
+}
+
+impl Scanner for OldFilesScanner {
+    fn name(&self) -> &'static str {
+        "Old Files Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let home = match &config.paths.home {
+            Some(h) => h.clone(),
+            None => return Ok(()),
+        };
+
+        // Scan user data directories
+        let dirs: Vec<PathBuf> = Self::user_data_dirs()
+            .into_iter()
+            .map(|d| home.join(d))
+            .filter(|d| d.exists())
+            .collect();
+
+        // Only the oldest files are reported, so findings are emitted once
+        // the walk is done
+        let mut results = Vec::new();
+        let indexed = if config.use_index {
+            file_index::indexed_files(&dirs, MIN_SIZE)
+        } else {
+            None
+        };
+        for dir_path in &dirs {
+            if read_root(dir_path, visitor).is_none() {
+                continue;
+            }
+            match &indexed {
+                Some(matches) => results.extend(
+                    file_index::reachable(dir_path, matches, MAX_DEPTH, false, |p| {
+                        Self::skip_dir(p, config)
+                    })
+                    .iter()
+                    .filter_map(|r| Self::evaluate(&r.path, config)),
+                ),
+                None => Self::walk(dir_path, config, visitor, &mut results),
+            }
+        }
+
+        let results = Self::group_by_folder(results, &dirs, config);
+        for file in Self::oldest(results) {
+            visitor.finding(file)?;
+        }
 
-        Ok(results)
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File, FileTimes};
+    use std::time::{Duration, SystemTime};
+
+    /// Write a 20 KB file last used in September 2020
+    fn write_old(path: &Path) {
+        let then = SystemTime::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![1u8; 20 * 1024]).unwrap();
+        File::open(path)
+            .unwrap()
+            .set_times(FileTimes::new().set_accessed(then).set_modified(then))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_index_matches_walk() {
+        let dir = tempfile::Builder::new()
+            .prefix("duster-test")
+            .tempdir()
+            .unwrap();
+        let home = dir.path().canonicalize().unwrap();
+        for file in [
+            "Documents/thesis.pdf",
+            "Documents/a/b/c/d/deep.pdf",
+            "Documents/a/b/c/d/e/too-deep.pdf",
+            "Documents/.hidden/notes.txt",
+            "Documents/Report.pages/Data/image.png",
+            "Documents/project/node_modules/pkg/readme.md",
+            "Pictures/2019/beach.jpg",
+            "Downloads/old.zip",
+        ] {
+            write_old(&home.join(file));
+        }
+
+        let config = Config {
+            paths: crate::config::Paths::sandboxed(&home),
+            ..Config::default()
+        };
+        let mut walked = Vec::new();
+        for dir in ["Documents", "Pictures"] {
+            OldFilesScanner::walk(&home.join(dir), &config, &mut Vec::new(), &mut walked);
+        }
+        let walked = OldFilesScanner::oldest(walked);
+
+        // The index knows every file, wherever it is
+        let indexed: Vec<PathBuf> = WalkDir::new(&home)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect();
+        let mut from_index = Vec::new();
+        for dir in ["Documents", "Pictures"] {
+            from_index.extend(
+                file_index::reachable(&home.join(dir), &indexed, MAX_DEPTH, false, |p| {
+                    OldFilesScanner::skip_dir(p, &config)
+                })
+                .iter()
+                .filter_map(|r| OldFilesScanner::evaluate(&r.path, &config)),
+            );
+        }
+        let from_index = OldFilesScanner::oldest(from_index);
+
+        let paths = |files: &[CleanableFile]| -> Vec<PathBuf> {
+            files.iter().map(|f| f.path.clone()).collect()
+        };
+        assert_eq!(paths(&from_index), paths(&walked));
+        assert_eq!(
+            paths(&walked),
+            vec![
+                home.join("Documents/a/b/c/d/deep.pdf"),
+                home.join("Documents/thesis.pdf"),
+                home.join("Pictures/2019/beach.jpg"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repositories_and_synced_folders_are_skipped() {
+        let dir = tempfile::Builder::new()
+            .prefix("duster-test")
+            .tempdir()
+            .unwrap();
+        let home = dir.path().canonicalize().unwrap();
+        for file in [
+            "Documents/taxes-2019.pdf",
+            "Documents/code/app/src/main.rs",
+            "Documents/Notes/daily/2020-01-01.md",
+            "Documents/Calibre Library/Author/book.epub",
+        ] {
+            write_old(&home.join(file));
+        }
+        fs::create_dir(home.join("Documents/code/app/.git")).unwrap();
+        fs::create_dir(home.join("Documents/Notes/.obsidian")).unwrap();
+
+        let walk = |config: &Config| {
+            let mut found = Vec::new();
+            OldFilesScanner::walk(&home.join("Documents"), config, &mut Vec::new(), &mut found);
+            let mut paths: Vec<PathBuf> = found.into_iter().map(|f| f.path).collect();
+            paths.sort();
+            paths
+        };
+
+        let mut config = Config::default();
+        assert_eq!(walk(&config), vec![home.join("Documents/taxes-2019.pdf")]);
+
+        config.old_files.skip_repos = false;
+        assert_eq!(walk(&config).len(), 4);
+    }
+
+    #[test]
+    fn test_untouched_folders_are_grouped() {
+        let dir = tempfile::Builder::new()
+            .prefix("duster-test")
+            .tempdir()
+            .unwrap();
+        let home = dir.path().canonicalize().unwrap();
+        let documents = home.join("Documents");
+        for file in [
+            "taxes-2019.pdf",
+            "old-client-work/brief.pdf",
+            "old-client-work/assets/logo.psd",
+            "old-client-work/assets/notes.txt",
+            "mixed/old.pdf",
+            "mixed/older.pdf",
+        ] {
+            write_old(&documents.join(file));
+        }
+        fs::write(documents.join("mixed/current.pdf"), vec![1u8; 20 * 1024]).unwrap();
+
+        let config = Config::default();
+        let mut found = Vec::new();
+        OldFilesScanner::walk(&documents, &config, &mut Vec::new(), &mut found);
+        let dirs = [documents.clone()];
+        let found = OldFilesScanner::group_by_folder(found, &dirs, &config);
+        let mut paths: Vec<PathBuf> = found.iter().map(|f| f.path.clone()).collect();
+        paths.sort();
+
+        assert_eq!(
+            paths,
+            vec![
+                documents.join("mixed/old.pdf"),
+                documents.join("mixed/older.pdf"),
+                documents.join("old-client-work"),
+                documents.join("taxes-2019.pdf"),
+            ]
+        );
+        let folder = found.iter().find(|f| f.is_directory).unwrap();
+        assert_eq!(folder.file_count, 3);
+        assert_eq!(folder.size, 3 * 20 * 1024);
+        assert!(folder.reason.ends_with("years: old-client-work (3 files)"));
+        let largest = folder.extra[extra::LARGEST_FILES].as_array().unwrap();
+        assert_eq!(largest.len(), 3);
     }
 }
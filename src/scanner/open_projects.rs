@@ -0,0 +1,218 @@
+//! Projects currently open in an editor or used by a running process, which
+//! count as recently used however old their files are
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Editor state files written within this many days count as an open window
+const EDITOR_STATE_DAYS: u32 = 1;
+
+struct OpenProjects {
+    /// Folders open in VS Code or a JetBrains IDE
+    editors: Vec<PathBuf>,
+    /// Working directories of running processes
+    processes: Vec<PathBuf>,
+}
+
+static OPEN_PROJECTS: OnceLock<OpenProjects> = OnceLock::new();
+
+fn open_projects() -> &'static OpenProjects {
+    OPEN_PROJECTS.get_or_init(|| {
+        let home = dirs::home_dir();
+        // An editor opened on the home directory (or above) says nothing
+        // about individual projects
+        let is_specific = |p: &PathBuf| home.as_ref().is_none_or(|h| !h.starts_with(p));
+
+        let mut editors = vscode_folders();
+        editors.extend(jetbrains_projects());
+        editors.retain(is_specific);
+
+        // Our own working directory, and that of the shell that started us,
+        // is where the user asked to scan, not a sign of use
+        let own = std::env::current_dir().ok();
+        let mut processes = process_cwds();
+        processes.retain(|p| is_specific(p) && Some(p) != own.as_ref());
+
+        OpenProjects { editors, processes }
+    })
+}
+
+/// Whether `project_root` is open in an editor, inside a folder open in one,
+/// or the working directory of a running process (e.g. a dev server)
+pub fn is_open(project_root: &Path) -> bool {
+    let open = open_projects();
+    open.editors
+        .iter()
+        .any(|p| p.starts_with(project_root) || project_root.starts_with(p))
+        || open.processes.iter().any(|p| p.starts_with(project_root))
+}
+
+/// Working directories of running processes
+#[cfg(target_os = "linux")]
+pub fn process_cwds() -> Vec<PathBuf> {
+    let own = std::process::id().to_string();
+    let procs = match std::fs::read_dir("/proc") {
+        Ok(p) => p,
+        Err(_) => return Vec::new(),
+    };
+
+    procs
+        .flatten()
+        .filter(|entry| entry.file_name() != own.as_str())
+        .filter_map(|entry| std::fs::read_link(entry.path().join("cwd")).ok())
+        .collect()
+}
+
+/// Working directories of running processes, as reported by `lsof`
+#[cfg(target_os = "macos")]
+pub fn process_cwds() -> Vec<PathBuf> {
+    let output = std::process::Command::new("lsof")
+        .args(["-a", "-d", "cwd", "-Fn"])
+        .stderr(std::process::Stdio::null())
+        .output();
+
+    match output {
+        Ok(o) => String::from_utf8_lossy(&o.stdout)
+            .lines()
+            .filter_map(|line| line.strip_prefix('n'))
+            .map(PathBuf::from)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Process working directories aren't inspectable here
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn process_cwds() -> Vec<PathBuf> {
+    Vec::new()
+}
+
+/// Turn a `file://` URI from editor state into a path
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let path = super::percent_decode(uri.strip_prefix("file://")?)?;
+    // Windows URIs look like file:///c%3A/Users/...
+    let path = match path.strip_prefix('/') {
+        Some(rest) if cfg!(windows) => rest.to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// Folders in VS Code's (and its forks') window state, plus workspaces whose
+/// state was written within the last day
+fn vscode_folders() -> Vec<PathBuf> {
+    let config = match dirs::config_dir() {
+        Some(c) => c,
+        None => return Vec::new(),
+    };
+
+    let mut folders = Vec::new();
+    for app in ["Code", "Code - Insiders", "VSCodium", "Cursor"] {
+        let user = config.join(app).join("User");
+
+        let storage = std::fs::read_to_string(user.join("globalStorage").join("storage.json"))
+            .ok()
+            .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok());
+        if let Some(state) = storage.as_ref().map(|s| &s["windowsState"]) {
+            let windows = std::iter::once(&state["lastActiveWindow"])
+                .chain(state["openedWindows"].as_array().into_iter().flatten());
+            folders.extend(
+                windows
+                    .filter_map(|w| w["folder"].as_str())
+                    .filter_map(file_uri_to_path),
+            );
+        }
+
+        let workspaces = match std::fs::read_dir(user.join("workspaceStorage")) {
+            Ok(w) => w,
+            Err(_) => continue,
+        };
+        for workspace in workspaces.flatten() {
+            let dir = workspace.path();
+            if !super::was_modified_within_days(&dir.join("state.vscdb"), EDITOR_STATE_DAYS) {
+                continue;
+            }
+            let folder = std::fs::read_to_string(dir.join("workspace.json"))
+                .ok()
+                .and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+                .and_then(|w| w["folder"].as_str().and_then(file_uri_to_path));
+            folders.extend(folder);
+        }
+    }
+
+    folders
+}
+
+/// Projects a JetBrains IDE marks as open in `recentProjects.xml`
+fn jetbrains_projects() -> Vec<PathBuf> {
+    let (config, home) = match (dirs::config_dir(), dirs::home_dir()) {
+        (Some(c), Some(h)) => (c, h),
+        _ => return Vec::new(),
+    };
+    let products = match std::fs::read_dir(config.join("JetBrains")) {
+        Ok(p) => p,
+        Err(_) => return Vec::new(),
+    };
+
+    products
+        .flatten()
+        .filter_map(|product| {
+            std::fs::read_to_string(product.path().join("options").join("recentProjects.xml")).ok()
+        })
+        .flat_map(|xml| parse_recent_projects(&xml, &home))
+        .collect()
+}
+
+/// Entries of a `recentProjects.xml` whose meta info says `opened="true"`
+fn parse_recent_projects(xml: &str, home: &Path) -> Vec<PathBuf> {
+    xml.split("<entry key=\"")
+        .skip(1)
+        .filter_map(|entry| {
+            let (key, rest) = entry.split_once('"')?;
+            let meta = rest.split("</entry>").next().unwrap_or(rest);
+            if !meta.contains("opened=\"true\"") {
+                return None;
+            }
+            let path = key.replace("$USER_HOME$", &home.to_string_lossy());
+            Some(PathBuf::from(path))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recent_projects_only_open_entries() {
+        let xml = r#"<application>
+  <component name="RecentProjectsManager">
+    <option name="additionalInfo">
+      <map>
+        <entry key="$USER_HOME$/work/api">
+          <value><RecentProjectMetaInfo opened="true" projectWorkspaceId="a" /></value>
+        </entry>
+        <entry key="$USER_HOME$/work/old">
+          <value><RecentProjectMetaInfo projectWorkspaceId="b" /></value>
+        </entry>
+      </map>
+    </option>
+  </component>
+</application>"#;
+
+        assert_eq!(
+            parse_recent_projects(xml, Path::new("/home/me")),
+            vec![PathBuf::from("/home/me/work/api")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_uri_to_path_decodes_escapes() {
+        assert_eq!(
+            file_uri_to_path("file:///home/me/my%20app"),
+            Some(PathBuf::from("/home/me/my app"))
+        );
+        assert_eq!(file_uri_to_path("vscode-remote://ssh/x"), None);
+    }
+}
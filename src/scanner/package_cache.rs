@@ -0,0 +1,201 @@
+//! System package manager caches (Linux): APT, DNF and pacman keep every
+//! downloaded package. They are root-owned, so they are scanned only as root,
+//! and cleaned with the distro's own tool rather than deleted by hand.
+
+use super::{
+    calculate_dir_stats, get_last_modified, Category, CleanableFile, CleanupAction, ScanVisitor,
+    Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Package versions `paccache -r` keeps per package
+const PACCACHE_KEEP: usize = 3;
+
+struct PackageManager {
+    name: &'static str,
+    /// `/etc/os-release` `ID` / `ID_LIKE` values of distros using it
+    distros: &'static [&'static str],
+    cache: &'static str,
+    program: &'static str,
+    args: &'static [&'static str],
+}
+
+const PACKAGE_MANAGERS: &[PackageManager] = &[
+    PackageManager {
+        name: "APT",
+        distros: &["debian", "ubuntu"],
+        cache: "/var/cache/apt/archives",
+        program: "apt-get",
+        args: &["clean"],
+    },
+    PackageManager {
+        name: "DNF",
+        distros: &["fedora", "rhel", "centos"],
+        cache: "/var/cache/dnf",
+        program: "dnf",
+        args: &["clean", "all"],
+    },
+    PackageManager {
+        name: "pacman",
+        distros: &["arch"],
+        cache: "/var/cache/pacman/pkg",
+        program: "paccache",
+        args: &["-r"],
+    },
+];
+
+/// `ID` and `ID_LIKE` values from os-release contents
+fn parse_os_ids(os_release: &str) -> Vec<String> {
+    os_release
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("ID=")
+                .or_else(|| line.strip_prefix("ID_LIKE="))
+        })
+        .flat_map(|value| value.trim_matches('"').split_whitespace())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Package managers of the running distro
+fn detect() -> Vec<&'static PackageManager> {
+    let ids = fs::read_to_string("/etc/os-release")
+        .map(|s| parse_os_ids(&s))
+        .unwrap_or_default();
+    PACKAGE_MANAGERS
+        .iter()
+        .filter(|pm| pm.distros.iter().any(|d| ids.iter().any(|id| id == d)))
+        .collect()
+}
+
+/// Name of a pacman package file (`name-version-release-arch.pkg.tar.zst`),
+/// whose name may itself contain dashes
+fn pacman_package_name(file: &str) -> Option<&str> {
+    if !file.contains(".pkg.tar") || file.ends_with(".sig") {
+        return None;
+    }
+    let mut parts = file.rsplitn(4, '-');
+    parts.nth(3)
+}
+
+/// Package files `paccache -r` would remove: all but the newest
+/// `PACCACHE_KEEP` of each package
+fn paccache_candidates(cache: &Path) -> Vec<(PathBuf, u64)> {
+    let mut by_package: HashMap<String, Vec<(PathBuf, u64, std::time::SystemTime)>> =
+        HashMap::new();
+    for entry in fs::read_dir(cache).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let (package, metadata) = match (pacman_package_name(&name), entry.metadata()) {
+            (Some(p), Ok(m)) => (p.to_string(), m),
+            _ => continue,
+        };
+        let modified = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+        by_package
+            .entry(package)
+            .or_default()
+            .push((entry.path(), metadata.len(), modified));
+    }
+
+    by_package
+        .into_values()
+        .flat_map(|mut versions| {
+            versions.sort_by_key(|v| std::cmp::Reverse(v.2));
+            versions.into_iter().skip(PACCACHE_KEEP)
+        })
+        .map(|(path, size, _)| (path, size))
+        .collect()
+}
+
+pub struct PackageCacheScanner;
+
+impl PackageCacheScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PackageCacheScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for PackageCacheScanner {
+    fn name(&self) -> &'static str {
+        "Package Cache Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        if !cfg!(target_os = "linux") || !config.paths.system {
+            return Ok(());
+        }
+
+        for pm in detect() {
+            let cache = Path::new(pm.cache);
+            if !cache.is_dir() || config.is_excluded(cache) {
+                continue;
+            }
+
+            let (size, file_count, reason) = if pm.program == "paccache" {
+                let old = paccache_candidates(cache);
+                let size = old.iter().map(|(_, s)| s).sum();
+                let reason = format!(
+                    "{} package cache: versions beyond the newest {}",
+                    pm.name, PACCACHE_KEEP
+                );
+                (size, old.len() as u64, reason)
+            } else {
+                let stats = calculate_dir_stats(cache);
+                let reason = format!("{} package cache", pm.name);
+                (stats.size, stats.file_count, reason)
+            };
+            if size == 0 {
+                continue;
+            }
+
+            let action = if pm.program == "paccache" && !Path::new("/usr/bin/paccache").exists() {
+                CleanupAction::ReportOnly {
+                    hint: "install pacman-contrib, then run `paccache -r`".to_string(),
+                }
+            } else {
+                CleanupAction::command(pm.program, pm.args, None)
+            };
+
+            visitor.finding(CleanableFile {
+                last_accessed: get_last_modified(cache).unwrap_or_else(Utc::now),
+                is_directory: true,
+                file_count,
+                action,
+                recoverable: true,
+                ..CleanableFile::new(cache.to_path_buf(), size, Category::Cache, reason)
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distro_detection_and_pacman_names() {
+        let ids = parse_os_ids("NAME=\"Pop!_OS\"\nID=pop\nID_LIKE=\"ubuntu debian\"\n");
+        assert_eq!(ids, vec!["pop", "ubuntu", "debian"]);
+
+        assert_eq!(
+            pacman_package_name("python-numpy-1.26.4-1-x86_64.pkg.tar.zst"),
+            Some("python-numpy")
+        );
+        assert_eq!(
+            pacman_package_name("python-numpy-1.26.4-1-x86_64.pkg.tar.zst.sig"),
+            None
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Python environments outside projects: conda environments and pyenv
+//! interpreter versions, reported when unused within `min_age_days`.
+//! Project virtualenvs (`venv`, `.venv`) are build artifacts and found with
+//! the projects they belong to.
+//!
+//! Activating an environment writes nothing, so use is read from the
+//! access time of its interpreter, next to when packages last changed.
+//! pyenv versions that a project's `.python-version` pins are left alone.
+
+use super::{
+    calculate_dir_stats, get_last_accessed, get_last_modified, subdirs, Category, CleanableFile,
+    ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Where conda installers put their root, relative to the home
+const CONDA_ROOTS: &[&str] = &[
+    "miniconda3",
+    "anaconda3",
+    "miniforge3",
+    "mambaforge",
+    "opt/miniconda3",
+    "opt/anaconda3",
+    ".conda",
+];
+
+/// Conda's list of every environment it created, relative to the home
+const CONDA_ENVIRONMENTS: &str = ".conda/environments.txt";
+
+/// pyenv's root, relative to the home, unless `PYENV_ROOT` says otherwise
+const PYENV_ROOT: &str = ".pyenv";
+
+/// An environment's interpreter, relative to it
+const INTERPRETERS: &[&str] = &["bin/python", "python.exe"];
+
+/// How far below the home projects' `.python-version` files are looked for
+const PIN_DEPTH: usize = 4;
+
+/// Directories that hold no projects of their own
+const NOT_PROJECTS: &[&str] = &["node_modules", "target", "vendor", "Library"];
+
+pub struct PythonEnvScanner;
+
+impl PythonEnvScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for PythonEnvScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// When the environment at `path` was last run or changed: its interpreter's
+/// access time, and when `changes` (relative to it) was written
+fn last_used(path: &Path, changes: &str) -> Option<DateTime<Utc>> {
+    INTERPRETERS
+        .iter()
+        .map(|i| get_last_accessed(&path.join(i)))
+        .chain([get_last_modified(&path.join(changes))])
+        .flatten()
+        .max()
+}
+
+/// A finding for the environment at `path` if it wasn't used within
+/// `min_age_days`; `what` starts the reason
+fn unused(
+    path: PathBuf,
+    what: String,
+    last_used: Option<DateTime<Utc>>,
+    recoverable: bool,
+    config: &Config,
+) -> Option<CleanableFile> {
+    if config.is_excluded(&path) {
+        return None;
+    }
+    let last_used = last_used?;
+    let days = (Utc::now() - last_used).num_days();
+    if days < config.min_age_days_for(&path) as i64 {
+        return None;
+    }
+    let stats = calculate_dir_stats(&path);
+    if stats.size == 0 {
+        return None;
+    }
+
+    Some(CleanableFile {
+        last_accessed: last_used,
+        is_directory: true,
+        file_count: stats.file_count,
+        recoverable,
+        ..CleanableFile::new(
+            path,
+            stats.size,
+            Category::BuildArtifact,
+            format!("{}, not used in {} days", what, days),
+        )
+    })
+}
+
+/// Conda environments: those in each root's `envs`, plus ones created
+/// elsewhere that conda listed in `environments`, if they are `within` that
+/// directory. A conda root's own base environment is left alone.
+fn conda_envs(roots: &[PathBuf], environments: &Path, within: Option<&Path>) -> Vec<PathBuf> {
+    let listed = fs::read_to_string(environments).unwrap_or_default();
+    let listed = listed
+        .lines()
+        .map(|l| PathBuf::from(l.trim()))
+        .filter(|env| env.is_absolute() && within.is_none_or(|w| env.starts_with(w)));
+    let mut seen = HashSet::new();
+    roots
+        .iter()
+        .flat_map(|root| subdirs(&root.join("envs")))
+        .chain(listed)
+        .filter(|env| env.join("conda-meta").is_dir() && !env.join("condabin").is_dir())
+        .filter(|env| seen.insert(env.canonicalize().unwrap_or_else(|_| env.clone())))
+        .collect()
+}
+
+/// The versions a pyenv `version` or `.python-version` file names
+fn version_names(contents: &str) -> impl Iterator<Item = String> + '_ {
+    contents
+        .lines()
+        .filter(|l| !l.trim_start().starts_with('#'))
+        .flat_map(str::split_whitespace)
+        .map(String::from)
+}
+
+/// The versions projects pin in `.python-version` files, up to `PIN_DEPTH`
+/// levels below `dir`
+fn pinned_versions(dir: &Path, config: &Config) -> HashSet<String> {
+    let mut pins = HashSet::new();
+    let mut walker = WalkDir::new(dir).max_depth(PIN_DEPTH).into_iter();
+    while let Some(entry) = walker.next() {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        if !entry.file_type().is_dir() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy();
+        let skip = name.starts_with('.') || NOT_PROJECTS.contains(&name.as_ref());
+        if entry.depth() > 0 && (skip || config.is_excluded(entry.path())) {
+            walker.skip_current_dir();
+            continue;
+        }
+        if let Ok(pin) = fs::read_to_string(entry.path().join(".python-version")) {
+            pins.extend(version_names(&pin));
+        }
+    }
+    pins
+}
+
+/// Whether `pin` names `version`: exactly, by a prefix as in `3.12`, or by
+/// one of its pyenv-virtualenv environments
+fn pins(pin: &str, version: &Path, envs: &[String]) -> bool {
+    let name = version.file_name().unwrap_or_default().to_string_lossy();
+    name == pin
+        || name
+            .strip_prefix(pin)
+            .is_some_and(|rest| rest.starts_with('.'))
+        || envs.iter().any(|e| e == pin)
+}
+
+/// The names of the pyenv-virtualenv environments inside `version`
+fn virtualenvs(version: &Path) -> Vec<String> {
+    subdirs(&version.join("envs"))
+        .iter()
+        .filter_map(|e| e.file_name())
+        .map(|n| n.to_string_lossy().to_string())
+        .collect()
+}
+
+/// pyenv's versions, other than the global ones in its `version` file and
+/// those `pinned` by projects, with their virtualenvs. pyenv-virtualenv's
+/// environments live inside a version and go with it.
+fn pyenv_versions(pyenv_root: &Path, pinned: &HashSet<String>) -> Vec<(PathBuf, Vec<String>)> {
+    let global = fs::read_to_string(pyenv_root.join("version")).unwrap_or_default();
+    let global: Vec<String> = version_names(&global).collect();
+    subdirs(&pyenv_root.join("versions"))
+        .into_iter()
+        .map(|v| {
+            let envs = virtualenvs(&v);
+            (v, envs)
+        })
+        .filter(|(v, envs)| !global.iter().chain(pinned).any(|pin| pins(pin, v, envs)))
+        .collect()
+}
+
+/// Findings for the conda environments in `conda_roots` (and listed in
+/// `environments`, `within` a directory) and the pyenv versions in
+/// `pyenv_root` that aren't `pinned`
+fn scan_envs(
+    conda_roots: &[PathBuf],
+    environments: &Path,
+    within: Option<&Path>,
+    pyenv_root: &Path,
+    pinned: &HashSet<String>,
+    config: &Config,
+    visitor: &mut dyn ScanVisitor,
+) -> Result<()> {
+    for env in conda_envs(conda_roots, environments, within) {
+        visitor.progress(&env);
+        let what = format!(
+            "Conda environment {}",
+            env.file_name().unwrap_or_default().to_string_lossy()
+        );
+        let last_used = last_used(&env, "conda-meta/history");
+        if let Some(file) = unused(env, what, last_used, false, config) {
+            visitor.finding(file)?;
+        }
+    }
+
+    for (version, envs) in pyenv_versions(pyenv_root, pinned) {
+        visitor.progress(&version);
+        let mut what = format!(
+            "pyenv Python {}",
+            version.file_name().unwrap_or_default().to_string_lossy()
+        );
+        if !envs.is_empty() {
+            what.push_str(&format!(" and its virtualenv(s) {}", envs.join(", ")));
+        }
+        // `pyenv install` brings a version back, but not the virtualenvs
+        // made from it. Installing a package with scripts adds to `bin`.
+        let last_used = last_used(&version, "bin");
+        if let Some(file) = unused(version, what, last_used, envs.is_empty(), config) {
+            visitor.finding(file)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Scanner for PythonEnvScanner {
+    fn name(&self) -> &'static str {
+        "Python Environment Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let Some(home) = &config.paths.home else {
+            return Ok(());
+        };
+        let conda_roots: Vec<PathBuf> = CONDA_ROOTS.iter().map(|r| home.join(r)).collect();
+        // Environments elsewhere are only looked at when scans may leave the home
+        let within = (!config.paths.system).then_some(home.as_path());
+        let pyenv_root = std::env::var_os("PYENV_ROOT")
+            .filter(|_| config.paths.system)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(PYENV_ROOT));
+
+        let pinned = if pyenv_root.join("versions").is_dir() {
+            pinned_versions(home, config)
+        } else {
+            HashSet::new()
+        };
+
+        scan_envs(
+            &conda_roots,
+            &home.join(CONDA_ENVIRONMENTS),
+            within,
+            &pyenv_root,
+            &pinned,
+            config,
+            visitor,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{File, FileTimes};
+    use std::time::{Duration, SystemTime};
+
+    /// Write `path` and date it and its parents below `root` `days` back
+    fn write_aged(root: &Path, path: &str, days: u64) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, b"data").unwrap();
+        let then = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+        let times = FileTimes::new().set_accessed(then).set_modified(then);
+        let below_root = |p: &&Path| p.starts_with(root) && *p != root;
+        for entry in path.ancestors().take_while(below_root) {
+            File::open(entry).unwrap().set_times(times).unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unused_conda_envs_and_pyenv_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        for (path, days) in [
+            // The base environment
+            ("miniconda3/conda-meta/history", 200),
+            ("miniconda3/condabin/conda", 200),
+            ("miniconda3/bin/python", 200),
+            ("miniconda3/envs/old-ml/conda-meta/history", 200),
+            ("miniconda3/envs/old-ml/bin/python", 150),
+            ("miniconda3/envs/current/conda-meta/history", 200),
+            ("miniconda3/envs/current/bin/python", 0),
+            ("work/env/conda-meta/history", 90),
+            ("pyenv/versions/3.8.18/lib/python3.8/os.py", 100),
+            ("pyenv/versions/3.8.18/bin/python", 100),
+            ("pyenv/versions/3.12.1/bin/python", 100),
+            ("pyenv/versions/3.10.4/bin/python", 100),
+            ("pyenv/versions/3.10.4/envs/tools/bin/python", 100),
+            // Pinned by projects, by version and by virtualenv
+            ("pyenv/versions/3.9.18/bin/python", 100),
+            ("pyenv/versions/3.11.2/bin/python", 100),
+            ("pyenv/versions/3.11.2/envs/webapp/bin/python", 100),
+            ("work/app/.python-version", 0),
+            ("work/site/.python-version", 0),
+        ] {
+            write_aged(root, path, days);
+        }
+        fs::write(
+            root.join("environments.txt"),
+            format!(
+                "{}\n{}\n/opt/shared/env\n",
+                root.join("miniconda3").display(),
+                root.join("work/env").display()
+            ),
+        )
+        .unwrap();
+        fs::write(root.join("pyenv/version"), "3.12.1\n").unwrap();
+        fs::write(root.join("work/app/.python-version"), "3.9\n").unwrap();
+        fs::write(root.join("work/site/.python-version"), "# pyenv\nwebapp\n").unwrap();
+        let pinned = pinned_versions(root, &Config::default());
+
+        let mut found: Vec<CleanableFile> = Vec::new();
+        scan_envs(
+            &[root.join("miniconda3")],
+            &root.join("environments.txt"),
+            Some(root),
+            &root.join("pyenv"),
+            &pinned,
+            &Config::default(),
+            &mut found,
+        )
+        .unwrap();
+
+        let summary: Vec<(&Path, &str)> = found
+            .iter()
+            .map(|f| (f.path.strip_prefix(root).unwrap(), f.reason.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    Path::new("miniconda3/envs/old-ml"),
+                    "Conda environment old-ml, not used in 150 days"
+                ),
+                (
+                    Path::new("work/env"),
+                    "Conda environment env, not used in 90 days"
+                ),
+                (
+                    Path::new("pyenv/versions/3.10.4"),
+                    "pyenv Python 3.10.4 and its virtualenv(s) tools, not used in 100 days"
+                ),
+                (
+                    Path::new("pyenv/versions/3.8.18"),
+                    "pyenv Python 3.8.18, not used in 100 days"
+                ),
+            ]
+        );
+        assert!(!found[0].recoverable);
+        // Reinstalling the version doesn't bring its virtualenvs back
+        assert!(!found[2].recoverable);
+        assert!(found[3].recoverable);
+    }
+}
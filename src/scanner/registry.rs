@@ -0,0 +1,485 @@
+//! The set of scanners a scan can run. Built-in scanners register
+//! themselves with their category and platform; embedding crates can add
+//! their own before scanning.
+
+use super::{
+    android::AndroidScanner,
+    bazel::BazelScanner,
+    browsers::BrowserCacheScanner,
+    build_artifacts::{BuildArtifactsScanner, GlobalCacheScanner},
+    cache::{CacheScanner, KnownCacheScanner},
+    ci_runner::CiRunnerScanner,
+    desktop::DesktopScanner,
+    docker::DockerScanner,
+    downloads::DownloadsScanner,
+    duplicates::DuplicatesScanner,
+    electron::ElectronCacheScanner,
+    git::GitScanner,
+    homebrew::HomebrewScanner,
+    junk::JunkScanner,
+    large_files::LargeFilesScanner,
+    logs::LogsScanner,
+    metadata_junk::MetadataJunkScanner,
+    nix::NixStoreScanner,
+    old_files::OldFilesScanner,
+    package_cache::PackageCacheScanner,
+    python_envs::PythonEnvScanner,
+    simulators::SimulatorScanner,
+    systemd::{CoredumpScanner, JournalScanner},
+    temp::TempScanner,
+    trash::TrashScanner,
+    versioned_cache::VersionedCacheScanner,
+    volumes::VolumeIndexScanner,
+    windows::{ComponentStoreScanner, RecycleBinScanner, WindowsUpdateScanner},
+    wsl::WslDiskScanner,
+    Scanner,
+};
+use crate::cli::{ScanCategory, ScanOptions};
+use crate::config::Config;
+use crate::owner;
+
+/// Operating systems a scanner runs on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    Any,
+    Linux,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    /// Whether this is the platform duster is running on
+    pub fn is_current(&self) -> bool {
+        match self {
+            Platform::Any => true,
+            Platform::Linux => cfg!(target_os = "linux"),
+            Platform::MacOs => cfg!(target_os = "macos"),
+            Platform::Windows => cfg!(windows),
+        }
+    }
+}
+
+/// A registered scanner and when it runs
+pub struct Registration {
+    scanner: Box<dyn Scanner>,
+    /// Name for `--scanner` / `--no-scanner` and `scanners.disabled`
+    pub id: &'static str,
+    /// Category flag that selects the scanner
+    pub category: ScanCategory,
+    /// Whether a bare scan or `--all` runs it; otherwise only its category
+    /// flag does
+    pub default_enabled: bool,
+    pub platform: Platform,
+    /// Only run when duster runs as root
+    pub root_only: bool,
+}
+
+impl Registration {
+    pub fn name(&self) -> &'static str {
+        self.scanner.name()
+    }
+
+    /// Run only on `platform`
+    pub fn platform(&mut self, platform: Platform) -> &mut Self {
+        self.platform = platform;
+        self
+    }
+
+    /// Run only when duster runs as root
+    pub fn root_only(&mut self) -> &mut Self {
+        self.root_only = true;
+        self
+    }
+
+    /// Leave out of bare scans and `--all`; run only when the category flag
+    /// is given
+    pub fn opt_in(&mut self) -> &mut Self {
+        self.default_enabled = false;
+        self
+    }
+
+    /// Whether a scan with `options` runs this scanner here, given all
+    /// registered scanners
+    fn is_selected(&self, options: &ScanOptions, all: &[Registration]) -> bool {
+        let is_named = |id: &str| options.scanner.iter().any(|s| s == id);
+        let category_selected = if self.default_enabled {
+            options.should_scan(self.category)
+        } else {
+            options.selects(self.category)
+        };
+        // Naming a scanner of a selected category narrows that category down
+        // to the named scanners
+        let sibling_named = all
+            .iter()
+            .any(|r| r.category == self.category && is_named(r.id));
+
+        let selected = is_named(self.id) || (category_selected && !sibling_named);
+        selected && self.platform.is_current() && (!self.root_only || owner::is_root())
+    }
+}
+
+/// Scanners in the order they were registered
+#[derive(Default)]
+pub struct ScannerRegistry {
+    registrations: Vec<Registration>,
+}
+
+impl ScannerRegistry {
+    /// An empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A registry with every built-in scanner
+    pub fn with_builtins() -> Self {
+        let mut registry = Self::new();
+
+        registry.register(
+            "cache-generic",
+            ScanCategory::Cache,
+            Box::new(CacheScanner::new()),
+        );
+        registry.register(
+            "known-cache",
+            ScanCategory::Cache,
+            Box::new(KnownCacheScanner::new()),
+        );
+        registry.register(
+            "browsers",
+            ScanCategory::Cache,
+            Box::new(BrowserCacheScanner::new()),
+        );
+        registry.register(
+            "electron-cache",
+            ScanCategory::Cache,
+            Box::new(ElectronCacheScanner::new()),
+        );
+        registry.register(
+            "versioned-cache",
+            ScanCategory::Cache,
+            Box::new(VersionedCacheScanner::new()),
+        );
+        registry.register(
+            "android",
+            ScanCategory::Cache,
+            Box::new(AndroidScanner::new()),
+        );
+        registry.register(
+            "homebrew",
+            ScanCategory::Cache,
+            Box::new(HomebrewScanner::new()),
+        );
+        registry.register(
+            "nix-store",
+            ScanCategory::Cache,
+            Box::new(NixStoreScanner::new()),
+        );
+        registry
+            .register(
+                "windows-update",
+                ScanCategory::Cache,
+                Box::new(WindowsUpdateScanner::new()),
+            )
+            .platform(Platform::Windows);
+        registry
+            .register(
+                "component-store",
+                ScanCategory::Cache,
+                Box::new(ComponentStoreScanner::new()),
+            )
+            .platform(Platform::Windows);
+        registry
+            .register(
+                "wsl-disk",
+                ScanCategory::Cache,
+                Box::new(WslDiskScanner::new()),
+            )
+            .platform(Platform::Windows);
+        registry
+            .register(
+                "volume-index",
+                ScanCategory::Cache,
+                Box::new(VolumeIndexScanner::new()),
+            )
+            .platform(Platform::MacOs);
+        registry
+            .register(
+                "simulators",
+                ScanCategory::Cache,
+                Box::new(SimulatorScanner::new()),
+            )
+            .platform(Platform::MacOs);
+        // System logs, core dumps and package caches can only be cleaned by root
+        registry
+            .register(
+                "package-cache",
+                ScanCategory::Cache,
+                Box::new(PackageCacheScanner::new()),
+            )
+            .platform(Platform::Linux)
+            .root_only();
+        registry
+            .register(
+                "journal",
+                ScanCategory::Cache,
+                Box::new(JournalScanner::new()),
+            )
+            .platform(Platform::Linux)
+            .root_only();
+        registry
+            .register(
+                "coredump",
+                ScanCategory::Cache,
+                Box::new(CoredumpScanner::new()),
+            )
+            .platform(Platform::Linux)
+            .root_only();
+
+        registry.register("trash", ScanCategory::Trash, Box::new(TrashScanner::new()));
+        registry
+            .register(
+                "recycle-bin",
+                ScanCategory::Trash,
+                Box::new(RecycleBinScanner::new()),
+            )
+            .platform(Platform::Windows);
+
+        registry.register("temp", ScanCategory::Temp, Box::new(TempScanner::new()));
+        registry.register(
+            "downloads",
+            ScanCategory::Downloads,
+            Box::new(DownloadsScanner::new()),
+        );
+        registry.register(
+            "desktop",
+            ScanCategory::Desktop,
+            Box::new(DesktopScanner::new()),
+        );
+
+        registry.register(
+            "build-artifacts",
+            ScanCategory::Build,
+            Box::new(BuildArtifactsScanner::new()),
+        );
+        registry.register(
+            "global-cache",
+            ScanCategory::Build,
+            Box::new(GlobalCacheScanner::new()),
+        );
+        registry.register("bazel", ScanCategory::Build, Box::new(BazelScanner::new()));
+        registry.register(
+            "python-envs",
+            ScanCategory::Build,
+            Box::new(PythonEnvScanner::new()),
+        );
+
+        registry.register(
+            "ci-runner",
+            ScanCategory::Ci,
+            Box::new(CiRunnerScanner::new()),
+        );
+        registry.register(
+            "large-files",
+            ScanCategory::Large,
+            Box::new(LargeFilesScanner::new()),
+        );
+        registry.register(
+            "duplicates",
+            ScanCategory::Duplicates,
+            Box::new(DuplicatesScanner::new()),
+        );
+        registry.register(
+            "old-files",
+            ScanCategory::Old,
+            Box::new(OldFilesScanner::new()),
+        );
+        registry.register("junk", ScanCategory::Junk, Box::new(JunkScanner::new()));
+        registry.register("logs", ScanCategory::Logs, Box::new(LogsScanner::new()));
+
+        registry
+            .register(
+                "metadata-junk",
+                ScanCategory::MetadataJunk,
+                Box::new(MetadataJunkScanner::new()),
+            )
+            .opt_in();
+        registry
+            .register("git", ScanCategory::Git, Box::new(GitScanner::new()))
+            .opt_in();
+        registry
+            .register("docker", ScanCategory::Docker, Box::new(DockerScanner::new()))
+            .opt_in();
+
+        registry
+    }
+
+    /// Add a scanner, run on every platform whenever `category` is scanned.
+    /// Adjust that through the returned registration.
+    pub fn register(
+        &mut self,
+        id: &'static str,
+        category: ScanCategory,
+        scanner: Box<dyn Scanner>,
+    ) -> &mut Registration {
+        self.registrations.push(Registration {
+            scanner,
+            id,
+            category,
+            default_enabled: true,
+            platform: Platform::Any,
+            root_only: false,
+        });
+        self.registrations.last_mut().unwrap()
+    }
+
+    /// Drop a registered scanner, e.g. to replace a built-in one
+    pub fn unregister(&mut self, id: &str) {
+        self.registrations.retain(|r| r.id != id);
+    }
+
+    pub fn registrations(&self) -> &[Registration] {
+        &self.registrations
+    }
+
+    /// The scanners a scan with `options` runs here, minus the config's
+    /// `scanners.disabled`. Fails if `--scanner` or `--no-scanner` names a
+    /// scanner that isn't registered.
+    pub fn into_selected(
+        self,
+        options: &ScanOptions,
+        config: &Config,
+    ) -> anyhow::Result<Vec<Box<dyn Scanner>>> {
+        let unknown: Vec<&str> = options
+            .scanner
+            .iter()
+            .chain(&options.no_scanner)
+            .filter(|id| !self.registrations.iter().any(|r| r.id == id.as_str()))
+            .map(String::as_str)
+            .collect();
+        if !unknown.is_empty() {
+            let ids: Vec<&str> = self.registrations.iter().map(|r| r.id).collect();
+            anyhow::bail!(
+                "Unknown scanner: {}. Scanners: {}",
+                unknown.join(", "),
+                ids.join(", ")
+            );
+        }
+
+        let selected: Vec<bool> = self
+            .registrations
+            .iter()
+            .map(|r| {
+                r.is_selected(options, &self.registrations)
+                    && !config.scanners.disabled.iter().any(|id| id == r.id)
+            })
+            .collect();
+
+        Ok(self
+            .registrations
+            .into_iter()
+            .zip(selected)
+            .filter(|(_, selected)| *selected)
+            .map(|(r, _)| r.scanner)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::scanner::ScanVisitor;
+    use clap::Parser;
+
+    struct Custom;
+
+    impl Scanner for Custom {
+        fn name(&self) -> &'static str {
+            "Custom Scanner"
+        }
+
+        fn scan_with(
+            &self,
+            _config: &Config,
+            _visitor: &mut dyn ScanVisitor,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn names(registry: ScannerRegistry, args: &[&str]) -> Vec<&'static str> {
+        let options = ScanOptions::try_parse_from(args).unwrap();
+        let mut config = Config::default();
+        config.apply_cli_options(&options);
+        registry
+            .into_selected(&options, &config)
+            .unwrap()
+            .iter()
+            .map(|s| s.name())
+            .collect()
+    }
+
+    #[test]
+    fn test_registry_selection() {
+        let mut registry = ScannerRegistry::with_builtins();
+        registry
+            .register("custom", ScanCategory::Large, Box::new(Custom))
+            .opt_in();
+        registry.unregister("large-files");
+
+        let all = names(registry, &["duster", "--all"]);
+        assert!(all.contains(&"Temp Scanner"));
+        assert!(!all.contains(&"Large Files Scanner"));
+        // Opt-in scanners need their flag
+        assert!(!all.contains(&"Custom Scanner"));
+        assert!(!all.contains(&"Git Scanner"));
+
+        let mut registry = ScannerRegistry::new();
+        registry
+            .register("custom", ScanCategory::Large, Box::new(Custom))
+            .opt_in();
+        registry
+            .register("custom-windows", ScanCategory::Large, Box::new(Custom))
+            .platform(Platform::Windows);
+        let expected = if cfg!(windows) { 2 } else { 1 };
+        assert_eq!(names(registry, &["duster", "--large"]).len(), expected);
+    }
+
+    #[test]
+    fn test_scanner_toggles() {
+        let builtins = ScannerRegistry::with_builtins;
+
+        // Naming one scanner of a category leaves out its siblings
+        let cache = names(
+            builtins(),
+            &["duster", "--cache", "--scanner", "known-cache"],
+        );
+        assert_eq!(cache, vec!["Known Cache Scanner"]);
+
+        // A named scanner runs on its own, like a category flag
+        let named = names(
+            builtins(),
+            &["duster", "--scanner", "global-cache", "--large"],
+        );
+        assert_eq!(named, vec!["Global Cache Scanner", "Large Files Scanner"]);
+
+        let without = names(
+            builtins(),
+            &["duster", "--build", "--no-scanner", "build-artifacts"],
+        );
+        assert!(!without.contains(&"Build Artifacts Scanner"));
+        assert!(without.contains(&"Global Cache Scanner"));
+    }
+
+    #[test]
+    fn test_unknown_scanner_ids_are_rejected() {
+        let options = ScanOptions::try_parse_from(["duster", "--scanner", "known-cahce"]).unwrap();
+        let err = ScannerRegistry::with_builtins()
+            .into_selected(&options, &Config::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.starts_with("Unknown scanner: known-cahce. Scanners: "));
+        assert!(err.contains("known-cache"));
+    }
+}
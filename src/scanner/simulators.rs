@@ -0,0 +1,300 @@
+//! Xcode simulators: devices whose runtime is gone, and runtimes no device
+//! uses any more. Both are listed by `xcrun simctl` and removed through it,
+//! since CoreSimulator keeps its own records of what exists.
+
+use super::{
+    calculate_dir_stats, get_last_modified, Category, CleanableFile, CleanupAction, ScanVisitor,
+    Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Where CoreSimulator keeps each device's data, relative to the home
+const DEVICES_DIR: &str = "Library/Developer/CoreSimulator/Devices";
+
+/// Prefix of runtime identifiers ("com.apple.CoreSimulator.SimRuntime.iOS-17-0")
+const RUNTIME_PREFIX: &str = "com.apple.CoreSimulator.SimRuntime.";
+
+pub struct SimulatorScanner;
+
+impl SimulatorScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SimulatorScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A device from `xcrun simctl list -j devices`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Device {
+    udid: String,
+    name: String,
+    #[serde(default = "available")]
+    is_available: bool,
+    #[serde(default)]
+    availability_error: Option<String>,
+    #[serde(default)]
+    last_booted_at: Option<DateTime<Utc>>,
+}
+
+fn available() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceList {
+    /// Devices by runtime identifier
+    devices: HashMap<String, Vec<Device>>,
+}
+
+/// A runtime image from `xcrun simctl runtime list -j` (Xcode 15 and later)
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Runtime {
+    identifier: String,
+    runtime_identifier: String,
+    #[serde(default)]
+    size_bytes: u64,
+    #[serde(default)]
+    path: Option<PathBuf>,
+    #[serde(default)]
+    deletable: bool,
+    #[serde(default)]
+    last_used_at: Option<DateTime<Utc>>,
+}
+
+/// Run `xcrun simctl` and return stdout, or `None` without Xcode
+fn simctl_output(args: &[&str]) -> Option<String> {
+    let output = Command::new("xcrun")
+        .arg("simctl")
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// "iOS 17.0" for "com.apple.CoreSimulator.SimRuntime.iOS-17-0"
+fn runtime_name(identifier: &str) -> String {
+    let short = identifier
+        .strip_prefix(RUNTIME_PREFIX)
+        .unwrap_or(identifier);
+    match short.split_once('-') {
+        Some((platform, version)) => format!("{} {}", platform, version.replace('-', ".")),
+        None => short.to_string(),
+    }
+}
+
+/// Findings for the unavailable devices in `devices` output, whose data
+/// sits in `devices_dir`
+fn unavailable_devices(devices: &str, devices_dir: &Path, config: &Config) -> Vec<CleanableFile> {
+    let Ok(list) = serde_json::from_str::<DeviceList>(devices) else {
+        return Vec::new();
+    };
+
+    let mut findings = Vec::new();
+    for (runtime, devices) in &list.devices {
+        for device in devices.iter().filter(|d| !d.is_available) {
+            let path = devices_dir.join(&device.udid);
+            if !path.is_dir() || config.is_excluded(&path) {
+                continue;
+            }
+            let stats = calculate_dir_stats(&path);
+            let error = device
+                .availability_error
+                .as_deref()
+                .map(|e| format!(": {}", e))
+                .unwrap_or_default();
+
+            findings.push(CleanableFile {
+                last_accessed: device
+                    .last_booted_at
+                    .or_else(|| get_last_modified(&path))
+                    .unwrap_or_else(Utc::now),
+                is_directory: true,
+                file_count: stats.file_count,
+                action: CleanupAction::command("xcrun", &["simctl", "delete", &device.udid], None),
+                // A device without its runtime can't be booted again
+                recoverable: false,
+                ..CleanableFile::new(
+                    path,
+                    stats.size,
+                    Category::Cache,
+                    format!(
+                        "Unavailable simulator: {} ({}){}",
+                        device.name,
+                        runtime_name(runtime),
+                        error
+                    ),
+                )
+            });
+        }
+    }
+    findings
+}
+
+/// Findings for the runtimes in `runtimes` output that no device in
+/// `devices` output uses
+fn unused_runtimes(runtimes: &str, devices: &str, config: &Config) -> Vec<CleanableFile> {
+    let Ok(runtimes) = serde_json::from_str::<HashMap<String, Runtime>>(runtimes) else {
+        return Vec::new();
+    };
+    let used: HashSet<String> = serde_json::from_str::<DeviceList>(devices)
+        .map(|list| {
+            list.devices
+                .into_iter()
+                .filter(|(_, devices)| !devices.is_empty())
+                .map(|(runtime, _)| runtime)
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut runtimes: Vec<Runtime> = runtimes.into_values().collect();
+    runtimes.sort_by(|a, b| a.runtime_identifier.cmp(&b.runtime_identifier));
+
+    let mut findings = Vec::new();
+    for runtime in runtimes {
+        if !runtime.deletable || used.contains(&runtime.runtime_identifier) {
+            continue;
+        }
+        let Some(path) = runtime.path else {
+            continue;
+        };
+        if config.is_excluded(&path) {
+            continue;
+        }
+
+        findings.push(CleanableFile {
+            last_accessed: runtime
+                .last_used_at
+                .or_else(|| get_last_modified(&path))
+                .unwrap_or_else(Utc::now),
+            action: CleanupAction::command(
+                "xcrun",
+                &["simctl", "runtime", "delete", &runtime.identifier],
+                None,
+            ),
+            // Xcode downloads it again when needed
+            recoverable: true,
+            ..CleanableFile::new(
+                path,
+                runtime.size_bytes,
+                Category::Cache,
+                format!(
+                    "Simulator runtime with no devices: {}",
+                    runtime_name(&runtime.runtime_identifier)
+                ),
+            )
+        });
+    }
+    findings
+}
+
+impl Scanner for SimulatorScanner {
+    fn name(&self) -> &'static str {
+        "Simulator Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let Some(home) = &config.paths.home else {
+            return Ok(());
+        };
+        // Without Xcode there are no simulators
+        let Some(devices) = simctl_output(&["list", "-j", "devices"]) else {
+            return Ok(());
+        };
+
+        for file in unavailable_devices(&devices, &home.join(DEVICES_DIR), config) {
+            visitor.finding(file)?;
+        }
+        // Runtime images are only listed by Xcode 15 and later
+        if let Some(runtimes) = simctl_output(&["runtime", "list", "-j"]) {
+            for file in unused_runtimes(&runtimes, &devices, config) {
+                visitor.finding(file)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const DEVICES: &str = r#"{"devices": {
+        "com.apple.CoreSimulator.SimRuntime.iOS-15-0": [
+            {"udid": "AAAA", "name": "iPhone 8", "isAvailable": false,
+             "availabilityError": "runtime profile not found",
+             "lastBootedAt": "2022-03-01T10:00:00Z", "state": "Shutdown"}
+        ],
+        "com.apple.CoreSimulator.SimRuntime.iOS-17-0": [
+            {"udid": "BBBB", "name": "iPhone 15", "isAvailable": true, "state": "Shutdown"}
+        ],
+        "com.apple.CoreSimulator.SimRuntime.watchOS-10-0": []
+    }}"#;
+
+    #[test]
+    fn test_unavailable_devices_and_unused_runtimes() {
+        assert_eq!(
+            runtime_name("com.apple.CoreSimulator.SimRuntime.iOS-17-0"),
+            "iOS 17.0"
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        for udid in ["AAAA", "BBBB"] {
+            fs::create_dir_all(dir.path().join(udid).join("data")).unwrap();
+            fs::write(dir.path().join(udid).join("data/app.db"), b"data").unwrap();
+        }
+
+        let config = Config::default();
+        let devices = unavailable_devices(DEVICES, dir.path(), &config);
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].path, dir.path().join("AAAA"));
+        assert_eq!(
+            devices[0].reason,
+            "Unavailable simulator: iPhone 8 (iOS 15.0): runtime profile not found"
+        );
+        assert_eq!(
+            devices[0].action.describe().unwrap(),
+            "runs `xcrun simctl delete AAAA`"
+        );
+
+        let runtimes = r#"{
+            "R17": {"identifier": "R17", "runtimeIdentifier":
+                "com.apple.CoreSimulator.SimRuntime.iOS-17-0", "sizeBytes": 7000000000,
+                "path": "/Library/Developer/CoreSimulator/Images/R17.dmg", "deletable": true},
+            "W10": {"identifier": "W10", "runtimeIdentifier":
+                "com.apple.CoreSimulator.SimRuntime.watchOS-10-0", "sizeBytes": 4000000000,
+                "path": "/Library/Developer/CoreSimulator/Images/W10.dmg", "deletable": true}
+        }"#;
+        let unused = unused_runtimes(runtimes, DEVICES, &config);
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].size, 4_000_000_000);
+        assert_eq!(
+            unused[0].reason,
+            "Simulator runtime with no devices: watchOS 10.0"
+        );
+        assert_eq!(
+            unused[0].action.describe().unwrap(),
+            "runs `xcrun simctl runtime delete W10`"
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! systemd journal and core dump reporting (Linux). Neither is deleted
+//! directly: the journal is trimmed with `journalctl --vacuum-*` so its index
+//! stays consistent, and core dumps are pruned by `systemd-tmpfiles`, which
+//! applies the same age policy `coredumpctl` expects.
+
+use super::{get_last_modified, Category, CleanableFile, CleanupAction, ScanVisitor, Scanner};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use walkdir::WalkDir;
+
+/// Persistent and volatile journal locations
+const JOURNAL_DIRS: &[&str] = &["/var/log/journal", "/run/log/journal"];
+
+/// Journal size left after vacuuming
+const JOURNAL_KEEP_BYTES: u64 = 500 * 1024 * 1024;
+
+/// Journal entries newer than this survive vacuuming
+const JOURNAL_KEEP_DAYS: u32 = 14;
+
+const COREDUMP_DIR: &str = "/var/lib/systemd/coredump";
+
+/// systemd's default tmpfiles age for core dumps
+const COREDUMP_KEEP_DAYS: u32 = 3;
+
+/// Parse a size as journalctl prints it ("1.2G", "512.0M", "0B")
+fn parse_journal_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let multiplier: u64 = match unit {
+        "" | "B" => 1,
+        "K" => 1 << 10,
+        "M" => 1 << 20,
+        "G" => 1 << 30,
+        "T" => 1 << 40,
+        _ => return None,
+    };
+    Some((number * multiplier as f64) as u64)
+}
+
+/// Total journal size from `journalctl --disk-usage` output
+fn parse_disk_usage(output: &str) -> Option<u64> {
+    output.lines().find_map(|line| {
+        let rest = line.split(" take up ").nth(1)?;
+        parse_journal_size(rest.split_whitespace().next()?)
+    })
+}
+
+fn journal_disk_usage() -> Option<u64> {
+    let output = Command::new("journalctl")
+        .arg("--disk-usage")
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    parse_disk_usage(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reports journal space beyond what vacuuming keeps
+pub struct JournalScanner;
+
+impl JournalScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for JournalScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for JournalScanner {
+    fn name(&self) -> &'static str {
+        "Journal Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        if !cfg!(target_os = "linux") || !config.paths.system {
+            return Ok(());
+        }
+        let dir = match JOURNAL_DIRS.iter().map(PathBuf::from).find(|d| d.is_dir()) {
+            Some(d) if !config.is_excluded(&d) => d,
+            _ => return Ok(()),
+        };
+        let usage = match journal_disk_usage() {
+            Some(u) if u > JOURNAL_KEEP_BYTES => u,
+            _ => return Ok(()),
+        };
+
+        visitor.finding(CleanableFile {
+            last_accessed: get_last_modified(&dir).unwrap_or_else(Utc::now),
+            is_directory: true,
+            file_count: 0,
+            action: CleanupAction::command(
+                "journalctl",
+                &[
+                    &format!("--vacuum-size={}M", JOURNAL_KEEP_BYTES >> 20),
+                    &format!("--vacuum-time={}d", JOURNAL_KEEP_DAYS),
+                ],
+                None,
+            ),
+            ..CleanableFile::new(
+                dir,
+                usage - JOURNAL_KEEP_BYTES,
+                Category::Cache,
+                format!(
+                    "systemd journal uses {} (vacuum keeps {} and the last {} days)",
+                    crate::format::format_size(usage),
+                    crate::format::format_size(JOURNAL_KEEP_BYTES),
+                    JOURNAL_KEEP_DAYS
+                ),
+            )
+        })?;
+        Ok(())
+    }
+}
+
+/// Programs whose crashes left dumps, most recent last, per `coredumpctl`
+fn crashed_programs() -> Vec<String> {
+    let output = match Command::new("coredumpctl")
+        .args(["list", "--no-legend", "--no-pager"])
+        .stderr(Stdio::null())
+        .output()
+    {
+        Ok(o) if o.status.success() => o,
+        _ => return Vec::new(),
+    };
+    parse_coredump_list(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Executables of entries whose core file is still present, from
+/// `coredumpctl list` (`TIME PID UID GID SIG COREFILE EXE [SIZE]`)
+fn parse_coredump_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            // The timestamp is "Day YYYY-MM-DD HH:MM:SS TZ"
+            let corefile = *fields.get(8)?;
+            let exe = fields.get(9)?;
+            (corefile == "present").then(|| {
+                Path::new(exe)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| exe.to_string())
+            })
+        })
+        .collect()
+}
+
+/// Reports core dumps old enough for systemd's cleanup policy
+pub struct CoredumpScanner;
+
+impl CoredumpScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for CoredumpScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for CoredumpScanner {
+    fn name(&self) -> &'static str {
+        "Core Dump Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let dir = Path::new(COREDUMP_DIR);
+        if !cfg!(target_os = "linux") || !config.paths.system {
+            return Ok(());
+        }
+        if !dir.is_dir() || config.is_excluded(dir) {
+            return Ok(());
+        }
+
+        let mut size = 0;
+        let mut count = 0;
+        for entry in WalkDir::new(dir)
+            .min_depth(1)
+            .max_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            if super::was_modified_within_days(entry.path(), COREDUMP_KEEP_DAYS) {
+                continue;
+            }
+            if let Ok(metadata) = entry.metadata() {
+                size += metadata.len();
+                count += 1;
+            }
+        }
+        if count == 0 {
+            return Ok(());
+        }
+
+        let programs = crashed_programs();
+        let latest = programs
+            .last()
+            .map(|p| format!(", latest from {}", p))
+            .unwrap_or_default();
+
+        visitor.finding(CleanableFile {
+            last_accessed: get_last_modified(dir).unwrap_or_else(Utc::now),
+            is_directory: true,
+            file_count: count,
+            action: CleanupAction::command(
+                "systemd-tmpfiles",
+                &["--clean", &format!("--prefix={}", COREDUMP_DIR)],
+                None,
+            ),
+            ..CleanableFile::new(
+                dir.to_path_buf(),
+                size,
+                Category::Cache,
+                format!(
+                    "{} core dump(s) older than {} days{}",
+                    count, COREDUMP_KEEP_DAYS, latest
+                ),
+            )
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_journal_output() {
+        assert_eq!(
+            parse_disk_usage("Archived and active journals take up 1.5G in the file system.\n"),
+            Some(1610612736)
+        );
+        assert_eq!(parse_journal_size("0B"), Some(0));
+
+        let list = "\
+Mon 2026-10-12 09:14:02 UTC 1234 1000 1000 SIGSEGV missing /usr/bin/old 1.2M
+Tue 2026-10-13 10:00:00 UTC 5678 1000 1000 SIGABRT present /usr/lib/firefox/firefox 80.5M
+";
+        assert_eq!(parse_coredump_list(list), vec!["firefox".to_string()]);
+    }
+}
@@ -1,13 +1,49 @@
 //! Temporary files scanner
 
-use super::{get_last_accessed, was_modified_within_days, Category, CleanableFile, Scanner};
+use super::{
+    get_last_accessed, read_root, was_modified_within_days, Cancelled, Category, CleanableFile,
+    DirStats, ScanVisitor, Scanner,
+};
 use crate::config::Config;
 use anyhow::Result;
 use chrono::Utc;
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+/// Path component prefixes (lowercase) that identify the application owning a temp entry
+const TEMP_OWNERS: &[(&str, &str)] = &[
+    ("cargo-install", "Cargo"),
+    ("rustc", "Rust compiler"),
+    ("com.google.chrome", "Chrome"),
+    (".com.google.chrome", "Chrome"),
+    ("com.brave.browser", "Brave"),
+    ("org.mozilla.firefox", "Firefox"),
+    ("com.microsoft.vscode", "VS Code"),
+    ("vscode-", "VS Code"),
+    ("com.tinyspeck.slackmacgap", "Slack"),
+    ("com.docker", "Docker"),
+    ("com.apple.", "macOS"),
+    ("electron", "Electron apps"),
+    ("npm-", "npm"),
+    ("yarn--", "Yarn"),
+    ("pip-", "pip"),
+    ("go-build", "Go"),
+    ("node-compile-cache", "Node.js"),
+    ("jetbrains", "JetBrains IDEs"),
+];
+
+/// Determine which application a temp entry belongs to, if recognizable
+pub fn owning_app(path: &Path) -> Option<&'static str> {
+    path.components().rev().find_map(|component| {
+        let name = component.as_os_str().to_string_lossy().to_lowercase();
+        TEMP_OWNERS
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix))
+            .map(|(_, app)| *app)
+    })
+}
+
 pub struct TempScanner;
 
 impl TempScanner {
@@ -16,9 +52,22 @@ impl TempScanner {
     }
 
     /// Get temp directories to scan
-    fn get_temp_dirs(&self) -> Vec<PathBuf> {
+    fn get_temp_dirs(&self, config: &Config) -> Vec<PathBuf> {
         let mut dirs = Vec::new();
 
+        // User-specific temp on macOS
+        if let Some(home) = &config.paths.home {
+            let user_tmp = home.join("Library").join("Caches").join("TemporaryItems");
+            if user_tmp.exists() {
+                dirs.push(user_tmp);
+            }
+        }
+
+        // Shared temp directories are outside a `--path` scan
+        if !config.paths.system {
+            return dirs;
+        }
+
         // Standard temp directories
         dirs.push(PathBuf::from("/tmp"));
         dirs.push(PathBuf::from("/var/tmp"));
@@ -31,12 +80,21 @@ impl TempScanner {
             }
         }
 
-        // User-specific temp on macOS
-        if let Some(home) = dirs::home_dir() {
-            let user_tmp = home.join("Library").join("Caches").join("TemporaryItems");
-            if user_tmp.exists() {
+        // %TEMP% and the system temp directory on Windows
+        #[cfg(windows)]
+        {
+            let user_tmp = env::temp_dir();
+            if !dirs.contains(&user_tmp) {
                 dirs.push(user_tmp);
             }
+            if let Some(root) = super::windows::system_root() {
+                dirs.push(root.join("Temp"));
+            }
+        }
+
+        // The Windows user's %TEMP% when running in WSL
+        if config.wsl_windows_paths {
+            dirs.extend(super::wsl::windows_temp_dir());
         }
 
         dirs
@@ -54,86 +112,146 @@ impl Scanner for TempScanner {
         "Temp Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
-        let temp_dirs = self.get_temp_dirs();
-
-        // Only scan files older than 1 day to avoid active temp files
-        let min_age_days = 1;
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let temp_dirs = self.get_temp_dirs(config);
 
         for temp_dir in temp_dirs {
-            if !temp_dir.exists() {
+            if read_root(&temp_dir, visitor).is_none() {
                 continue;
             }
 
-            // Walk the temp directory (limit depth to avoid going too deep)
-            for entry in WalkDir::new(&temp_dir)
-                .max_depth(3)
-                .follow_links(false)
-                .into_iter()
-                .filter_map(|e| e.ok())
-            {
-                let path = entry.path().to_path_buf();
-
-                // Skip the root temp directory itself
-                if path == temp_dir {
-                    continue;
-                }
+            visitor.progress(&temp_dir);
+            collect_stale_entries(&temp_dir, 1, config, visitor)?;
+        }
 
-                // Skip if excluded
-                if config.is_excluded(&path) {
-                    continue;
-                }
+        Ok(())
+    }
+}
 
-                // Skip recently modified files (they might be in use)
-                if was_modified_within_days(&path, min_age_days) {
-                    continue;
-                }
+/// Only consider entries older than 1 day to avoid active temp files
+const MIN_AGE_DAYS: u32 = 1;
 
-                let metadata = match entry.metadata() {
-                    Ok(m) => m,
-                    Err(_) => continue,
-                };
+/// Maximum depth to descend into partially fresh directories
+const MAX_DEPTH: usize = 3;
 
-                // Skip if we don't have read permissions
-                if metadata.permissions().readonly() {
-                    continue;
-                }
+/// Collect stale entries under `dir`. Fully stale subdirectories are reported
+/// as a whole; subdirectories with recent activity are descended into so only
+/// their stale children are listed.
+fn collect_stale_entries(
+    dir: &Path,
+    depth: usize,
+    config: &Config,
+    visitor: &mut dyn ScanVisitor,
+) -> Result<(), Cancelled> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(e) => e,
+        Err(_) => return Ok(()),
+    };
 
-                let size = metadata.len();
-                let is_dir = metadata.is_dir();
+    for entry in entries.flatten() {
+        let path = entry.path();
 
-                // Skip small files and directories
-                if size < 1024 && !is_dir {
-                    continue;
-                }
+        // Skip if excluded
+        if config.is_excluded(&path) {
+            continue;
+        }
+
+        let metadata = match std::fs::symlink_metadata(&path) {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
 
-                // Skip directories in deeper walks (we handle top-level only for dirs)
-                if is_dir && entry.depth() > 1 {
-                    continue;
+        // Skip if we don't have write permissions
+        if metadata.permissions().readonly() {
+            continue;
+        }
+
+        let (size, file_count, is_dir) = if metadata.is_dir() {
+            let (stats, has_fresh) = inspect_tree(&path, MIN_AGE_DAYS);
+            if has_fresh {
+                // Something inside is still in use; only list its stale children
+                if depth < MAX_DEPTH {
+                    collect_stale_entries(&path, depth + 1, config, visitor)?;
                 }
+                continue;
+            }
+            (stats.size, stats.file_count, true)
+        } else {
+            // Skip recently modified files (they might be in use)
+            if was_modified_within_days(&path, MIN_AGE_DAYS) {
+                continue;
+            }
+            // Skip small files
+            if metadata.len() < 1024 {
+                continue;
+            }
+            (metadata.len(), 1, false)
+        };
+
+        let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
 
-                let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
-
-                let name = path
-                    .file_name()
-                    .map(|n| n.to_string_lossy().to_string())
-                    .unwrap_or_else(|| "Unknown".to_string());
-
-                results.push(CleanableFile {
-                    path,
-                    size,
-                    category: Category::Temp,
-                    last_accessed,
-                    reason: format!("Temp file: {}", name),
-                    is_directory: is_dir,
-                });
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        let reason = match owning_app(&path) {
+            Some(app) => format!("{} temp: {}", app, name),
+            None => format!("Temp file: {}", name),
+        };
+
+        visitor.finding(CleanableFile {
+            last_accessed,
+            is_directory: is_dir,
+            file_count,
+            ..CleanableFile::new(path, size, Category::Temp, reason)
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Walk a directory tree once, returning its size and entry count and whether
+/// anything inside it (including itself) was modified within `days`
+fn inspect_tree(path: &Path, days: u32) -> (DirStats, bool) {
+    let mut stats = DirStats::default();
+    let mut has_fresh = false;
+
+    for entry in WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if !has_fresh && was_modified_within_days(entry.path(), days) {
+            has_fresh = true;
+        }
+        if entry.depth() > 0 {
+            stats.file_count += 1;
+        }
+        if entry.file_type().is_file() {
+            if let Ok(m) = entry.metadata() {
+                stats.size += m.len();
             }
         }
+    }
+
+    (stats, has_fresh)
+}
 
-        // Sort by size descending
-        results.sort_by(|a, b| b.size.cmp(&a.size));
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok(results)
+    #[test]
+    fn test_owning_app() {
+        assert_eq!(
+            owning_app(Path::new("/tmp/cargo-installAbC123")),
+            Some("Cargo")
+        );
+        assert_eq!(
+            owning_app(Path::new("/var/folders/xy/abc/T/com.google.Chrome.x1/data")),
+            Some("Chrome")
+        );
+        assert_eq!(owning_app(Path::new("/tmp/XXXXXX")), None);
     }
 }
@@ -1,10 +1,74 @@
-//! Trash bin scanner
+//! Trash bin scanner, and where trash lives: other scanners skip these
+//! folders so trashed items are only ever counted as Trash
 
-use super::{calculate_dir_size, get_last_accessed, Category, CleanableFile, Scanner};
+use super::{
+    calculate_dir_stats, extra, get_last_accessed, percent_decode, read_root, Category,
+    CleanableFile, ScanVisitor, Scanner,
+};
 use crate::config::Config;
 use anyhow::Result;
 use chrono::Utc;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Whether `path` is a trash folder: the home trash (`.Trash` on macOS,
+/// `.local/share/Trash` on Linux) or a volume's (`.Trashes` on macOS,
+/// `.Trash`/`.Trash-<uid>` from freedesktop, Windows' `$RECYCLE.BIN`)
+pub fn is_trash_dir(path: &Path) -> bool {
+    let name = match path.file_name() {
+        Some(n) => n.to_string_lossy(),
+        None => return false,
+    };
+    matches!(name.as_ref(), ".Trash" | ".Trashes")
+        || name.starts_with(".Trash-")
+        || name.eq_ignore_ascii_case("$RECYCLE.BIN")
+        || path.ends_with(".local/share/Trash")
+}
+
+/// Folders holding the items trashed from a home directory
+pub fn home_trash_dirs(home: &Path) -> Vec<PathBuf> {
+    if cfg!(target_os = "macos") {
+        vec![home.join(".Trash")]
+    } else if cfg!(target_os = "linux") {
+        vec![home.join(".local/share/Trash/files")]
+    } else {
+        Vec::new()
+    }
+}
+
+/// Folders holding the current user's items trashed from mounted volumes:
+/// `/Volumes/*/.Trashes/<uid>` on macOS, `<mount>/.Trash-<uid>/files` and
+/// `<mount>/.Trash/<uid>/files` under `/media` and `/run/media` on Linux
+pub fn volume_trash_dirs() -> Vec<PathBuf> {
+    let uid = match crate::owner::resolve_filter(None).ok().flatten() {
+        Some(uid) => uid,
+        None => return Vec::new(),
+    };
+    let subdirs_of = |dir: PathBuf| -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|e| e.path())
+            .collect()
+    };
+
+    let mut dirs = Vec::new();
+    if cfg!(target_os = "macos") {
+        for volume in subdirs_of(PathBuf::from("/Volumes")) {
+            dirs.push(volume.join(".Trashes").join(uid.to_string()));
+        }
+    } else if cfg!(target_os = "linux") {
+        let user = crate::owner::user_name(uid);
+        for parent in [PathBuf::from("/media"), PathBuf::from("/run/media")] {
+            for mount in subdirs_of(parent.join(&user)) {
+                dirs.push(mount.join(format!(".Trash-{}", uid)).join("files"));
+                dirs.push(mount.join(".Trash").join(uid.to_string()).join("files"));
+            }
+        }
+    }
+    dirs.retain(|d| d.is_dir());
+    dirs
+}
 
 pub struct TrashScanner;
 
@@ -13,34 +77,47 @@ impl TrashScanner {
         Self
     }
 
-    /// Get trash directories based on platform
-    fn get_trash_dirs(&self) -> Vec<PathBuf> {
-        let mut dirs = Vec::new();
-
-        if let Some(home) = dirs::home_dir() {
-            // macOS
-            #[cfg(target_os = "macos")]
-            {
-                let trash = home.join(".Trash");
-                if trash.exists() {
-                    dirs.push(trash);
-                }
-            }
-
-            // Linux
-            #[cfg(target_os = "linux")]
-            {
-                let trash = home.join(".local/share/Trash/files");
-                if trash.exists() {
-                    dirs.push(trash);
-                }
-            }
+    /// Get the trash directories that exist, with the name of the drive
+    /// each one is on when it isn't the home's
+    fn get_trash_dirs(&self, config: &Config) -> Vec<(PathBuf, Option<String>)> {
+        let mut dirs: Vec<(PathBuf, Option<String>)> = config
+            .paths
+            .trash
+            .iter()
+            .filter(|d| d.exists())
+            .map(|d| (d.clone(), None))
+            .collect();
+        if config.paths.system {
+            dirs.extend(volume_trash_dirs().into_iter().map(|d| {
+                let drive = volume_name(&d);
+                (d, drive)
+            }));
         }
-
         dirs
     }
 }
 
+/// Name of the volume a volume trash folder is on: the folder holding its
+/// `.Trashes` or `.Trash-<uid>`
+fn volume_name(trash_dir: &Path) -> Option<String> {
+    let root = trash_dir.ancestors().find(|p| is_trash_dir(p))?;
+    Some(root.parent()?.file_name()?.to_string_lossy().to_string())
+}
+
+/// Where a freedesktop trash item was deleted from, per its `.trashinfo`
+fn original_path(item: &Path) -> Option<String> {
+    let name = item.file_name()?.to_string_lossy();
+    let info = item
+        .parent()?
+        .parent()?
+        .join("info")
+        .join(format!("{}.trashinfo", name));
+    let data = std::fs::read_to_string(info).ok()?;
+    data.lines()
+        .find_map(|line| line.strip_prefix("Path="))
+        .and_then(percent_decode)
+}
+
 impl Default for TrashScanner {
     fn default() -> Self {
         Self::new()
@@ -52,14 +129,12 @@ impl Scanner for TrashScanner {
         "Trash Scanner"
     }
 
-    fn scan(&self, config: &Config) -> Result<Vec<CleanableFile>> {
-        let mut results = Vec::new();
-        let trash_dirs = self.get_trash_dirs();
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let trash_dirs = self.get_trash_dirs(config);
 
-        for trash_dir in trash_dirs {
-            let entries = match std::fs::read_dir(&trash_dir) {
-                Ok(e) => e,
-                Err(_) => continue,
+        for (trash_dir, drive) in trash_dirs {
+            let Some(entries) = read_root(&trash_dir, visitor) else {
+                continue;
             };
 
             for entry in entries.flatten() {
@@ -71,10 +146,11 @@ impl Scanner for TrashScanner {
                 }
 
                 let is_dir = path.is_dir();
-                let size = if is_dir {
-                    calculate_dir_size(&path)
+                let (size, file_count) = if is_dir {
+                    let stats = calculate_dir_stats(&path);
+                    (stats.size, stats.file_count)
                 } else {
-                    entry.metadata().map(|m| m.len()).unwrap_or(0)
+                    (entry.metadata().map(|m| m.len()).unwrap_or(0), 1)
                 };
 
                 let last_accessed = get_last_accessed(&path).unwrap_or_else(Utc::now);
@@ -84,20 +160,28 @@ impl Scanner for TrashScanner {
                     .map(|n| n.to_string_lossy().to_string())
                     .unwrap_or_else(|| "Unknown".to_string());
 
-                results.push(CleanableFile {
-                    path,
-                    size,
-                    category: Category::Trash,
+                let original = original_path(&path);
+                let finding = CleanableFile {
                     last_accessed,
-                    reason: format!("Trashed item: {}", name),
                     is_directory: is_dir,
-                });
+                    file_count,
+                    ..CleanableFile::new(
+                        path,
+                        size,
+                        Category::Trash,
+                        match &drive {
+                            Some(drive) => format!("Trashed item on {}: {}", drive, name),
+                            None => format!("Trashed item: {}", name),
+                        },
+                    )
+                };
+                visitor.finding(match original {
+                    Some(original) => finding.with_extra(extra::ORIGINAL_TRASH_PATH, original),
+                    None => finding,
+                })?;
             }
         }
 
-        // Sort by size descending
-        results.sort_by(|a, b| b.size.cmp(&a.size));
-
-        Ok(results)
+        Ok(())
     }
 }
@@ -0,0 +1,116 @@
+//! What the OS recorded about whether and when a file was opened, which is
+//! better evidence than an access time any backup or indexer can bump.
+//!
+//! Each platform plugs in a [`UsageProvider`]; [`default_provider`] picks the
+//! one for the running OS, and platforms without one report nothing.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use std::path::Path;
+use std::process::Command;
+
+/// Usage evidence for a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Usage {
+    /// Downloaded and never opened since
+    NeverOpened,
+    /// Last opened at this time
+    LastUsed(DateTime<Utc>),
+    /// Nothing recorded beyond file times
+    Unknown,
+}
+
+/// Source of usage evidence on one platform
+pub trait UsageProvider: Send + Sync {
+    fn usage(&self, path: &Path) -> Usage;
+}
+
+/// For platforms that record nothing
+pub struct NoUsageProvider;
+
+impl UsageProvider for NoUsageProvider {
+    fn usage(&self, _path: &Path) -> Usage {
+        Usage::Unknown
+    }
+}
+
+/// Quarantine flag set once Gatekeeper let the user open the file
+const QUARANTINE_OPENED: u32 = 0x40;
+
+/// macOS: the `com.apple.quarantine` attribute browsers put on downloads,
+/// and Spotlight's `kMDItemLastUsedDate`. A file only counts as never
+/// opened when it carries an unopened quarantine flag and Spotlight has
+/// no last-used date for it.
+pub struct MacUsageProvider;
+
+impl UsageProvider for MacUsageProvider {
+    fn usage(&self, path: &Path) -> Usage {
+        let last_used = Command::new("mdls")
+            .args(["-raw", "-name", "kMDItemLastUsedDate"])
+            .arg(path)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .and_then(|o| parse_mdls_date(&String::from_utf8_lossy(&o.stdout)));
+        if let Some(last_used) = last_used {
+            return Usage::LastUsed(last_used);
+        }
+
+        let flags = Command::new("xattr")
+            .args(["-p", "com.apple.quarantine"])
+            .arg(path)
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .and_then(|o| parse_quarantine_flags(&String::from_utf8_lossy(&o.stdout)));
+        match flags {
+            Some(flags) if flags & QUARANTINE_OPENED == 0 => Usage::NeverOpened,
+            _ => Usage::Unknown,
+        }
+    }
+}
+
+/// The provider for the running OS
+pub fn default_provider() -> Box<dyn UsageProvider> {
+    if cfg!(target_os = "macos") {
+        Box::new(MacUsageProvider)
+    } else {
+        Box::new(NoUsageProvider)
+    }
+}
+
+/// A date from `mdls -raw` ("2024-01-01 10:00:00 +0000"); "(null)" when
+/// Spotlight has none
+fn parse_mdls_date(output: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_str(output.trim(), "%Y-%m-%d %H:%M:%S %z")
+        .map(|t| t.with_timezone(&Utc))
+        .or_else(|_| {
+            NaiveDateTime::parse_from_str(output.trim(), "%Y-%m-%d %H:%M:%S").map(|t| t.and_utc())
+        })
+        .ok()
+}
+
+/// Flags of a quarantine attribute ("0083;65a1b2c3;Safari;<uuid>")
+fn parse_quarantine_flags(value: &str) -> Option<u32> {
+    let flags = value.trim().split(';').next()?;
+    u32::from_str_radix(flags, 16).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_macos_usage_metadata() {
+        assert_eq!(
+            parse_mdls_date("2024-01-01 10:00:00 +0000\n").map(|t| t.to_rfc3339()),
+            Some("2024-01-01T10:00:00+00:00".to_string())
+        );
+        assert_eq!(parse_mdls_date("(null)"), None);
+
+        let unopened = parse_quarantine_flags("0083;65a1b2c3;Safari;ABC-123\n").unwrap();
+        assert_eq!(unopened & QUARANTINE_OPENED, 0);
+        let opened = parse_quarantine_flags("00c3;65a1b2c3;Safari;ABC-123").unwrap();
+        assert_ne!(opened & QUARANTINE_OPENED, 0);
+        assert_eq!(parse_quarantine_flags(""), None);
+    }
+}
@@ -0,0 +1,327 @@
+//! Versioned cache scanner: keeps the newest versions of caches that are laid
+//! out one directory per version and flags the older ones
+
+use super::{
+    calculate_dir_stats, get_last_modified, was_modified_within_days, Category, CleanableFile,
+    ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// A family of caches with one entry per version
+struct Family {
+    /// Key in the `keep_versions` config table
+    name: &'static str,
+    label: &'static str,
+    /// Cache roots relative to home
+    roots: &'static [&'static str],
+    /// Depth below a root at which the versioned entries live
+    depth: usize,
+}
+
+const FAMILIES: &[Family] = &[
+    // IntelliJIdea2023.2, PyCharm2024.1, ...
+    Family {
+        name: "jetbrains",
+        label: "JetBrains IDE",
+        roots: &[
+            "Library/Caches/JetBrains",
+            "Library/Logs/JetBrains",
+            ".cache/JetBrains",
+        ],
+        depth: 1,
+    },
+    // caches/8.5, caches/transforms-4, daemon/8.5, ...; wrapper
+    // distributions are reported by age in `android`
+    Family {
+        name: "gradle",
+        label: "Gradle",
+        roots: &[".gradle/caches", ".gradle/daemon"],
+        depth: 1,
+    },
+    // <hash>/electron-v28.1.0-darwin-arm64.zip
+    Family {
+        name: "electron",
+        label: "Electron",
+        roots: &["Library/Caches/electron", ".cache/electron"],
+        depth: 2,
+    },
+];
+
+/// Split a name into the version it contains and the rest, e.g.
+/// "IntelliJIdea2023.2" into ("IntelliJIdea", [2023, 2]) and
+/// "gradle-8.5-bin" into ("gradle--bin", [8, 5])
+fn split_version(name: &str) -> Option<(String, Vec<u64>)> {
+    let start = name.find(|c: char| c.is_ascii_digit())?;
+    let len = name[start..]
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(name.len() - start);
+    let token = name[start..start + len].trim_end_matches('.');
+
+    let version: Vec<u64> = token
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let key = format!("{}{}", &name[..start], &name[start + token.len()..]);
+    Some((key, version))
+}
+
+/// Entries older than the newest `keep` versions of each product, i.e. of
+/// each group of names that are the same apart from the version. Entries of
+/// the same version (e.g. one download in two hash directories) are never
+/// older than each other.
+fn stale_versions(entries: &[PathBuf], keep: usize) -> Vec<(PathBuf, PathBuf)> {
+    let mut groups: HashMap<String, Vec<(Vec<u64>, &PathBuf)>> = HashMap::new();
+    for entry in entries {
+        let name = match entry.file_name() {
+            Some(n) => n.to_string_lossy(),
+            None => continue,
+        };
+        if let Some((key, version)) = split_version(&name) {
+            groups.entry(key).or_default().push((version, entry));
+        }
+    }
+
+    let mut stale = Vec::new();
+    for (_, mut versions) in groups {
+        versions.sort_by(|a, b| b.0.cmp(&a.0));
+        let newest = versions[0].1.clone();
+        let mut distinct: Vec<&Vec<u64>> = versions.iter().map(|(v, _)| v).collect();
+        distinct.dedup();
+        let oldest_kept = distinct[(keep.max(1) - 1).min(distinct.len() - 1)].clone();
+        for (version, path) in versions {
+            if version < oldest_kept {
+                stale.push((path.clone(), newest.clone()));
+            }
+        }
+    }
+    stale
+}
+
+/// Whether anything near the top of an entry changed recently
+fn recently_used(path: &Path, days: u32) -> bool {
+    WalkDir::new(path)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .any(|e| was_modified_within_days(e.path(), days))
+}
+
+pub struct VersionedCacheScanner;
+
+impl VersionedCacheScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for VersionedCacheScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for VersionedCacheScanner {
+    fn name(&self) -> &'static str {
+        "Versioned Cache Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let home = match &config.paths.home {
+            Some(h) => h.clone(),
+            None => return Ok(()),
+        };
+
+        for family in FAMILIES {
+            let keep = config.keep_versions.get(family.name).copied().unwrap_or(1);
+
+            // Versions are compared within a root; Electron keeps each
+            // download in its own hash directory below it
+            let stale: Vec<(PathBuf, PathBuf)> = family
+                .roots
+                .iter()
+                .map(|root| home.join(root))
+                .filter(|root| root.is_dir())
+                .flat_map(|root| {
+                    let entries: Vec<PathBuf> = WalkDir::new(root)
+                        .min_depth(family.depth)
+                        .max_depth(family.depth)
+                        .into_iter()
+                        .filter_map(|e| e.ok())
+                        .map(|e| e.into_path())
+                        .collect();
+                    stale_versions(&entries, keep)
+                })
+                .collect();
+
+            for (path, newest) in stale {
+                if config.is_excluded(&path)
+                    || recently_used(&path, config.project_recent_days_for(&path))
+                {
+                    continue;
+                }
+
+                let is_directory = path.is_dir();
+                let (size, file_count) = if is_directory {
+                    let stats = calculate_dir_stats(&path);
+                    (stats.size, stats.file_count)
+                } else {
+                    (path.metadata().map(|m| m.len()).unwrap_or(0), 1)
+                };
+                if size == 0 {
+                    continue;
+                }
+
+                let name = |p: &Path| {
+                    p.file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                };
+
+                let reason = format!(
+                    "Older {} version {} (keeping {})",
+                    family.label,
+                    name(&path),
+                    name(&newest)
+                );
+                visitor.finding(CleanableFile {
+                    last_accessed: get_last_modified(&path).unwrap_or_else(Utc::now),
+                    is_directory,
+                    file_count,
+                    recoverable: true,
+                    ..CleanableFile::new(path, size, Category::Cache, reason)
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Paths;
+    use std::fs::{self, File, FileTimes};
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_split_version() {
+        assert_eq!(
+            split_version("IntelliJIdea2023.2"),
+            Some(("IntelliJIdea".to_string(), vec![2023, 2]))
+        );
+        assert_eq!(
+            split_version("gradle-8.5-bin"),
+            Some(("gradle--bin".to_string(), vec![8, 5]))
+        );
+        assert_eq!(
+            split_version("transforms-4"),
+            Some(("transforms-".to_string(), vec![4]))
+        );
+        assert_eq!(split_version("modules"), None);
+    }
+
+    #[test]
+    fn test_stale_versions_keep_newest_per_product() {
+        let root = PathBuf::from("/cache/JetBrains");
+        let entries: Vec<PathBuf> = [
+            "IntelliJIdea2023.2",
+            "IntelliJIdea2024.1",
+            "IntelliJIdea2023.10",
+            "PyCharm2024.1",
+        ]
+        .iter()
+        .map(|n| root.join(n))
+        .collect();
+
+        let mut stale: Vec<PathBuf> = stale_versions(&entries, 1)
+            .into_iter()
+            .map(|(p, _)| p)
+            .collect();
+        stale.sort();
+        assert_eq!(
+            stale,
+            vec![
+                root.join("IntelliJIdea2023.10"),
+                root.join("IntelliJIdea2023.2")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stale_versions_never_flags_equal_versions() {
+        let root = PathBuf::from("/cache/electron");
+        let entries: Vec<PathBuf> = [
+            "a1/electron-v28.1.0-darwin-arm64.zip",
+            "b2/electron-v28.1.0-darwin-arm64.zip",
+            "c3/electron-v27.0.0-darwin-arm64.zip",
+            "d4/electron-v26.2.1-darwin-arm64.zip",
+            "e5/electron-v26.2.1-darwin-arm64.zip",
+        ]
+        .iter()
+        .map(|n| root.join(n))
+        .collect();
+
+        let stale = |keep| {
+            let mut stale: Vec<PathBuf> = stale_versions(&entries, keep)
+                .into_iter()
+                .map(|(p, _)| p)
+                .collect();
+            stale.sort();
+            stale
+        };
+        assert_eq!(stale(1), entries[2..].to_vec());
+        assert_eq!(stale(2), entries[3..].to_vec());
+        assert!(stale(3).is_empty());
+        assert!(stale(5).is_empty());
+    }
+
+    #[test]
+    fn test_older_jetbrains_and_gradle_caches_are_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        let then = SystemTime::now() - Duration::from_secs(120 * 24 * 60 * 60);
+        for path in [
+            ".cache/JetBrains/IntelliJIdea2023.2/index/data",
+            ".cache/JetBrains/IntelliJIdea2024.1/index/data",
+            ".gradle/caches/8.4/generated-gradle-jars/api.jar",
+            ".gradle/caches/8.5/generated-gradle-jars/api.jar",
+            ".gradle/caches/modules-2/files-2.1/lib.jar",
+        ] {
+            let path = home.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"cached").unwrap();
+            for entry in path.ancestors().take_while(|p| *p != home) {
+                let times = FileTimes::new().set_accessed(then).set_modified(then);
+                File::open(entry).unwrap().set_times(times).unwrap();
+            }
+        }
+
+        let config = Config {
+            paths: Paths::sandboxed(home),
+            ..Config::default()
+        };
+        let mut found: Vec<CleanableFile> = Vec::new();
+        VersionedCacheScanner::new()
+            .scan_with(&config, &mut found)
+            .unwrap();
+
+        let mut paths: Vec<&Path> = found
+            .iter()
+            .map(|f| f.path.strip_prefix(home).unwrap())
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new(".cache/JetBrains/IntelliJIdea2023.2"),
+                Path::new(".gradle/caches/8.4"),
+            ]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Spotlight indexes and file system event logs on external drives. macOS
+//! writes them to every volume it mounts, where they quietly grow; they are
+//! only reported, with how to shrink them, since the OS owns both.
+
+use super::{
+    calculate_dir_stats, get_last_modified, Category, CleanableFile, CleanupAction, ScanVisitor,
+    Scanner,
+};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+/// Where macOS mounts external volumes
+const VOLUMES_DIR: &str = "/Volumes";
+
+/// Smaller indexes aren't worth a finding
+const MIN_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Hidden folders macOS keeps on each volume, what they are and how to
+/// shrink them; `{}` is the volume's path
+const VOLUME_FOLDERS: &[(&str, &str, &str)] = &[
+    (
+        ".Spotlight-V100",
+        "Spotlight index",
+        concat!(
+            "run `sudo mdutil -i off '{}'` then `sudo mdutil -X '{}'` to stop ",
+            "indexing the drive and remove the index"
+        ),
+    ),
+    (
+        ".fseventsd",
+        "File system event log",
+        concat!(
+            "macOS recreates it; create an empty `.fseventsd/no_log` file to stop ",
+            "logging on this drive, then remove the old logs with sudo"
+        ),
+    ),
+];
+
+pub struct VolumeIndexScanner;
+
+impl VolumeIndexScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for VolumeIndexScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Mounted external volumes; the startup disk appears as a symlink to `/`
+fn external_volumes(volumes_dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(volumes_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .map(|e| e.path())
+        .collect()
+}
+
+/// Findings for the index folders on each volume under `volumes_dir`
+fn scan_volumes(volumes_dir: &Path, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+    for volume in external_volumes(volumes_dir) {
+        let volume_name = volume
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        for (folder, what, hint) in VOLUME_FOLDERS {
+            let path = volume.join(folder);
+            if !path.is_dir() || config.is_excluded(&path) {
+                continue;
+            }
+            visitor.progress(&path);
+
+            let stats = calculate_dir_stats(&path);
+            if stats.size < MIN_SIZE {
+                continue;
+            }
+
+            visitor.finding(CleanableFile {
+                last_accessed: get_last_modified(&path).unwrap_or_else(Utc::now),
+                is_directory: true,
+                file_count: stats.file_count,
+                action: CleanupAction::ReportOnly {
+                    hint: hint.replace("{}", &volume.to_string_lossy()),
+                },
+                recoverable: true,
+                ..CleanableFile::new(
+                    path,
+                    stats.size,
+                    Category::Cache,
+                    format!("{} on {}", what, volume_name),
+                )
+            })?;
+        }
+    }
+    Ok(())
+}
+
+impl Scanner for VolumeIndexScanner {
+    fn name(&self) -> &'static str {
+        "Volume Index Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        if !cfg!(target_os = "macos") || !config.paths.system {
+            return Ok(());
+        }
+        scan_volumes(Path::new(VOLUMES_DIR), config, visitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_volume_indexes_are_report_only() {
+        let volumes = tempfile::tempdir().unwrap();
+        let drive = volumes.path().join("Backup");
+        fs::create_dir_all(drive.join(".Spotlight-V100/Store-V2")).unwrap();
+        fs::write(
+            drive.join(".Spotlight-V100/Store-V2/index"),
+            vec![0u8; MIN_SIZE as usize],
+        )
+        .unwrap();
+        fs::create_dir_all(drive.join(".fseventsd")).unwrap();
+        fs::write(drive.join(".fseventsd/0001"), b"small").unwrap();
+        // The startup disk's link to `/` is not an external drive
+        std::os::unix::fs::symlink(drive.join("missing"), volumes.path().join("Macintosh HD"))
+            .unwrap();
+
+        let mut found: Vec<CleanableFile> = Vec::new();
+        scan_volumes(volumes.path(), &Config::default(), &mut found).unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, drive.join(".Spotlight-V100"));
+        assert_eq!(found[0].reason, "Spotlight index on Backup");
+        match &found[0].action {
+            CleanupAction::ReportOnly { hint } => {
+                assert!(hint.contains(&format!("mdutil -X '{}'", drive.display())))
+            }
+            other => panic!("expected a report-only finding, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,456 @@
+//! Windows-native scanners: the Recycle Bin through the shell, the Windows
+//! Update download cache, and WinSxS component store reporting via DISM.
+//! They find nothing on other platforms.
+
+use super::{
+    calculate_dir_stats, extra, get_last_modified, read_root, was_modified_within_days, Category,
+    CleanableFile, CleanupAction, ScanVisitor, Scanner,
+};
+use crate::config::Config;
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Run a PowerShell script and return its stdout
+pub(super) fn powershell(script: &str) -> Result<String> {
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", script])
+        .output()
+        .context("Failed to run PowerShell")?;
+    if !output.status.success() {
+        bail!(
+            "PowerShell failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Quote a string for a single-quoted PowerShell literal
+pub(super) fn ps_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Lists the Recycle Bin (shell namespace 10) as JSON
+const LIST_RECYCLE_BIN: &str = r#"
+$bin = (New-Object -ComObject Shell.Application).NameSpace(10)
+@($bin.Items() | ForEach-Object {
+    $deleted = $_.ExtendedProperty('System.Recycle.DateDeleted')
+    [pscustomobject]@{
+        Name = $_.Name
+        Path = $_.Path
+        Size = [uint64]$_.ExtendedProperty('System.Size')
+        DeletedFrom = $_.ExtendedProperty('System.Recycle.DeletedFrom')
+        DateDeleted = if ($deleted) { $deleted.ToUniversalTime().ToString('o') } else { $null }
+    }
+}) | ConvertTo-Json -Compress
+"#;
+
+/// An item in the Recycle Bin
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RecycledItem {
+    /// Display name, which may hide the extension
+    pub name: String,
+    /// Where the item is stored (`$Recycle.Bin\<SID>\$R...`)
+    pub path: PathBuf,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub deleted_from: Option<PathBuf>,
+    #[serde(default)]
+    pub date_deleted: Option<DateTime<Utc>>,
+}
+
+impl RecycledItem {
+    /// Path the item had before it was deleted
+    pub fn original_path(&self) -> PathBuf {
+        let mut name = PathBuf::from(&self.name);
+        // Explorer may hide known extensions; the stored copy keeps it
+        if name.extension().is_none() {
+            if let Some(ext) = self.path.extension() {
+                name.set_extension(ext);
+            }
+        }
+        match &self.deleted_from {
+            Some(dir) => dir.join(name),
+            None => name,
+        }
+    }
+
+    /// The `$I` file holding this item's metadata, next to its `$R` data
+    fn index_path(&self) -> Option<PathBuf> {
+        let name = self.path.file_name()?.to_string_lossy();
+        let rest = name.strip_prefix("$R")?;
+        Some(self.path.with_file_name(format!("$I{}", rest)))
+    }
+}
+
+/// Parse `ConvertTo-Json` output, which is a bare object for a single item
+fn parse_recycle_bin(json: &str) -> Vec<RecycledItem> {
+    let json = json.trim();
+    if json.is_empty() {
+        return Vec::new();
+    }
+    serde_json::from_str::<Vec<RecycledItem>>(json)
+        .or_else(|_| serde_json::from_str::<RecycledItem>(json).map(|item| vec![item]))
+        .unwrap_or_default()
+}
+
+/// Items in the Recycle Bin, empty on other platforms
+pub fn recycle_bin_items() -> Result<Vec<RecycledItem>> {
+    if !cfg!(windows) {
+        return Ok(Vec::new());
+    }
+    Ok(parse_recycle_bin(&powershell(LIST_RECYCLE_BIN)?))
+}
+
+/// Restore a Recycle Bin item to its original location. `target` is the
+/// item's original path or its stored `$R` path.
+pub fn restore_recycled(target: &Path) -> Result<RecycledItem> {
+    if !cfg!(windows) {
+        bail!("The Recycle Bin is only available on Windows");
+    }
+
+    let item = recycle_bin_items()?
+        .into_iter()
+        .find(|i| i.path == target || i.original_path() == target)
+        .with_context(|| format!("Not in the Recycle Bin: {}", target.display()))?;
+
+    let script = format!(
+        "$bin = (New-Object -ComObject Shell.Application).NameSpace(10)\n\
+         $item = $bin.Items() | Where-Object {{ $_.Path -eq {} }} | Select-Object -First 1\n\
+         if (-not $item) {{ exit 2 }}\n\
+         $item.InvokeVerb('undelete')",
+        ps_quote(&item.path.to_string_lossy())
+    );
+    powershell(&script)?;
+
+    Ok(item)
+}
+
+pub struct RecycleBinScanner;
+
+impl RecycleBinScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for RecycleBinScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for RecycleBinScanner {
+    fn name(&self) -> &'static str {
+        "Recycle Bin Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        if !config.paths.system {
+            return Ok(());
+        }
+        for item in recycle_bin_items()? {
+            let original = item.original_path();
+            if config.is_excluded(&item.path) || config.is_excluded(&original) {
+                continue;
+            }
+
+            let is_directory = item.path.is_dir();
+            let (size, file_count) = if is_directory {
+                let stats = calculate_dir_stats(&item.path);
+                (stats.size, stats.file_count)
+            } else {
+                (item.size, 1)
+            };
+
+            // Removing both halves keeps the bin's index consistent
+            let mut targets = vec![ps_quote(&item.path.to_string_lossy())];
+            targets.extend(item.index_path().map(|p| ps_quote(&p.to_string_lossy())));
+            let remove = format!(
+                "Remove-Item -LiteralPath {} -Recurse -Force",
+                targets.join(",")
+            );
+
+            let finding = CleanableFile {
+                last_accessed: item
+                    .date_deleted
+                    .or_else(|| get_last_modified(&item.path))
+                    .unwrap_or_else(Utc::now),
+                is_directory,
+                file_count,
+                action: CleanupAction::command(
+                    "powershell",
+                    &["-NoProfile", "-NonInteractive", "-Command", &remove],
+                    None,
+                ),
+                ..CleanableFile::new(
+                    item.path,
+                    size,
+                    Category::Trash,
+                    format!("Recycle Bin item: {}", original.display()),
+                )
+            };
+            visitor.finding(
+                finding.with_extra(extra::ORIGINAL_TRASH_PATH, original.display().to_string()),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// `%SystemRoot%`, e.g. `C:\Windows`
+pub fn system_root() -> Option<PathBuf> {
+    std::env::var_os("SystemRoot").map(PathBuf::from)
+}
+
+/// Where Windows Update keeps downloaded update payloads
+pub fn update_download_dir() -> Option<PathBuf> {
+    system_root().map(|root| root.join("SoftwareDistribution").join("Download"))
+}
+
+pub struct WindowsUpdateScanner;
+
+impl WindowsUpdateScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for WindowsUpdateScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for WindowsUpdateScanner {
+    fn name(&self) -> &'static str {
+        "Windows Update Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        if !cfg!(windows) || !config.paths.system {
+            return Ok(());
+        }
+        let download_dir = match update_download_dir() {
+            Some(d) => d,
+            None => return Ok(()),
+        };
+        let Some(entries) = read_root(&download_dir, visitor) else {
+            return Ok(());
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            // Recent entries may belong to an update being installed
+            if config.is_excluded(&path) || was_modified_within_days(&path, 1) {
+                continue;
+            }
+
+            let is_directory = path.is_dir();
+            let (size, file_count) = if is_directory {
+                let stats = calculate_dir_stats(&path);
+                (stats.size, stats.file_count)
+            } else {
+                (entry.metadata().map(|m| m.len()).unwrap_or(0), 1)
+            };
+            if size == 0 {
+                continue;
+            }
+
+            visitor.finding(CleanableFile {
+                last_accessed: get_last_modified(&path).unwrap_or_else(Utc::now),
+                is_directory,
+                file_count,
+                recoverable: true,
+                ..CleanableFile::new(
+                    path,
+                    size,
+                    Category::Cache,
+                    "Windows Update download (needs administrator to delete)",
+                )
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parse a DISM size like "7.85 GB" or "0 bytes"
+fn parse_dism_size(text: &str) -> Option<u64> {
+    let (number, unit) = text.trim().split_once(' ')?;
+    let value: f64 = number.replace(',', "").parse().ok()?;
+    let scale: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "bytes" | "b" => 1,
+        "kb" => 1 << 10,
+        "mb" => 1 << 20,
+        "gb" => 1 << 30,
+        "tb" => 1 << 40,
+        _ => return None,
+    };
+    Some((value * scale as f64) as u64)
+}
+
+/// What `/AnalyzeComponentStore` reports
+#[derive(Debug, PartialEq)]
+struct ComponentStoreAnalysis {
+    actual_size: u64,
+    /// Backups, disabled features, cache and temporary data
+    reclaimable: u64,
+    cleanup_recommended: bool,
+}
+
+fn parse_component_store(output: &str) -> Option<ComponentStoreAnalysis> {
+    let field = |label: &str| {
+        output
+            .lines()
+            .find(|l| l.trim_start().starts_with(label))
+            .and_then(|l| l.split_once(':'))
+            .map(|(_, value)| value.trim().to_string())
+    };
+    let size = |label: &str| field(label).and_then(|v| parse_dism_size(&v));
+
+    Some(ComponentStoreAnalysis {
+        actual_size: size("Actual Size of Component Store")?,
+        reclaimable: size("Backups and Disabled Features").unwrap_or(0)
+            + size("Cache and Temporary Data").unwrap_or(0),
+        cleanup_recommended: field("Component Store Cleanup Recommended")
+            .is_some_and(|v| v.eq_ignore_ascii_case("yes")),
+    })
+}
+
+/// Reports what DISM could reclaim from the WinSxS component store. WinSxS
+/// must never be deleted by hand, so the finding is report-only.
+pub struct ComponentStoreScanner;
+
+impl ComponentStoreScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for ComponentStoreScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for ComponentStoreScanner {
+    fn name(&self) -> &'static str {
+        "WinSxS Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        let winsxs = match system_root().filter(|_| cfg!(windows) && config.paths.system) {
+            Some(root) => root.join("WinSxS"),
+            None => return Ok(()),
+        };
+
+        // Needs an elevated prompt; otherwise DISM exits with an error
+        let output = match Command::new("Dism.exe")
+            .args([
+                "/Online",
+                "/Cleanup-Image",
+                "/AnalyzeComponentStore",
+                "/English",
+            ])
+            .output()
+        {
+            Ok(o) if o.status.success() => String::from_utf8_lossy(&o.stdout).to_string(),
+            _ => return Ok(()),
+        };
+        let analysis = match parse_component_store(&output) {
+            Some(a) if a.reclaimable > 0 => a,
+            _ => return Ok(()),
+        };
+
+        let recommended = if analysis.cleanup_recommended {
+            "cleanup recommended"
+        } else {
+            "cleanup not yet recommended"
+        };
+
+        visitor.finding(CleanableFile {
+            last_accessed: get_last_modified(&winsxs).unwrap_or_else(Utc::now),
+            is_directory: true,
+            file_count: 0,
+            action: CleanupAction::ReportOnly {
+                hint:
+                    "run `Dism.exe /Online /Cleanup-Image /StartComponentCleanup` as administrator"
+                        .to_string(),
+            },
+            recoverable: true,
+            ..CleanableFile::new(
+                winsxs,
+                analysis.reclaimable,
+                Category::Cache,
+                format!(
+                    "WinSxS component store ({} total, {})",
+                    crate::format::format_size(analysis.actual_size),
+                    recommended
+                ),
+            )
+        })?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_recycle_bin_single_and_many() {
+        let one = r#"{"Name":"report","Path":"C:\\$Recycle.Bin\\S-1-5-21\\$RAB12CD.docx","Size":2048,"DeletedFrom":"C:\\Users\\me\\Documents","DateDeleted":"2024-05-01T10:00:00.0000000Z"}"#;
+        let mut items = parse_recycle_bin(one);
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].size, 2048);
+        assert!(items[0].date_deleted.is_some());
+
+        // Use this platform's separator for the path checks
+        let bin = PathBuf::from("bin");
+        items[0].path = bin.join("$RAB12CD.docx");
+        items[0].deleted_from = Some(PathBuf::from("docs"));
+        assert_eq!(items[0].index_path(), Some(bin.join("$IAB12CD.docx")));
+        assert_eq!(
+            items[0].original_path(),
+            PathBuf::from("docs").join("report.docx")
+        );
+
+        let many = format!("[{},{}]", one, one);
+        assert_eq!(parse_recycle_bin(&many).len(), 2);
+        assert!(parse_recycle_bin("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_component_store() {
+        let output = "\
+Component Store (WinSxS) information:
+
+Windows Explorer Reported Size of Component Store : 8.12 GB
+
+Actual Size of Component Store : 7.85 GB
+
+    Shared with Windows : 5.60 GB
+    Backups and Disabled Features : 1.50 GB
+    Cache and Temporary Data : 512.00 MB
+
+Number of Reclaimable Packages : 2
+Component Store Cleanup Recommended : Yes
+";
+        assert_eq!(
+            parse_component_store(output),
+            Some(ComponentStoreAnalysis {
+                actual_size: (7.85 * (1u64 << 30) as f64) as u64,
+                reclaimable: (1.5 * (1u64 << 30) as f64) as u64 + 512 * (1 << 20),
+                cleanup_recommended: true,
+            })
+        );
+        assert_eq!(parse_dism_size("0 bytes"), Some(0));
+    }
+}
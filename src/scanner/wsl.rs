@@ -0,0 +1,269 @@
+//! WSL awareness: inside WSL, the Windows user's Downloads and Temp folders
+//! under `/mnt/c`; on Windows, WSL distro disks (`ext4.vhdx`) that have grown
+//! well past what the distro uses and would shrink if compacted
+
+use super::{get_last_modified, Category, CleanableFile, CleanupAction, ScanVisitor, Scanner};
+use crate::config::Config;
+use anyhow::Result;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+/// Disks with less unused space than this aren't worth compacting
+const MIN_VHDX_SLACK: u64 = 1024 * 1024 * 1024;
+
+/// Whether we are running inside WSL
+pub fn is_wsl() -> bool {
+    static IS_WSL: OnceLock<bool> = OnceLock::new();
+    *IS_WSL.get_or_init(|| {
+        cfg!(target_os = "linux")
+            && (Path::new("/proc/sys/fs/binfmt_misc/WSLInterop").exists()
+                || std::fs::read_to_string("/proc/sys/kernel/osrelease")
+                    .is_ok_and(|r| r.to_lowercase().contains("microsoft")))
+    })
+}
+
+/// The Windows user profile as seen from WSL (e.g. `/mnt/c/Users/Ada`)
+pub fn windows_profile() -> Option<PathBuf> {
+    static PROFILE: OnceLock<Option<PathBuf>> = OnceLock::new();
+    PROFILE
+        .get_or_init(|| {
+            if !is_wsl() {
+                return None;
+            }
+            // The Windows user name often differs from the Linux one, so ask
+            // Windows; fall back to a same-named profile
+            let profile = windows_env("USERPROFILE")
+                .and_then(|p| to_wsl_path(&p))
+                .or_else(|| {
+                    let user = std::env::var("USER").ok()?;
+                    Some(Path::new("/mnt/c/Users").join(user))
+                })?;
+            profile.is_dir().then_some(profile)
+        })
+        .clone()
+}
+
+/// Windows-side folders scanned from WSL when `wsl_windows_paths` is on
+pub fn windows_downloads_dir() -> Option<PathBuf> {
+    windows_profile().map(|p| p.join("Downloads"))
+}
+
+pub fn windows_temp_dir() -> Option<PathBuf> {
+    windows_profile().map(|p| p.join("AppData").join("Local").join("Temp"))
+}
+
+/// Read a Windows environment variable through interop
+fn windows_env(name: &str) -> Option<String> {
+    let output = Command::new("cmd.exe")
+        .args(["/c", &format!("echo %{}%", name)])
+        .current_dir("/mnt/c")
+        .output()
+        .ok()?;
+    let value = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !value.is_empty() && !value.contains('%')).then_some(value)
+}
+
+/// Map a Windows path into WSL with `wslpath`, or by drive letter
+fn to_wsl_path(windows: &str) -> Option<PathBuf> {
+    let output = Command::new("wslpath").args(["-u", windows]).output().ok();
+    if let Some(o) = output.filter(|o| o.status.success()) {
+        let path = String::from_utf8_lossy(&o.stdout).trim().to_string();
+        if !path.is_empty() {
+            return Some(PathBuf::from(path));
+        }
+    }
+    drive_path(windows)
+}
+
+/// `C:\Users\Ada` -> `/mnt/c/Users/Ada`
+fn drive_path(windows: &str) -> Option<PathBuf> {
+    let (drive, rest) = windows.split_once(":\\")?;
+    let drive = drive.chars().next().filter(|c| c.is_ascii_alphabetic())?;
+    let mut path = PathBuf::from(format!("/mnt/{}", drive.to_ascii_lowercase()));
+    path.extend(rest.split('\\').filter(|c| !c.is_empty()));
+    Some(path)
+}
+
+/// A WSL distro's virtual disk
+#[derive(Debug, Clone, PartialEq)]
+struct WslDisk {
+    distro: String,
+    vhdx: PathBuf,
+}
+
+/// Distros registered under `HKCU\...\Lxss`, from `reg query /s` output
+fn parse_lxss(output: &str) -> Vec<WslDisk> {
+    let mut disks = Vec::new();
+    let mut name = None;
+    let mut base = None;
+
+    // Each distro is a key block of `Name  REG_SZ  Value` lines
+    for line in output.lines().map(str::trim).chain(std::iter::once("")) {
+        if line.is_empty() || line.starts_with("HKEY_") {
+            if let (Some(distro), Some(base)) = (name.take(), base.take()) {
+                disks.push(WslDisk {
+                    distro,
+                    vhdx: Path::new(&base).join("ext4.vhdx"),
+                });
+            }
+            continue;
+        }
+        let mut parts = line.splitn(3, "    ").map(str::trim);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("DistributionName"), Some(_), Some(value)) => name = Some(value.to_string()),
+            (Some("BasePath"), Some(_), Some(value)) => {
+                // Store-installed distros use the `\\?\` long path prefix
+                base = Some(value.trim_start_matches(r"\\?\").to_string())
+            }
+            _ => {}
+        }
+    }
+
+    disks
+}
+
+/// Registered distros, plus Docker Desktop's data disk
+fn wsl_disks() -> Vec<WslDisk> {
+    let mut disks = Command::new("reg")
+        .args([
+            "query",
+            r"HKCU\Software\Microsoft\Windows\CurrentVersion\Lxss",
+            "/s",
+        ])
+        .output()
+        .ok()
+        .filter(|o| o.status.success())
+        .map(|o| parse_lxss(&String::from_utf8_lossy(&o.stdout)))
+        .unwrap_or_default();
+
+    if let Some(local) = dirs::data_local_dir() {
+        let docker = local
+            .join("Docker")
+            .join("wsl")
+            .join("data")
+            .join("ext4.vhdx");
+        if !disks.iter().any(|d| d.vhdx == docker) {
+            disks.push(WslDisk {
+                distro: "docker-desktop-data".to_string(),
+                vhdx: docker,
+            });
+        }
+    }
+
+    disks.retain(|d| d.vhdx.is_file());
+    disks
+}
+
+/// Bytes in use on a distro's root filesystem, from `df -B1 --output=used`
+fn parse_df_used(output: &str) -> Option<u64> {
+    output.lines().skip(1).find_map(|l| l.trim().parse().ok())
+}
+
+/// Ask the distro how much of its disk it uses. This starts the distro if
+/// it isn't running.
+fn used_bytes(distro: &str) -> Option<u64> {
+    let output = Command::new("wsl.exe")
+        .args(["-d", distro, "-e", "df", "-B1", "--output=used", "/"])
+        .env("WSL_UTF8", "1")
+        .output()
+        .ok()
+        .filter(|o| o.status.success())?;
+    parse_df_used(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Reports WSL disks whose size is well beyond the data they hold; a VHDX
+/// grows as files are written but never shrinks on its own
+pub struct WslDiskScanner;
+
+impl WslDiskScanner {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for WslDiskScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scanner for WslDiskScanner {
+    fn name(&self) -> &'static str {
+        "WSL Disk Scanner"
+    }
+
+    fn scan_with(&self, config: &Config, visitor: &mut dyn ScanVisitor) -> Result<()> {
+        if !cfg!(windows) || !config.paths.system {
+            return Ok(());
+        }
+
+        for disk in wsl_disks() {
+            if config.is_excluded(&disk.vhdx) {
+                continue;
+            }
+            let size = match std::fs::metadata(&disk.vhdx) {
+                Ok(m) => m.len(),
+                Err(_) => continue,
+            };
+            let used = match used_bytes(&disk.distro) {
+                Some(u) => u,
+                None => continue,
+            };
+            let slack = size.saturating_sub(used);
+            if slack < MIN_VHDX_SLACK {
+                continue;
+            }
+
+            visitor.finding(CleanableFile {
+                last_accessed: get_last_modified(&disk.vhdx).unwrap_or_else(Utc::now),
+                action: CleanupAction::ReportOnly {
+                    hint: format!(
+                        "compact the VHDX: run `wsl --shutdown`, then `Optimize-VHD -Path \"{}\" -Mode Full` as administrator (or `wsl --manage {} --set-sparse true`)",
+                        disk.vhdx.display(),
+                        disk.distro
+                    ),
+                },
+                recoverable: true,
+                ..CleanableFile::new(disk.vhdx, slack, Category::Cache, format!(
+                    "WSL disk for {} is {} but holds {}",
+                    disk.distro,
+                    crate::format::format_size(size),
+                    crate::format::format_size(used)
+                ))
+            })?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lxss_and_df() {
+        let reg = r"
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\Lxss
+    DefaultDistribution    REG_SZ    {a}
+
+HKEY_CURRENT_USER\Software\Microsoft\Windows\CurrentVersion\Lxss\{a}
+    State    REG_DWORD    0x1
+    DistributionName    REG_SZ    Ubuntu
+    BasePath    REG_SZ    \\?\C:\Users\Ada\AppData\Local\Packages\Ubuntu\LocalState
+";
+        let disks = parse_lxss(reg);
+        assert_eq!(disks.len(), 1);
+        assert_eq!(disks[0].distro, "Ubuntu");
+        assert!(disks[0].vhdx.to_string_lossy().starts_with(r"C:\Users\Ada"));
+        assert!(disks[0].vhdx.ends_with("ext4.vhdx"));
+
+        assert_eq!(parse_df_used("     Used\n 5368709120\n"), Some(5368709120));
+        assert_eq!(
+            drive_path(r"C:\Users\Ada"),
+            Some(PathBuf::from("/mnt/c/Users/Ada"))
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Read-only report server: runs scans on demand and serves the results over HTTP.
+//!
+//! Intended for headless build agents where admins want to check cleanable
+//! space from a browser. Nothing here deletes files.
+//!
+//! The last report served is kept as a session. Chart requests read from it
+//! rather than scanning again, so they describe the report the browser shows.
+
+use crate::analyzer;
+use crate::cli::ServeOptions;
+use crate::config::Config;
+use crate::format;
+use crate::scanner::ScanResult;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+/// How long a client may take to send its request or read the response.
+/// Requests are handled one at a time, so a stalled one holds up the rest.
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Levels of `/api/treemap` when the request doesn't say
+const TREEMAP_DEPTH: usize = 3;
+
+/// The last scan served
+struct Session {
+    /// Sent with the JSON report as `session_id`
+    id: String,
+    result: ScanResult,
+}
+
+impl Session {
+    fn scan(options: &ServeOptions, config: &Config) -> Result<Self> {
+        Ok(Self {
+            id: Utc::now().timestamp_millis().to_string(),
+            result: analyzer::run_scan(&options.scan, config)?,
+        })
+    }
+}
+
+/// The value of `name` in a query string
+fn param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .find_map(|p| p.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// The session a request reads from: the one its `session_id` names, or
+/// the last one served. `None` if the named one is gone; with no session
+/// yet and none named, a scan is run for the request.
+fn session<'a>(
+    query: &str,
+    last: &'a mut Option<Session>,
+    options: &ServeOptions,
+    config: &Config,
+) -> Result<Option<&'a Session>> {
+    match param(query, "session_id") {
+        Some(id) => Ok(last.as_ref().filter(|s| s.id == id)),
+        None if last.is_none() => Ok(Some(last.insert(Session::scan(options, config)?))),
+        None => Ok(last.as_ref()),
+    }
+}
+
+/// Run the server until interrupted. Requests are handled one at a time so
+/// concurrent visitors can't start overlapping full-disk scans.
+pub fn run(options: &ServeOptions, config: &Config) -> Result<()> {
+    let address = format!("{}:{}", options.bind, options.port);
+    let listener =
+        TcpListener::bind(&address).with_context(|| format!("Failed to bind {}", address))?;
+
+    println!("Serving scan reports on http://{}", address);
+    println!("  GET /           HTML report");
+    println!("  GET /api/scan   JSON report");
+    println!("  GET /api/treemap?depth=N[&session_id=ID]   findings by path, as nested sizes");
+    println!("  GET /api/finding?index=N[&session_id=ID]   details of one finding of /api/scan");
+
+    let mut last = None;
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(s) => s,
+            Err(_) => continue,
+        };
+        if stream.set_read_timeout(Some(IO_TIMEOUT)).is_err()
+            || stream.set_write_timeout(Some(IO_TIMEOUT)).is_err()
+        {
+            continue;
+        }
+        if let Err(e) = handle_connection(stream, &mut last, options, config) {
+            eprintln!("Request failed: {:#}", e);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_connection(
+    mut stream: TcpStream,
+    last: &mut Option<Session>,
+    options: &ServeOptions,
+    config: &Config,
+) -> Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut request_line)
+        .context("Failed to read request")?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default();
+    let target = parts.next().unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    if method != "GET" {
+        return respond(
+            &mut stream,
+            "405 Method Not Allowed",
+            "text/plain",
+            "Read-only server\n",
+        );
+    }
+
+    match path {
+        "/" | "/report.html" => {
+            let session = last.insert(Session::scan(options, config)?);
+            respond(
+                &mut stream,
+                "200 OK",
+                "text/html; charset=utf-8",
+                &render_html(&session.result),
+            )
+        }
+        "/api/scan" => {
+            let session = last.insert(Session::scan(options, config)?);
+            let mut report = analyzer::json_report(&session.result);
+            report["session_id"] = session.id.clone().into();
+            let body = serde_json::to_string_pretty(&report)?;
+            respond(&mut stream, "200 OK", "application/json", &body)
+        }
+        "/api/treemap" => {
+            let depth = param(query, "depth")
+                .and_then(|d| d.parse().ok())
+                .unwrap_or(TREEMAP_DEPTH);
+            let Some(session) = session(query, last, options, config)? else {
+                return session_gone(&mut stream);
+            };
+            let body = serde_json::to_string_pretty(&analyzer::treemap(&session.result, depth))?;
+            respond(&mut stream, "200 OK", "application/json", &body)
+        }
+        "/api/finding" => {
+            let index = param(query, "index").and_then(|i| i.parse().ok());
+            let Some(index) = index else {
+                return respond(
+                    &mut stream,
+                    "400 Bad Request",
+                    "text/plain",
+                    "Expected ?index=N\n",
+                );
+            };
+            let Some(session) = session(query, last, options, config)? else {
+                return session_gone(&mut stream);
+            };
+            match analyzer::file_details(&session.result, index, config.metered_connection) {
+                Some(details) => {
+                    let body = serde_json::to_string_pretty(&details)?;
+                    respond(&mut stream, "200 OK", "application/json", &body)
+                }
+                None => respond(
+                    &mut stream,
+                    "404 Not Found",
+                    "text/plain",
+                    "No such finding\n",
+                ),
+            }
+        }
+        _ => respond(&mut stream, "404 Not Found", "text/plain", "Not found\n"),
+    }
+}
+
+/// The answer to a request for a session a later scan or a restart replaced
+fn session_gone(stream: &mut TcpStream) -> Result<()> {
+    respond(
+        stream,
+        "410 Gone",
+        "text/plain",
+        "That scan is no longer kept; fetch /api/scan again\n",
+    )
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &str) -> Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+    .context("Failed to write response")
+}
+
+/// Escape text for inclusion in HTML
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render a self-contained HTML report of scan results
+pub fn render_html(result: &ScanResult) -> String {
+    let mut html = String::from(
+        "<!doctype html><html><head><meta charset=\"utf-8\"><title>duster report</title>\
+         <style>body{font-family:sans-serif;margin:2em}table{border-collapse:collapse}\
+         td,th{padding:4px 12px;text-align:left}td.num{text-align:right}\
+         tr:nth-child(even){background:#f4f4f4}</style></head><body>",
+    );
+
+    html.push_str(&format!(
+        "<h1>Scan Results</h1><p>{} across {} items</p>",
+        format::format_size(result.total_size()),
+        format::format_number(result.total_count() as u64)
+    ));
+
+    html.push_str("<table><tr><th>Category</th><th>Items</th><th>Size</th></tr>");
+    for (category, files) in result.by_category() {
+        let size: u64 = files.iter().map(|f| f.size).sum();
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"num\">{}</td><td class=\"num\">{}</td></tr>",
+            category.display_name(),
+            format::format_number(files.len() as u64),
+            format::format_size(size)
+        ));
+    }
+    html.push_str("</table>");
+
+    for (category, files) in result.by_category() {
+        html.push_str(&format!("<h2>{}</h2><table>", category.display_name()));
+        for file in files {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td class=\"num\">{}</td><td>{}</td></tr>",
+                escape_html(&format::format_path(&file.path)),
+                format::format_size(file.size),
+                escape_html(&file.reason)
+            ));
+        }
+        html.push_str("</table>");
+    }
+
+    if !result.errors.is_empty() {
+        html.push_str("<h2>Errors</h2><ul>");
+        for error in &result.errors {
+            html.push_str(&format!("<li>{}</li>", escape_html(error)));
+        }
+        html.push_str("</ul>");
+    }
+
+    html.push_str("</body></html>");
+    html
+}
@@ -1,11 +1,16 @@
-//! Disk space reporting (total / free) for a given path's filesystem
+//! Disk space reporting (total / free) for a given path's filesystem, and
+//! predicted free space per volume after a cleanup
 
 use anyhow::{Context, Result};
 use colored::Colorize;
+use serde::Serialize;
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use sysinfo::Disks;
+use sysinfo::{Disk, Disks};
 
+use crate::cleaner;
 use crate::cli::SpaceOptions;
+use crate::scanner::CleanableFile;
 use crate::ui;
 
 /// Run the space command: resolve path, find disk, print total/free.
@@ -39,25 +44,118 @@ fn resolve_target_path(options: &SpaceOptions) -> Result<PathBuf> {
 
 fn find_disk_for_path(target: &Path) -> Result<(u64, u64, PathBuf)> {
     let disks = Disks::new_with_refreshed_list();
+    let disk = disk_for_path(&disks, target).context("No disk found containing the given path")?;
 
-    let mut matching: Vec<_> = disks
+    let total = disk.total_space();
+    let free = disk.available_space();
+    Ok((total, free, disk.mount_point().to_path_buf()))
+}
+
+/// The disk whose mount point is the longest prefix of `target` (handles
+/// nested mounts like / vs /home)
+fn disk_for_path<'a>(disks: &'a Disks, target: &Path) -> Option<&'a Disk> {
+    disks
         .list()
         .iter()
         .filter(|disk| target.starts_with(disk.mount_point()))
-        .map(|disk| (disk.mount_point().to_path_buf(), disk))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+}
+
+/// Free space on one volume before and after a cleanup
+#[derive(Debug, Clone, Serialize)]
+pub struct VolumeForecast {
+    pub mount_point: PathBuf,
+    pub total_bytes: u64,
+    pub free_bytes: u64,
+    /// Bytes the cleanup frees on this volume
+    pub reclaim_bytes: u64,
+    pub free_after_bytes: u64,
+    /// Findings that live on this volume
+    pub items: usize,
+}
+
+/// Predict free space per volume if `files` were cleaned. Report-only
+/// findings free nothing, and findings inside a directory that is cleaned
+/// too are only counted once.
+pub fn predict_free_space(files: &[CleanableFile]) -> Vec<VolumeForecast> {
+    let disks = Disks::new_with_refreshed_list();
+    let mut volumes: Vec<VolumeForecast> = Vec::new();
+    // Only what clean would actually remove counts
+    let refused: HashSet<&Path> = files
+        .iter()
+        .filter(|f| !cleaner::would_clean(f))
+        .map(|f| f.path.as_path())
         .collect();
 
-    // Longest mount point first (handles nested mounts like / vs /home)
-    matching.sort_by(|a, b| b.0.as_os_str().len().cmp(&a.0.as_os_str().len()));
+    for item in cleaner::cleanup_plan(files) {
+        if item.covered_by.is_some() || refused.contains(item.path.as_path()) {
+            continue;
+        }
 
-    let (mount_point, disk) = matching
-        .into_iter()
-        .next()
-        .context("No disk found containing the given path")?;
+        // Resolve symlinked parents so the item lands on the right volume
+        let path = item.path.canonicalize().unwrap_or(item.path);
+        let disk = match disk_for_path(&disks, &path) {
+            Some(d) => d,
+            None => continue,
+        };
 
-    let total = disk.total_space();
-    let free = disk.available_space();
-    Ok((total, free, mount_point))
+        let index = match volumes.iter().position(|v| v.mount_point == disk.mount_point()) {
+            Some(i) => i,
+            None => {
+                volumes.push(VolumeForecast {
+                    mount_point: disk.mount_point().to_path_buf(),
+                    total_bytes: disk.total_space(),
+                    free_bytes: disk.available_space(),
+                    reclaim_bytes: 0,
+                    free_after_bytes: 0,
+                    items: 0,
+                });
+                volumes.len() - 1
+            }
+        };
+        volumes[index].reclaim_bytes += item.size;
+        volumes[index].items += 1;
+    }
+
+    for volume in &mut volumes {
+        volume.free_after_bytes = (volume.free_bytes + volume.reclaim_bytes).min(volume.total_bytes);
+    }
+    volumes.sort_by_key(|v| std::cmp::Reverse(v.reclaim_bytes));
+    volumes
+}
+
+/// Print predicted free space per volume for `clean --simulate`
+pub fn print_forecast(volumes: &[VolumeForecast], json: bool) -> Result<()> {
+    if json {
+        let output = serde_json::json!({ "volumes": volumes });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    ui::print_header("Predicted free space");
+    if volumes.is_empty() {
+        ui::print_info("Nothing would be freed.");
+        return Ok(());
+    }
+
+    let width = volumes
+        .iter()
+        .map(|v| v.mount_point.display().to_string().len())
+        .max()
+        .unwrap_or(0);
+    for volume in volumes {
+        println!(
+            "{:<width$} : {} → {} free  {}",
+            volume.mount_point.display().to_string(),
+            ui::format_size(volume.free_bytes),
+            ui::format_size(volume.free_after_bytes).green(),
+            format!("({} item(s))", volume.items).dimmed(),
+            width = width
+        );
+    }
+    println!();
+    ui::print_info("Simulation only; nothing was deleted.");
+    Ok(())
 }
 
 fn print_human(total: u64, free: u64, mount_point: &Path) {
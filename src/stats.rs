@@ -0,0 +1,210 @@
+//! Lifetime cleanup statistics, kept only in a local `stats.toml` and shown
+//! by `duster stats`. Nothing is ever sent anywhere.
+
+use crate::cleaner::{CleanupOutcome, CleanupResult};
+use crate::scanner::{Category, CleanableFile};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(feature = "cli")]
+use crate::cli::StatsOptions;
+#[cfg(feature = "cli")]
+use crate::ui;
+#[cfg(feature = "cli")]
+use chrono::Datelike;
+#[cfg(feature = "cli")]
+use colored::*;
+
+/// What one category contributed over all cleanups
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CategoryStats {
+    pub bytes_freed: u64,
+    pub items: u64,
+}
+
+/// Totals over every cleanup since the first one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    /// When the first recorded cleanup ran
+    pub since: Option<DateTime<Utc>>,
+    pub cleans: u64,
+    pub bytes_freed: u64,
+    pub items: u64,
+    /// Per category; last, since TOML tables must follow plain values
+    #[serde(default)]
+    pub categories: BTreeMap<Category, CategoryStats>,
+}
+
+fn stats_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("duster").join("stats.toml"))
+}
+
+impl Stats {
+    /// Load the statistics; a missing or unreadable file means none yet
+    pub fn load() -> Self {
+        stats_path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| toml::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = match stats_path() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create data dir: {}", parent.display()))?;
+        }
+        let contents = toml::to_string_pretty(self).context("Failed to serialize stats")?;
+        fs::write(&path, contents)
+            .with_context(|| format!("Failed to write stats: {}", path.display()))
+    }
+
+    /// Count one cleanup run of `files`
+    pub fn add(&mut self, files: &[CleanableFile], result: &CleanupResult) {
+        let by_path: HashMap<&PathBuf, &CleanableFile> =
+            files.iter().map(|f| (&f.path, f)).collect();
+        let cleaned = result
+            .items
+            .iter()
+            .filter(|item| matches!(item.outcome, CleanupOutcome::Cleaned))
+            .filter_map(|item| by_path.get(&item.path));
+
+        let mut any = false;
+        for file in cleaned {
+            let category = self.categories.entry(file.category).or_default();
+            category.bytes_freed += file.size;
+            category.items += 1;
+            self.items += 1;
+            any = true;
+        }
+        if !any {
+            return;
+        }
+
+        self.since.get_or_insert_with(Utc::now);
+        self.cleans += 1;
+        self.bytes_freed += result.freed_bytes;
+    }
+
+    /// Categories by bytes freed, largest first
+    pub fn top_categories(&self) -> Vec<(Category, CategoryStats)> {
+        let mut categories: Vec<_> = self.categories.iter().map(|(c, s)| (*c, *s)).collect();
+        categories.sort_by_key(|(_, s)| std::cmp::Reverse(s.bytes_freed));
+        categories
+    }
+}
+
+/// Add a cleanup run to the saved statistics
+pub fn record(files: &[CleanableFile], result: &CleanupResult) -> Result<()> {
+    let mut stats = Stats::load();
+    stats.add(files, result);
+    stats.save()
+}
+
+/// "you've reclaimed 1.2 TB since March", naming the year when it isn't
+/// this one
+#[cfg(feature = "cli")]
+fn headline(stats: &Stats) -> String {
+    let since = match stats.since {
+        Some(since) if since.year() == Utc::now().year() => since.format("%B").to_string(),
+        Some(since) => since.format("%B %Y").to_string(),
+        None => return "Nothing cleaned yet".to_string(),
+    };
+    format!(
+        "You've reclaimed {} since {}",
+        ui::format_size(stats.bytes_freed),
+        since
+    )
+}
+
+/// Run the stats command
+#[cfg(feature = "cli")]
+pub fn run(options: &StatsOptions) -> Result<()> {
+    let stats = Stats::load();
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+        return Ok(());
+    }
+
+    if stats.cleans == 0 {
+        ui::print_info("No cleanups recorded yet. Run `duster clean` to get started.");
+        return Ok(());
+    }
+
+    ui::print_header("Your duster stats");
+    println!("{}", format!("{}!", headline(&stats)).bold().green());
+    println!(
+        "{}",
+        format!(
+            "{} item(s) over {} cleanup(s), {} per cleanup on average",
+            stats.items,
+            stats.cleans,
+            ui::format_size(stats.bytes_freed / stats.cleans)
+        )
+        .dimmed()
+    );
+    println!();
+
+    println!("{}", "Most cleaned:".bold());
+    for (category, category_stats) in stats.top_categories().into_iter().take(5) {
+        let share = category_stats.bytes_freed as f64 * 100.0 / stats.bytes_freed.max(1) as f64;
+        println!(
+            "  {:<22} {:>10}  {:>3.0}%  {} item(s)",
+            category.display_name(),
+            ui::format_size(category_stats.bytes_freed),
+            share,
+            category_stats.items
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cleaner::CleanupItem;
+    use crate::scanner::testing::finding;
+
+    #[test]
+    fn test_stats_count_cleaned_items() {
+        let files = vec![
+            finding("/a", 100, Category::Cache),
+            finding("/b", 300, Category::BuildArtifact),
+            finding("/c", 50, Category::BuildArtifact),
+        ];
+        let mut result = CleanupResult::new();
+        result.freed_bytes = 400;
+        for (path, outcome) in [
+            ("/a", CleanupOutcome::Cleaned),
+            ("/b", CleanupOutcome::Cleaned),
+            ("/c", CleanupOutcome::Skipped),
+        ] {
+            result.items.push(CleanupItem {
+                path: PathBuf::from(path),
+                size: 0,
+                outcome,
+            });
+        }
+
+        let mut stats = Stats::default();
+        stats.add(&files, &result);
+        // A run that cleaned nothing doesn't count
+        stats.add(&files, &CleanupResult::new());
+
+        assert_eq!(stats.cleans, 1);
+        assert_eq!(stats.items, 2);
+        assert_eq!(stats.bytes_freed, 400);
+        assert_eq!(stats.top_categories()[0].0, Category::BuildArtifact);
+
+        let round_trip: Stats = toml::from_str(&toml::to_string_pretty(&stats).unwrap()).unwrap();
+        assert_eq!(round_trip, stats);
+    }
+}
@@ -0,0 +1,77 @@
+//! Status-bar output (waybar, xbar, polybar) from a fast scan
+
+use anyhow::Result;
+
+use crate::analyzer;
+use crate::cli::{ScanOptions, StatusFormat, StatusOptions};
+use crate::config::Config;
+use crate::format;
+use crate::scanner::ScanResult;
+
+/// Run the status command
+pub fn run(options: &StatusOptions, config: &Config) -> Result<()> {
+    let result = analyzer::run_scan(&ScanOptions::fast()?, config)?;
+    println!("{}", render(&result, options.format));
+    Ok(())
+}
+
+/// Render a scan result in the format a status bar expects
+pub fn render(result: &ScanResult, status_format: StatusFormat) -> String {
+    let total = format::format_size_compact(result.total_size());
+
+    let mut categories: Vec<(String, u64)> = result
+        .by_category()
+        .iter()
+        .map(|(cat, files)| {
+            (
+                cat.display_name().to_string(),
+                files.iter().map(|f| f.size).sum(),
+            )
+        })
+        .collect();
+    categories.sort_by_key(|(_, size)| std::cmp::Reverse(*size));
+    let breakdown: Vec<String> = categories
+        .iter()
+        .map(|(name, size)| format!("{}: {}", name, format::format_size(*size)))
+        .collect();
+
+    match status_format {
+        // waybar custom module with "return-type": "json"
+        StatusFormat::Waybar => serde_json::json!({
+            "text": total,
+            "tooltip": breakdown.join("\n"),
+            "class": "duster",
+        })
+        .to_string(),
+        // xbar/SwiftBar plugin: title line, separator, dropdown items
+        StatusFormat::Xbar => {
+            let mut lines = vec![format!("duster {}", total), "---".to_string()];
+            lines.extend(breakdown);
+            lines.push("Clean caches, trash and temp | shell=duster param1=clean param2=--cache param3=--trash param4=--temp terminal=true".to_string());
+            lines.join("\n")
+        }
+        // polybar custom/script: a single line
+        StatusFormat::Polybar => format!("duster {}", total),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{Category, CleanableFile};
+
+    #[test]
+    fn test_waybar_output_is_json() {
+        let mut result = ScanResult::new();
+        result.add_files(vec![CleanableFile {
+            is_directory: true,
+            recoverable: true,
+            ..CleanableFile::new("/cache", 2 * 1024 * 1024, Category::Cache, "")
+        }]);
+
+        let value: serde_json::Value =
+            serde_json::from_str(&render(&result, StatusFormat::Waybar)).unwrap();
+        assert_eq!(value["text"], "2.0M");
+        assert_eq!(value["tooltip"], "System Cache: 2.0 MB");
+    }
+}
@@ -0,0 +1,87 @@
+//! "Top easy wins": a fast scan ranked into a short list of one-key cleanups
+
+use anyhow::{Context, Result};
+use colored::*;
+use console::Term;
+
+use crate::analyzer::{self, Suggestion};
+use crate::cleaner;
+use crate::cli::{ScanOptions, SuggestOptions};
+use crate::config::Config;
+use crate::stats;
+use crate::ui;
+
+/// Run the suggest command
+pub fn run(options: &SuggestOptions, config: &Config) -> Result<()> {
+    let scan = ScanOptions::fast().context("Invalid suggest scan options")?;
+
+    let result = analyzer::run_scan(&scan, config)?;
+    let mut ranked = analyzer::suggestions(&result, options.limit, config.metered_connection);
+
+    if ranked.is_empty() {
+        ui::print_info("Nothing worth cleaning right now.");
+        return Ok(());
+    }
+
+    let term = Term::stdout();
+
+    loop {
+        print_suggestions(&ranked, config.metered_connection);
+
+        // Not a terminal (piped output): just show the list
+        if !term.is_term() {
+            return Ok(());
+        }
+
+        println!();
+        println!(
+            "{}",
+            format!(
+                "Press 1-{} to apply a suggestion, any other key to quit.",
+                ranked.len()
+            )
+            .dimmed()
+        );
+
+        let key = term.read_char().context("Failed to read key")?;
+        let index = match key.to_digit(10) {
+            Some(n) if n >= 1 && (n as usize) <= ranked.len() => n as usize - 1,
+            _ => return Ok(()),
+        };
+
+        let suggestion = ranked.remove(index);
+        ui::print_info(&format!("Applying: {}", suggestion.title));
+        let cleanup = cleaner::delete_files(&suggestion.files, None)?;
+        let _ = stats::record(&suggestion.files, &cleanup);
+        cleaner::print_cleanup_result(&cleanup);
+
+        if ranked.is_empty() {
+            return Ok(());
+        }
+    }
+}
+
+fn print_suggestions(ranked: &[Suggestion], metered: bool) {
+    ui::print_header("Top easy wins");
+
+    for (i, suggestion) in ranked.iter().enumerate() {
+        println!(
+            "  {}  {}: {}",
+            format!("[{}]", i + 1).cyan().bold(),
+            suggestion.title,
+            ui::format_size(suggestion.size).yellow()
+        );
+
+        let redownload: u64 = suggestion
+            .files
+            .iter()
+            .filter_map(|f| f.redownload_bytes())
+            .sum();
+        if redownload > 0 {
+            println!(
+                "       {}",
+                analyzer::redownload_note(redownload, metered).dimmed()
+            );
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! Triage tags on findings (`duster tag <path> keep|later|delete`), kept
+//! across runs so scans and cleans can honor earlier decisions
+
+use crate::scanner::{extra, CleanableFile, CleanupAction};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "cli")]
+use crate::cli::{TagAction, TagOptions};
+#[cfg(feature = "cli")]
+use crate::ui;
+#[cfg(feature = "cli")]
+use colored::*;
+
+/// A user's decision about a path and everything below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Tag {
+    /// Never report it again
+    Keep,
+    /// Report it, but never clean it without asking
+    Later,
+    /// Clean it, even unattended
+    Delete,
+}
+
+impl Tag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Tag::Keep => "keep",
+            Tag::Later => "later",
+            Tag::Delete => "delete",
+        }
+    }
+}
+
+/// Tagged paths
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Tags {
+    paths: BTreeMap<PathBuf, Tag>,
+}
+
+fn tags_path() -> Option<PathBuf> {
+    dirs::data_dir().map(|p| p.join("duster").join("tags.json"))
+}
+
+impl Tags {
+    /// Load saved tags; a missing or unreadable file means no tags
+    pub fn load() -> Self {
+        tags_path()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = match tags_path() {
+            Some(p) => p,
+            None => return Ok(()),
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create data dir: {}", parent.display()))?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize tags")?;
+        fs::write(&path, json).with_context(|| format!("Failed to write tags: {}", path.display()))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn set(&mut self, path: PathBuf, tag: Tag) {
+        self.paths.insert(path, tag);
+    }
+
+    /// Remove a path's own tag; returns whether it had one
+    pub fn clear(&mut self, path: &Path) -> bool {
+        self.paths.remove(path).is_some()
+    }
+
+    /// Tagged paths, sorted
+    pub fn iter(&self) -> impl Iterator<Item = (&PathBuf, &Tag)> {
+        self.paths.iter()
+    }
+
+    /// The tag of `path` or, failing that, of its closest tagged ancestor
+    pub fn get(&self, path: &Path) -> Option<Tag> {
+        if self.paths.is_empty() {
+            return None;
+        }
+        path.ancestors().find_map(|p| self.paths.get(p).copied())
+    }
+
+    /// A path tagged keep strictly below `path`, if any
+    pub fn kept_below(&self, path: &Path) -> Option<&Path> {
+        self.paths
+            .range::<Path, _>((Bound::Excluded(path), Bound::Unbounded))
+            .take_while(|(p, _)| p.starts_with(path))
+            .find(|(_, tag)| **tag == Tag::Keep)
+            .map(|(p, _)| p.as_path())
+    }
+
+    /// Drop a finding tagged keep and note the tag on any other, so reports
+    /// and JSON show it. A directory holding a kept path is only reported,
+    /// since cleaning it would take the kept path along.
+    pub fn apply_one(&self, file: CleanableFile) -> Option<CleanableFile> {
+        if self.paths.is_empty() {
+            return Some(file);
+        }
+        let file = match self.get(&file.path) {
+            Some(Tag::Keep) => return None,
+            Some(tag) => file.with_extra(extra::TAG, tag.as_str()),
+            None => file,
+        };
+        let kept = match self.kept_below(&file.path) {
+            Some(kept) if file.is_directory => kept,
+            _ => return Some(file),
+        };
+        Some(CleanableFile {
+            action: CleanupAction::ReportOnly {
+                hint: format!("holds {}, tagged keep; clean around it", kept.display()),
+            },
+            ..file
+        })
+    }
+
+    /// `apply_one` for each of `files`
+    pub fn apply(&self, files: Vec<CleanableFile>) -> Vec<CleanableFile> {
+        files
+            .into_iter()
+            .filter_map(|f| self.apply_one(f))
+            .collect()
+    }
+}
+
+/// Make a path absolute without requiring it to exist, so tags can outlive
+/// the files they name
+#[cfg(feature = "cli")]
+fn absolute(path: &Path) -> Result<PathBuf> {
+    if let Ok(canonical) = path.canonicalize() {
+        return Ok(canonical);
+    }
+    let cwd = std::env::current_dir().context("Could not determine current directory")?;
+    Ok(cwd.join(path))
+}
+
+/// Run the tag command
+#[cfg(feature = "cli")]
+pub fn run(options: &TagOptions) -> Result<()> {
+    let mut tags = Tags::load();
+
+    let (path, action) = match (&options.path, options.action) {
+        (Some(path), Some(action)) => (absolute(path)?, action),
+        (None, None) => {
+            print_tags(&tags);
+            return Ok(());
+        }
+        _ => anyhow::bail!("Give both a path and a tag (keep, later, delete or clear)"),
+    };
+
+    let tag = match action {
+        TagAction::Keep => Tag::Keep,
+        TagAction::Later => Tag::Later,
+        TagAction::Delete => Tag::Delete,
+        TagAction::Clear => {
+            if tags.clear(&path) {
+                tags.save()?;
+                ui::print_success(&format!("Cleared the tag on {}", ui::format_path(&path)));
+            } else {
+                ui::print_info(&format!("{} has no tag", ui::format_path(&path)));
+            }
+            return Ok(());
+        }
+    };
+
+    tags.set(path.clone(), tag);
+    tags.save()?;
+    ui::print_success(&format!(
+        "Tagged {} as {}",
+        ui::format_path(&path),
+        tag.as_str()
+    ));
+    Ok(())
+}
+
+/// Ask which findings tagged later to clean this time; none are picked
+/// unless the user checks them
+#[cfg(feature = "cli")]
+pub fn pick_later(files: Vec<CleanableFile>, tags: &Tags) -> Vec<CleanableFile> {
+    let (later, mut rest): (Vec<_>, Vec<_>) = files
+        .into_iter()
+        .partition(|f| tags.get(&f.path) == Some(Tag::Later));
+    if later.is_empty() {
+        return rest;
+    }
+
+    let labels = labels(&later);
+    let picked = ui::multi_select("Also clean any of these items tagged later?", &labels);
+    rest.extend(
+        later
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| picked.contains(i))
+            .map(|(_, f)| f),
+    );
+    rest
+}
+
+/// Let the user tag some of `files` after declining a cleanup, so the next
+/// scan remembers the decision
+#[cfg(feature = "cli")]
+pub fn offer_tagging(files: &[CleanableFile]) -> Result<()> {
+    if files.is_empty() || !ui::confirm("Tag some of these items for future cleanups?") {
+        return Ok(());
+    }
+
+    let picked = ui::multi_select("Items to tag:", &labels(files));
+    if picked.is_empty() {
+        return Ok(());
+    }
+    let choices = [
+        "keep: never report them again",
+        "later: report them, but only clean them when picked",
+        "delete: clean them, even with --yes",
+    ];
+    let tag = match ui::select("Tag them as", &choices, 0) {
+        0 => Tag::Keep,
+        1 => Tag::Later,
+        _ => Tag::Delete,
+    };
+
+    let mut tags = Tags::load();
+    for &i in &picked {
+        tags.set(files[i].path.clone(), tag);
+    }
+    tags.save()?;
+    ui::print_success(&format!(
+        "Tagged {} item(s) as {}",
+        picked.len(),
+        tag.as_str()
+    ));
+    Ok(())
+}
+
+#[cfg(feature = "cli")]
+fn labels(files: &[CleanableFile]) -> Vec<String> {
+    files
+        .iter()
+        .map(|f| format!("{} ({})", ui::format_path(&f.path), ui::format_size(f.size)))
+        .collect()
+}
+
+#[cfg(feature = "cli")]
+fn print_tags(tags: &Tags) {
+    if tags.is_empty() {
+        ui::print_info("No tags yet. Tag a path with `duster tag <PATH> keep|later|delete`.");
+        return;
+    }
+
+    ui::print_header("Tags");
+    for (path, tag) in tags.iter() {
+        let label = match tag {
+            Tag::Keep => tag.as_str().green(),
+            Tag::Later => tag.as_str().yellow(),
+            Tag::Delete => tag.as_str().red(),
+        };
+        println!("  {:<7} {}", label, ui::format_path(path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::testing::finding;
+    use crate::scanner::Category;
+
+    #[test]
+    fn test_tags_apply_to_paths_below() {
+        let mut tags = Tags::default();
+        tags.set(PathBuf::from("/home/me/videos"), Tag::Keep);
+        tags.set(PathBuf::from("/home/me/isos"), Tag::Later);
+        tags.set(PathBuf::from("/home/me/isos/old.iso"), Tag::Delete);
+
+        assert_eq!(
+            tags.get(Path::new("/home/me/isos/new.iso")),
+            Some(Tag::Later)
+        );
+        // The closest tag wins
+        assert_eq!(
+            tags.get(Path::new("/home/me/isos/old.iso")),
+            Some(Tag::Delete)
+        );
+        assert_eq!(tags.get(Path::new("/home/me/videos-other")), None);
+
+        let files = tags.apply(vec![
+            finding("/home/me/videos/a.mkv", 1, Category::LargeFile),
+            finding("/home/me/isos/new.iso", 1, Category::LargeFile),
+            finding("/home/me/b.bin", 1, Category::LargeFile),
+        ]);
+        let paths: Vec<&Path> = files.iter().map(|f| f.path.as_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                Path::new("/home/me/isos/new.iso"),
+                Path::new("/home/me/b.bin")
+            ]
+        );
+        assert!(files[0].extra_matches(extra::TAG, "later"));
+    }
+
+    #[test]
+    fn test_directories_holding_kept_paths_are_only_reported() {
+        let mut tags = Tags::default();
+        tags.set(PathBuf::from("/home/me/projects/app/data"), Tag::Keep);
+        tags.set(PathBuf::from("/home/me/projects/app-old"), Tag::Later);
+
+        let dir = |path: &str| CleanableFile {
+            is_directory: true,
+            ..finding(path, 1, Category::OldFile)
+        };
+        let files = tags.apply(vec![
+            dir("/home/me/projects"),
+            dir("/home/me/projects/app"),
+            dir("/home/me/projects/app-old"),
+            dir("/home/me/projects/app/data/raw"),
+        ]);
+        let actions: Vec<(&Path, bool)> = files
+            .iter()
+            .map(|f| {
+                let report_only = matches!(f.action, CleanupAction::ReportOnly { .. });
+                (f.path.as_path(), report_only)
+            })
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                (Path::new("/home/me/projects"), true),
+                (Path::new("/home/me/projects/app"), true),
+                (Path::new("/home/me/projects/app-old"), false),
+            ]
+        );
+    }
+}
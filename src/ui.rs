@@ -1,40 +1,12 @@
 //! Terminal UI helpers for formatting, prompts, and progress indicators
 
 use colored::*;
-use dialoguer::{Confirm, MultiSelect};
+use dialoguer::{Confirm, Input, MultiSelect, Select};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::path::Path;
 use std::time::Duration;
 
-/// Format bytes as human-readable size
-pub fn format_size(bytes: u64) -> String {
-    const KB: u64 = 1024;
-    const MB: u64 = KB * 1024;
-    const GB: u64 = MB * 1024;
-    const TB: u64 = GB * 1024;
-
-    if bytes >= TB {
-        format!("{:.1} TB", bytes as f64 / TB as f64)
-    } else if bytes >= GB {
-        format!("{:.1} GB", bytes as f64 / GB as f64)
-    } else if bytes >= MB {
-        format!("{:.1} MB", bytes as f64 / MB as f64)
-    } else if bytes >= KB {
-        format!("{:.1} KB", bytes as f64 / KB as f64)
-    } else {
-        format!("{} B", bytes)
-    }
-}
-
-/// Format path, replacing home directory with ~
-pub fn format_path(path: &Path) -> String {
-    if let Some(home) = dirs::home_dir() {
-        if let Ok(relative) = path.strip_prefix(&home) {
-            return format!("~/{}", relative.display());
-        }
-    }
-    path.display().to_string()
-}
+pub use crate::format::{format_duration, format_number, format_path, format_size};
 
 /// Print a table row with formatting
 pub fn print_table_row(columns: &[(&str, usize)]) {
@@ -99,6 +71,39 @@ pub fn multi_select(prompt: &str, items: &[String]) -> Vec<usize> {
         .unwrap_or_default()
 }
 
+/// Multi-select with some items pre-checked
+pub fn multi_select_with_defaults(prompt: &str, items: &[String], defaults: &[bool]) -> Vec<usize> {
+    if items.is_empty() {
+        return Vec::new();
+    }
+
+    MultiSelect::new()
+        .with_prompt(prompt)
+        .items(items)
+        .defaults(defaults)
+        .interact()
+        .unwrap_or_default()
+}
+
+/// Pick one item from a list, falling back to `default` if the prompt fails
+pub fn select(prompt: &str, items: &[&str], default: usize) -> usize {
+    Select::new()
+        .with_prompt(prompt)
+        .items(items)
+        .default(default)
+        .interact()
+        .unwrap_or(default)
+}
+
+/// Ask for a line of free text (may be empty)
+pub fn input(prompt: &str) -> String {
+    Input::<String>::new()
+        .with_prompt(prompt)
+        .allow_empty(true)
+        .interact_text()
+        .unwrap_or_default()
+}
+
 /// Create a spinner for indeterminate progress
 pub fn create_spinner(message: &str) -> ProgressBar {
     let pb = ProgressBar::new_spinner();
@@ -126,19 +131,6 @@ pub fn create_progress_bar(total: u64, message: &str) -> ProgressBar {
     pb
 }
 
-/// Format a number with thousand separators
-pub fn format_number(n: u64) -> String {
-    let s = n.to_string();
-    let mut result = String::new();
-    for (i, c) in s.chars().rev().enumerate() {
-        if i > 0 && i % 3 == 0 {
-            result.push(',');
-        }
-        result.push(c);
-    }
-    result.chars().rev().collect()
-}
-
 /// Print a category header with size
 pub fn print_category_header(name: &str, size: u64, count: usize) {
     println!(
@@ -149,14 +141,16 @@ pub fn print_category_header(name: &str, size: u64, count: usize) {
     );
 }
 
-/// Print a file entry with optional indentation
-pub fn print_file_entry(path: &Path, size: u64, indent: usize) {
+/// Print a file entry with optional indentation and owner
+pub fn print_file_entry(path: &Path, size: u64, indent: usize, owner: Option<&str>) {
     let indent_str = "  ".repeat(indent);
+    let owner_str = owner.map(|o| format!("  [{}]", o)).unwrap_or_default();
     println!(
-        "{}{}  {}",
+        "{}{}  {}{}",
         indent_str,
         format_path(path),
-        format_size(size).dimmed()
+        format_size(size).dimmed(),
+        owner_str.dimmed()
     );
 }
 
@@ -181,35 +175,3 @@ pub fn print_deletion_warning() {
         "This action is permanent and cannot be undone.".red().bold()
     );
 }
-
-/// Format a duration in human-readable form
-pub fn format_duration(seconds: u64) -> String {
-    if seconds < 60 {
-        format!("{}s", seconds)
-    } else if seconds < 3600 {
-        format!("{}m {}s", seconds / 60, seconds % 60)
-    } else {
-        format!("{}h {}m", seconds / 3600, (seconds % 3600) / 60)
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_format_size() {
-        assert_eq!(format_size(500), "500 B");
-        assert_eq!(format_size(1024), "1.0 KB");
-        assert_eq!(format_size(1536), "1.5 KB");
-        assert_eq!(format_size(1048576), "1.0 MB");
-        assert_eq!(format_size(1073741824), "1.0 GB");
-    }
-
-    #[test]
-    fn test_format_number() {
-        assert_eq!(format_number(1000), "1,000");
-        assert_eq!(format_number(1000000), "1,000,000");
-        assert_eq!(format_number(42), "42");
-    }
-}
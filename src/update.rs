@@ -0,0 +1,383 @@
+//! `duster self-update`: replace the running binary with the build of the
+//! latest GitHub release on a channel, once its SHA-256 checksum matches the
+//! one published next to it
+
+use anyhow::{bail, Context, Result};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::cli::{SelfUpdateOptions, UpdateChannel};
+use crate::ui;
+
+const REPO: &str = "ericzakariasson/duster";
+
+/// The commit this binary was built from, set by the release workflow
+const BUILD_COMMIT: Option<&str> = option_env!("DUSTER_COMMIT");
+
+/// A downloadable file of a release
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Asset {
+    name: String,
+    url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Release {
+    tag: String,
+    /// The commit a nightly was built from, from its "Built from <sha>" notes
+    commit: Option<String>,
+    assets: Vec<Asset>,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Option<&Asset> {
+        self.assets.iter().find(|a| a.name == name)
+    }
+}
+
+/// Release archive for this platform, named like `install.sh` and the
+/// release workflow name them
+fn asset_name() -> Option<String> {
+    let os = match std::env::consts::OS {
+        "macos" => "macos",
+        "linux" => "linux",
+        _ => return None,
+    };
+    let arch = match (os, std::env::consts::ARCH) {
+        (_, "x86_64") => "x86_64",
+        ("macos", "aarch64") => "arm64",
+        _ => return None,
+    };
+    Some(format!("duster-{}-{}.tar.gz", os, arch))
+}
+
+/// Fetch a URL with curl, which every supported platform ships
+fn fetch(url: &str) -> Result<Vec<u8>> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "-H", "Accept: application/vnd.github+json", url])
+        .output()
+        .context("Failed to run curl")?;
+    if !output.status.success() {
+        bail!(
+            "Failed to download {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(output.stdout)
+}
+
+fn parse_release(json: &[u8]) -> Result<Release> {
+    let value: serde_json::Value =
+        serde_json::from_slice(json).context("Invalid release metadata from GitHub")?;
+    let tag = value["tag_name"]
+        .as_str()
+        .context("Release metadata has no tag")?
+        .to_string();
+    let commit = value["body"]
+        .as_str()
+        .and_then(|body| body.trim().strip_prefix("Built from "))
+        .map(|sha| sha.trim().to_string());
+    let assets = value["assets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|a| {
+            Some(Asset {
+                name: a["name"].as_str()?.to_string(),
+                url: a["browser_download_url"].as_str()?.to_string(),
+            })
+        })
+        .collect();
+    Ok(Release {
+        tag,
+        commit,
+        assets,
+    })
+}
+
+/// Latest release on a channel: the newest tagged release, or the
+/// `nightly` prerelease rebuilt from the main branch
+fn latest_release(channel: UpdateChannel) -> Result<Release> {
+    let url = match channel {
+        UpdateChannel::Stable => format!("https://api.github.com/repos/{}/releases/latest", REPO),
+        UpdateChannel::Nightly => {
+            format!(
+                "https://api.github.com/repos/{}/releases/tags/nightly",
+                REPO
+            )
+        }
+    };
+    parse_release(&fetch(&url)?)
+}
+
+/// Numeric parts of a version tag ("v0.1.10" → [0, 1, 10])
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map(|p| p.parse().unwrap_or(0))
+        .collect()
+}
+
+fn is_newer(tag: &str, current: &str) -> bool {
+    version_parts(tag) > version_parts(current)
+}
+
+/// Whether `release` is another build than this one: a higher version on
+/// the stable channel; nightlies share a version, so their commit tells
+/// them apart
+fn is_update(release: &Release, channel: UpdateChannel, current: &str) -> bool {
+    match channel {
+        UpdateChannel::Stable => is_newer(&release.tag, current),
+        UpdateChannel::Nightly => {
+            release.commit.is_none() || release.commit.as_deref() != BUILD_COMMIT
+        }
+    }
+}
+
+/// The checksum for `asset` in a `.sha256` file, either bare or in
+/// `sha256sum` format
+fn parse_checksum(contents: &str, asset: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let hash = fields.next()?;
+        let valid = hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+        match fields.next() {
+            Some(name) if name.trim_start_matches('*') != asset => None,
+            _ if valid => Some(hash.to_lowercase()),
+            _ => None,
+        }
+    })
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Unpack the `duster` binary from a release archive in memory, so what
+/// gets installed comes straight from the checked archive
+fn extract_binary(archive: &[u8]) -> Result<Vec<u8>> {
+    let mut child = Command::new("tar")
+        .args(["-xzOf", "-", "duster"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to run tar")?;
+    let mut stdin = child.stdin.take().context("Failed to run tar")?;
+    let output = std::thread::scope(|s| {
+        // tar stops reading once it fails; its status says why
+        s.spawn(move || stdin.write_all(archive));
+        child.wait_with_output()
+    })
+    .context("Failed to run tar")?;
+    if !output.status.success() || output.stdout.is_empty() {
+        bail!("The release archive has no duster binary");
+    }
+    Ok(output.stdout)
+}
+
+/// Swap `exe` for `binary` with a rename in the same directory, so the
+/// path always holds a whole binary, old or new
+fn replace_binary(exe: &Path, binary: &[u8]) -> Result<()> {
+    let dir = exe
+        .parent()
+        .context("The running binary has no directory")?;
+    let staged = dir.join(format!(".duster-update-{}", std::process::id()));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&staged)
+        .and_then(|mut file| file.write_all(binary))
+        .with_context(|| {
+            format!(
+                "Failed to write to {}; try again with sudo",
+                ui::format_path(dir)
+            )
+        })?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+
+    if let Err(e) = fs::rename(&staged, exe) {
+        let _ = fs::remove_file(&staged);
+        return Err(e).with_context(|| format!("Failed to replace {}", exe.display()));
+    }
+
+    // What runs from now on is what is at `exe`, so that is what's checked
+    let installed = fs::read(exe).with_context(|| format!("Failed to read {}", exe.display()))?;
+    if installed != binary {
+        bail!(
+            "{} changed while it was being replaced; reinstall duster",
+            exe.display()
+        );
+    }
+    Ok(())
+}
+
+/// Whether `exe` is managed by `cargo install`
+fn is_cargo_install(exe: &Path) -> bool {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|h| h.join(".cargo")));
+    cargo_home.is_some_and(|home| exe.starts_with(home.join("bin")))
+}
+
+/// Run the self-update command
+pub fn run(options: &SelfUpdateOptions) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let asset_name = asset_name().with_context(|| {
+        format!(
+            "No prebuilt duster for this platform; update with `cargo install --git https://github.com/{}`",
+            REPO
+        )
+    })?;
+    let exe = std::env::current_exe()
+        .and_then(|p| p.canonicalize())
+        .context("Could not locate the running binary")?;
+
+    let release = latest_release(options.channel)?;
+    if !is_update(&release, options.channel, current) && !options.force {
+        ui::print_success(&format!("duster {} is up to date", current));
+        return Ok(());
+    }
+    if options.check {
+        ui::print_info(&format!(
+            "duster {} is available (running {})",
+            release.tag, current
+        ));
+        return Ok(());
+    }
+
+    let archive = release
+        .asset(&asset_name)
+        .with_context(|| format!("Release {} has no {}", release.tag, asset_name))?;
+    let checksum_name = format!("{}.sha256", asset_name);
+    let checksum = release.asset(&checksum_name).with_context(|| {
+        format!(
+            "Release {} publishes no checksum for {}; not installing it",
+            release.tag, asset_name
+        )
+    })?;
+
+    if is_cargo_install(&exe) {
+        ui::print_warning(
+            "duster was installed with cargo; reinstalling it with cargo also updates it.",
+        );
+    }
+    if !options.yes
+        && !ui::confirm(&format!(
+            "Replace duster {} at {} with {}?",
+            current,
+            ui::format_path(&exe),
+            release.tag
+        ))
+    {
+        return Ok(());
+    }
+
+    let spinner = ui::create_spinner(&format!("Downloading duster {}...", release.tag));
+    let data = fetch(&archive.url);
+    let expected = fetch(&checksum.url);
+    spinner.finish_and_clear();
+    let (data, expected) = (data?, expected?);
+
+    let expected = parse_checksum(&String::from_utf8_lossy(&expected), &asset_name)
+        .with_context(|| format!("Could not read {}", checksum_name))?;
+    let actual = sha256_hex(&data);
+    if actual != expected {
+        bail!(
+            "Checksum mismatch for {}: expected {}, got {}; not installing it",
+            asset_name,
+            expected,
+            actual
+        );
+    }
+
+    let binary = extract_binary(&data)?;
+    replace_binary(&exe, &binary)?;
+    ui::print_success(&format!("Updated duster {} → {}", current, release.tag));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_metadata_and_checksums() {
+        let release = parse_release(
+            br#"{
+                "tag_name": "v0.2.0",
+                "assets": [
+                    {"name": "duster-linux-x86_64.tar.gz", "browser_download_url": "https://example.com/a"},
+                    {"name": "duster-linux-x86_64.tar.gz.sha256", "browser_download_url": "https://example.com/b"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(release.tag, "v0.2.0");
+        assert_eq!(
+            release
+                .asset("duster-linux-x86_64.tar.gz.sha256")
+                .unwrap()
+                .url,
+            "https://example.com/b"
+        );
+
+        assert!(is_newer("v0.2.0", "0.1.2"));
+        assert!(is_newer("v0.1.10", "0.1.9"));
+        assert!(!is_newer("v0.1.2", "0.1.2"));
+
+        let hash = sha256_hex(b"duster");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(parse_checksum(&hash, "a.tar.gz"), Some(hash.clone()));
+        let listing = format!("{}  b.tar.gz\n{}  a.tar.gz\n", "0".repeat(64), hash);
+        assert_eq!(parse_checksum(&listing, "a.tar.gz"), Some(hash));
+        assert_eq!(parse_checksum("not a checksum", "a.tar.gz"), None);
+    }
+
+    #[test]
+    fn test_nightly_is_compared_by_commit() {
+        let nightly = |body: &str| {
+            let json = serde_json::json!({ "tag_name": "nightly", "body": body, "assets": [] });
+            parse_release(json.to_string().as_bytes()).unwrap()
+        };
+        let is_update = |release: &Release| is_update(release, UpdateChannel::Nightly, "0.1.2");
+
+        let this = BUILD_COMMIT.unwrap_or("abc123");
+        let built = nightly(&format!("Built from {}\n", this));
+        assert_eq!(built.commit.as_deref(), Some(this));
+        // A build that doesn't know its commit can't be the same nightly
+        assert_eq!(is_update(&built), BUILD_COMMIT.is_none());
+        assert!(is_update(&nightly("Built from 0ther")));
+        assert!(is_update(&nightly("")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_binary_is_extracted_in_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("duster"), b"#!/bin/sh\n").unwrap();
+        let archive = Command::new("tar")
+            .args(["-czf", "-", "-C"])
+            .arg(dir.path())
+            .arg("duster")
+            .output()
+            .unwrap()
+            .stdout;
+
+        assert_eq!(extract_binary(&archive).unwrap(), b"#!/bin/sh\n");
+        assert!(extract_binary(b"not an archive").is_err());
+    }
+}
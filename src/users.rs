@@ -0,0 +1,401 @@
+//! System service mode: run by root, scan each user's home as that user,
+//! with that user's own config, and report per user
+
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::cli::UsersOptions;
+use crate::config::{Config, SystemScan};
+use crate::scanner::package_cache::PackageCacheScanner;
+use crate::scanner::systemd::{CoredumpScanner, JournalScanner};
+use crate::scanner::{ScanResult, Scanner};
+use crate::{analyzer, cleaner, owner, ui};
+
+/// Directories holding user homes
+const HOME_ROOTS: &[&str] = &["/home", "/Users"];
+
+/// Largest config file read from a user's home
+const MAX_CONFIG_SIZE: u64 = 1024 * 1024;
+
+/// A user home found under one of `HOME_ROOTS`
+struct UserHome {
+    name: String,
+    uid: u32,
+    home: PathBuf,
+}
+
+/// Outcome for one user
+#[derive(serde::Serialize)]
+struct UserReport {
+    user: String,
+    home: PathBuf,
+    mode: &'static str,
+    /// The user's `scan --json` output, or `clean --json` when cleaned
+    report: Option<serde_json::Value>,
+    error: Option<String>,
+}
+
+/// Homes owned by regular users; the owner of the directory is the user
+fn user_homes() -> Vec<UserHome> {
+    let mut homes: Vec<UserHome> = HOME_ROOTS
+        .iter()
+        .filter_map(|root| fs::read_dir(root).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') || name == "Shared" {
+                return None;
+            }
+            let uid = owner::owner_uid(&e.path()).filter(|&uid| uid != 0)?;
+            Some(UserHome {
+                name,
+                uid,
+                home: e.path(),
+            })
+        })
+        .collect();
+
+    homes.sort_by(|a, b| a.name.cmp(&b.name));
+    homes
+}
+
+/// The user's config, read as root without trusting what the user put in
+/// their home: the directories leading to it must be the user's own and not
+/// links, and the file itself is opened without following a link and must be
+/// a regular file the user owns. A missing config is the default one.
+#[cfg(unix)]
+fn load_user_config(user: &UserHome) -> Result<Config> {
+    use std::io::{ErrorKind, Read};
+    use std::os::unix::fs::{MetadataExt, OpenOptionsExt};
+
+    let path = Config::config_path_in_home(&user.home);
+    let relative = path.strip_prefix(&user.home).unwrap_or(&path);
+    let mut dir = user.home.clone();
+    for component in relative.parent().into_iter().flat_map(Path::components) {
+        dir.push(component);
+        match fs::symlink_metadata(&dir) {
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", dir.display())),
+            Ok(m) if !m.is_dir() || m.uid() != user.uid => {
+                bail!(
+                    "{} is not a directory owned by {}",
+                    dir.display(),
+                    user.name
+                )
+            }
+            Ok(_) => {}
+        }
+    }
+
+    let file = match fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NOFOLLOW | libc::O_NONBLOCK)
+        .open(&path)
+    {
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+        file => file.with_context(|| format!("Failed to open {}", path.display()))?,
+    };
+    let metadata = file
+        .metadata()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    if !metadata.is_file() || metadata.uid() != user.uid {
+        bail!("{} is not a file owned by {}", path.display(), user.name);
+    }
+
+    let mut contents = String::new();
+    file.take(MAX_CONFIG_SIZE)
+        .read_to_string(&mut contents)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Config::parse(&contents, &path)
+}
+
+#[cfg(not(unix))]
+fn load_user_config(user: &UserHome) -> Result<Config> {
+    Config::load_from(&Config::config_path_in_home(&user.home))
+}
+
+/// Run duster as the home's owner, so its caches and history land in the
+/// user's home with the right ownership and it can't touch other users' files
+#[cfg(unix)]
+fn run_as_user(user: &UserHome, args: &[String]) -> Result<serde_json::Value> {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::process::CommandExt;
+
+    let gid = fs::metadata(&user.home)
+        .with_context(|| format!("Failed to read {}", user.home.display()))?
+        .gid();
+    let exe = std::env::current_exe().context("Failed to locate the duster binary")?;
+
+    let output = Command::new(exe)
+        .args(args)
+        .env("HOME", &user.home)
+        .env("USER", &user.name)
+        .env("LOGNAME", &user.name)
+        .env_remove("XDG_CONFIG_HOME")
+        .env_remove("XDG_CACHE_HOME")
+        .env_remove("XDG_DATA_HOME")
+        .current_dir(&user.home)
+        .uid(user.uid)
+        .gid(gid)
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to run duster as {}", user.name))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        bail!(
+            "{}",
+            stderr
+                .lines()
+                .find(|l| !l.trim().is_empty())
+                .unwrap_or("duster failed")
+        );
+    }
+
+    // A scan that finds nothing prints a message instead of a report
+    Ok(serde_json::from_slice(&output.stdout).unwrap_or(serde_json::Value::Null))
+}
+
+#[cfg(not(unix))]
+fn run_as_user(_user: &UserHome, _args: &[String]) -> Result<serde_json::Value> {
+    bail!("Per-user scans are only supported on Unix")
+}
+
+/// Scan (and, where allowed, clean) one user's home. Homes whose config
+/// doesn't set `system_scan` are only scanned, for a report, with
+/// `--all-users`.
+fn process_user(user: &UserHome, options: &UsersOptions) -> UserReport {
+    let mut report = UserReport {
+        user: user.name.clone(),
+        home: user.home.clone(),
+        mode: "unset",
+        report: None,
+        error: None,
+    };
+
+    let mode = match load_user_config(user) {
+        Ok(config) => config
+            .system_scan
+            .or(options.all_users.then_some(SystemScan::Report)),
+        Err(e) => {
+            report.error = Some(format!("{:#}", e));
+            return report;
+        }
+    };
+    let mode = match mode {
+        Some(SystemScan::Off) | None => {
+            report.mode = mode.map_or("unset", |m| m.as_str());
+            return report;
+        }
+        Some(mode) => mode,
+    };
+    report.mode = mode.as_str();
+
+    let command = if options.clean && mode == SystemScan::Clean {
+        &["clean", "--yes"][..]
+    } else {
+        &["scan"][..]
+    };
+    let mut args: Vec<String> = std::iter::once("--no-wizard")
+        .chain(command.iter().copied())
+        .chain(["--json", "--owner", &user.uid.to_string()])
+        .map(String::from)
+        .collect();
+    args.extend(options.scan_args.iter().cloned());
+
+    match run_as_user(user, &args) {
+        Ok(value) => report.report = Some(value),
+        Err(e) => report.error = Some(format!("{:#}", e)),
+    }
+    report
+}
+
+/// System-wide findings (package caches, journal, core dumps), scanned here
+/// as root. Root's
+/// own `system_scan` setting decides whether `--clean` may clean them.
+fn process_system(options: &UsersOptions) -> Result<UserReport> {
+    let config = Config::load()?;
+    let mode = config.system_scan.unwrap_or(SystemScan::Report);
+    let mut report = UserReport {
+        user: "system".to_string(),
+        home: PathBuf::from("/"),
+        mode: mode.as_str(),
+        report: None,
+        error: None,
+    };
+    if mode == SystemScan::Off {
+        return Ok(report);
+    }
+
+    let scanners: [&dyn Scanner; 3] = [
+        &PackageCacheScanner::new(),
+        &JournalScanner::new(),
+        &CoredumpScanner::new(),
+    ];
+    let mut result = ScanResult::new();
+    for scanner in scanners {
+        match scanner.scan(&config) {
+            Ok(files) => result.add_files(files),
+            Err(e) => result.add_error(format!("{}: {:#}", scanner.name(), e)),
+        }
+    }
+
+    let clean = options.clean && mode == SystemScan::Clean;
+    report.report = Some(if clean {
+        let cleanup = cleaner::delete_files(&result.files, None)?;
+        cleaner::json_report(&result, 0, Some(&cleanup))
+    } else {
+        analyzer::json_report(&result)
+    });
+    Ok(report)
+}
+
+/// Total size of the findings in a user's report
+fn reported_size(report: &serde_json::Value) -> (u64, u64) {
+    let scan = report.get("plan").unwrap_or(report);
+    let count = scan["summary"]["total_files"].as_u64().unwrap_or(0);
+    let size = scan["summary"]["total_size"].as_u64().unwrap_or(0);
+    (count, size)
+}
+
+/// Run the users command
+pub fn run(options: &UsersOptions) -> Result<()> {
+    if !owner::is_root() {
+        bail!("`duster users` scans every user's home and must run as root");
+    }
+
+    if let Some(dir) = &options.report_dir {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create report directory: {}", dir.display()))?;
+    }
+
+    let homes = user_homes();
+    let spinner = (!options.json).then(|| ui::create_spinner("Scanning user homes..."));
+    let mut reports = Vec::with_capacity(homes.len());
+    for user in &homes {
+        if let Some(spinner) = &spinner {
+            spinner.set_message(format!("Scanning {}...", user.name));
+        }
+        let report = process_user(user, options);
+        if let Some(dir) = &options.report_dir {
+            write_report(dir, &report)?;
+        }
+        reports.push(report);
+    }
+    if cfg!(target_os = "linux") {
+        if let Some(spinner) = &spinner {
+            spinner.set_message("Scanning system logs...");
+        }
+        let report = process_system(options)?;
+        if let Some(dir) = &options.report_dir {
+            write_report(dir, &report)?;
+        }
+        reports.push(report);
+    }
+    if let Some(spinner) = spinner {
+        spinner.finish_and_clear();
+    }
+
+    if options.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({ "users": reports }))?
+        );
+    } else {
+        print_reports(&reports);
+    }
+
+    Ok(())
+}
+
+fn write_report(dir: &Path, report: &UserReport) -> Result<()> {
+    let path = dir.join(format!("{}.json", report.user));
+    fs::write(&path, serde_json::to_string_pretty(report)?)
+        .with_context(|| format!("Failed to write report: {}", path.display()))
+}
+
+fn print_reports(reports: &[UserReport]) {
+    if reports.is_empty() {
+        ui::print_info("No user homes found.");
+        return;
+    }
+
+    ui::print_header("Per-user Scan");
+
+    for report in reports {
+        let detail = match (&report.error, &report.report) {
+            (Some(error), _) => error.red().to_string(),
+            (None, None) if report.mode == "unset" => {
+                "skipped (system_scan not set; see --all-users)"
+                    .dimmed()
+                    .to_string()
+            }
+            (None, None) => "skipped (system_scan = \"off\")".dimmed().to_string(),
+            (None, Some(value)) => {
+                let (count, size) = reported_size(value);
+                let freed = value["result"]["freed_bytes"].as_u64();
+                match freed {
+                    Some(freed) => format!(
+                        "{} findings, {}; freed {}",
+                        ui::format_number(count),
+                        ui::format_size(size),
+                        ui::format_size(freed).green()
+                    ),
+                    None => format!(
+                        "{} findings, {}",
+                        ui::format_number(count),
+                        ui::format_size(size).yellow()
+                    ),
+                }
+            }
+        };
+
+        println!(
+            "  {:<16} {:<8} {}",
+            report.user.bold(),
+            report.mode.dimmed(),
+            detail
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_user_config_is_not_read_through_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path().join("dev");
+        fs::create_dir(&home).unwrap();
+        let user = UserHome {
+            name: "dev".to_string(),
+            uid: owner::owner_uid(&home).unwrap(),
+            home: home.clone(),
+        };
+        assert_eq!(load_user_config(&user).unwrap().system_scan, None);
+
+        let path = Config::config_path_in_home(&home);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "system_scan = \"clean\"\n").unwrap();
+        let config = load_user_config(&user).unwrap();
+        assert_eq!(config.system_scan, Some(SystemScan::Clean));
+
+        let secret = dir.path().join("secret");
+        fs::write(&secret, "system_scan = \"report\"\n").unwrap();
+        fs::remove_file(&path).unwrap();
+        std::os::unix::fs::symlink(&secret, &path).unwrap();
+        assert!(load_user_config(&user).is_err());
+
+        // A linked directory on the way
+        let elsewhere = dir.path().join("elsewhere");
+        fs::rename(path.parent().unwrap(), &elsewhere).unwrap();
+        std::os::unix::fs::symlink(&elsewhere, path.parent().unwrap()).unwrap();
+        assert!(load_user_config(&user).is_err());
+    }
+}
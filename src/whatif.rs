@@ -0,0 +1,238 @@
+//! `duster whatif`: re-evaluate the last cached scan under other thresholds,
+//! so settings can be tuned without waiting for a full rescan
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use colored::*;
+use std::collections::BTreeMap;
+
+use crate::cli::WhatifOptions;
+use crate::config::parse_size_mb;
+use crate::format;
+use crate::scan_cache::{self, Thresholds};
+use crate::scanner::build_artifacts::is_project_recently_used;
+use crate::scanner::{extra, Category, CleanableFile};
+use crate::ui;
+
+/// Findings and bytes of one category before and after the change
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Delta {
+    pub before_count: usize,
+    pub before_size: u64,
+    pub after_count: usize,
+    pub after_size: u64,
+}
+
+/// The scanned thresholds with the options' overrides applied
+fn proposed(options: &WhatifOptions, scanned: Thresholds) -> Result<Thresholds> {
+    let min_large_size_mb = match &options.min_size {
+        Some(size) => parse_size_mb(size).with_context(|| format!("Invalid size: {}", size))?,
+        None => scanned.min_large_size_mb,
+    };
+    Ok(Thresholds {
+        min_age_days: options.min_age.unwrap_or(scanned.min_age_days),
+        min_large_size_mb,
+        project_recent_days: options.project_age.unwrap_or(scanned.project_recent_days),
+    })
+}
+
+/// Whether a finding of a scan run with `scanned` is still reported under
+/// `proposed`. Only stricter thresholds can be judged this way: anything a
+/// looser one would add was never recorded.
+pub fn still_found(file: &CleanableFile, scanned: &Thresholds, proposed: &Thresholds) -> bool {
+    match file.category {
+        Category::OldFile if proposed.min_age_days > scanned.min_age_days => {
+            (Utc::now() - file.last_accessed).num_days() >= proposed.min_age_days as i64
+        }
+        Category::LargeFile if proposed.min_large_size_mb > scanned.min_large_size_mb => {
+            file.size >= proposed.min_large_size_mb * 1024 * 1024
+        }
+        // Project activity isn't stored, but checking a project's own files
+        // is cheap
+        Category::BuildArtifact if proposed.project_recent_days > scanned.project_recent_days => {
+            match file.extra.get(extra::PROJECT_ROOT).and_then(|v| v.as_str()) {
+                Some(root) => {
+                    !is_project_recently_used(root.as_ref(), proposed.project_recent_days)
+                }
+                None => true,
+            }
+        }
+        _ => true,
+    }
+}
+
+/// Per-category counts and sizes before and after, for categories with
+/// findings
+pub fn deltas(
+    files: &[CleanableFile],
+    scanned: &Thresholds,
+    proposed: &Thresholds,
+) -> BTreeMap<Category, Delta> {
+    let mut deltas: BTreeMap<Category, Delta> = BTreeMap::new();
+    for file in files {
+        let delta = deltas.entry(file.category).or_default();
+        delta.before_count += 1;
+        delta.before_size += file.size;
+        if still_found(file, scanned, proposed) {
+            delta.after_count += 1;
+            delta.after_size += file.size;
+        }
+    }
+    deltas
+}
+
+/// Thresholds lowered below the scanned ones, which only a rescan can judge
+fn needs_rescan(scanned: &Thresholds, proposed: &Thresholds) -> Vec<String> {
+    let mut notes = Vec::new();
+    if proposed.min_age_days < scanned.min_age_days {
+        notes.push(format!(
+            "--min-age {} is below the {} days the scan used",
+            proposed.min_age_days, scanned.min_age_days
+        ));
+    }
+    if proposed.min_large_size_mb < scanned.min_large_size_mb {
+        notes.push(format!(
+            "--min-size {} MB is below the {} MB the scan used",
+            proposed.min_large_size_mb, scanned.min_large_size_mb
+        ));
+    }
+    if proposed.project_recent_days < scanned.project_recent_days {
+        notes.push(format!(
+            "--project-age {} is below the {} days the scan used",
+            proposed.project_recent_days, scanned.project_recent_days
+        ));
+    }
+    notes
+}
+
+/// Run the whatif command
+pub fn run(options: &WhatifOptions) -> Result<()> {
+    let last = scan_cache::load_last()
+        .context("No saved scan to compare against; run `duster scan` first")?;
+    let scanned = last
+        .thresholds
+        .context("The saved scan predates whatif support; run `duster scan` again first")?;
+    let proposed = proposed(options, scanned)?;
+    let deltas = deltas(&last.result.files, &scanned, &proposed);
+    let notes = needs_rescan(&scanned, &proposed);
+
+    if options.json {
+        let categories: Vec<_> = deltas
+            .iter()
+            .map(|(category, d)| {
+                serde_json::json!({
+                    "category": category.display_name(),
+                    "before_count": d.before_count,
+                    "before_size": d.before_size,
+                    "after_count": d.after_count,
+                    "after_size": d.after_size,
+                })
+            })
+            .collect();
+        let output = serde_json::json!({
+            "scan_age_secs": last.age_secs,
+            "scanned": scanned,
+            "proposed": proposed,
+            "categories": categories,
+            "needs_rescan": notes,
+        });
+        println!("{}", serde_json::to_string_pretty(&output)?);
+        return Ok(());
+    }
+
+    ui::print_header("What if");
+    println!(
+        "{}",
+        format!(
+            "Comparing against the scan from {} ago",
+            format::format_duration(last.age_secs)
+        )
+        .dimmed()
+    );
+    println!();
+
+    if deltas.is_empty() {
+        ui::print_info("The saved scan has no findings.");
+    }
+    for (category, d) in &deltas {
+        let change = if d.after_count == d.before_count {
+            "unchanged".dimmed().to_string()
+        } else {
+            format!(
+                "-{} item(s), -{}",
+                d.before_count - d.after_count,
+                format::format_size(d.before_size - d.after_size)
+            )
+            .yellow()
+            .to_string()
+        };
+        println!(
+            "  {:<22} {:>5} → {:<5} {:>10} → {:<10} {}",
+            category.display_name(),
+            d.before_count,
+            d.after_count,
+            format::format_size(d.before_size),
+            format::format_size(d.after_size),
+            change
+        );
+    }
+
+    let before: u64 = deltas.values().map(|d| d.before_size).sum();
+    let after: u64 = deltas.values().map(|d| d.after_size).sum();
+    println!();
+    println!(
+        "{} {} → {}",
+        "Total:".bold(),
+        format::format_size(before),
+        format::format_size(after)
+    );
+
+    for note in notes {
+        ui::print_warning(&format!(
+            "{}; items between the two only show up after `duster scan`.",
+            note
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::testing::{file_aged, finding};
+
+    #[test]
+    fn test_stricter_thresholds_drop_findings() {
+        const MB: u64 = 1024 * 1024;
+        let scanned = Thresholds {
+            min_age_days: 30,
+            min_large_size_mb: 100,
+            project_recent_days: 14,
+        };
+        let proposed = Thresholds {
+            min_age_days: 60,
+            min_large_size_mb: 500,
+            project_recent_days: 14,
+        };
+        let files = vec![
+            file_aged(45, MB),
+            file_aged(90, MB),
+            finding("/home/me/small.iso", 200 * MB, Category::LargeFile),
+            finding("/home/me/big.iso", 800 * MB, Category::LargeFile),
+            finding("/home/me/.cache/app", MB, Category::Cache),
+        ];
+
+        let deltas = deltas(&files, &scanned, &proposed);
+        assert_eq!(deltas[&Category::OldFile].after_count, 1);
+        assert_eq!(deltas[&Category::LargeFile].after_size, 800 * MB);
+        assert_eq!(deltas[&Category::Cache].after_count, 1);
+
+        // Looser thresholds keep everything and ask for a rescan
+        let looser = Thresholds {
+            min_age_days: 7,
+            ..scanned
+        };
+        assert!(files.iter().all(|f| still_found(f, &scanned, &looser)));
+        assert_eq!(needs_rescan(&scanned, &looser).len(), 1);
+    }
+}
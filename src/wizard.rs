@@ -0,0 +1,164 @@
+//! First-run setup wizard that writes a tailored config.toml
+
+use anyhow::Result;
+use std::io::IsTerminal;
+
+use crate::cli::{Cli, Command};
+use crate::config::Config;
+use crate::ui;
+
+/// Categories offered by the wizard: (flag name, label, checked by default)
+const CATEGORIES: &[(&str, &str, bool)] = &[
+    ("cache", "App and system caches", true),
+    ("trash", "Trash bin", true),
+    ("temp", "Temp files", true),
+    ("downloads", "Old downloads", true),
+    ("desktop", "Desktop clutter", true),
+    ("build", "Build artifacts of inactive projects", true),
+    ("ci", "Self-hosted CI runner workspaces", true),
+    ("large", "Large files", true),
+    ("duplicates", "Duplicate files", true),
+    ("old", "Old unused files", true),
+    ("junk", "Empty directories and broken symlinks", true),
+    ("logs", "Rotated and idle log files", true),
+    (
+        "metadata-junk",
+        "OS metadata files (.DS_Store, Thumbs.db)",
+        false,
+    ),
+    ("git", "Git LFS objects and large blobs in history", false),
+    (
+        "docker",
+        "Docker images, containers, volumes and build cache",
+        false,
+    ),
+];
+
+/// Age and size thresholds for one aggressiveness level
+struct Preset {
+    label: &'static str,
+    min_age_days: u32,
+    project_recent_days: u32,
+    download_age_days: u32,
+    desktop_age_days: u32,
+    min_large_size_mb: u64,
+}
+
+const PRESETS: &[Preset] = &[
+    Preset {
+        label: "Conservative - only things untouched for months",
+        min_age_days: 90,
+        project_recent_days: 30,
+        download_age_days: 90,
+        desktop_age_days: 90,
+        min_large_size_mb: 500,
+    },
+    Preset {
+        label: "Balanced - the defaults",
+        min_age_days: 30,
+        project_recent_days: 14,
+        download_age_days: 30,
+        desktop_age_days: 30,
+        min_large_size_mb: 100,
+    },
+    Preset {
+        label: "Aggressive - anything idle for a couple of weeks",
+        min_age_days: 14,
+        project_recent_days: 7,
+        download_age_days: 14,
+        desktop_age_days: 14,
+        min_large_size_mb: 50,
+    },
+];
+
+/// Whether to offer the wizard before running this command: there's no
+/// config file yet, a person is at the terminal, and the command is interactive
+pub fn should_offer(cli: &Cli) -> bool {
+    if cli.no_wizard {
+        return false;
+    }
+
+    // Servers, status bars and root's per-user scans run unattended;
+    // `config --setup` runs it itself
+    if matches!(
+        cli.command,
+        Command::Mcp
+            | Command::Serve(_)
+            | Command::Status(_)
+            | Command::Monitor(_)
+            | Command::Users(_)
+            | Command::Config(_)
+    ) {
+        return false;
+    }
+
+    let config_exists = Config::config_path().is_some_and(|p| p.exists());
+    !config_exists && std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Offer the wizard on first run. Declining saves the defaults so the
+/// question isn't asked again.
+pub fn offer() -> Result<Config> {
+    ui::print_info("No duster config file found.");
+    if !ui::confirm("Set up duster now? (takes a few seconds)") {
+        let config = Config::default();
+        config.save()?;
+        ui::print_info("Saved default settings. Run `duster config --setup` to change them later.");
+        return Ok(config);
+    }
+
+    run()
+}
+
+/// Ask the setup questions, save the resulting config and return it
+pub fn run() -> Result<Config> {
+    ui::print_header("duster setup");
+
+    let labels: Vec<&str> = PRESETS.iter().map(|p| p.label).collect();
+    let preset = &PRESETS[ui::select("How aggressive should cleanup be?", &labels, 1)];
+
+    let items: Vec<String> = CATEGORIES
+        .iter()
+        .map(|(_, label, _)| label.to_string())
+        .collect();
+    let defaults: Vec<bool> = CATEGORIES.iter().map(|(_, _, on)| *on).collect();
+    let selected = ui::multi_select_with_defaults(
+        "Which categories should scans include? (space to toggle, enter to confirm)",
+        &items,
+        &defaults,
+    );
+
+    let protected = ui::input("Paths to always protect (comma-separated, blank for none)");
+
+    let mut config = Config {
+        min_age_days: preset.min_age_days,
+        project_recent_days: preset.project_recent_days,
+        download_age_days: preset.download_age_days,
+        desktop_age_days: preset.desktop_age_days,
+        min_large_size_mb: preset.min_large_size_mb,
+        ..Config::default()
+    };
+
+    // Selecting exactly the default set is the same as not choosing
+    let matches_defaults = (0..CATEGORIES.len()).all(|i| selected.contains(&i) == defaults[i]);
+    if !matches_defaults {
+        config.enabled_categories = selected
+            .iter()
+            .map(|&i| CATEGORIES[i].0.to_string())
+            .collect();
+    }
+
+    config.excluded_paths = protected
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .map(String::from)
+        .collect();
+
+    config.save()?;
+    if let Some(path) = Config::config_path() {
+        ui::print_success(&format!("Saved {}", path.display()));
+    }
+
+    Ok(config)
+}
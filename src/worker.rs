@@ -0,0 +1,82 @@
+//! Background scan worker for embedding applications.
+//!
+//! `run_scan` blocks for as long as the scanners take. GUIs and async runtimes
+//! should not call it from their event loop; instead they submit scans to a
+//! `ScanWorker`, which runs them on its own thread and replies over a channel.
+
+use crate::analyzer;
+use crate::cli::ScanOptions;
+use crate::config::Config;
+use crate::scanner::ScanResult;
+use anyhow::{Context, Result};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// A scan request queued on the worker thread
+struct ScanJob {
+    options: ScanOptions,
+    config: Config,
+    reply: Sender<Result<ScanResult>>,
+}
+
+/// Runs scans one at a time on a dedicated thread
+pub struct ScanWorker {
+    sender: Option<Sender<ScanJob>>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ScanWorker {
+    /// Start the worker thread
+    pub fn spawn() -> Result<Self> {
+        let (sender, receiver) = mpsc::channel::<ScanJob>();
+
+        let handle = thread::Builder::new()
+            .name("duster-scan".to_string())
+            .spawn(move || {
+                for job in receiver {
+                    let result = analyzer::run_scan(&job.options, &job.config);
+                    // The requester may have given up waiting; that's fine
+                    let _ = job.reply.send(result);
+                }
+            })
+            .context("Failed to spawn scan worker thread")?;
+
+        Ok(Self {
+            sender: Some(sender),
+            handle: Some(handle),
+        })
+    }
+
+    /// Queue a scan without blocking. The returned receiver yields the result
+    /// once the scan has finished; use `try_recv` to poll or `recv` to wait.
+    pub fn submit(
+        &self,
+        options: ScanOptions,
+        config: Config,
+    ) -> Result<Receiver<Result<ScanResult>>> {
+        let (reply, receiver) = mpsc::channel();
+
+        self.sender
+            .as_ref()
+            .context("Scan worker has shut down")?
+            .send(ScanJob {
+                options,
+                config,
+                reply,
+            })
+            .ok()
+            .context("Scan worker has shut down")?;
+
+        Ok(receiver)
+    }
+}
+
+impl Drop for ScanWorker {
+    fn drop(&mut self) {
+        // Closing the channel lets the worker finish queued jobs and exit
+        self.sender.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,177 @@
+//! Scan and clean a synthetic home directory end to end, checking exactly
+//! which paths survive.
+//!
+//! `--path` confines every scanner to the synthetic home, so nothing outside
+//! it is reported or touched.
+
+#![cfg(unix)]
+
+use clap::Parser;
+use duster::analyzer;
+use duster::cleaner;
+use duster::cli::ScanOptions;
+use duster::config::{Config, Paths};
+use std::collections::BTreeSet;
+use std::fs::{self, File, FileTimes};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+const MB: usize = 1024 * 1024;
+
+/// Write `size` bytes of `fill`, creating parent directories
+fn write(path: &Path, fill: u8, size: usize) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, vec![fill; size]).unwrap();
+}
+
+/// Set a file's or directory's access and modification times `days` back
+fn age(path: &Path, days: u64) {
+    let then = SystemTime::now() - Duration::from_secs(days * 24 * 60 * 60);
+    File::open(path)
+        .unwrap()
+        .set_times(FileTimes::new().set_accessed(then).set_modified(then))
+        .unwrap();
+}
+
+fn trash_dir(home: &Path) -> PathBuf {
+    if cfg!(target_os = "macos") {
+        home.join(".Trash")
+    } else {
+        home.join(".local/share/Trash/files")
+    }
+}
+
+/// Files left under `root`, relative to it
+fn surviving_files(root: &Path) -> BTreeSet<String> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| {
+            e.path()
+                .strip_prefix(root)
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect()
+}
+
+fn build_home(home: &Path) {
+    // Caches
+    write(&home.join(".cache/thumbnails/large.bin"), 1, 2 * MB);
+
+    // Trash, emptied regardless of age
+    write(&trash_dir(home).join("old-report.pdf"), 2, 1024);
+
+    // Downloads: one stale, one fresh
+    let stale = home.join("Downloads/installer.dmg");
+    write(&stale, 3, 2 * MB);
+    age(&stale, 90);
+    write(&home.join("Downloads/today.pdf"), 4, 1024);
+
+    // A project untouched for two months, and one in active development
+    let old_project = home.join("projects/old-app");
+    write(&old_project.join("Cargo.toml"), b'#', 16);
+    write(&old_project.join("src/main.rs"), b'/', 16);
+    write(&old_project.join("target/debug/old-app"), 5, 2 * MB);
+    age(&old_project.join("Cargo.toml"), 60);
+
+    let active_project = home.join("projects/active-app");
+    write(&active_project.join("Cargo.toml"), b'#', 16);
+    write(&active_project.join("src/main.rs"), b'/', 16);
+    write(&active_project.join("target/debug/active-app"), 6, 2 * MB);
+
+    // Two copies of the same video; the older one is kept
+    let original = home.join("Videos/holiday.mp4");
+    write(&original, 7, 2 * MB);
+    age(&original, 10);
+    write(&home.join("Videos/copy/holiday.mp4"), 7, 2 * MB);
+
+    // Documents nothing should touch
+    write(&home.join("Documents/notes.txt"), b'n', 1024);
+}
+
+#[test]
+fn test_scan_and_clean_synthetic_home() {
+    // Scanners skip hidden directories, which default temp dir names are
+    let home_dir = tempfile::Builder::new()
+        .prefix("duster-home")
+        .tempdir()
+        .unwrap();
+    let home = home_dir.path().canonicalize().unwrap();
+    let state = tempfile::tempdir().unwrap();
+
+    // Keep duster's own state (hash index, tags) out of the user's; this
+    // file holds a single test so nothing else reads the environment
+    std::env::set_var("XDG_CACHE_HOME", state.path().join("cache"));
+    std::env::set_var("XDG_DATA_HOME", state.path().join("data"));
+
+    build_home(&home);
+
+    let options = ScanOptions::try_parse_from([
+        "duster",
+        "--cache",
+        "--trash",
+        "--downloads",
+        "--build",
+        "--duplicates",
+        "--path",
+        home.to_str().unwrap(),
+    ])
+    .unwrap();
+    let mut config = Config::default();
+    config.apply_cli_options(&options);
+    assert_eq!(config.paths, Paths::sandboxed(&home));
+
+    let result = analyzer::run_scan(&options, &config).unwrap();
+    assert!(result.errors.is_empty(), "{:?}", result.errors);
+
+    let found: BTreeSet<String> = result
+        .files
+        .iter()
+        .map(|f| {
+            f.path
+                .strip_prefix(&home)
+                .unwrap()
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect();
+    let trash = trash_dir(&home).join("old-report.pdf");
+    let expected: BTreeSet<String> = [
+        ".cache/thumbnails",
+        trash.strip_prefix(&home).unwrap().to_str().unwrap(),
+        "Downloads/installer.dmg",
+        "projects/old-app/target",
+        "Videos/copy/holiday.mp4",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect();
+    assert_eq!(found, expected);
+
+    let cleanup = cleaner::delete_files(&result.files, None).unwrap();
+    assert!(cleanup.errors.is_empty(), "{:?}", cleanup.errors);
+    assert_eq!(cleanup.deleted_count, expected.len());
+
+    let expected_survivors: BTreeSet<String> = [
+        "Downloads/today.pdf",
+        "projects/old-app/Cargo.toml",
+        "projects/old-app/src/main.rs",
+        "projects/active-app/Cargo.toml",
+        "projects/active-app/src/main.rs",
+        "projects/active-app/target/debug/active-app",
+        "Videos/holiday.mp4",
+        "Documents/notes.txt",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect();
+    assert_eq!(surviving_files(&home), expected_survivors);
+
+    // A second pass finds nothing left to clean
+    let rescan = analyzer::run_scan(&options, &config).unwrap();
+    assert!(rescan.files.is_empty(), "{:?}", rescan.files);
+}